@@ -1,37 +1,143 @@
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use flashmaster_core::{
+    parse_grade_input, Card, CardPatch, Deck, DeckCounts, Grade, GradeAction, GradeScheme,
+};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
 use uuid::Uuid;
 
-#[derive(Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DeckOut {
     pub id: Uuid,
     pub name: String,
     pub created_at: DateTime<Utc>,
+    /// Only with `GET /decks?counts=true`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counts: Option<DeckCounts>,
+    /// When a card of the deck was last graded; only with
+    /// `GET /decks?counts=true`, and absent for a deck never studied.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_reviewed_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CardOut {
     pub id: Uuid,
     pub deck_id: Uuid,
     pub front: String,
     pub back: String,
+    pub alt_backs: Vec<String>,
     pub hint: Option<String>,
+    pub source: Option<String>,
     pub tags: Vec<String>,
     pub due_at: DateTime<Utc>,
     pub suspended: bool,
 }
 
+impl From<Card> for CardOut {
+    fn from(c: Card) -> Self {
+        Self {
+            id: c.id,
+            deck_id: c.deck_id,
+            front: c.front,
+            back: c.back,
+            alt_backs: c.alt_backs,
+            hint: c.hint,
+            source: c.source,
+            tags: c.tags,
+            due_at: c.due_at,
+            suspended: c.suspended,
+        }
+    }
+}
+
+impl From<Deck> for DeckOut {
+    fn from(d: Deck) -> Self {
+        Self {
+            id: d.id,
+            name: d.name,
+            created_at: d.created_at,
+            counts: None,
+            last_reviewed_at: None,
+        }
+    }
+}
+
+/// Body of `POST /decks`.
+#[derive(Serialize, Deserialize)]
+pub struct DeckIn {
+    pub name: String,
+}
+
+/// Body of every error response.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorOut {
+    pub error: String,
+}
+
+/// Body of `PATCH /cards/{id}`; omitted fields are left alone and
+/// `"hint": null` clears the hint (and `"source": null` the source).
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct CardPatchIn {
+    pub front: Option<String>,
+    pub back: Option<String>,
+    #[serde(default, deserialize_with = "de_present")]
+    pub hint: Option<Option<String>>,
+    #[serde(default, deserialize_with = "de_present")]
+    pub source: Option<Option<String>>,
+    pub tags: Option<Vec<String>>,
+    pub suspended: Option<bool>,
+}
+
+impl From<CardPatchIn> for CardPatch {
+    fn from(p: CardPatchIn) -> Self {
+        Self {
+            front: p.front,
+            back: p.back,
+            hint: p.hint,
+            source: p.source,
+            tags: p.tags,
+            suspended: p.suspended,
+        }
+    }
+}
+
+/// Distinguishes an explicit `null` (`Some(None)`) from a missing field (`None`).
+fn de_present<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Option<String>>, D::Error> {
+    Option::<String>::deserialize(d).map(Some)
+}
+
 #[derive(Deserialize)]
 pub struct ReviewIn {
     pub card_id: Uuid,
-    pub grade: String,
+    /// Either a score (`0`..=`3`, 0 being Again) or a name such as `"easy"`.
+    #[serde(deserialize_with = "de_grade")]
+    pub grade: Grade,
+}
+
+fn de_grade<'de, D: Deserializer<'de>>(d: D) -> Result<Grade, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Score(i64),
+        Name(String),
+    }
+    match Raw::deserialize(d)? {
+        Raw::Score(n) => i32::try_from(n)
+            .ok()
+            .and_then(Grade::from_score)
+            .ok_or_else(|| D::Error::custom(format!("grade out of range: {n}"))),
+        Raw::Name(s) => {
+            parse_grade(&s).ok_or_else(|| D::Error::custom(format!("unknown grade: {s}")))
+        }
+    }
 }
 
-pub fn parse_grade(s: &str) -> Option<flashmaster_core::Grade> {
-    match s.to_lowercase().as_str() {
-        "1" | "h" | "hard" => Some(flashmaster_core::Grade::Hard),
-        "2" | "m" | "med" | "medium" => Some(flashmaster_core::Grade::Medium),
-        "3" | "e" | "easy" => Some(flashmaster_core::Grade::Easy),
-        _ => None,
+/// A grade name or score as the CLI and TUI accept it under the three-key
+/// layout, whose keys are the API's `1`..=`3` scores (plus `0`/`again`).
+pub fn parse_grade(s: &str) -> Option<Grade> {
+    match parse_grade_input(s, GradeScheme::Three)? {
+        GradeAction::Grade(g) => Some(g),
+        GradeAction::Skip | GradeAction::Quit => None,
     }
 }
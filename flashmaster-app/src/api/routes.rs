@@ -1,18 +1,73 @@
-use axum::{extract::{Query, State}, http::StatusCode, Json};
+use axum::{
+    extract::{rejection::JsonRejection, Path, Query, State},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
 use serde::Deserialize;
 use std::sync::Arc;
 
 use flashmaster_core::{
-    filters::{filter_by_due, filter_not_suspended},
+    pool::{build_review_pool, pool_counts, PoolCounts, PoolOpts},
     scheduler::apply_grade,
-    DueStatus,
+    CardRules, CoreError, DeckId, EventLog, ReviewEvent,
 };
+use uuid::Uuid;
 
-use crate::api::dto::{CardOut, DeckOut, ReviewIn, parse_grade};
+use crate::api::dto::{CardOut, CardPatchIn, DeckIn, DeckOut, ErrorOut, ReviewIn};
 
 #[derive(Clone)]
 pub struct AppState {
     pub repo: Arc<dyn flashmaster_core::Repository>,
+    pub events: Option<Arc<EventLog>>,
+}
+
+/// Seconds a client is told to wait (`Retry-After`) after a 503.
+pub const RETRY_AFTER_SECS: u32 = 1;
+
+/// Handler error: a status and a short message, sent as `{"error": msg}`.
+/// `CoreError::Timeout` maps to 503 with `Retry-After` so clients can tell
+/// transient from permanent failures.
+#[derive(Debug)]
+pub struct ApiError(pub StatusCode, pub &'static str);
+
+impl From<CoreError> for ApiError {
+    fn from(e: CoreError) -> Self {
+        match e {
+            CoreError::Timeout(_) => {
+                ApiError(StatusCode::SERVICE_UNAVAILABLE, "storage busy; retry later")
+            }
+            _ => ApiError(StatusCode::INTERNAL_SERVER_ERROR, "storage error"),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let mut res = (
+            self.0,
+            Json(ErrorOut {
+                error: self.1.to_string(),
+            }),
+        )
+            .into_response();
+        if self.0 == StatusCode::SERVICE_UNAVAILABLE {
+            res.headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(RETRY_AFTER_SECS));
+        }
+        res
+    }
+}
+
+/// Maps a lookup failure to `fallback`, unless the backend timed out.
+fn or_client_err(fallback: ApiError) -> impl FnOnce(CoreError) -> ApiError {
+    move |e| {
+        if matches!(e, CoreError::Timeout(_)) {
+            e.into()
+        } else {
+            fallback
+        }
+    }
 }
 
 #[derive(Deserialize)]
@@ -23,41 +78,188 @@ pub struct DueQuery {
     max: Option<usize>,
 }
 
-pub async fn list_decks(State(st): State<Arc<AppState>>) -> Result<Json<Vec<DeckOut>>, StatusCode> {
-    let mut decks = st.repo.list_decks().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    decks.sort_by_key(|d| d.created_at);
-    Ok(Json(decks.into_iter().map(|d| DeckOut { id: d.id, name: d.name, created_at: d.created_at }).collect()))
+/// Readiness probe: 204 once the store answers a ping, 503 while it doesn't.
+pub async fn readyz(State(st): State<Arc<AppState>>) -> Result<StatusCode, ApiError> {
+    st.repo
+        .ping()
+        .await
+        .map_err(|_| ApiError(StatusCode::SERVICE_UNAVAILABLE, "storage unavailable"))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+pub struct DecksQuery {
+    counts: Option<bool>,
+}
+
+pub async fn list_decks(
+    State(st): State<Arc<AppState>>,
+    Query(q): Query<DecksQuery>,
+) -> Result<Json<Vec<DeckOut>>, ApiError> {
+    if !q.counts.unwrap_or(false) {
+        let decks = st.repo.list_decks().await?;
+        return Ok(Json(decks.into_iter().map(DeckOut::from).collect()));
+    }
+    let decks = st.repo.list_decks_with_counts(chrono::Utc::now()).await?;
+    Ok(Json(
+        decks
+            .into_iter()
+            .map(|(d, counts)| DeckOut {
+                counts: Some(counts),
+                last_reviewed_at: counts.last_reviewed_at,
+                ..d.into()
+            })
+            .collect(),
+    ))
+}
+
+pub async fn create_deck(
+    State(st): State<Arc<AppState>>,
+    body: Result<Json<DeckIn>, JsonRejection>,
+) -> Result<(StatusCode, Json<DeckOut>), ApiError> {
+    let Json(body) = body.map_err(|_| ApiError(StatusCode::BAD_REQUEST, "invalid deck body"))?;
+    if body.name.trim().is_empty() {
+        return Err(ApiError(StatusCode::BAD_REQUEST, "deck name is empty"));
+    }
+    match st.repo.create_deck(body.name.trim()).await {
+        Ok(deck) => Ok((StatusCode::CREATED, Json(deck.into()))),
+        Err(CoreError::Conflict(_)) => {
+            Err(ApiError(StatusCode::CONFLICT, "deck name already exists"))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn due_cards(
+    State(st): State<Arc<AppState>>,
+    Query(q): Query<DueQuery>,
+) -> Result<Json<Vec<CardOut>>, ApiError> {
+    let pool = due_pool(&st, &q, chrono::Utc::now()).await?;
+    Ok(Json(pool.into_iter().map(CardOut::from).collect()))
 }
 
-pub async fn due_cards(State(st): State<Arc<AppState>>, Query(q): Query<DueQuery>)
-    -> Result<Json<Vec<CardOut>>, StatusCode>
-{
+/// Per-phase size of the pool `GET /due` would return for the same query.
+pub async fn due_count(
+    State(st): State<Arc<AppState>>,
+    Query(q): Query<DueQuery>,
+) -> Result<Json<PoolCounts>, ApiError> {
     let now = chrono::Utc::now();
-    let deck_id = if let Some(sel) = q.deck.clone() {
-        Some(super::server::resolve_deck(&*st.repo, &sel).await.map_err(|_| StatusCode::BAD_REQUEST)?.id)
-    } else { None };
-
-    let mut cards = st.repo.list_cards(deck_id).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    cards = filter_not_suspended(&cards);
-
-    let mut pool = Vec::new();
-    if q.include_new.unwrap_or(false) { pool.extend(filter_by_due(&cards, now, DueStatus::New)); }
-    pool.extend(filter_by_due(&cards, now, DueStatus::DueToday));
-    if q.include_lapsed.unwrap_or(false) { pool.extend(filter_by_due(&cards, now, DueStatus::Lapsed)); }
-    pool.sort_by_key(|c| (c.due_at, c.created_at));
-    if let Some(m) = q.max { pool.truncate(m); }
-
-    Ok(Json(pool.into_iter().map(|c| CardOut {
-        id: c.id, deck_id: c.deck_id, front: c.front, back: c.back, hint: c.hint, tags: c.tags,
-        due_at: c.due_at, suspended: c.suspended
-    }).collect()))
-}
-
-pub async fn post_review(State(st): State<Arc<AppState>>, Json(body): Json<ReviewIn>) -> Result<StatusCode, StatusCode> {
-    let card = st.repo.get_card(body.card_id).await.map_err(|_| StatusCode::BAD_REQUEST)?;
-    let grade = parse_grade(&body.grade).ok_or(StatusCode::BAD_REQUEST)?;
-    let out = apply_grade(card, grade);
-    st.repo.update_card(&out.updated_card).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    st.repo.insert_review(&out.review).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let pool = due_pool(&st, &q, now).await?;
+    Ok(Json(pool_counts(&pool, now)))
+}
+
+async fn due_pool(
+    st: &AppState,
+    q: &DueQuery,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<Vec<flashmaster_core::Card>, ApiError> {
+    let deck_id = deck_param(st, q.deck.as_deref()).await?;
+    let cards = st.repo.list_cards(deck_id).await?;
+    let opts = PoolOpts {
+        include_new: q.include_new.unwrap_or(false),
+        include_lapsed: q.include_lapsed.unwrap_or(false),
+        max: q.max,
+        ..Default::default()
+    };
+    Ok(build_review_pool(&cards, now, &opts))
+}
+
+/// The id of the `?deck=` selector (name or id); 400 when it matches no deck,
+/// or several decks share the name.
+async fn deck_param(st: &AppState, sel: Option<&str>) -> Result<Option<DeckId>, ApiError> {
+    let Some(sel) = sel else {
+        return Ok(None);
+    };
+    let unknown = ApiError(StatusCode::BAD_REQUEST, "unknown deck");
+    let deck = super::server::resolve_deck(&*st.repo, sel)
+        .await
+        .map_err(|e| match e.downcast::<CoreError>() {
+            Ok(CoreError::Conflict(msg)) => ApiError(StatusCode::BAD_REQUEST, msg),
+            Ok(e) => or_client_err(unknown)(e),
+            Err(_) => unknown,
+        })?;
+    Ok(Some(deck.id))
+}
+
+/// Page size `GET /cards` uses without `?limit=`.
+pub const CARDS_PAGE_DEFAULT: u64 = 100;
+/// Largest `?limit=` honoured by `GET /cards`; bigger values are capped.
+pub const CARDS_PAGE_MAX: u64 = 1000;
+
+#[derive(Deserialize)]
+pub struct CardsQuery {
+    deck: Option<String>,
+    offset: Option<u64>,
+    limit: Option<u64>,
+}
+
+/// Cards oldest first, one page at a time.
+pub async fn list_cards(
+    State(st): State<Arc<AppState>>,
+    Query(q): Query<CardsQuery>,
+) -> Result<Json<Vec<CardOut>>, ApiError> {
+    let deck_id = deck_param(&st, q.deck.as_deref()).await?;
+    let limit = q.limit.unwrap_or(CARDS_PAGE_DEFAULT).min(CARDS_PAGE_MAX);
+    let cards = st
+        .repo
+        .list_cards_paged(deck_id, q.offset.unwrap_or(0), limit)
+        .await?;
+    Ok(Json(cards.into_iter().map(CardOut::from).collect()))
+}
+
+/// Edits only the given content fields, leaving scheduling as stored.
+pub async fn patch_card(
+    State(st): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    body: Result<Json<CardPatchIn>, JsonRejection>,
+) -> Result<Json<CardOut>, ApiError> {
+    let Json(body) = body.map_err(|_| ApiError(StatusCode::BAD_REQUEST, "invalid card patch"))?;
+    let patch = body.into();
+    CardRules::default()
+        .check_patch(&patch)
+        .map_err(|_| ApiError(StatusCode::BAD_REQUEST, "field too long"))?;
+    match st.repo.patch_card(id, patch).await {
+        Ok(card) => Ok(Json(card.into())),
+        Err(CoreError::NotFound(_)) => Err(ApiError(StatusCode::NOT_FOUND, "unknown card")),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ReviewQuery {
+    force: Option<bool>,
+}
+
+pub async fn post_review(
+    State(st): State<Arc<AppState>>,
+    Query(q): Query<ReviewQuery>,
+    body: Result<Json<ReviewIn>, JsonRejection>,
+) -> Result<StatusCode, ApiError> {
+    // Malformed bodies and out-of-range grades are client errors (400), not 422.
+    let Json(body) = body.map_err(|_| ApiError(StatusCode::BAD_REQUEST, "invalid review body"))?;
+    let card = st
+        .repo
+        .get_card(body.card_id)
+        .await
+        .map_err(or_client_err(ApiError(
+            StatusCode::BAD_REQUEST,
+            "unknown card",
+        )))?;
+    // A stale client must not reschedule a card the user paused.
+    if card.suspended && !q.force.unwrap_or(false) {
+        return Err(ApiError(
+            StatusCode::CONFLICT,
+            "card is suspended; pass ?force=true to grade it anyway",
+        ));
+    }
+    let out = apply_grade(card, body.grade);
+    st.repo.update_card(&out.updated_card).await?;
+    st.repo.insert_review(&out.review).await?;
+    if let (Some(log), Some(ev)) = (&st.events, ReviewEvent::from_card(&out.updated_card)) {
+        log.append(&ev);
+        if let Some(e) = log.take_error() {
+            eprintln!("warning: event log {e}");
+        }
+    }
     Ok(StatusCode::NO_CONTENT)
 }
@@ -1,33 +1,264 @@
-use axum::{routing::{get, post}, Router};
+use axum::{
+    routing::{get, patch, post},
+    Router,
+};
 use std::{net::SocketAddr, sync::Arc};
-use tower_http::trace::TraceLayer;
 use tokio::net::TcpListener;
+use tower_http::trace::TraceLayer;
 
-use flashmaster_core::{Repository, Deck};
-use crate::api::routes::{AppState, list_decks, due_cards, post_review};
+use crate::api::routes::{
+    create_deck, due_cards, due_count, list_cards, list_decks, patch_card, post_review, readyz,
+    AppState,
+};
+use flashmaster_core::{CoreError, Deck, EventLog, Repository};
 
-pub async fn run(repo: Arc<dyn Repository>, addr: SocketAddr) -> anyhow::Result<()> {
-    let state = Arc::new(AppState { repo });
+pub fn router(repo: Arc<dyn Repository>, events: Option<Arc<EventLog>>) -> Router {
+    let state = Arc::new(AppState { repo, events });
 
-    let app = Router::new()
-        .route("/decks", get(list_decks))
+    Router::new()
+        .route("/readyz", get(readyz))
+        .route("/decks", get(list_decks).post(create_deck))
         .route("/due", get(due_cards))
+        .route("/due/count", get(due_count))
         .route("/review", post(post_review))
+        .route("/cards", get(list_cards))
+        .route("/cards/:id", patch(patch_card))
         .with_state(state)
-        .layer(TraceLayer::new_for_http());
+        .layer(TraceLayer::new_for_http())
+}
+
+pub async fn run(
+    repo: Arc<dyn Repository>,
+    addr: SocketAddr,
+    events: Option<Arc<EventLog>>,
+) -> anyhow::Result<()> {
+    let app = router(repo.clone(), events);
 
     let listener = TcpListener::bind(addr).await?;
-    axum::serve(listener, app.into_make_service()).await?;
+    axum::serve(listener, app.into_make_service())
+        .with_graceful_shutdown(async {
+            tokio::signal::ctrl_c().await.ok();
+        })
+        .await?;
+    repo.close().await?;
     Ok(())
 }
 
 pub async fn resolve_deck<R: Repository + ?Sized>(repo: &R, sel: &str) -> anyhow::Result<Deck> {
     if let Ok(id) = uuid::Uuid::parse_str(sel) {
-        if let Ok(d) = repo.get_deck(id).await { return Ok(d); }
+        if let Ok(d) = repo.get_deck(id).await {
+            return Ok(d);
+        }
     }
     let decks = repo.list_decks().await?;
-    if let Some(d) = decks.into_iter().find(|d| d.name.eq_ignore_ascii_case(sel)) {
-        return Ok(d);
+    let mut found = decks
+        .into_iter()
+        .filter(|d| d.name.eq_ignore_ascii_case(sel));
+    match (found.next(), found.next()) {
+        (Some(d), None) => Ok(d),
+        (Some(_), Some(_)) => Err(CoreError::Conflict("ambiguous deck name, use the id").into()),
+        (None, _) => anyhow::bail!("deck not found"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use flashmaster_core::repo::memory::MemoryRepo;
+    use tower::ServiceExt;
+
+    async fn post_grade(grade: &str) -> StatusCode {
+        post_review_for(grade, false, "/review").await
+    }
+
+    async fn post_review_for(grade: &str, suspended: bool, uri: &str) -> StatusCode {
+        let repo = Arc::new(MemoryRepo::new());
+        let deck = repo.create_deck("Spanish").await.unwrap();
+        let card = repo
+            .add_card(deck.id, "hola", "hello", None, &[])
+            .await
+            .unwrap();
+        repo.set_suspended(card.id, suspended).await.unwrap();
+        let body = format!(r#"{{"card_id":"{}","grade":{}}}"#, card.id, grade);
+        let req = Request::post(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(body))
+            .unwrap();
+        router(repo, None).oneshot(req).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn review_accepts_numeric_grade() {
+        assert_eq!(post_grade("3").await, StatusCode::NO_CONTENT);
+    }
+
+    #[tokio::test]
+    async fn review_accepts_string_grade() {
+        assert_eq!(post_grade(r#""easy""#).await, StatusCode::NO_CONTENT);
+    }
+
+    #[tokio::test]
+    async fn review_of_suspended_card_is_rejected_unless_forced() {
+        assert_eq!(
+            post_review_for("3", true, "/review").await,
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            post_review_for("3", true, "/review?force=true").await,
+            StatusCode::NO_CONTENT
+        );
+    }
+
+    #[tokio::test]
+    async fn patch_card_changes_only_given_fields() {
+        let repo = Arc::new(MemoryRepo::new());
+        let deck = repo.create_deck("Spanish").await.unwrap();
+        let card = repo
+            .add_card(deck.id, "hola", "hello", Some("greeting"), &[])
+            .await
+            .unwrap();
+        let patch = |uri: String, body: &'static str| {
+            Request::patch(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        let app = router(repo.clone(), None);
+        let res = app
+            .clone()
+            .oneshot(patch(
+                format!("/cards/{}", card.id),
+                r#"{"back":"hi","hint":null}"#,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let stored = repo.get_card(card.id).await.unwrap();
+        assert_eq!(
+            (stored.front.as_str(), stored.back.as_str(), stored.hint),
+            ("hola", "hi", None)
+        );
+
+        let res = app
+            .clone()
+            .oneshot(patch(format!("/cards/{}", uuid::Uuid::new_v4()), "{}"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        let res = app
+            .oneshot(patch(format!("/cards/{}", card.id), r#"{"reps":0}"#))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn exhausted_pool_is_503_with_retry_after() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fm.sqlite3");
+        std::fs::File::create(&path).unwrap();
+        let opts = flashmaster_sqlite::SqliteOpts {
+            acquire_timeout: Some(std::time::Duration::from_millis(50)),
+            ..Default::default()
+        };
+        let repo = Arc::new(
+            flashmaster_sqlite::SqliteRepo::open_file_with(&path, &opts)
+                .await
+                .unwrap(),
+        );
+        let mut held = Vec::new();
+        for _ in 0..5 {
+            held.push(repo.pool().acquire().await.unwrap());
+        }
+
+        let res = router(repo.clone(), None)
+            .oneshot(Request::get("/decks").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(res.headers()["retry-after"], "1");
+    }
+
+    #[tokio::test]
+    async fn readyz_follows_store_ping() {
+        let ready = |repo: Arc<dyn Repository>| async move {
+            let req = Request::get("/readyz").body(Body::empty()).unwrap();
+            router(repo, None).oneshot(req).await.unwrap().status()
+        };
+        assert_eq!(
+            ready(Arc::new(MemoryRepo::new())).await,
+            StatusCode::NO_CONTENT
+        );
+
+        let repo = Arc::new(flashmaster_sqlite::SqliteRepo::open_memory().await.unwrap());
+        repo.pool().close().await;
+        assert_eq!(ready(repo).await, StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn due_count_reports_phases() {
+        let repo = Arc::new(MemoryRepo::new());
+        let deck = repo.create_deck("Spanish").await.unwrap();
+        repo.add_card(deck.id, "hola", "hello", None, &[])
+            .await
+            .unwrap();
+        let mut due = repo
+            .add_card(deck.id, "adiós", "bye", None, &[])
+            .await
+            .unwrap();
+        due.reps = 1;
+        repo.update_card(&due).await.unwrap();
+
+        let req = Request::get("/due/count?deck=spanish&include_new=true")
+            .body(Body::empty())
+            .unwrap();
+        let res = router(repo, None).oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+            serde_json::json!({"new": 1, "due": 1, "lapsed": 0})
+        );
+    }
+
+    #[tokio::test]
+    async fn deck_counts_only_when_asked() {
+        let repo = Arc::new(MemoryRepo::new());
+        let deck = repo.create_deck("Spanish").await.unwrap();
+        repo.add_card(deck.id, "hola", "hello", None, &[])
+            .await
+            .unwrap();
+        let list = |uri: &'static str| {
+            let app = router(repo.clone(), None);
+            async move {
+                let res = app
+                    .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+            }
+        };
+
+        assert!(list("/decks").await[0].get("counts").is_none());
+        assert_eq!(
+            list("/decks?counts=true").await[0]["counts"],
+            serde_json::json!({"total": 1, "new": 1, "due": 0, "suspended": 0})
+        );
+    }
+
+    #[tokio::test]
+    async fn review_rejects_out_of_range_grade() {
+        assert_eq!(post_grade("7").await, StatusCode::BAD_REQUEST);
+        assert_eq!(post_grade(r#""meh""#).await, StatusCode::BAD_REQUEST);
     }
-    anyhow::bail!("deck not found")
 }
@@ -1,309 +1,2616 @@
-use crate::cli::opts::*;
 use crate::api::server as api_server;
+use crate::cli::ics::forecast_to_ics;
+use crate::cli::opts::*;
+use crate::cli::picker::{self, PickItem};
+use crate::cli::revlog::reviews_to_revlog_csv;
+use crate::cli::templates::{self, Template};
 use crate::tui::app::TuiApp;
 
 use anyhow::{anyhow, bail, Result};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use flashmaster_core::{
-    filters::{filter_by_due, filter_not_suspended},
-    scheduler::apply_grade,
-    DueStatus, Grade, Repository,
+    add_card_with_rules, backup_file_name, check_answer_for, edit_tags,
+    filters::{
+        filter_by_tag, filter_by_text, filter_created_between, filter_not_suspended_ref,
+        find_duplicates,
+    },
+    nest_decks, parse_grade_input,
+    pool::{build_cram_pool, build_review_pool, pool_counts, PoolCounts, PoolOpts, PoolOrder},
+    rotate_backups,
+    scheduler::{
+        humanize_ago, humanize_interval, preview_intervals, retrievability, SCHEDULER_NAME,
+    },
+    session::{elapsed_ms, record_grade_timed, ReviewMode},
+    stats::{
+        accuracy_by_hour, accuracy_trend, additions_per_day, average_duration_ms, daily_streak_tz,
+        due_forecast, maturity_breakdown, most_overdue, per_deck_totals, per_tag_totals,
+        reviews_by_hour, summarize, Totals,
+    },
+    AnswerNorm, CardRules, DueStatus, EventLog, FailedCards, GradeAction, GradeScheme, Repository,
+    ReviewEvent, ReviewSession, SavedFilter, SavedFilters, SessionSummary, StruggleTracker, Trend,
+    BACKUP_PREFIX,
+};
+use flashmaster_core::{Card, CardPatch, CoreError, Deck, DeckId, PromptWith, Review};
+use flashmaster_json::paths::{data_root, default_store_file, filters_file, sessions_dir};
+use flashmaster_json::{migrate_file, JsonStore};
+use flashmaster_pg::{
+    conninfo::{redact_url, resolve_url},
+    PostgresRepo,
 };
-use flashmaster_core::{Card, Deck};
-use flashmaster_json::paths::data_root;
-use flashmaster_json::JsonStore;
-use flashmaster_sqlite::SqliteRepo;
-use std::io::{stdin, stdout, Write};
-use std::path::PathBuf;
+use flashmaster_sqlite::{SqliteOpts, SqliteRepo, Synchronous};
+use rand::seq::SliceRandom;
+use std::collections::HashMap;
+use std::io::{stdin, stdout, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::runtime::Runtime;
 use uuid::Uuid;
 
+/// Set from `--quiet` when the CLI starts; read by [`say`].
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Prints confirmations ("ok", "wrote <path>") unless `--quiet`. A command's
+/// actual output (new ids, listings, reports) uses `println!` directly.
+fn say(msg: impl std::fmt::Display) {
+    if !QUIET.load(Ordering::Relaxed) {
+        println!("{msg}");
+    }
+}
+
 pub async fn run_cli(args: Cli) -> Result<()> {
+    QUIET.store(args.quiet, Ordering::Relaxed);
     match &args.cmd {
-        Command::Tui => {
+        Command::Tui(cmd) => {
             // (kept for completeness but main routes TUI directly)
-            let repo = open_repo(&args.store, args.db_path.clone()).await?;
+            let repo = open_repo(&args).await?;
             let rt = Arc::new(Runtime::new()?);
             let mut app = TuiApp::new(repo, rt);
+            app.grade_keys = args.grade_keys.into();
+            (app.goal, app.stop_at_goal) = (cmd.goal.goal, cmd.goal.stop_at_goal);
+            app.rules = CardRules {
+                auto_suspend_tags: args.auto_suspend_tags.clone(),
+                max_field_len: args.max_field_len,
+            };
             app.run()?;
             Ok(())
         }
         Command::Api(api) => {
-            let repo = open_repo(&args.store, args.db_path.clone()).await?;
+            let repo = open_repo(&args).await?;
             let addr: std::net::SocketAddr = api.addr.parse()?;
-            api_server::run(repo, addr).await
+            api_server::run(repo, addr, open_event_log(&args)?).await
+        }
+        Command::Backup(cmd) => {
+            let path = backup_store(&args, cmd, chrono::Local::now()).await?;
+            println!("{}", path.display());
+            Ok(())
+        }
+        Command::Doctor(cmd) => {
+            println!("store: {}", describe_store(&args)?);
+            if cmd.check_store {
+                open_repo(&args).await?.ping().await?;
+                say("store ok");
+            }
+            Ok(())
+        }
+        Command::Migrate => {
+            if !matches!(args.store, StoreKind::Json) {
+                bail!("migrate is for the json store; sqlite and postgres upgrade their schema when opened");
+            }
+            let (file, backups) = default_store_file();
+            let report = migrate_file(&file, &backups, !args.json_compact)?;
+            let Some(backup) = report.backup else {
+                say(format!(
+                    "{} is already at version {}",
+                    file.display(),
+                    report.to
+                ));
+                return Ok(());
+            };
+            say(format!(
+                "upgraded {} from version {} to {}",
+                file.display(),
+                report.from,
+                report.to
+            ));
+            for c in &report.changes {
+                say(format!("  {c}"));
+            }
+            say(format!("original saved to {}", backup.display()));
+            Ok(())
+        }
+        // One write at the end instead of a save plus backup per card.
+        Command::Import(cmd) if cmd.no_backup() && matches!(args.store, StoreKind::Json) => {
+            let store = Arc::new(open_json_store(&args).await?);
+            let rules = CardRules {
+                auto_suspend_tags: args.auto_suspend_tags.clone(),
+                max_field_len: args.max_field_len,
+            };
+            let pick = args.pick && stdin().is_terminal();
+            store
+                .with_backups_disabled(|_| import_cmd(store.clone(), cmd.clone(), pick, &rules))
+                .await?;
+            store.close().await?;
+            Ok(())
         }
         _ => {
-            let repo = open_repo(&args.store, args.db_path.clone()).await?;
-            match args.cmd.clone() {
-                Command::Deck(cmd) => deck_cmd(repo, cmd).await,
-                Command::Card(cmd) => card_cmd(repo, cmd).await,
-                Command::Review(cmd) => review_cmd(repo, cmd).await,
-                Command::Export(cmd) => export_cmd(repo, cmd).await,
-                Command::Import(cmd) => import_cmd(repo, cmd).await,
+            let repo = open_repo(&args).await?;
+            // --pick only makes sense with someone at the keyboard.
+            let pick = args.pick && stdin().is_terminal();
+            let rules = CardRules {
+                auto_suspend_tags: args.auto_suspend_tags.clone(),
+                max_field_len: args.max_field_len,
+            };
+            let out = match args.cmd.clone() {
+                Command::Deck(cmd) => deck_cmd(repo.clone(), cmd, &rules).await,
+                Command::Card(cmd) => card_cmd(repo.clone(), cmd, pick, &rules).await,
+                Command::Tag(cmd) => tag_cmd(repo.clone(), cmd, pick).await,
+                Command::Filter(cmd) => filter_cmd(repo.clone(), cmd, pick).await,
+                Command::Review(cmd) => {
+                    review_cmd(
+                        repo.clone(),
+                        cmd,
+                        pick,
+                        open_event_log(&args)?,
+                        args.grade_keys.into(),
+                    )
+                    .await
+                }
+                Command::Stats(cmd) => stats_cmd(repo.clone(), cmd, pick).await,
+                Command::Export(cmd) => export_cmd(repo.clone(), cmd, pick).await,
+                Command::Import(cmd) => import_cmd(repo.clone(), cmd, pick, &rules).await,
                 _ => unreachable!(),
-            }
+            };
+            repo.close().await?;
+            out
         }
     }
 }
 
-pub async fn open_repo(store: &StoreKind, db_path: Option<PathBuf>) -> Result<Arc<dyn Repository>> {
-    match store {
-        StoreKind::Json => {
-            let s = JsonStore::open_default().await?;
-            Ok(Arc::new(s))
+/// "3 days ago" by local calendar days, or "never".
+fn last_studied(at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> String {
+    let Some(at) = at else {
+        return "never".to_string();
+    };
+    let days = (now.with_timezone(&chrono::Local).date_naive()
+        - at.with_timezone(&chrono::Local).date_naive())
+    .num_days();
+    humanize_ago(days.max(0) as u32)
+}
+
+/// Backend and location of the configured store, with any password redacted.
+fn describe_store(args: &Cli) -> Result<String> {
+    Ok(match args.store {
+        StoreKind::Json => format!("json {}", default_store_file().0.display()),
+        StoreKind::Sqlite => format!("sqlite {}", sqlite_path(args).display()),
+        StoreKind::Postgres => {
+            let url = resolve_url(args.pg_url.as_deref(), args.pg_url_file.as_deref())?;
+            format!("postgres {}", redact_url(&url))
+        }
+    })
+}
+
+/// SQLite database path for `--store sqlite`.
+fn sqlite_path(args: &Cli) -> PathBuf {
+    args.db_path
+        .clone()
+        .unwrap_or_else(|| data_root().join("flashmaster.sqlite3"))
+}
+
+/// Copies the store into the backups dir as of `at` (gzipped with `--gzip`),
+/// then prunes all but the newest `--keep`. Returns the new backup's path.
+async fn backup_store(
+    args: &Cli,
+    cmd: &BackupCmd,
+    at: chrono::DateTime<chrono::Local>,
+) -> Result<PathBuf> {
+    let (dir, ext) = match args.store {
+        StoreKind::Json => (
+            cmd.dir.clone().unwrap_or_else(|| default_store_file().1),
+            "json",
+        ),
+        StoreKind::Sqlite => (
+            cmd.dir
+                .clone()
+                .unwrap_or_else(|| sqlite_path(args).with_extension("backups")),
+            "sqlite3",
+        ),
+        StoreKind::Postgres => {
+            bail!("backup supports the json and sqlite stores; use pg_dump for postgres")
+        }
+    };
+    std::fs::create_dir_all(&dir)?;
+    let dest = dir.join(backup_file_name(BACKUP_PREFIX, at, ext));
+    match args.store {
+        StoreKind::Sqlite => {
+            SqliteRepo::open_file(sqlite_path(args))
+                .await?
+                .backup_to(&dest)
+                .await?
         }
+        _ => {
+            std::fs::copy(default_store_file().0, &dest)?;
+        }
+    }
+    let dest = if cmd.gzip { gzip_file(&dest)? } else { dest };
+    rotate_backups(&dir, BACKUP_PREFIX, cmd.keep)?;
+    Ok(dest)
+}
+
+/// Replaces `path` with `path.gz`.
+fn gzip_file(path: &Path) -> Result<PathBuf> {
+    let mut gz_name = path.as_os_str().to_owned();
+    gz_name.push(".gz");
+    let gz_path = PathBuf::from(gz_name);
+    let mut enc = flate2::write::GzEncoder::new(
+        std::fs::File::create(&gz_path)?,
+        flate2::Compression::default(),
+    );
+    std::io::copy(&mut std::fs::File::open(path)?, &mut enc)?;
+    enc.finish()?.sync_all()?;
+    std::fs::remove_file(path)?;
+    Ok(gz_path)
+}
+
+/// Opens the selected backend. The returned `Arc` is meant to be shared: clone it
+/// for the API server and any background tasks instead of calling this again, so
+/// pool-backed stores keep a single connection pool.
+pub async fn open_repo(args: &Cli) -> Result<Arc<dyn Repository>> {
+    match args.store {
+        StoreKind::Json => Ok(Arc::new(open_json_store(args).await?)),
         StoreKind::Sqlite => {
-            let p = db_path.unwrap_or_else(|| data_root().join("flashmaster.sqlite3"));
+            let p = sqlite_path(args);
             if let Some(parent) = p.parent() {
                 std::fs::create_dir_all(parent).ok();
             }
-            let s = SqliteRepo::open_file(&p).await?;
+            let synchronous = match args.sqlite_synchronous {
+                SqliteSync::Full => Synchronous::Full,
+                SqliteSync::Normal => Synchronous::Normal,
+                SqliteSync::Off => Synchronous::Off,
+            };
+            let opts = SqliteOpts {
+                synchronous,
+                ..Default::default()
+            };
+            let s = SqliteRepo::open_file_with(&p, &opts)
+                .await?
+                .with_unique_deck_names(args.unique_deck_names)
+                .await?;
+            Ok(Arc::new(s))
+        }
+        StoreKind::Postgres => {
+            // PG* env vars / --pg-url-file keep the password out of argv.
+            let url = resolve_url(args.pg_url.as_deref(), args.pg_url_file.as_deref())?;
+            let s = PostgresRepo::connect(&url)
+                .await
+                .map_err(|e| anyhow!("{e} ({})", redact_url(&url)))?
+                .with_unique_deck_names(args.unique_deck_names)
+                .await?;
             Ok(Arc::new(s))
         }
     }
 }
 
-async fn deck_cmd(repo: Arc<dyn Repository>, cmd: DeckCmd) -> Result<()> {
+async fn open_json_store(args: &Cli) -> Result<JsonStore> {
+    Ok(JsonStore::open_default()
+        .await?
+        .with_pretty(!args.json_compact)
+        .with_unique_deck_names(args.unique_deck_names))
+}
+
+async fn deck_cmd(repo: Arc<dyn Repository>, cmd: DeckCmd, rules: &CardRules) -> Result<()> {
     match cmd {
-        DeckCmd::Add { name } => {
-            let d = repo.create_deck(&name).await?;
+        DeckCmd::Add { name, parent } => {
+            let d = match parent {
+                Some(sel) => {
+                    let p = resolve_deck(&*repo, &sel).await?;
+                    repo.create_subdeck(&name, p.id).await?
+                }
+                None => repo.create_deck(&name).await?,
+            };
             println!("{}", d.id);
         }
-        DeckCmd::List => {
-            let mut v = repo.list_decks().await?;
-            v.sort_by_key(|d| d.created_at);
-            for d in v {
-                println!("{}\t{}", d.id, d.name);
+        DeckCmd::Init { list: true, .. } => {
+            for t in templates::TEMPLATES {
+                println!("{:<24} {}", t.name, t.description);
+            }
+        }
+        DeckCmd::Init { template, .. } => {
+            let name = template.unwrap_or_default();
+            let t = templates::find(&name)
+                .ok_or_else(|| anyhow!("unknown template: {name} (see `deck init --list`)"))?;
+            let (deck, n) = init_template(&*repo, t, rules).await?;
+            say(format!("created {deck} with {n} card(s)"));
+        }
+        DeckCmd::List { sort } => {
+            let now = Utc::now();
+            let mut v = repo.list_decks_with_counts(now).await?;
+            match sort {
+                DeckListSort::Position => {}
+                DeckListSort::Created => v.sort_by_key(|(d, _)| d.created_at),
+                // As sort_decks_by_recent: most recently studied first, never-studied last.
+                DeckListSort::Recent => {
+                    v.sort_by_key(|(d, c)| (std::cmp::Reverse(c.last_reviewed_at), d.created_at))
+                }
+            }
+            // Subdecks follow their parent, indented; siblings keep the chosen order.
+            for (depth, (d, counts)) in nest_decks(v, |(d, _)| d) {
+                println!(
+                    "{}\t{}{}\t{}",
+                    d.id,
+                    "  ".repeat(depth),
+                    d.name,
+                    last_studied(counts.last_reviewed_at, now)
+                );
             }
         }
         DeckCmd::Rm { deck } => {
             let d = resolve_deck(&*repo, &deck).await?;
             repo.delete_deck(d.id).await?;
-            println!("ok");
+            say("ok");
+        }
+        DeckCmd::Merge {
+            from,
+            into,
+            on_duplicate,
+        } => {
+            let from = resolve_deck(&*repo, &from).await?;
+            let into = resolve_deck(&*repo, &into).await?;
+            let dropped = merge_decks(&*repo, &from, &into, on_duplicate).await?;
+            say(format!(
+                "merged {} into {} ({} duplicate(s) dropped)",
+                from.name, into.name, dropped
+            ));
+        }
+        DeckCmd::MoveUp { deck } => {
+            let d = resolve_deck(&*repo, &deck).await?;
+            move_deck(&*repo, d.id, -1).await?;
+            say("ok");
+        }
+        DeckCmd::MoveDown { deck } => {
+            let d = resolve_deck(&*repo, &deck).await?;
+            move_deck(&*repo, d.id, 1).await?;
+            say("ok");
+        }
+        DeckCmd::Prompt { deck, side } => {
+            let d = resolve_deck(&*repo, &deck).await?;
+            repo.set_deck_prompt(d.id, side.into()).await?;
+            say("ok");
+        }
+        DeckCmd::Reset { deck, drop_history } => {
+            let deck = resolve_deck(&*repo, &deck).await?;
+            let n = repo.reset_deck(deck.id, !drop_history).await?;
+            say(format!("reset {} card(s) in {}", n, deck.name));
+        }
+        DeckCmd::Stats { sort, format } => {
+            let decks = repo.list_decks().await?;
+            let cards = repo.list_cards(None).await?;
+            let reviews = repo.list_reviews().await?;
+            let rows = deck_leaderboard(&decks, &cards, &reviews, Utc::now(), sort);
+            match format {
+                OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&rows)?),
+                OutputFormat::Text => {
+                    println!(
+                        "{:<24} {:>8} {:>9} {:>6} {:>6}",
+                        "deck", "reviews", "accuracy", "due", "new"
+                    );
+                    for r in rows {
+                        println!(
+                            "{:<24} {:>8} {:>8.1}% {:>6} {:>6}",
+                            r.name,
+                            r.reviews,
+                            r.accuracy * 100.0,
+                            r.due,
+                            r.new
+                        );
+                    }
+                }
+            }
         }
     }
     Ok(())
 }
 
-async fn card_cmd(repo: Arc<dyn Repository>, cmd: CardCmd) -> Result<()> {
+/// Swaps a deck with its neighbour `by` places away (-1 = up) in the listing
+/// order, then renumbers every deck so positions stay dense. Moving past
+/// either end is a no-op.
+async fn move_deck<R: Repository + ?Sized>(repo: &R, id: Uuid, by: isize) -> Result<()> {
+    let mut ids: Vec<Uuid> = repo.list_decks().await?.into_iter().map(|d| d.id).collect();
+    let i = ids
+        .iter()
+        .position(|d| *d == id)
+        .ok_or_else(|| anyhow!("deck not found"))?;
+    let Some(j) = i.checked_add_signed(by).filter(|j| *j < ids.len()) else {
+        return Ok(());
+    };
+    ids.swap(i, j);
+    repo.reorder_decks(&ids).await?;
+    Ok(())
+}
+
+/// Merges `from` into `into`; returns how many duplicate cards were dropped.
+async fn merge_decks<R: Repository + ?Sized>(
+    repo: &R,
+    from: &Deck,
+    into: &Deck,
+    policy: OnDuplicate,
+) -> Result<usize> {
+    if from.id == into.id {
+        bail!("cannot merge a deck into itself");
+    }
+    let drop: Vec<Uuid> = match policy {
+        OnDuplicate::Keep => Vec::new(),
+        OnDuplicate::Skip => {
+            let existing: std::collections::HashSet<String> = repo
+                .list_cards(Some(into.id))
+                .await?
+                .iter()
+                .map(|c| c.front.trim().to_lowercase())
+                .collect();
+            repo.list_cards(Some(from.id))
+                .await?
+                .into_iter()
+                .filter(|c| existing.contains(&c.front.trim().to_lowercase()))
+                .map(|c| c.id)
+                .collect()
+        }
+    };
+    repo.merge_decks(from.id, into.id, &drop).await?;
+    Ok(drop.len())
+}
+
+#[derive(Debug, serde::Serialize)]
+struct DeckStatsRow {
+    id: Uuid,
+    name: String,
+    reviews: u32,
+    accuracy: f32,
+    due: usize,
+    new: usize,
+}
+
+fn deck_leaderboard(
+    decks: &[Deck],
+    cards: &[Card],
+    reviews: &[Review],
+    now: DateTime<Utc>,
+    sort: DeckSort,
+) -> Vec<DeckStatsRow> {
+    let card_to_deck: HashMap<Uuid, Uuid> = cards.iter().map(|c| (c.id, c.deck_id)).collect();
+    let totals = per_deck_totals(reviews, &card_to_deck);
+    let active = filter_not_suspended_ref(cards);
+
+    let mut rows: Vec<DeckStatsRow> = decks
+        .iter()
+        .map(|d| {
+            let t = totals.get(&d.id).cloned().unwrap_or_default();
+            let in_deck = active.iter().filter(|c| c.deck_id == d.id);
+            let (mut due, mut new) = (0, 0);
+            for c in in_deck {
+                match c.due_status(now) {
+                    DueStatus::New => new += 1,
+                    DueStatus::DueToday | DueStatus::Lapsed => due += 1,
+                    DueStatus::Future => {}
+                }
+            }
+            DeckStatsRow {
+                id: d.id,
+                name: d.name.clone(),
+                reviews: t.total,
+                accuracy: t.accuracy(),
+                due,
+                new,
+            }
+        })
+        .collect();
+
+    match sort {
+        DeckSort::Reviews => {
+            rows.sort_by(|a, b| b.reviews.cmp(&a.reviews).then_with(|| a.name.cmp(&b.name)))
+        }
+        DeckSort::Accuracy => rows.sort_by(|a, b| {
+            a.accuracy
+                .total_cmp(&b.accuracy)
+                .then_with(|| a.name.cmp(&b.name))
+        }),
+        DeckSort::Due => rows.sort_by(|a, b| b.due.cmp(&a.due).then_with(|| a.name.cmp(&b.name))),
+    }
+    rows
+}
+
+async fn card_cmd(
+    repo: Arc<dyn Repository>,
+    cmd: CardCmd,
+    pick: bool,
+    rules: &CardRules,
+) -> Result<()> {
     match cmd {
         CardCmd::Add(a) => {
-            let deck = resolve_deck(&*repo, &a.deck).await?;
-            let c = repo
-                .add_card(deck.id, &a.front, &a.back, a.hint.as_deref(), &a.tags)
-                .await?;
+            let deck = select_deck(&*repo, a.deck, pick)
+                .await?
+                .ok_or_else(|| anyhow!("missing --deck (or use --pick)"))?;
+            rules.check_len(
+                a.alt_backs
+                    .iter()
+                    .map(String::as_str)
+                    .chain(a.source.as_deref()),
+            )?;
+            let mut c = add_card_with_rules(
+                &*repo,
+                rules,
+                deck.id,
+                &a.front,
+                &a.back,
+                a.hint.as_deref(),
+                &a.tags,
+            )
+            .await?;
+            if !a.alt_backs.is_empty() || a.source.is_some() {
+                c.alt_backs = a.alt_backs;
+                c.source = a.source;
+                repo.update_card(&c).await?;
+            }
             println!("{}", c.id);
         }
-        CardCmd::List { deck } => {
-            let deck_id = if let Some(sel) = deck {
-                Some(resolve_deck(&*repo, &sel).await?.id)
-            } else {
-                None
-            };
-            let mut cards = repo.list_cards(deck_id).await?;
+        CardCmd::List {
+            deck,
+            tag,
+            created_after,
+            created_before,
+            columns,
+            format,
+        } => {
+            let deck_id = select_deck(&*repo, deck, pick).await?.map(|d| d.id);
+            let mut cards = filter_created_between(
+                &repo.list_cards(deck_id).await?,
+                created_after,
+                created_before,
+            );
+            if let Some(t) = &tag {
+                cards = filter_by_tag(&cards, t);
+            }
             cards.sort_by_key(|c| c.created_at);
-            for c in cards {
-                let tags = if c.tags.is_empty() { "-".to_string() } else { c.tags.join(";") };
-                println!("{}\t{}\t{}\tdeck={}\ttags={}\tsuspended={}", c.id, c.front, c.back, c.deck_id, tags, c.suspended);
+            print_cards(&cards, &columns, format, &HashMap::new())?;
+        }
+        CardCmd::Search {
+            query,
+            deck,
+            columns,
+            format,
+        } => {
+            let deck_id = select_deck(&*repo, deck, pick).await?.map(|d| d.id);
+            let names: HashMap<Uuid, String> = repo
+                .list_decks()
+                .await?
+                .into_iter()
+                .map(|d| (d.id, d.name))
+                .collect();
+            let mut cards = repo.search_cards(&query, deck_id).await?;
+            cards.sort_by(|a, b| {
+                names
+                    .get(&a.deck_id)
+                    .cmp(&names.get(&b.deck_id))
+                    .then(a.created_at.cmp(&b.created_at))
+            });
+            print_cards(&cards, &columns, format, &names)?;
+        }
+        CardCmd::Overdue { deck, limit } => {
+            let deck_id = select_deck(&*repo, deck, pick).await?.map(|d| d.id);
+            let cards = repo.list_cards(deck_id).await?;
+            let fronts: HashMap<Uuid, &str> =
+                cards.iter().map(|c| (c.id, c.front.as_str())).collect();
+            let top = most_overdue(&cards, Utc::now(), limit);
+            if top.is_empty() {
+                println!("nothing overdue");
+            }
+            for (id, days) in top {
+                println!("{days:>5}d\t{id}\t{}", fronts[&id]);
+            }
+        }
+        CardCmd::Show {
+            card_id,
+            history,
+            schedule,
+        } => {
+            let id = select_card(&*repo, card_id, pick).await?;
+            let c = repo.get_card(id).await?;
+            let deck = repo.get_deck(c.deck_id).await?;
+            println!("id:        {}", c.id);
+            println!("deck:      {}", deck.name);
+            println!("front:     {}", c.front);
+            println!("back:      {}", c.display_back());
+            if let Some(h) = &c.hint {
+                println!("hint:      {h}");
+            }
+            if let Some(s) = &c.source {
+                println!("source:    {s}");
+            }
+            if !c.tags.is_empty() {
+                println!("tags:      {}", c.tags.join(", "));
+            }
+            println!(
+                "schedule:  reps {}, lapses {}, ef {:.2}, interval {}d, due {}{}",
+                c.reps,
+                c.lapses,
+                c.ef,
+                c.interval_days,
+                c.due_at.format("%Y-%m-%d"),
+                if c.suspended { " (suspended)" } else { "" }
+            );
+            if history > 0 {
+                let reviews = repo.recent_reviews_for_card(id, history).await?;
+                println!("history:   {} most recent", reviews.len());
+                for r in reviews {
+                    println!(
+                        "  {}  {:<6}  → {}d, ef {:.2}",
+                        r.reviewed_at.format("%Y-%m-%d %H:%M"),
+                        format!("{:?}", r.grade).to_lowercase(),
+                        r.interval_applied,
+                        r.ef_after
+                    );
+                }
+            }
+            if schedule {
+                let reviews = repo.list_reviews_for_card(id).await?;
+                for line in schedule_lines(&c, &reviews, Utc::now()) {
+                    println!("{line}");
+                }
             }
         }
         CardCmd::Rm { card_id } => {
-            let id = parse_uuid(&card_id)?;
+            let id = select_card(&*repo, card_id, pick).await?;
             repo.delete_card(id).await?;
-            println!("ok");
+            say("ok");
+        }
+        CardCmd::Move { card_id, deck } => {
+            let d = resolve_deck(&*repo, &deck).await?;
+            let id = select_card(&*repo, card_id, pick).await?;
+            repo.move_card(id, d.id).await?;
+            say(format!("moved to {}", d.name));
         }
         CardCmd::Edit(e) => {
-            let id = parse_uuid(&e.card_id)?;
-            let mut card = repo.get_card(id).await?;
-
-            if let Some(f) = e.front { card.front = f; }
-            if let Some(b) = e.back { card.back = b; }
-            if e.clear_hint { card.hint = None; }
-            if let Some(h) = e.hint { card.hint = Some(h); }
-
-            if !e.add_tags.is_empty() || !e.rm_tags.is_empty() {
-                let mut tags = card.tags.clone();
-                for t in e.add_tags { if !tags.iter().any(|x| x.eq_ignore_ascii_case(&t)) { tags.push(t); } }
-                if !e.rm_tags.is_empty() {
-                    tags.retain(|x| !e.rm_tags.iter().any(|r| x.eq_ignore_ascii_case(r)));
-                }
-                card.tags = tags;
+            let due = edit_due_at(&e, Utc::now())?;
+            let id = select_card(&*repo, e.card_id, pick).await?;
+            let mut patch = CardPatch {
+                front: e.front,
+                back: e.back,
+                ..Default::default()
+            };
+            if e.clear_hint {
+                patch.hint = Some(None);
+            }
+            if let Some(h) = e.hint {
+                patch.hint = Some(Some(h));
+            }
+            if e.clear_source {
+                patch.source = Some(None);
+            }
+            if let Some(s) = e.source {
+                patch.source = Some(Some(s));
+            }
+
+            // One read, for the tag edit and/or the new due date.
+            let edits_tags = !e.add_tags.is_empty() || !e.rm_tags.is_empty();
+            let card = if edits_tags || due.is_some() {
+                Some(repo.get_card(id).await?)
+            } else {
+                None
+            };
+            if let (true, Some(c)) = (edits_tags, &card) {
+                patch.tags = edit_tags(&c.tags, &e.add_tags, &e.rm_tags);
             }
 
             if e.suspend && e.unsuspend {
                 anyhow::bail!("cannot use --suspend and --unsuspend together");
             } else if e.suspend {
-                card.suspended = true;
+                patch.suspended = Some(true);
             } else if e.unsuspend {
-                card.suspended = false;
+                patch.suspended = Some(false);
+            }
+
+            rules.check_patch(&patch)?;
+            match (due, card) {
+                // A new due date is a scheduling change itself: one full write, patch included.
+                (Some(at), Some(mut card)) => {
+                    patch.apply(&mut card);
+                    card.due_at = at;
+                    repo.update_card(&card).await?;
+                }
+                // Otherwise only the edited fields are written, so a review landing meanwhile
+                // keeps its scheduling. Tags come from the read above; a concurrent tag edit can be lost.
+                _ => {
+                    repo.patch_card(id, patch).await?;
+                }
+            }
+            say("ok");
+        }
+        CardCmd::Dedup { deck, dry_run } => {
+            let deck = select_deck(&*repo, deck, pick)
+                .await?
+                .ok_or_else(|| anyhow!("missing --deck (or use --pick)"))?;
+            let n = dedup_deck(&*repo, deck.id, dry_run).await?;
+            if dry_run {
+                println!("{n} duplicate(s) would be removed from {}", deck.name);
+            } else {
+                say(format!("removed {n} duplicate(s) from {}", deck.name));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The `due_at` requested by `card edit --due/--due-now`, if any.
+fn edit_due_at(e: &CardEdit, now: DateTime<Utc>) -> Result<Option<DateTime<Utc>>> {
+    if e.due_now {
+        return Ok(Some(now));
+    }
+    let Some(spec) = &e.due else { return Ok(None) };
+    let at = parse_due_spec(spec, now).map_err(|m| anyhow!(m))?;
+    if at < now && !e.allow_past {
+        bail!("--due {spec} is in the past (add --allow-past to set it anyway)");
+    }
+    Ok(Some(at))
+}
+
+/// Deletes duplicate cards in a deck, keeping the oldest of each group and
+/// moving the others' reviews onto it, one [`Repository::merge_cards`] call
+/// per group. Returns how many cards were (or, with `dry_run`, would be)
+/// removed.
+async fn dedup_deck<R: Repository + ?Sized>(
+    repo: &R,
+    deck_id: Uuid,
+    dry_run: bool,
+) -> Result<usize> {
+    let cards = repo.list_cards(Some(deck_id)).await?;
+    let mut removed = 0;
+    for group in find_duplicates(&cards) {
+        let (keeper, rest) = group.split_first().expect("duplicate groups have 2+ cards");
+        removed += rest.len();
+        if !dry_run {
+            repo.merge_cards(*keeper, rest).await?;
+        }
+    }
+    Ok(removed)
+}
+
+/// Opens `--event-log` for appending, if given.
+fn open_event_log(args: &Cli) -> Result<Option<Arc<EventLog>>> {
+    Ok(match &args.event_log {
+        Some(p) => Some(Arc::new(EventLog::open(p)?)),
+        None => None,
+    })
+}
+
+async fn tag_cmd(repo: Arc<dyn Repository>, cmd: TagCmd, pick: bool) -> Result<()> {
+    match cmd {
+        TagCmd::Apply {
+            add,
+            rm,
+            deck,
+            tag,
+            query,
+        } => {
+            let deck_id = select_deck(&*repo, deck, pick).await?.map(|d| d.id);
+            let mut cards = repo.list_cards(deck_id).await?;
+            if let Some(t) = &tag {
+                cards = filter_by_tag(&cards, t);
+            }
+            if let Some(q) = &query {
+                cards = filter_by_text(&cards, q);
             }
+            let patches: Vec<(Uuid, CardPatch)> = cards
+                .iter()
+                .filter_map(|c| {
+                    edit_tags(&c.tags, &add, &rm).map(|tags| {
+                        (
+                            c.id,
+                            CardPatch {
+                                tags: Some(tags),
+                                ..Default::default()
+                            },
+                        )
+                    })
+                })
+                .collect();
+            repo.patch_cards(&patches).await?;
+            say(format!("updated {} card(s)", patches.len()));
+        }
+    }
+    Ok(())
+}
 
-            let _ = repo.update_card(&card).await?;
-            println!("ok");
+async fn filter_cmd(repo: Arc<dyn Repository>, cmd: FilterCmd, pick: bool) -> Result<()> {
+    let path = filters_file();
+    let mut saved = SavedFilters::load(&path)?;
+    match cmd {
+        FilterCmd::Add {
+            name,
+            deck,
+            tag,
+            query,
+            due_within,
+        } => {
+            let deck_id = select_deck(&*repo, deck, pick).await?.map(|d| d.id);
+            saved.upsert(SavedFilter {
+                name,
+                deck_id,
+                tag,
+                query,
+                due_within_days: due_within,
+            })?;
+            saved.save(&path)?;
+            say("ok");
+        }
+        FilterCmd::List => {
+            let names: HashMap<Uuid, String> = repo
+                .list_decks()
+                .await?
+                .into_iter()
+                .map(|d| (d.id, d.name))
+                .collect();
+            for f in &saved.filters {
+                println!("{}\t{}", f.name, describe_filter(f, &names));
+            }
+        }
+        FilterCmd::Rm { name } => {
+            saved.remove(&name)?;
+            saved.save(&path)?;
+            say("ok");
         }
     }
     Ok(())
 }
 
-async fn review_cmd(repo: Arc<dyn Repository>, cmd: ReviewCmd) -> Result<()> {
-    let now = Utc::now();
+/// "deck Spanish, tag exam, due within 7d"; "all cards" when nothing is set.
+fn describe_filter(f: &SavedFilter, deck_names: &HashMap<Uuid, String>) -> String {
+    let mut parts = Vec::new();
+    if let Some(id) = f.deck_id {
+        parts.push(format!(
+            "deck {}",
+            deck_names
+                .get(&id)
+                .cloned()
+                .unwrap_or_else(|| id.to_string())
+        ));
+    }
+    if let Some(t) = &f.tag {
+        parts.push(format!("tag {t}"));
+    }
+    if let Some(q) = &f.query {
+        parts.push(format!("query {q:?}"));
+    }
+    if let Some(d) = f.due_within_days {
+        parts.push(format!("due within {d}d"));
+    }
+    if parts.is_empty() {
+        "all cards".to_string()
+    } else {
+        parts.join(", ")
+    }
+}
 
-    let deck_filter = if let Some(sel) = cmd.deck {
-        Some(resolve_deck(&*repo, &sel).await?.id)
-    } else { None };
+/// The cards `review` draws from: those of the saved filter if one is given,
+/// else the deck's (or every) card.
+async fn review_cards(
+    repo: &dyn Repository,
+    deck_id: Option<DeckId>,
+    filter: Option<&SavedFilter>,
+    now: DateTime<Utc>,
+) -> Result<Vec<Card>> {
+    let cards = repo
+        .list_cards(filter.and_then(|f| f.deck_id).or(deck_id))
+        .await?;
+    Ok(match filter {
+        Some(f) => f.select(&cards, now),
+        None => cards,
+    })
+}
 
-    let mut cards = repo.list_cards(deck_filter).await?;
-    cards = filter_not_suspended(&cards);
+async fn review_cmd(
+    repo: Arc<dyn Repository>,
+    cmd: ReviewCmd,
+    pick: bool,
+    events: Option<Arc<EventLog>>,
+    keys: GradeScheme,
+) -> Result<()> {
+    // Picks the pool; each grade is stamped when it's given (unless `--now` pins it).
+    let now = cmd.now.unwrap_or_else(Utc::now);
 
-    let mut pool = Vec::new();
-    if cmd.include_new { pool.extend(filter_by_due(&cards, now, DueStatus::New)); }
-    pool.extend(filter_by_due(&cards, now, DueStatus::DueToday));
-    if cmd.include_lapsed { pool.extend(filter_by_due(&cards, now, DueStatus::Lapsed)); }
+    let saved_filter = match &cmd.filter {
+        Some(name) => Some(
+            SavedFilters::load(&filters_file())?
+                .get(name)
+                .cloned()
+                .ok_or_else(|| anyhow!("no saved filter named {name:?} (see `filter list`)"))?,
+        ),
+        None => None,
+    };
+    let deck_filter = if saved_filter.is_some() {
+        None
+    } else {
+        select_deck(&*repo, cmd.deck.clone(), pick)
+            .await?
+            .map(|d| d.id)
+    };
+    let due_ahead = saved_filter.as_ref().and_then(SavedFilter::due_window);
 
-    pool.sort_by_key(|c| (c.due_at, c.created_at));
-    if pool.is_empty() {
-        println!("no cards due");
+    if cmd.count_only {
+        let cards = review_cards(&*repo, deck_filter, saved_filter.as_ref(), now).await?;
+        println!(
+            "{}",
+            describe_pool_counts(&pool_counts(
+                &review_pool(&cards, &cmd, now, due_ahead),
+                now
+            ))
+        );
         return Ok(());
     }
 
+    // Cram runs are throwaway: no session file to resume, nothing rescheduled.
+    let mut mode = if cmd.cram {
+        ReviewMode::Cram
+    } else {
+        ReviewMode::Scheduled
+    };
+    let mut session_path = (mode == ReviewMode::Scheduled).then(|| match &saved_filter {
+        Some(f) => ReviewSession::file_for_filter(&sessions_dir(), &f.name),
+        None => ReviewSession::file_for(&sessions_dir(), deck_filter),
+    });
+    let saved = match &session_path {
+        Some(p) => ReviewSession::load(p)?,
+        None => None,
+    };
+    let resumed = match saved {
+        Some(s) if !s.is_finished() => {
+            let ans = read_line(&format!(
+                "resume unfinished session ({} left)? [y/N] ",
+                s.remaining().len()
+            ))?;
+            if matches!(ans.trim().to_lowercase().as_str(), "y" | "yes") {
+                Some(s)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    };
+
+    let mut session = match resumed {
+        Some(s) => s,
+        None => {
+            let cards = review_cards(&*repo, deck_filter, saved_filter.as_ref(), now).await?;
+            let pool = review_pool(&cards, &cmd, now, due_ahead);
+            if pool.is_empty() {
+                if let Some(p) = &session_path {
+                    ReviewSession::clear(p)?;
+                }
+                println!("no cards due");
+                return Ok(());
+            }
+            ReviewSession::new(deck_filter, pool.into_iter().map(|c| c.id).collect())
+        }
+    };
+    if let Some(p) = &session_path {
+        session.save(p)?;
+    }
+    if cmd.cram {
+        say("cram mode: grades are not recorded and nothing is rescheduled");
+    }
+
     let mut count = 0usize;
-    for mut card in pool.into_iter().take(cmd.max) {
-        count += 1;
-        println!("\n[{}/{}] {}", count, cmd.max, card.id);
-        println!("Q: {}", card.front);
-        prompt_enter("[enter=show]")?;
-        println!("A: {}", card.back);
-        if let Some(h) = &card.hint { println!("hint: {}", h); }
-        println!("[1=Hard, 2=Medium, 3=Easy, s=skip, q=quit]");
-        let g = loop {
-            let line = read_line("grade> ")?;
-            match line.trim().to_lowercase().as_str() {
-                "1" | "h" | "hard" => break Some(Grade::Hard),
-                "2" | "m" | "med" | "medium" => break Some(Grade::Medium),
-                "3" | "e" | "easy" => break Some(Grade::Easy),
-                "s" | "skip" => break None,
-                "q" | "quit" => return Ok(()),
-                _ => { println!("enter 1/2/3, s, or q"); }
+    let mut struggles = StruggleTracker::default();
+    let mut struggling_fronts: Vec<String> = Vec::new();
+    let mut failed = FailedCards::default();
+    let mut summary = SessionSummary::with_goal(cmd.goal.goal);
+    let prompts: HashMap<DeckId, PromptWith> = repo
+        .list_decks()
+        .await?
+        .into_iter()
+        .map(|d| (d.id, d.prompt_with))
+        .collect();
+    'rounds: loop {
+        let total = session.card_ids.len();
+        while let Some(card_id) = session.current() {
+            // Cards deleted or suspended since the session was saved are dropped silently.
+            let Ok(card) = repo.get_card(card_id).await else {
+                session.advance(None);
+                continue;
+            };
+            if card.suspended {
+                session.advance(None);
+                continue;
             }
-        };
+            count += 1;
+            println!("\n[{}/{}] {}", session.idx + 1, total, card.id);
+            let prompt = prompts.get(&card.deck_id).copied().unwrap_or_default();
+            println!("Q: {}", card.question(prompt));
+            if cmd.typed {
+                let ans = read_line("answer> ")?;
+                println!(
+                    "{}",
+                    if check_answer_for(&card, prompt, &ans, &AnswerNorm::default()) {
+                        "✓ correct"
+                    } else {
+                        "✗ not quite"
+                    }
+                );
+            } else {
+                prompt_enter("[enter=show]")?;
+            }
+            println!("A: {}", card.answer(prompt));
+            let shown = std::time::Instant::now();
+            if let Some(h) = &card.hint {
+                println!("hint: {}", h);
+            }
+            if struggles.is_flagged(card.id) {
+                println!("struggling — consider editing this card");
+            }
+            // Cram grades don't reschedule, so there is nothing to project.
+            let next = if mode == ReviewMode::Scheduled {
+                format!("  next: {}", keys.interval_hint(&preview_intervals(&card)))
+            } else {
+                String::new()
+            };
+            println!("[{}, s=skip, q=quit]{next}", keys.key_hint());
+            let (g, took) = loop {
+                let line = read_line("grade> ")?;
+                match parse_grade_input(&line, keys) {
+                    Some(GradeAction::Grade(g)) => break (Some(g), elapsed_ms(shown)),
+                    Some(GradeAction::Skip) => break (None, 0),
+                    // Session stays on disk so the next `review` can resume here.
+                    Some(GradeAction::Quit) => return Ok(()),
+                    None => println!("enter {}, s, or q", keys.keys()),
+                }
+            };
 
-        if let Some(grade) = g {
-            let out = apply_grade(card, grade);
-            repo.update_card(&out.updated_card).await?;
-            repo.insert_review(&out.review).await?;
-            card = out.updated_card;
-            println!("→ next due in {} day(s)", card.interval_days);
+            session.advance(g.as_ref());
+            let mut reached = false;
+            if let Some(grade) = g {
+                reached = summary.record();
+                failed.record(card.id, &grade);
+                if struggles.record(card.id, &grade) {
+                    println!("struggling — consider editing this card");
+                    struggling_fronts.push(card.front.clone());
+                }
+                let before = cmd.verbose.then(|| card.clone());
+                if let Some(updated) = record_grade_timed(
+                    &*repo,
+                    card,
+                    grade,
+                    mode,
+                    cmd.now.unwrap_or_else(Utc::now),
+                    Some(took),
+                )
+                .await?
+                {
+                    if let (Some(log), Some(ev)) = (&events, ReviewEvent::from_card(&updated)) {
+                        log.append(&ev);
+                        if let Some(e) = log.take_error() {
+                            eprintln!("warning: event log {e}");
+                        }
+                    }
+                    println!("→ next due {}", humanize_interval(updated.interval_days));
+                    if let Some(before) = before {
+                        write_schedule_details(&mut stdout(), &before, &updated)?;
+                    }
+                }
+            }
+            if let Some(p) = &session_path {
+                session.save(p)?;
+            }
+            if reached {
+                println!("\n🎉 goal reached: {} cards reviewed", summary.reviewed);
+                // The saved session keeps the rest for the next `review`.
+                if cmd.goal.stop_at_goal {
+                    break 'rounds;
+                }
+            }
+        }
+        if let Some(p) = &session_path {
+            ReviewSession::clear(p)?;
+        }
+
+        // Re-study rounds drill just the failed cards (fetched fresh above) as a
+        // cram run: their due dates were already set by this session's grades.
+        if failed.is_empty() {
+            break;
         }
+        let ans = read_line(&format!("\nre-study {} failed cards? [y/N] ", failed.len()))?;
+        if !matches!(ans.trim().to_lowercase().as_str(), "y" | "yes") {
+            break;
+        }
+        session = ReviewSession::new(deck_filter, failed.take());
+        mode = ReviewMode::Cram;
+        session_path = None;
     }
 
     println!("\nreviewed {}", count);
+    if let Some(goal) = summary.goal.filter(|_| !summary.goal_reached()) {
+        println!("goal: {}/{goal} graded", summary.reviewed);
+    }
+    if !struggling_fronts.is_empty() {
+        println!("review these:");
+        for f in struggling_fronts {
+            println!("  {f}");
+        }
+    }
+    Ok(())
+}
+
+/// One-line summary of a week-over-week [`Trend`].
+fn describe_trend(t: Trend) -> String {
+    match t {
+        Trend::Improving(d) => format!("accuracy up {:.0}% vs previous week", d * 100.0),
+        Trend::Declining(d) => format!("accuracy down {:.0}% vs previous week", -d * 100.0),
+        Trend::Flat(_) => "accuracy steady vs previous week".to_string(),
+        Trend::Unknown => "not enough reviews yet".to_string(),
+    }
+}
+
+/// The active scheduler, plus any others found in `reviews` (e.g. after switching algorithms).
+fn describe_schedulers(reviews: &[Review]) -> String {
+    let mut others: Vec<&str> = reviews
+        .iter()
+        .map(|r| r.scheduler.as_str())
+        .filter(|s| *s != SCHEDULER_NAME)
+        .collect();
+    others.sort_unstable();
+    others.dedup();
+    if others.is_empty() {
+        SCHEDULER_NAME.to_string()
+    } else {
+        format!("{SCHEDULER_NAME} (history also: {})", others.join(", "))
+    }
+}
+
+/// `card show --schedule`: what each grade would do next, then every
+/// interval applied so far, oldest first.
+fn schedule_lines(card: &Card, reviews: &[Review], now: DateTime<Utc>) -> Vec<String> {
+    let mut lines = vec!["next:".to_string()];
+    for (g, days) in preview_intervals(card) {
+        let due = now + chrono::Duration::days(days.into());
+        lines.push(format!(
+            "  {:<6}  → {days}d, due {}",
+            format!("{g:?}").to_lowercase(),
+            due.format("%Y-%m-%d")
+        ));
+    }
+    let mut past: Vec<&Review> = reviews.iter().collect();
+    past.sort_by_key(|r| r.reviewed_at);
+    let steps: Vec<String> = past
+        .iter()
+        .map(|r| format!("{}d", r.interval_applied))
+        .collect();
+    lines.push(match steps.len() {
+        0 => "intervals: none yet".to_string(),
+        n => format!(
+            "intervals: {} ({n} review{})",
+            steps.join(" → "),
+            if n == 1 { "" } else { "s" }
+        ),
+    });
+    lines
+}
+
+/// One-line `review --count-only` preview.
+fn describe_pool_counts(c: &PoolCounts) -> String {
+    format!(
+        "{} due, {} new, {} lapsed ({} in session)",
+        c.due,
+        c.new,
+        c.lapsed,
+        c.total()
+    )
+}
+
+/// The cards a fresh `review` run offers at `now`.
+/// `due_ahead` also takes in cards due that much later (a saved filter's window).
+fn review_pool(
+    cards: &[Card],
+    cmd: &ReviewCmd,
+    now: DateTime<Utc>,
+    due_ahead: Option<chrono::Duration>,
+) -> Vec<Card> {
+    if cmd.cram {
+        let mut pool = build_cram_pool(cards);
+        if cmd.shuffle {
+            pool.shuffle(&mut rand::thread_rng());
+        }
+        pool.truncate(cmd.max);
+        return pool;
+    }
+    let order = match cmd.order {
+        ReviewOrder::Due => PoolOrder::DueAt,
+        ReviewOrder::Created => PoolOrder::CreatedAt,
+        ReviewOrder::Retrievability => PoolOrder::Retrievability,
+    };
+    let opts = PoolOpts {
+        include_new: cmd.include_new,
+        include_lapsed: cmd.include_lapsed,
+        max: Some(cmd.max),
+        order,
+        order_phases: cmd.order_phases,
+        due_ahead,
+        ..Default::default()
+    };
+    build_review_pool(cards, now, &opts)
+}
+
+async fn stats_cmd(repo: Arc<dyn Repository>, cmd: StatsCmd, pick: bool) -> Result<()> {
+    let deck_filter = select_deck(&*repo, cmd.deck, pick).await?.map(|d| d.id);
+
+    let cards = repo.list_cards(deck_filter).await?;
+    let (since, until) = cmd.range.bounds();
+    let mut reviews = repo.list_reviews_in_range(since, until).await?;
+    // The trend always looks at the last two weeks, whatever --since/--until say.
+    let mut recent = repo
+        .list_reviews_in_range(
+            Utc::now() - chrono::Duration::days(14),
+            DateTime::<Utc>::MAX_UTC,
+        )
+        .await?;
+    if deck_filter.is_some() {
+        let ids: std::collections::HashSet<Uuid> = cards.iter().map(|c| c.id).collect();
+        reviews.retain(|r| ids.contains(&r.card_id));
+        recent.retain(|r| ids.contains(&r.card_id));
+    }
+    let trend = accuracy_trend(&recent, 7);
+
+    let summary = summarize(&reviews);
+    let t = &summary.totals;
+    println!("cards:    {}", cards.len());
+    let m = maturity_breakdown(&cards);
+    println!(
+        "maturity: {} new, {} young, {} mature, {} suspended",
+        m.new, m.young, m.mature, m.suspended
+    );
+    println!(
+        "reviews:  {} (again {}, hard {}, medium {}, easy {})",
+        t.total, t.again, t.hard, t.medium, t.easy
+    );
+    println!("accuracy: {:.1}%", t.accuracy() * 100.0);
+    println!(
+        "streak:   {} day(s)",
+        daily_streak_tz(&reviews, chrono::Local::now().date_naive(), chrono::Local)
+    );
+    println!("trend:    {}", describe_trend(trend));
+    println!("scheduler: {}", describe_schedulers(&reviews));
+    if let Some(ms) = average_duration_ms(&reviews) {
+        println!("time:     {:.1}s per answer", ms / 1000.0);
+    }
+
+    if cmd.growth {
+        println!("\ncards added per day:");
+        let added: Vec<Card> = cards
+            .iter()
+            .filter(|c| cmd.range.contains(c.created_at))
+            .cloned()
+            .collect();
+        for (day, n) in additions_per_day(&added) {
+            println!("{day}\t{n}");
+        }
+    }
+    if cmd.by_hour {
+        let tz = *chrono::Local::now().offset();
+        println!("\nreviews by hour (local time):");
+        let accuracy = accuracy_by_hour(&reviews, tz);
+        for (hour, n) in reviews_by_hour(&reviews, tz).into_iter().enumerate() {
+            if let Some(acc) = accuracy[hour] {
+                println!("{hour:02}:00\t{n}\t{:.1}%", acc * 100.0);
+            }
+        }
+    }
+    if cmd.by_tag {
+        println!("\nreviews by tag:");
+        let mut tags: Vec<(String, Totals)> =
+            per_tag_totals(&cards, &reviews).into_iter().collect();
+        tags.sort_by(|a, b| a.0.cmp(&b.0));
+        for (tag, t) in tags {
+            println!("{tag}\t{}\t{:.1}%", t.total, t.accuracy() * 100.0);
+        }
+    }
     Ok(())
 }
 
-async fn export_cmd(repo: Arc<dyn Repository>, cmd: ExportCmd) -> Result<()> {
+async fn export_cmd(repo: Arc<dyn Repository>, cmd: ExportCmd, pick: bool) -> Result<()> {
     match cmd {
-        ExportCmd::Json { path } => {
+        ExportCmd::Json {
+            path,
+            range,
+            compact,
+        } => {
             let decks = repo.list_decks().await?;
             let mut cards = repo.list_cards(None).await?;
+            cards.retain(|c| range.contains(c.created_at));
             cards.sort_by_key(|c| c.created_at);
-            let bundle = ExportBundle { version: 1, decks, cards };
-            let s = serde_json::to_string_pretty(&bundle)?;
+            let ids: std::collections::HashSet<Uuid> = cards.iter().map(|c| c.id).collect();
+            let mut reviews = repo.list_reviews().await?;
+            reviews.retain(|r| ids.contains(&r.card_id));
+            let bundle = ExportBundle {
+                version: 2,
+                decks,
+                cards,
+                reviews,
+            };
+            let s = if compact {
+                serde_json::to_vec(&bundle)?
+            } else {
+                serde_json::to_vec_pretty(&bundle)?
+            };
             std::fs::write(&path, s)?;
-            println!("wrote {}", path.display());
+            say(format!("wrote {}", path.display()));
         }
-        ExportCmd::Csv { path, deck } => {
-            let deck_id = if let Some(sel) = deck {
-                Some(resolve_deck(&*repo, &sel).await?.id)
-            } else { None };
+        ExportCmd::Csv { path, deck, range } => {
+            let deck_id = select_deck(&*repo, deck, pick).await?.map(|d| d.id);
             let mut cards = repo.list_cards(deck_id).await?;
+            cards.retain(|c| range.contains(c.created_at));
             cards.sort_by_key(|c| c.created_at);
 
             let decks = repo.list_decks().await?;
-            let mut deck_name: std::collections::HashMap<uuid::Uuid, String> =
+            let deck_name: std::collections::HashMap<uuid::Uuid, String> =
                 decks.into_iter().map(|d| (d.id, d.name)).collect();
 
             let mut wtr = csv::Writer::from_path(&path)?;
-            wtr.write_record(["deck","front","back","hint","tags","suspended"])?;
+            wtr.write_record(["deck", "front", "back", "hint", "tags", "suspended"])?;
             for c in cards {
-                let dn = deck_name.remove(&c.deck_id).unwrap_or_else(|| c.deck_id.to_string());
-                let tags = if c.tags.is_empty() { "".to_string() } else { c.tags.join(";") };
+                let dn = deck_name
+                    .get(&c.deck_id)
+                    .cloned()
+                    .unwrap_or_else(|| c.deck_id.to_string());
+                let tags = if c.tags.is_empty() {
+                    "".to_string()
+                } else {
+                    c.tags.join(";")
+                };
                 wtr.write_record([
                     dn,
                     c.front,
                     c.back,
                     c.hint.unwrap_or_default(),
                     tags,
-                    if c.suspended { "1".to_string() } else { "0".to_string() }
+                    if c.suspended {
+                        "1".to_string()
+                    } else {
+                        "0".to_string()
+                    },
                 ])?;
             }
             wtr.flush()?;
-            println!("wrote {}", path.display());
+            say(format!("wrote {}", path.display()));
+        }
+        ExportCmd::RevlogCsv { path, deck } => {
+            let deck_id = select_deck(&*repo, deck, pick).await?.map(|d| d.id);
+            let names: std::collections::HashMap<Uuid, String> = repo
+                .list_decks()
+                .await?
+                .into_iter()
+                .map(|d| (d.id, d.name))
+                .collect();
+            let deck_of: std::collections::HashMap<Uuid, String> = repo
+                .list_cards(deck_id)
+                .await?
+                .into_iter()
+                .map(|c| (c.id, names.get(&c.deck_id).cloned().unwrap_or_default()))
+                .collect();
+            let mut reviews = repo.list_reviews().await?;
+            reviews.retain(|r| deck_of.contains_key(&r.card_id));
+            std::fs::write(&path, reviews_to_revlog_csv(&reviews, &deck_of)?)?;
+            say(format!(
+                "wrote {} ({} review(s))",
+                path.display(),
+                reviews.len()
+            ));
+        }
+        ExportCmd::Ics { path, deck, days } => {
+            let deck_id = select_deck(&*repo, deck, pick).await?.map(|d| d.id);
+            let cards = repo.list_cards(deck_id).await?;
+            let forecast = due_forecast(&cards, Utc::now().date_naive(), days);
+            std::fs::write(&path, forecast_to_ics(&forecast))?;
+            say(format!(
+                "wrote {} ({} day(s) with cards due)",
+                path.display(),
+                forecast.len()
+            ));
         }
     }
     Ok(())
 }
 
-async fn import_cmd(repo: Arc<dyn Repository>, cmd: ImportCmd) -> Result<()> {
+async fn import_cmd(
+    repo: Arc<dyn Repository>,
+    cmd: ImportCmd,
+    pick: bool,
+    rules: &CardRules,
+) -> Result<()> {
     match cmd {
-        ImportCmd::Json { path } => {
+        ImportCmd::Json { path, .. } => {
             let data = std::fs::read_to_string(&path)?;
             let bundle: ExportBundle = serde_json::from_str(&data)?;
-            for d in bundle.decks { let _ = repo.create_deck(&d.name).await; }
-            let decks = repo.list_decks().await?;
-            for c in bundle.cards {
-                let deck = resolve_deck(&*repo, &select_deck_by_id_or_name(&decks, c.deck_id, None)).await?;
-                let _ = repo.add_card(deck.id, &c.front, &c.back, c.hint.as_deref(), &c.tags).await?;
-            }
-            println!("imported");
-        }
-        ImportCmd::Csv { path, deck } => {
-            let mut rdr = csv::Reader::from_path(&path)?;
-            let mut target_deck = None;
-            if let Some(sel) = deck { target_deck = Some(resolve_deck(&*repo, &sel).await?); }
-            for rec in rdr.records() {
-                let rec = rec?;
-                let deck_name = rec.get(0).unwrap_or("").trim();
+            let n = import_bundle(&*repo, bundle, rules).await?;
+            say(format!("imported {n} card(s)"));
+        }
+        ImportCmd::Csv { path, deck, .. } => {
+            let records = read_csv_records(&std::fs::read_to_string(&path)?)?;
+            let target_deck = select_deck(&*repo, deck, pick).await?;
+            for rec in records {
+                let deck_name = rec.get(0).unwrap_or("");
                 let front = rec.get(1).unwrap_or("").to_string();
-                let back  = rec.get(2).unwrap_or("").to_string();
-                let hint  = rec.get(3).map(|s| s.to_string()).filter(|s| !s.is_empty());
-                let tags  = rec.get(4).unwrap_or("").split(';').filter(|s| !s.is_empty()).map(|s| s.to_string()).collect::<Vec<_>>();
-                let suspended = rec.get(5).unwrap_or("0").trim() == "1";
+                let back = rec.get(2).unwrap_or("").to_string();
+                let hint = rec.get(3).map(|s| s.to_string()).filter(|s| !s.is_empty());
+                let tags = rec
+                    .get(4)
+                    .unwrap_or("")
+                    .split(';')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(|s| s.to_string())
+                    .collect::<Vec<_>>();
+                let suspended = rec.get(5).unwrap_or("0") == "1";
 
-                let deck_obj = if let Some(d) = &target_deck { d.clone() } else { ensure_deck_by_name(&*repo, deck_name).await? };
-                let card = repo.add_card(deck_obj.id, &front, &back, hint.as_deref(), &tags).await?;
-                if suspended { repo.set_suspended(card.id, true).await?; }
+                let deck_obj = if let Some(d) = &target_deck {
+                    d.clone()
+                } else {
+                    ensure_deck_by_name(&*repo, deck_name).await?
+                };
+                let card = add_card_with_rules(
+                    &*repo,
+                    rules,
+                    deck_obj.id,
+                    &front,
+                    &back,
+                    hint.as_deref(),
+                    &tags,
+                )
+                .await?;
+                if suspended && !card.suspended {
+                    repo.set_suspended(card.id, true).await?;
+                }
             }
-            println!("imported");
+            say("imported");
         }
     }
     Ok(())
 }
 
 // ===== Helpers =====
-fn parse_uuid(s: &str) -> Result<uuid::Uuid> { Uuid::parse_str(s).map_err(|_| anyhow!("invalid uuid")) }
+/// `deck_names` turns the deck column into names; ids missing from it print as-is.
+fn print_cards(
+    cards: &[Card],
+    columns: &[CardColumn],
+    format: OutputFormat,
+    deck_names: &HashMap<Uuid, String>,
+) -> Result<()> {
+    match format {
+        OutputFormat::Json => {
+            let rows: Vec<serde_json::Map<String, serde_json::Value>> = cards
+                .iter()
+                .map(|c| {
+                    columns
+                        .iter()
+                        .map(|col| {
+                            (
+                                col.name().to_string(),
+                                serde_json::Value::String(card_cell(c, *col, deck_names)),
+                            )
+                        })
+                        .collect()
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&rows)?);
+        }
+        OutputFormat::Text => {
+            for line in render_card_table(cards, columns, deck_names) {
+                println!("{line}");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn card_cell(c: &Card, col: CardColumn, deck_names: &HashMap<Uuid, String>) -> String {
+    match col {
+        CardColumn::Id => c.id.to_string(),
+        CardColumn::Front => c.front.clone(),
+        CardColumn::Back => c.back.clone(),
+        CardColumn::Deck => deck_names
+            .get(&c.deck_id)
+            .cloned()
+            .unwrap_or_else(|| c.deck_id.to_string()),
+        CardColumn::Hint => c.hint.clone().unwrap_or_else(|| "-".to_string()),
+        CardColumn::Tags => {
+            if c.tags.is_empty() {
+                "-".to_string()
+            } else {
+                c.tags.join(";")
+            }
+        }
+        CardColumn::Ef => format!("{:.2}", c.ef),
+        CardColumn::Interval => c.interval_days.to_string(),
+        CardColumn::Due => c.due_at.format("%Y-%m-%d %H:%M").to_string(),
+        CardColumn::Reps => c.reps.to_string(),
+        CardColumn::Suspended => c.suspended.to_string(),
+        CardColumn::Retrievability => format!("{:.2}", retrievability(c, Utc::now())),
+    }
+}
+
+/// Header plus one line per card, padded so columns line up.
+fn render_card_table(
+    cards: &[Card],
+    columns: &[CardColumn],
+    deck_names: &HashMap<Uuid, String>,
+) -> Vec<String> {
+    let mut rows: Vec<Vec<String>> = vec![columns.iter().map(|c| c.name().to_string()).collect()];
+    rows.extend(cards.iter().map(|c| {
+        columns
+            .iter()
+            .map(|col| card_cell(c, *col, deck_names))
+            .collect()
+    }));
+    let widths: Vec<usize> = (0..columns.len())
+        .map(|i| rows.iter().map(|r| r[i].chars().count()).max().unwrap_or(0))
+        .collect();
+    rows.into_iter()
+        .map(|r| {
+            let cells: Vec<String> = r
+                .iter()
+                .zip(&widths)
+                .map(|(v, w)| format!("{v:<w$}"))
+                .collect();
+            cells.join("  ").trim_end().to_string()
+        })
+        .collect()
+}
+
+/// `review -v` output: how grading moved the card's scheduling state.
+fn write_schedule_details(w: &mut impl Write, before: &Card, after: &Card) -> std::io::Result<()> {
+    writeln!(w, "  ef:       {:.2} → {:.2}", before.ef, after.ef)?;
+    writeln!(
+        w,
+        "  interval: {}d → {}d",
+        before.interval_days, after.interval_days
+    )?;
+    writeln!(
+        w,
+        "  due:      {} ({})",
+        after.due_at.format("%Y-%m-%d %H:%M"),
+        humanize_interval(after.interval_days)
+    )
+}
+
+fn parse_uuid(s: &str) -> Result<uuid::Uuid> {
+    Uuid::parse_str(s).map_err(|_| anyhow!("invalid uuid"))
+}
 
 async fn resolve_deck<R: Repository + ?Sized>(repo: &R, sel: &str) -> Result<Deck> {
-    if let Ok(id) = Uuid::parse_str(sel) { if let Ok(d) = repo.get_deck(id).await { return Ok(d); } }
+    if let Ok(id) = Uuid::parse_str(sel) {
+        match repo.get_deck(id).await {
+            Ok(d) => return Ok(d),
+            Err(CoreError::NotFound(_)) => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    // With duplicate names allowed, picking the first match could act on the wrong deck.
+    let mut found = repo
+        .list_decks()
+        .await?
+        .into_iter()
+        .filter(|d| d.name.eq_ignore_ascii_case(sel));
+    match (found.next(), found.next()) {
+        (Some(d), None) => Ok(d),
+        (Some(_), Some(_)) => bail!("ambiguous deck name {sel:?}, use the id"),
+        (None, _) => bail!("deck not found: {}", sel),
+    }
+}
+
+/// Resolves an optional deck selector; with `pick` and no selector, asks via the picker.
+async fn select_deck<R: Repository + ?Sized>(
+    repo: &R,
+    sel: Option<String>,
+    pick: bool,
+) -> Result<Option<Deck>> {
+    if let Some(sel) = sel {
+        return resolve_deck(repo, &sel).await.map(Some);
+    }
+    if !pick {
+        return Ok(None);
+    }
     let decks = repo.list_decks().await?;
-    if let Some(d) = decks.into_iter().find(|d| d.name.eq_ignore_ascii_case(sel)) { return Ok(d); }
-    bail!("deck not found: {}", sel)
+    let items: Vec<PickItem> = decks
+        .iter()
+        .map(|d| PickItem {
+            id: d.id,
+            label: d.name.clone(),
+        })
+        .collect();
+    let id = picker::pick("deck", &items)?.ok_or_else(|| anyhow!("no deck picked"))?;
+    Ok(decks.into_iter().find(|d| d.id == id))
+}
+
+/// Card id from the argument, or from the picker when `pick` is set.
+async fn select_card<R: Repository + ?Sized>(
+    repo: &R,
+    sel: Option<String>,
+    pick: bool,
+) -> Result<Uuid> {
+    if let Some(sel) = sel {
+        return parse_uuid(&sel);
+    }
+    if !pick {
+        bail!("missing card id (or use --pick)");
+    }
+    let decks: HashMap<Uuid, String> = repo
+        .list_decks()
+        .await?
+        .into_iter()
+        .map(|d| (d.id, d.name))
+        .collect();
+    let mut cards = repo.list_cards(None).await?;
+    cards.sort_by_key(|c| c.created_at);
+    let items: Vec<PickItem> = cards
+        .iter()
+        .map(|c| {
+            let deck = decks.get(&c.deck_id).map(String::as_str).unwrap_or("?");
+            PickItem {
+                id: c.id,
+                label: format!("{} → {}  [{}]", c.front, c.back, deck),
+            }
+        })
+        .collect();
+    picker::pick("card", &items)?.ok_or_else(|| anyhow!("no card picked"))
 }
 
 async fn ensure_deck_by_name<R: Repository + ?Sized>(repo: &R, name: &str) -> Result<Deck> {
     let decks = repo.list_decks().await?;
-    if let Some(d) = decks.into_iter().find(|d| d.name.eq_ignore_ascii_case(name)) { return Ok(d); }
+    if let Some(d) = decks
+        .into_iter()
+        .find(|d| d.name.eq_ignore_ascii_case(name))
+    {
+        return Ok(d);
+    }
     let d = repo.create_deck(name).await?;
     Ok(d)
 }
 
-fn prompt_enter(label: &str) -> Result<()> { print!("{label}"); stdout().flush().ok(); let mut s = String::new(); stdin().read_line(&mut s)?; Ok(()) }
-fn read_line(prompt: &str) -> Result<String> { print!("{prompt}"); stdout().flush().ok(); let mut s = String::new(); stdin().read_line(&mut s)?; Ok(s) }
+/// Data rows of an import CSV (the first row is the header). Spreadsheet
+/// exports often start with a UTF-8 BOM and use CRLF; both are dropped and
+/// every field is trimmed so they can't leak into deck names.
+fn read_csv_records(raw: &str) -> Result<Vec<csv::StringRecord>> {
+    let text = raw
+        .strip_prefix('\u{feff}')
+        .unwrap_or(raw)
+        .replace("\r\n", "\n");
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(text.as_bytes());
+    Ok(rdr.records().collect::<Result<_, _>>()?)
+}
+
+fn prompt_enter(label: &str) -> Result<()> {
+    print!("{label}");
+    stdout().flush().ok();
+    let mut s = String::new();
+    stdin().read_line(&mut s)?;
+    Ok(())
+}
+fn read_line(prompt: &str) -> Result<String> {
+    print!("{prompt}");
+    stdout().flush().ok();
+    let mut s = String::new();
+    stdin().read_line(&mut s)?;
+    Ok(s)
+}
+
+/// Version 2 adds `reviews`; version 1 bundles import as before (cards start new).
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+struct ExportBundle {
+    version: u32,
+    decks: Vec<Deck>,
+    cards: Vec<Card>,
+    #[serde(default)]
+    reviews: Vec<Review>,
+}
+
+/// Imports a bundle, carrying each card's scheduling state and review history
+/// over so it resumes where it left off. Cards and reviews keep their ids, so
+/// importing the same bundle again skips the cards already there. Decks it
+/// creates keep the bundle's position, prompt side and nesting; a deck whose
+/// name is taken takes the cards into the existing deck.
+async fn import_bundle<R: Repository + ?Sized>(
+    repo: &R,
+    bundle: ExportBundle,
+    rules: &CardRules,
+) -> Result<usize> {
+    // Bundle deck id -> the deck its cards go to.
+    let mut created: HashMap<DeckId, DeckId> = HashMap::new();
+    // Parents come first, so a subdeck's parent is already mapped.
+    for (_, d) in nest_decks(bundle.decks.iter().collect(), |d| *d) {
+        let new = match d.parent_id.and_then(|p| created.get(&p)) {
+            Some(&parent) => repo.create_subdeck(&d.name, parent).await,
+            None => repo.create_deck(&d.name).await,
+        };
+        let id = match new {
+            Ok(new) => {
+                repo.set_deck_position(new.id, d.position).await?;
+                repo.set_deck_prompt(new.id, d.prompt_with).await?;
+                new.id
+            }
+            // Decks that already exist keep their own settings.
+            Err(CoreError::Conflict(_)) => resolve_deck(repo, &d.name).await?.id,
+            Err(e) => return Err(e.into()),
+        };
+        created.insert(d.id, id);
+    }
+    let mut reviews_by_card: HashMap<Uuid, Vec<Review>> = HashMap::new();
+    for r in bundle.reviews {
+        reviews_by_card.entry(r.card_id).or_default().push(r);
+    }
+
+    let mut n = 0;
+    for c in bundle.cards {
+        rules.check_card(&c)?;
+        if repo.card_exists(c.id).await? {
+            continue;
+        }
+        let deck_id = match created.get(&c.deck_id) {
+            Some(&id) => id,
+            None => resolve_deck(repo, &c.deck_id.to_string()).await?.id,
+        };
+        let suspended = c.suspended || rules.should_suspend(&c.tags);
+        let card = repo
+            .insert_card(Card {
+                deck_id,
+                suspended,
+                ..c
+            })
+            .await?;
+        for r in reviews_by_card.remove(&card.id).unwrap_or_default() {
+            repo.insert_review(&r).await?;
+        }
+        n += 1;
+    }
+    Ok(n)
+}
+
+/// Creates the template's deck through the bundle import path; refuses to
+/// add to a deck that already exists. Returns the deck name and card count.
+async fn init_template<R: Repository + ?Sized>(
+    repo: &R,
+    t: &Template,
+    rules: &CardRules,
+) -> Result<(String, usize)> {
+    let data = t.data();
+    if repo
+        .list_decks()
+        .await?
+        .iter()
+        .any(|d| d.name.eq_ignore_ascii_case(&data.deck))
+    {
+        bail!("deck already exists: {}", data.deck);
+    }
+    let deck = Deck::new(&data.deck);
+    let cards = data
+        .cards
+        .into_iter()
+        .map(|(front, back)| {
+            let mut c = Card::new(deck.id, front, back);
+            c.tags = data.tags.clone();
+            c
+        })
+        .collect();
+    let bundle = ExportBundle {
+        version: 2,
+        decks: vec![deck],
+        cards,
+        reviews: Vec::new(),
+    };
+    let n = import_bundle(repo, bundle, rules).await?;
+    Ok((data.deck, n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use flashmaster_core::{session::record_grade_at, Grade};
+
+    #[test]
+    fn schedule_projection_matches_grading_and_lists_history() {
+        let now = Utc::now();
+        let mut card = Card::new(Uuid::new_v4(), "hola", "hello");
+        let mut reviews = Vec::new();
+        for (days_ago, g) in [(20, Grade::Medium), (19, Grade::Medium), (13, Grade::Easy)] {
+            let out = flashmaster_core::apply_grade_at(card, g, now - Duration::days(days_ago));
+            card = out.updated_card;
+            reviews.push(out.review);
+        }
+        reviews.reverse();
+
+        let lines = schedule_lines(&card, &reviews, now);
+        let easy = flashmaster_core::apply_grade_at(card.clone(), Grade::Easy, now).updated_card;
+        let easy_line = lines
+            .iter()
+            .find(|l| l.trim_start().starts_with("easy"))
+            .unwrap();
+        assert!(
+            easy_line.contains(&format!(
+                "→ {}d, due {}",
+                easy.interval_days,
+                easy.due_at.format("%Y-%m-%d")
+            )),
+            "{easy_line}"
+        );
+
+        let history = lines.last().unwrap();
+        let steps = history
+            .trim_start_matches("intervals: ")
+            .split(" (")
+            .next()
+            .unwrap();
+        assert_eq!(steps.split(" → ").count(), reviews.len());
+        assert!(history.starts_with("intervals: 1d → 6d →"), "{history}");
+        assert_eq!(
+            schedule_lines(&card, &[], now).last().unwrap(),
+            "intervals: none yet"
+        );
+    }
+
+    #[tokio::test]
+    async fn import_honors_auto_suspend_tags() {
+        let deck = Deck::new("Spanish");
+        let mut someday = Card::new(deck.id, "hola", "hello");
+        someday.tags = vec!["someday".into()];
+        let now = Card::new(deck.id, "adiós", "bye");
+        let bundle = ExportBundle {
+            version: 2,
+            decks: vec![deck],
+            cards: vec![someday, now],
+            reviews: vec![],
+        };
+        let rules = CardRules {
+            auto_suspend_tags: vec!["someday".into()],
+            ..Default::default()
+        };
+
+        let repo = flashmaster_core::repo::memory::MemoryRepo::new();
+        import_bundle(&repo, bundle, &rules).await.unwrap();
+        let cards = repo.list_cards(None).await.unwrap();
+        let suspended = |front: &str| cards.iter().find(|c| c.front == front).unwrap().suspended;
+        assert!(suspended("hola"));
+        assert!(!suspended("adiós"));
+    }
+
+    #[tokio::test]
+    async fn import_keeps_scheduling_state_and_history() {
+        let deck = Deck::new("Spanish");
+        let mut card = Card::new(deck.id, "hola", "hello");
+        let due = Utc::now() + Duration::days(12);
+        card.reps = 3;
+        card.interval_days = 12;
+        card.ef = 2.6;
+        card.due_at = due;
+        card.last_grade = Some(Grade::Easy);
+        let reviews = vec![
+            Review::new(
+                card.id,
+                Grade::Medium,
+                Utc::now() - Duration::days(7),
+                1,
+                2.5,
+            ),
+            Review::new(
+                card.id,
+                Grade::Easy,
+                Utc::now() - Duration::days(1),
+                12,
+                2.6,
+            ),
+        ];
+        let bundle = ExportBundle {
+            version: 2,
+            decks: vec![deck],
+            cards: vec![card],
+            reviews,
+        };
+
+        let repo = flashmaster_core::repo::memory::MemoryRepo::new();
+        assert_eq!(
+            import_bundle(&repo, bundle, &CardRules::default())
+                .await
+                .unwrap(),
+            1
+        );
+
+        let imported = repo.list_cards(None).await.unwrap().remove(0);
+        assert_eq!(imported.reps, 3);
+        assert_eq!(imported.interval_days, 12);
+        assert_eq!(imported.due_at, due);
+        assert!(!imported.is_new());
+        assert_eq!(
+            repo.list_reviews_for_card(imported.id).await.unwrap().len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn reimport_keeps_ids_and_nests_under_existing_decks() {
+        let parent = Deck::new("Spanish");
+        let mut verbs = Deck::new("Verbs");
+        verbs.parent_id = Some(parent.id);
+        let card = Card::new(verbs.id, "ir", "to go");
+        let review = Review::new(card.id, Grade::Medium, Utc::now(), 1, 2.5);
+        let bundle = ExportBundle {
+            version: 2,
+            decks: vec![parent, verbs],
+            cards: vec![card.clone()],
+            reviews: vec![review.clone()],
+        };
+
+        let repo = flashmaster_core::repo::memory::MemoryRepo::new();
+        let existing = repo.create_deck("Spanish").await.unwrap();
+        assert_eq!(
+            import_bundle(&repo, bundle.clone(), &CardRules::default())
+                .await
+                .unwrap(),
+            1
+        );
+        let got = repo.get_card(card.id).await.unwrap();
+        assert_eq!(
+            repo.get_deck(got.deck_id).await.unwrap().parent_id,
+            Some(existing.id)
+        );
+        assert_eq!(
+            repo.list_reviews_for_card(card.id).await.unwrap()[0].id,
+            review.id
+        );
+
+        assert_eq!(
+            import_bundle(&repo, bundle, &CardRules::default())
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(repo.list_cards(None).await.unwrap().len(), 1);
+    }
+
+    /// What survives each export format; the README's "Format fidelity" table says the same.
+    #[tokio::test]
+    async fn export_import_fidelity_matrix() {
+        use flashmaster_core::repo::memory::MemoryRepo;
+        let dir = tempfile::tempdir().unwrap();
+        let src: Arc<dyn Repository> = Arc::new(MemoryRepo::new());
+        let deck = src.create_deck("Spanish").await.unwrap();
+        src.set_deck_prompt(deck.id, PromptWith::Back)
+            .await
+            .unwrap();
+        let mut card = src
+            .add_card(deck.id, "hola", "hello", Some("greeting"), &["es".into()])
+            .await
+            .unwrap();
+        (card.reps, card.interval_days, card.ef, card.due_at) =
+            (3, 12, 2.6, Utc::now() + Duration::days(12));
+        card.alt_backs = vec!["hi".into()];
+        card.source = Some("ch1".into());
+        src.update_card(&card).await.unwrap();
+        src.insert_review(&Review::new(card.id, Grade::Easy, Utc::now(), 12, 2.6))
+            .await
+            .unwrap();
+        let other = src
+            .add_card(deck.id, "adiós", "bye", None, &[])
+            .await
+            .unwrap();
+        src.set_suspended(other.id, true).await.unwrap();
+        let content = |c: &Card| {
+            (
+                c.front.clone(),
+                c.back.clone(),
+                c.hint.clone(),
+                c.tags.clone(),
+            )
+        };
+
+        // JSON bundle: everything, ids included.
+        let path = dir.path().join("bundle.json");
+        export_cmd(
+            src.clone(),
+            ExportCmd::Json {
+                path: path.clone(),
+                range: DateRange::default(),
+                compact: false,
+            },
+            false,
+        )
+        .await
+        .unwrap();
+        let dst: Arc<dyn Repository> = Arc::new(MemoryRepo::new());
+        import_cmd(
+            dst.clone(),
+            ImportCmd::Json {
+                path,
+                no_backup: false,
+            },
+            false,
+            &CardRules::default(),
+        )
+        .await
+        .unwrap();
+        let got = dst
+            .search_cards("front:hola", None)
+            .await
+            .unwrap()
+            .remove(0);
+        assert_eq!((got.id, got.created_at), (card.id, card.created_at));
+        assert_eq!(content(&got), content(&card));
+        assert_eq!(
+            (got.reps, got.interval_days, got.ef, got.due_at),
+            (3, 12, 2.6, card.due_at)
+        );
+        assert_eq!(
+            (&got.alt_backs, &got.source),
+            (&card.alt_backs, &card.source)
+        );
+        assert_eq!(dst.list_reviews_for_card(got.id).await.unwrap().len(), 1);
+        assert_eq!(
+            dst.get_deck(got.deck_id).await.unwrap().prompt_with,
+            PromptWith::Back
+        );
+        assert!(dst.search_cards("front:adiós", None).await.unwrap()[0].suspended);
+
+        // CSV: content, deck name and the suspended flag; cards start new.
+        let path = dir.path().join("cards.csv");
+        export_cmd(
+            src.clone(),
+            ExportCmd::Csv {
+                path: path.clone(),
+                deck: None,
+                range: DateRange::default(),
+            },
+            false,
+        )
+        .await
+        .unwrap();
+        let dst: Arc<dyn Repository> = Arc::new(MemoryRepo::new());
+        import_cmd(
+            dst.clone(),
+            ImportCmd::Csv {
+                path,
+                deck: None,
+                no_backup: false,
+            },
+            false,
+            &CardRules::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            dst.list_decks()
+                .await
+                .unwrap()
+                .iter()
+                .map(|d| d.name.as_str())
+                .collect::<Vec<_>>(),
+            ["Spanish"]
+        );
+        let got = dst
+            .search_cards("front:hola", None)
+            .await
+            .unwrap()
+            .remove(0);
+        assert_eq!(content(&got), content(&card));
+        assert!(
+            got.is_new()
+                && got.ef == flashmaster_core::EF_DEFAULT
+                && got.alt_backs.is_empty()
+                && got.source.is_none()
+        );
+        assert!(dst.list_reviews().await.unwrap().is_empty());
+        assert!(dst.search_cards("front:adiós", None).await.unwrap()[0].suspended);
+    }
+
+    #[tokio::test]
+    async fn merge_moves_cards_and_removes_source() {
+        let repo = flashmaster_core::repo::memory::MemoryRepo::new();
+        let from = repo.create_deck("spanish ").await.unwrap();
+        let into = repo.create_deck("Spanish").await.unwrap();
+        repo.add_card(from.id, "hola", "hello", None, &[])
+            .await
+            .unwrap();
+        repo.add_card(from.id, "Gracias", "thanks", None, &[])
+            .await
+            .unwrap();
+        repo.add_card(into.id, "adios", "bye", None, &[])
+            .await
+            .unwrap();
+        repo.add_card(into.id, "gracias", "thanks", None, &[])
+            .await
+            .unwrap();
+
+        let dropped = merge_decks(&repo, &from, &into, OnDuplicate::Skip)
+            .await
+            .unwrap();
+        assert_eq!(dropped, 1);
+
+        let mut fronts: Vec<String> = repo
+            .list_cards(Some(into.id))
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|c| c.front)
+            .collect();
+        fronts.sort();
+        assert_eq!(fronts, ["adios", "gracias", "hola"]);
+        assert!(repo.get_deck(from.id).await.is_err());
+        assert_eq!(repo.list_cards(None).await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn dedup_keeps_oldest_and_its_history() {
+        let repo = flashmaster_core::repo::memory::MemoryRepo::new();
+        let deck = repo.create_deck("Spanish").await.unwrap();
+        let keep = repo
+            .add_card(deck.id, "hola", "hello", None, &[])
+            .await
+            .unwrap();
+        let mut dup = repo
+            .add_card(deck.id, "Hola", "hello ", None, &[])
+            .await
+            .unwrap();
+        dup.created_at = keep.created_at + Duration::seconds(1);
+        repo.update_card(&dup).await.unwrap();
+        repo.add_card(deck.id, "adios", "bye", None, &[])
+            .await
+            .unwrap();
+        repo.insert_review(&Review::new(dup.id, Grade::Easy, Utc::now(), 1, 2.6))
+            .await
+            .unwrap();
+
+        assert_eq!(dedup_deck(&repo, deck.id, true).await.unwrap(), 1);
+        assert_eq!(repo.list_cards(Some(deck.id)).await.unwrap().len(), 3);
+
+        assert_eq!(dedup_deck(&repo, deck.id, false).await.unwrap(), 1);
+        assert_eq!(repo.list_cards(Some(deck.id)).await.unwrap().len(), 2);
+        assert!(!repo.card_exists(dup.id).await.unwrap());
+        assert_eq!(repo.list_reviews_for_card(keep.id).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn duplicate_deck_names_need_the_id() {
+        let repo = flashmaster_core::repo::memory::MemoryRepo::new().with_unique_deck_names(false);
+        let a = repo.create_deck("Verbs").await.unwrap();
+        repo.create_deck("verbs").await.unwrap();
+        let err = resolve_deck(&repo, "Verbs").await.unwrap_err();
+        assert!(err.to_string().contains("ambiguous"), "{err}");
+        assert_eq!(
+            resolve_deck(&repo, &a.id.to_string()).await.unwrap().id,
+            a.id
+        );
+    }
+
+    #[tokio::test]
+    async fn template_init_creates_its_cards_once() {
+        let repo = flashmaster_core::repo::memory::MemoryRepo::new();
+        let t = templates::find("us-states").unwrap();
+        let (name, n) = init_template(&repo, t, &CardRules::default())
+            .await
+            .unwrap();
+        assert_eq!(n, 50);
+        let deck = resolve_deck(&repo, &name).await.unwrap();
+        let cards = repo.list_cards(Some(deck.id)).await.unwrap();
+        assert_eq!(cards.len(), 50);
+        assert!(cards.iter().all(|c| c.is_new() && c.tags == ["geography"]));
+        assert!(init_template(&repo, t, &CardRules::default())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn review_now_makes_pool_and_grading_deterministic() {
+        use clap::Parser;
+        let cli = Cli::try_parse_from(["flashmaster", "review", "--now", "2031-05-01T09:00:00Z"])
+            .unwrap();
+        let Command::Review(cmd) = cli.cmd else {
+            panic!("expected review")
+        };
+        let now = cmd.now.unwrap();
+
+        let repo = flashmaster_core::repo::memory::MemoryRepo::new();
+        let deck = repo.create_deck("Spanish").await.unwrap();
+        let mut due = repo
+            .add_card(deck.id, "hola", "hello", None, &[])
+            .await
+            .unwrap();
+        due.reps = 2;
+        due.interval_days = 6;
+        due.due_at = "2031-05-01T00:00:00Z".parse().unwrap();
+        repo.update_card(&due).await.unwrap();
+        let mut later = repo
+            .add_card(deck.id, "adiós", "bye", None, &[])
+            .await
+            .unwrap();
+        later.reps = 1;
+        later.due_at = "2031-05-02T00:00:00Z".parse().unwrap();
+        repo.update_card(&later).await.unwrap();
+
+        let cards = repo.list_cards(None).await.unwrap();
+        let pool = review_pool(&cards, &cmd, now, None);
+        assert_eq!(pool.iter().map(|c| c.id).collect::<Vec<_>>(), [due.id]);
+
+        let graded = record_grade_at(
+            &repo,
+            pool[0].clone(),
+            Grade::Medium,
+            ReviewMode::Scheduled,
+            now,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(graded.interval_days, 15);
+        assert_eq!(
+            graded.due_at,
+            "2031-05-16T09:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        assert_eq!(
+            repo.list_reviews_for_card(due.id).await.unwrap()[0].reviewed_at,
+            now
+        );
+    }
+
+    #[tokio::test]
+    async fn count_only_reports_phases_without_reviewing() {
+        use clap::Parser;
+        let cli = Cli::try_parse_from([
+            "flashmaster",
+            "review",
+            "--include-new",
+            "--include-lapsed",
+            "--count-only",
+            "--now",
+            "2031-05-10T09:00:00Z",
+        ])
+        .unwrap();
+        let Command::Review(cmd) = cli.cmd else {
+            panic!("expected review")
+        };
+        let now = cmd.now.unwrap();
+
+        let repo = Arc::new(flashmaster_core::repo::memory::MemoryRepo::new());
+        let deck = repo.create_deck("Spanish").await.unwrap();
+        for (front, due_at) in [
+            ("due", "2031-05-10T08:00:00Z"),
+            ("lapsed", "2031-05-01T00:00:00Z"),
+            ("later", "2031-06-01T00:00:00Z"),
+        ] {
+            let mut c = repo.add_card(deck.id, front, "x", None, &[]).await.unwrap();
+            c.reps = 1;
+            c.due_at = due_at.parse().unwrap();
+            repo.update_card(&c).await.unwrap();
+        }
+        for front in ["uno", "dos"] {
+            repo.add_card(deck.id, front, "x", None, &[]).await.unwrap();
+        }
+
+        let cards = repo.list_cards(None).await.unwrap();
+        let counts = pool_counts(&review_pool(&cards, &cmd, now, None), now);
+        assert_eq!(
+            counts,
+            PoolCounts {
+                new: 2,
+                due: 1,
+                lapsed: 1
+            }
+        );
+        assert_eq!(
+            describe_pool_counts(&counts),
+            "1 due, 2 new, 1 lapsed (4 in session)"
+        );
+
+        review_cmd(repo.clone(), cmd, false, None, GradeScheme::default())
+            .await
+            .unwrap();
+        assert!(repo.list_reviews().await.unwrap().is_empty());
+        let due_dates = |cs: &[Card]| cs.iter().map(|c| (c.id, c.due_at)).collect::<Vec<_>>();
+        assert_eq!(
+            due_dates(&repo.list_cards(None).await.unwrap()),
+            due_dates(&cards)
+        );
+    }
+
+    #[tokio::test]
+    async fn sqlite_backups_rotate_and_gzip() {
+        use clap::Parser;
+        use std::io::Read;
+        let tmp = tempfile::tempdir().unwrap();
+        let db = tmp.path().join("fm.sqlite3");
+        std::fs::File::create(&db).unwrap();
+        SqliteRepo::open_file(&db)
+            .await
+            .unwrap()
+            .create_deck("Spanish")
+            .await
+            .unwrap();
+        let backups = tmp.path().join("backups");
+        let (db_arg, dir_arg) = (db.to_str().unwrap(), backups.to_str().unwrap());
+        let cli = Cli::try_parse_from([
+            "flashmaster",
+            "--store",
+            "sqlite",
+            "--db-path",
+            db_arg,
+            "backup",
+            "--dir",
+            dir_arg,
+            "--keep",
+            "2",
+        ])
+        .unwrap();
+        let Command::Backup(cmd) = &cli.cmd else {
+            panic!("expected backup")
+        };
+
+        let start = chrono::Local::now();
+        let mut made = Vec::new();
+        for i in 0..4 {
+            made.push(
+                backup_store(&cli, cmd, start + chrono::Duration::seconds(i))
+                    .await
+                    .unwrap(),
+            );
+        }
+        let mut left: Vec<PathBuf> = std::fs::read_dir(&backups)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect();
+        left.sort();
+        assert_eq!(left, made[2..]);
+        let copy = SqliteRepo::open_file_readonly(&left[1]).await.unwrap();
+        assert_eq!(copy.list_decks().await.unwrap()[0].name, "Spanish");
+
+        let gzip = BackupCmd {
+            gzip: true,
+            ..cmd.clone()
+        };
+        let gz = backup_store(&cli, &gzip, start + chrono::Duration::seconds(10))
+            .await
+            .unwrap();
+        assert!(gz.to_string_lossy().ends_with(".sqlite3.gz"));
+        let mut head = [0u8; 16];
+        flate2::read::GzDecoder::new(std::fs::File::open(&gz).unwrap())
+            .read_exact(&mut head)
+            .unwrap();
+        assert_eq!(&head, b"SQLite format 3\0");
+        assert_eq!(std::fs::read_dir(&backups).unwrap().count(), 2);
+    }
+
+    #[tokio::test]
+    async fn tag_apply_tags_only_query_matches() {
+        use clap::Parser;
+        let repo = Arc::new(flashmaster_core::repo::memory::MemoryRepo::new());
+        let deck = repo.create_deck("Spanish").await.unwrap();
+        let verbs = ["comer", "beber"];
+        for (front, back) in [
+            ("comer", "to eat"),
+            ("beber", "to drink"),
+            ("casa", "house"),
+        ] {
+            repo.add_card(deck.id, front, back, None, &["Spanish".to_string()])
+                .await
+                .unwrap();
+        }
+        let cli = Cli::try_parse_from([
+            "flashmaster",
+            "tag",
+            "apply",
+            "--add",
+            "verbs",
+            "--add",
+            "spanish",
+            "--query",
+            "to ",
+        ])
+        .unwrap();
+        let Command::Tag(cmd) = cli.cmd else {
+            panic!("expected tag")
+        };
+        tag_cmd(repo.clone(), cmd, false).await.unwrap();
+
+        for c in repo.list_cards(None).await.unwrap() {
+            let want: &[&str] = if verbs.contains(&c.front.as_str()) {
+                &["Spanish", "verbs"]
+            } else {
+                &["Spanish"]
+            };
+            assert_eq!(c.tags, want, "{}", c.front);
+        }
+    }
+
+    #[tokio::test]
+    async fn card_edit_due_pushes_or_pulls_the_next_review() {
+        use clap::Parser;
+        let repo = Arc::new(flashmaster_core::repo::memory::MemoryRepo::new());
+        let deck = repo.create_deck("Spanish").await.unwrap();
+        let mut card = repo
+            .add_card(deck.id, "hola", "hello", None, &[])
+            .await
+            .unwrap();
+        card.reps = 3;
+        card.due_at = Utc::now() + Duration::days(30);
+        repo.update_card(&card).await.unwrap();
+        let rules = CardRules::default();
+        let edit = |args: &[&str]| {
+            let cli = Cli::try_parse_from(
+                [&["flashmaster", "card", "edit", &card.id.to_string()], args].concat(),
+            )
+            .unwrap();
+            let Command::Card(cmd) = cli.cmd else {
+                panic!("expected card")
+            };
+            card_cmd(repo.clone(), cmd, false, &rules)
+        };
 
-#[derive(serde::Serialize, serde::Deserialize)]
-struct ExportBundle { version: u32, decks: Vec<Deck>, cards: Vec<Card> }
+        edit(&["--due", "+7d"]).await.unwrap();
+        let due_at = repo.get_card(card.id).await.unwrap().due_at;
+        assert!(
+            (due_at - (Utc::now() + Duration::days(7)))
+                .num_seconds()
+                .abs()
+                < 60
+        );
 
-fn select_deck_by_id_or_name(decks: &[Deck], id: uuid::Uuid, name: Option<String>) -> String {
-    if let Some(d) = decks.iter().find(|d| d.id == id) { d.name.clone() } else if let Some(n) = name { n } else { id.to_string() }
+        assert!(edit(&["--due", "2000-01-01"]).await.is_err());
+        assert_eq!(repo.get_card(card.id).await.unwrap().due_at, due_at);
+        edit(&["--due", "2000-01-01", "--allow-past"])
+            .await
+            .unwrap();
+        assert_eq!(
+            repo.get_card(card.id)
+                .await
+                .unwrap()
+                .due_at
+                .date_naive()
+                .to_string(),
+            "2000-01-01"
+        );
+
+        edit(&["--due", "+30d"]).await.unwrap();
+        edit(&["--due-now"]).await.unwrap();
+        let cards = repo.list_cards(None).await.unwrap();
+        let pool = build_review_pool(&cards, Utc::now(), &PoolOpts::default());
+        assert_eq!(pool.iter().map(|c| c.id).collect::<Vec<_>>(), [card.id]);
+    }
+
+    #[test]
+    fn trend_is_described_as_week_over_week_change() {
+        assert_eq!(
+            describe_trend(Trend::Improving(0.04)),
+            "accuracy up 4% vs previous week"
+        );
+        assert_eq!(
+            describe_trend(Trend::Declining(-0.13)),
+            "accuracy down 13% vs previous week"
+        );
+        assert_eq!(describe_trend(Trend::Unknown), "not enough reviews yet");
+    }
+
+    #[tokio::test]
+    async fn move_deck_swaps_with_neighbour() {
+        let repo = flashmaster_core::repo::memory::MemoryRepo::new();
+        let a = repo.create_deck("A").await.unwrap();
+        let b = repo.create_deck("B").await.unwrap();
+        let c = repo.create_deck("C").await.unwrap();
+        repo.reorder_decks(&[a.id, b.id, c.id]).await.unwrap();
+        let names = |v: Vec<Deck>| v.into_iter().map(|d| d.name).collect::<Vec<_>>();
+
+        move_deck(&repo, c.id, -1).await.unwrap();
+        assert_eq!(names(repo.list_decks().await.unwrap()), ["A", "C", "B"]);
+        move_deck(&repo, a.id, -1).await.unwrap();
+        assert_eq!(
+            names(repo.list_decks().await.unwrap()),
+            ["A", "C", "B"],
+            "already first"
+        );
+        move_deck(&repo, a.id, 1).await.unwrap();
+        assert_eq!(names(repo.list_decks().await.unwrap()), ["C", "A", "B"]);
+    }
+
+    #[test]
+    fn csv_import_strips_bom_and_crlf() {
+        let raw = "\u{feff}deck,front,back,hint,tags,suspended\r\nSpanish , hola,hello ,,greeting; basics,0\r\nSpanish,adiós,bye,,,1\r\n";
+        let recs = read_csv_records(raw).unwrap();
+        assert_eq!(recs.len(), 2);
+        assert_eq!(
+            recs[0].iter().collect::<Vec<_>>(),
+            ["Spanish", "hola", "hello", "", "greeting; basics", "0"]
+        );
+        assert_eq!(&recs[1][5], "1");
+        assert!(recs
+            .iter()
+            .flat_map(|r| r.iter())
+            .all(|f| !f.contains(['\r', '\u{feff}'])));
+    }
+
+    #[test]
+    fn verbose_details_show_ef_change() {
+        let before = Card::new(Uuid::new_v4(), "hola", "hello");
+        let after = flashmaster_core::apply_grade(before.clone(), Grade::Easy).updated_card;
+        let mut out = Vec::new();
+        write_schedule_details(&mut out, &before, &after).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("ef:       2.50 → 2.60"), "{out}");
+        assert!(out.contains("interval: 0d → 1d"), "{out}");
+        assert!(out.contains("tomorrow"), "{out}");
+    }
+
+    #[test]
+    fn card_list_prints_only_selected_columns() {
+        use clap::Parser;
+        let cli =
+            Cli::try_parse_from(["flashmaster", "card", "list", "--columns", "id,ef"]).unwrap();
+        let Command::Card(CardCmd::List { columns, .. }) = cli.cmd else {
+            panic!("expected card list")
+        };
+        assert_eq!(columns, vec![CardColumn::Id, CardColumn::Ef]);
+
+        let card = Card::new(Uuid::new_v4(), "hola", "hello");
+        let lines = render_card_table(std::slice::from_ref(&card), &columns, &HashMap::new());
+        assert_eq!(
+            lines[0].split_whitespace().collect::<Vec<_>>(),
+            ["id", "ef"]
+        );
+        assert_eq!(
+            lines[1].split_whitespace().collect::<Vec<_>>(),
+            [card.id.to_string(), "2.50".to_string()]
+        );
+    }
+
+    #[test]
+    fn card_search_shows_deck_names() {
+        use clap::Parser;
+        let cli = Cli::try_parse_from(["flashmaster", "card", "search", "house"]).unwrap();
+        let Command::Card(CardCmd::Search { columns, .. }) = cli.cmd else {
+            panic!("expected card search")
+        };
+
+        let deck = Deck::new("Spanish");
+        let card = Card::new(deck.id, "casa", "house");
+        let names = HashMap::from([(deck.id, deck.name.clone())]);
+        let lines = render_card_table(std::slice::from_ref(&card), &columns, &names);
+        assert_eq!(
+            lines[0].split_whitespace().collect::<Vec<_>>(),
+            ["deck", "id", "front", "back"]
+        );
+        assert!(lines[1].starts_with("Spanish "), "{}", lines[1]);
+    }
+
+    #[test]
+    fn card_list_rejects_unknown_column() {
+        use clap::Parser;
+        let err = Cli::try_parse_from(["flashmaster", "card", "list", "--columns", "id,bogus"])
+            .unwrap_err();
+        assert!(err.to_string().contains("possible values"));
+    }
+
+    #[test]
+    fn leaderboard_sorts_most_reviewed_first() {
+        let quiet = Deck::new("Quiet");
+        let busy = Deck::new("Busy");
+        let c1 = Card::new(quiet.id, "a", "b");
+        let c2 = Card::new(busy.id, "c", "d");
+        let now = Utc::now();
+        let reviews = vec![
+            Review::new(c1.id, Grade::Easy, now - Duration::days(1), 1, 2.6),
+            Review::new(c2.id, Grade::Easy, now - Duration::days(2), 1, 2.6),
+            Review::new(c2.id, Grade::Hard, now - Duration::days(1), 1, 2.4),
+        ];
+
+        let rows = deck_leaderboard(
+            &[quiet, busy.clone()],
+            &[c1, c2],
+            &reviews,
+            now,
+            DeckSort::Reviews,
+        );
+        assert_eq!(rows[0].id, busy.id);
+        assert_eq!(rows[0].reviews, 2);
+        assert_eq!(rows[1].reviews, 1);
+    }
 }
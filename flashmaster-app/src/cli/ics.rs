@@ -0,0 +1,52 @@
+//! iCalendar output for `export ics`.
+
+use chrono::{Duration, NaiveDate};
+use std::collections::BTreeMap;
+
+/// One all-day VEVENT per day with due cards. Lines end in CRLF as RFC 5545
+/// requires; `DTSTAMP` is pinned to the event day so output is reproducible.
+pub fn forecast_to_ics(forecast: &BTreeMap<NaiveDate, u32>) -> String {
+    let mut out = String::new();
+    let mut line = |s: &str| {
+        out.push_str(s);
+        out.push_str("\r\n");
+    };
+    line("BEGIN:VCALENDAR");
+    line("VERSION:2.0");
+    line("PRODID:-//FlashMaster//Due forecast//EN");
+    line("CALSCALE:GREGORIAN");
+    for (day, &n) in forecast.iter().filter(|(_, &n)| n > 0) {
+        let d = day.format("%Y%m%d");
+        let next = (*day + Duration::days(1)).format("%Y%m%d");
+        let noun = if n == 1 { "card" } else { "cards" };
+        line("BEGIN:VEVENT");
+        line(&format!("UID:flashmaster-due-{d}@flashmaster"));
+        line(&format!("DTSTAMP:{d}T000000Z"));
+        line(&format!("DTSTART;VALUE=DATE:{d}"));
+        line(&format!("DTEND;VALUE=DATE:{next}"));
+        line(&format!("SUMMARY:FlashMaster: {n} {noun} due"));
+        line("TRANSP:TRANSPARENT");
+        line("END:VEVENT");
+    }
+    line("END:VCALENDAR");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn day_with_due_cards_becomes_all_day_event() {
+        let day = NaiveDate::from_ymd_opt(2025, 4, 2).unwrap();
+        let ics = forecast_to_ics(&BTreeMap::from([(day, 3)]));
+
+        let lines: Vec<&str> = ics.split("\r\n").collect();
+        assert_eq!(lines.first(), Some(&"BEGIN:VCALENDAR"));
+        assert_eq!(lines.iter().filter(|l| **l == "BEGIN:VEVENT").count(), 1);
+        assert!(lines.contains(&"DTSTART;VALUE=DATE:20250402"));
+        assert!(lines.contains(&"DTEND;VALUE=DATE:20250403"));
+        assert!(lines.contains(&"SUMMARY:FlashMaster: 3 cards due"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    }
+}
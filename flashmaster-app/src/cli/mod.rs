@@ -1,3 +1,6 @@
 pub mod commands;
+pub mod ics;
 pub mod opts;
-
+pub mod picker;
+pub mod revlog;
+pub mod templates;
@@ -1,10 +1,115 @@
-use clap::{Parser, Subcommand, Args, ValueEnum};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
+use flashmaster_core::{GradeScheme, Phase, PromptWith, MAX_FIELD_LEN};
 use std::path::PathBuf;
 
+/// Shared `--since/--until` options. `since` is inclusive, `until` exclusive;
+/// both accept anything `parse_date_spec` does.
+#[derive(Debug, Args, Clone, Default)]
+pub struct DateRange {
+    /// Start (inclusive): YYYY-MM-DD, today, yesterday, or 7d / 2w / 3m ago
+    #[arg(long, value_parser = parse_date_arg)]
+    pub since: Option<DateTime<Utc>>,
+    /// End (exclusive), same forms as --since
+    #[arg(long, value_parser = parse_date_arg)]
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl DateRange {
+    pub fn contains(&self, t: DateTime<Utc>) -> bool {
+        self.since.is_none_or(|s| t >= s) && self.until.is_none_or(|u| t < u)
+    }
+
+    /// The range as `[since, until)`, open ends widened to the extremes.
+    pub fn bounds(&self) -> (DateTime<Utc>, DateTime<Utc>) {
+        (
+            self.since.unwrap_or(DateTime::<Utc>::MIN_UTC),
+            self.until.unwrap_or(DateTime::<Utc>::MAX_UTC),
+        )
+    }
+}
+
+fn parse_date_arg(s: &str) -> Result<DateTime<Utc>, String> {
+    parse_date_spec(s, Utc::now())
+}
+
+/// Resolves `YYYY-MM-DD`, `today`, `yesterday`, or `<n>d|w|m` (days, weeks,
+/// 30-day months before `now`). Calendar forms resolve to midnight UTC.
+pub fn parse_date_spec(s: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, String> {
+    let s = s.trim().to_lowercase();
+    let midnight = |d: NaiveDate| d.and_hms_opt(0, 0, 0).expect("midnight").and_utc();
+    match s.as_str() {
+        "today" => return Ok(midnight(now.date_naive())),
+        "yesterday" => return Ok(midnight(now.date_naive() - Duration::days(1))),
+        _ => {}
+    }
+    if let Ok(d) = NaiveDate::parse_from_str(&s, "%Y-%m-%d") {
+        return Ok(midnight(d));
+    }
+    let bad = || format!("invalid date '{s}' (use YYYY-MM-DD, today, yesterday, 7d, 2w, 3m)");
+    let (num, unit) = s.split_at(s.len().saturating_sub(1));
+    if num.is_empty() || !num.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(bad());
+    }
+    let n: i64 = num.parse().map_err(|_| bad())?;
+    let per = match unit {
+        "d" => 1,
+        "w" => 7,
+        "m" => 30,
+        _ => return Err(bad()),
+    };
+    n.checked_mul(per)
+        .and_then(Duration::try_days)
+        .and_then(|back| now.checked_sub_signed(back))
+        .ok_or_else(|| format!("date '{s}' is out of range"))
+}
+
+/// A due date for `card edit --due`: anything [`parse_date_spec`] accepts, or
+/// `+<n>d|w|m` for that long after `now`.
+pub fn parse_due_spec(s: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, String> {
+    match s.trim().to_lowercase().strip_prefix('+') {
+        // `+7d` is `7d` (ago) mirrored around now.
+        Some(rel) if rel.ends_with(['d', 'w', 'm']) => now
+            .checked_add_signed(now - parse_date_spec(rel, now)?)
+            .ok_or_else(|| format!("due date '{s}' is out of range")),
+        Some(_) => Err(format!(
+            "invalid due date '{s}' (use +7d, +2w, +3m, or a date)"
+        )),
+        None => parse_date_spec(s, now),
+    }
+}
+
 #[derive(Debug, Clone, ValueEnum)]
 pub enum StoreKind {
     Json,
     Sqlite,
+    Postgres,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum SqliteSync {
+    Full,
+    Normal,
+    Off,
+}
+
+/// Grade key layout (see [`GradeScheme`]).
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum GradeKeys {
+    /// 1=Hard 2=Medium 3=Easy
+    #[default]
+    Three,
+    /// 1=Again 2=Hard 3=Good 4=Easy
+    Four,
+}
+
+impl From<GradeKeys> for GradeScheme {
+    fn from(k: GradeKeys) -> Self {
+        match k {
+            GradeKeys::Three => GradeScheme::Three,
+            GradeKeys::Four => GradeScheme::Four,
+        }
+    }
 }
 
 #[derive(Debug, Parser, Clone)]
@@ -18,6 +123,56 @@ pub struct Cli {
     #[arg(long)]
     pub db_path: Option<PathBuf>,
 
+    /// Write the JSON store without indentation (about half the size; loads either way)
+    #[arg(long)]
+    pub json_compact: bool,
+
+    /// SQLite `PRAGMA synchronous`; `normal`/`off` trade durability for speed
+    #[arg(long, value_enum, default_value_t = SqliteSync::Full)]
+    pub sqlite_synchronous: SqliteSync,
+
+    /// Postgres URL when --store postgres (visible in `ps`; prefer the options below)
+    #[arg(long)]
+    pub pg_url: Option<String>,
+
+    /// File containing the Postgres URL; takes precedence over PG* env vars and --pg-url
+    #[arg(long)]
+    pub pg_url_file: Option<PathBuf>,
+
+    /// Choose a deck/card interactively when its selector is omitted (TTY only)
+    #[arg(long, global = true)]
+    pub pick: bool,
+
+    /// Skip confirmations like "ok" and "wrote <path>"; ids and listings still print, errors go to stderr
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    /// Append a JSON line per graded review to this file (review and api)
+    #[arg(long, global = true)]
+    pub event_log: Option<PathBuf>,
+
+    /// New cards with this tag start suspended (repeatable; the env var takes a comma list)
+    #[arg(
+        long = "auto-suspend-tag",
+        global = true,
+        env = "FLASHMASTER_AUTO_SUSPEND_TAGS",
+        value_delimiter = ','
+    )]
+    pub auto_suspend_tags: Vec<String>,
+
+    /// Grade keys in the review prompt and TUI
+    #[arg(long, global = true, value_enum, env = "FLASHMASTER_GRADE_KEYS", default_value_t = GradeKeys::Three)]
+    pub grade_keys: GradeKeys,
+
+    /// Reject card fields (front, back, hint, tags) longer than this many bytes when writing
+    #[arg(long, global = true, env = "FLASHMASTER_MAX_FIELD_LEN", default_value_t = MAX_FIELD_LEN)]
+    pub max_field_len: usize,
+
+    /// Refuse a new deck whose name another deck already has (ignoring case); `false`
+    /// allows e.g. `Spanish::Verbs` and `French::Verbs` both named `Verbs`
+    #[arg(long, global = true, env = "FLASHMASTER_UNIQUE_DECK_NAMES", default_value_t = true, action = ArgAction::Set)]
+    pub unique_deck_names: bool,
+
     #[command(subcommand)]
     pub cmd: Command,
 }
@@ -30,52 +185,334 @@ pub enum Command {
     /// Card operations (CLI)
     #[command(subcommand)]
     Card(CardCmd),
+    /// Tag operations across many cards (CLI)
+    #[command(subcommand)]
+    Tag(TagCmd),
+    /// Saved filters ("smart decks") that `review --filter` studies across decks (CLI)
+    #[command(subcommand)]
+    Filter(FilterCmd),
     /// Review loop (CLI)
     Review(ReviewCmd),
+    /// Review statistics for one deck or all decks (CLI)
+    Stats(StatsCmd),
     /// Export data (CLI)
     #[command(subcommand)]
     Export(ExportCmd),
     /// Import data (CLI)
     #[command(subcommand)]
     Import(ImportCmd),
+    /// Copy the JSON or SQLite store into a backups directory (CLI)
+    Backup(BackupCmd),
+    /// Show which store is configured and, with --check-store, whether it answers (CLI)
+    Doctor(DoctorCmd),
+    /// Upgrade the JSON store file to the current format, backing up the original first (CLI)
+    Migrate,
     /// Launch Terminal UI
-    Tui,
+    Tui(TuiCmd),
     /// Launch Axum HTTP API
     Api(ApiCmd),
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DeckListSort {
+    /// Your order (`deck move-up` / `move-down`), then oldest first
+    Position,
+    /// Oldest deck first
+    Created,
+    /// Most recently reviewed first
+    Recent,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum DeckSort {
+    /// Most reviewed first
+    Reviews,
+    /// Lowest accuracy first
+    Accuracy,
+    /// Most due cards first
+    Due,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PromptSide {
+    /// Show the front, reveal the back
+    Front,
+    /// Show the back, reveal the front
+    Back,
+}
+
+impl From<PromptSide> for PromptWith {
+    fn from(s: PromptSide) -> Self {
+        match s {
+            PromptSide::Front => PromptWith::Front,
+            PromptSide::Back => PromptWith::Back,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OnDuplicate {
+    /// Drop the incoming card when the target deck has the same front
+    Skip,
+    /// Keep both cards
+    Keep,
+}
+
 #[derive(Debug, Subcommand, Clone)]
 pub enum DeckCmd {
-    Add { name: String },
+    Add {
+        name: String,
+        /// Nest the new deck under this one (name or id)
+        #[arg(long)]
+        parent: Option<String>,
+    },
+    List {
+        #[arg(long, value_enum, default_value_t = DeckListSort::Position)]
+        sort: DeckListSort,
+    },
+    /// Create a deck from a built-in template
+    Init {
+        #[arg(long, required_unless_present = "list")]
+        template: Option<String>,
+        /// Show the available templates
+        #[arg(long)]
+        list: bool,
+    },
+    Rm {
+        deck: String,
+    },
+    /// Leaderboard across all decks (reviews, accuracy, due, new)
+    Stats {
+        #[arg(long, value_enum, default_value_t = DeckSort::Reviews)]
+        sort: DeckSort,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+    /// Move all cards of <from> into <into>, then delete <from>
+    Merge {
+        from: String,
+        into: String,
+        /// What to do when both decks have a card with the same front
+        #[arg(long, value_enum, default_value_t = OnDuplicate::Skip)]
+        on_duplicate: OnDuplicate,
+    },
+    /// Move a deck one place earlier in deck listings
+    MoveUp {
+        deck: String,
+    },
+    /// Move a deck one place later in deck listings
+    MoveDown {
+        deck: String,
+    },
+    /// Choose which side reviews show as the question (back = answer-first)
+    Prompt {
+        deck: String,
+        #[arg(value_enum)]
+        side: PromptSide,
+    },
+    /// Send every card of the deck back to New
+    Reset {
+        deck: String,
+        /// Also delete the deck's review history
+        #[arg(long)]
+        drop_history: bool,
+    },
+}
+
+#[derive(Debug, Subcommand, Clone)]
+pub enum TagCmd {
+    /// Add and/or remove tags on every card matching the filters (all cards without any)
+    Apply {
+        /// Tag to add (repeatable; skipped where already present, ignoring case)
+        #[arg(long, required_unless_present = "rm")]
+        add: Vec<String>,
+        /// Tag to remove (repeatable, ignoring case)
+        #[arg(long)]
+        rm: Vec<String>,
+        #[arg(long)]
+        deck: Option<String>,
+        /// Only cards carrying this tag
+        #[arg(long)]
+        tag: Option<String>,
+        /// Only cards matching this text, as in `card search`
+        #[arg(long)]
+        query: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand, Clone)]
+pub enum FilterCmd {
+    /// Save a filter (replacing one of the same name); unset criteria match every card
+    Add {
+        /// Letters, digits, `-` and `_`
+        name: String,
+        #[arg(long)]
+        deck: Option<String>,
+        #[arg(long)]
+        tag: Option<String>,
+        /// Text to match, as in `card search` (`source:ch3` etc.)
+        #[arg(long)]
+        query: Option<String>,
+        /// Only cards due within this many days; `review --filter` studies them ahead
+        #[arg(long)]
+        due_within: Option<u32>,
+    },
     List,
-    Rm { deck: String },
+    Rm {
+        name: String,
+    },
 }
 
 #[derive(Debug, Subcommand, Clone)]
 pub enum CardCmd {
     Add(CardAdd),
-    List { #[arg(long)] deck: Option<String> },
-    Rm { card_id: String },
+    List {
+        #[arg(long)]
+        deck: Option<String>,
+        #[arg(long)]
+        tag: Option<String>,
+        /// Only cards created on or after this: YYYY-MM-DD, today, yesterday, or 7d / 2w / 3m ago
+        #[arg(long, value_parser = parse_date_arg)]
+        created_after: Option<DateTime<Utc>>,
+        /// Only cards created before this, same forms as --created-after
+        #[arg(long, value_parser = parse_date_arg)]
+        created_before: Option<DateTime<Utc>>,
+        /// Comma-separated columns to print, in order
+        #[arg(long, value_enum, value_delimiter = ',', default_values_t = CardColumn::DEFAULT)]
+        columns: Vec<CardColumn>,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+    /// Find cards by text in one deck or (without --deck) across all decks
+    Search {
+        query: String,
+        #[arg(long)]
+        deck: Option<String>,
+        /// Comma-separated columns to print, in order
+        #[arg(long, value_enum, value_delimiter = ',', default_values_t = CardColumn::SEARCH)]
+        columns: Vec<CardColumn>,
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+    /// Cards furthest past their due date, most overdue first
+    Overdue {
+        #[arg(long)]
+        deck: Option<String>,
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Print one card and its most recent reviews
+    Show {
+        card_id: Option<String>,
+        /// How many recent reviews to list (0 hides them)
+        #[arg(long, default_value_t = 10)]
+        history: usize,
+        /// Also project the next interval for each grade and list every interval applied so far
+        #[arg(long)]
+        schedule: bool,
+    },
+    Rm {
+        card_id: Option<String>,
+    },
+    /// Move a card to another deck, keeping its schedule and review history
+    Move {
+        card_id: Option<String>,
+        /// Target deck (name or id)
+        #[arg(long)]
+        deck: String,
+    },
     Edit(CardEdit),
+    /// Remove cards with the same front and back, keeping the oldest (history is merged into it)
+    Dedup {
+        #[arg(long)]
+        deck: Option<String>,
+        /// Only report what would be removed
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CardColumn {
+    Id,
+    Front,
+    Back,
+    Deck,
+    Hint,
+    Tags,
+    Ef,
+    Interval,
+    Due,
+    Reps,
+    Suspended,
+    /// Estimated recall probability right now
+    Retrievability,
+}
+
+impl CardColumn {
+    pub const DEFAULT: [CardColumn; 6] = [
+        CardColumn::Id,
+        CardColumn::Front,
+        CardColumn::Back,
+        CardColumn::Deck,
+        CardColumn::Tags,
+        CardColumn::Suspended,
+    ];
+    /// `card search` leads with the deck, since that's usually what you're looking for.
+    pub const SEARCH: [CardColumn; 4] = [
+        CardColumn::Deck,
+        CardColumn::Id,
+        CardColumn::Front,
+        CardColumn::Back,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CardColumn::Id => "id",
+            CardColumn::Front => "front",
+            CardColumn::Back => "back",
+            CardColumn::Deck => "deck",
+            CardColumn::Hint => "hint",
+            CardColumn::Tags => "tags",
+            CardColumn::Ef => "ef",
+            CardColumn::Interval => "interval",
+            CardColumn::Due => "due",
+            CardColumn::Reps => "reps",
+            CardColumn::Suspended => "suspended",
+            CardColumn::Retrievability => "retrievability",
+        }
+    }
 }
 
 #[derive(Debug, Args, Clone)]
 pub struct CardAdd {
     #[arg(long)]
-    pub deck: String,
+    pub deck: Option<String>,
     #[arg(long)]
     pub front: String,
     #[arg(long)]
     pub back: String,
+    /// Another accepted answer for typed review (repeatable)
+    #[arg(long = "alt")]
+    pub alt_backs: Vec<String>,
     #[arg(long)]
     pub hint: Option<String>,
+    /// Where the card came from, e.g. "Chapter 3, p.42"
+    #[arg(long)]
+    pub source: Option<String>,
     #[arg(long = "tag")]
     pub tags: Vec<String>,
 }
 
 #[derive(Debug, Args, Clone)]
 pub struct CardEdit {
-    pub card_id: String,
+    pub card_id: Option<String>,
     #[arg(long)]
     pub front: Option<String>,
     #[arg(long)]
@@ -84,6 +521,10 @@ pub struct CardEdit {
     pub hint: Option<String>,
     #[arg(long)]
     pub clear_hint: bool,
+    #[arg(long)]
+    pub source: Option<String>,
+    #[arg(long)]
+    pub clear_source: bool,
     #[arg(long = "add-tag")]
     pub add_tags: Vec<String>,
     #[arg(long = "rm-tag")]
@@ -92,30 +533,198 @@ pub struct CardEdit {
     pub suspend: bool,
     #[arg(long)]
     pub unsuspend: bool,
+    /// Next review date: YYYY-MM-DD, today, or +7d / +2w / +3m from now (new cards stay new)
+    #[arg(long, conflicts_with = "due_now")]
+    pub due: Option<String>,
+    /// Make the card due immediately
+    #[arg(long)]
+    pub due_now: bool,
+    /// Accept a --due date in the past
+    #[arg(long, requires = "due")]
+    pub allow_past: bool,
 }
 
 #[derive(Debug, Args, Clone)]
 pub struct ReviewCmd {
     #[arg(long)]
     pub deck: Option<String>,
+    /// Study the cards of a saved filter (see `filter add`) instead of a deck
+    #[arg(long, conflicts_with = "deck")]
+    pub filter: Option<String>,
     #[arg(long)]
     pub include_new: bool,
     #[arg(long)]
     pub include_lapsed: bool,
     #[arg(long, default_value_t = 50)]
     pub max: usize,
+    /// Type the answer before it is revealed and have it checked
+    #[arg(long)]
+    pub typed: bool,
+    /// Order within the pool
+    #[arg(long, value_enum, default_value_t = ReviewOrder::Due)]
+    pub order: ReviewOrder,
+    /// Serve phases in this order, e.g. `new,due,lapsed` (default: interleaved by due date)
+    #[arg(long, value_parser = parse_phases)]
+    pub order_phases: Option<[Phase; 3]>,
+    /// Drill every non-suspended card regardless of due dates; grades are not recorded
+    #[arg(long)]
+    pub cram: bool,
+    /// Shuffle the cram pool
+    #[arg(long, requires = "cram")]
+    pub shuffle: bool,
+    /// After each grade, print the ef/interval change and the new due date
+    #[arg(short, long)]
+    pub verbose: bool,
+    /// Pretend it is this instant (RFC 3339) when picking and grading cards
+    #[arg(long, hide = true)]
+    pub now: Option<DateTime<Utc>>,
+    /// Print how many new/due/lapsed cards the session would have, then exit
+    #[arg(long, conflicts_with = "cram")]
+    pub count_only: bool,
+    #[command(flatten)]
+    pub goal: GoalArgs,
+}
+
+/// Shared `--goal/--stop-at-goal` options for `review` and `tui`.
+#[derive(Debug, Args, Clone, Copy, Default)]
+pub struct GoalArgs {
+    /// Aim to grade N cards this session; a banner marks reaching it
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub goal: Option<u32>,
+    /// End the session once the goal is reached; the rest stays resumable
+    #[arg(long, requires = "goal")]
+    pub stop_at_goal: bool,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct TuiCmd {
+    #[command(flatten)]
+    pub goal: GoalArgs,
+}
+
+/// Parses a comma-separated permutation of `new`, `due` and `lapsed`.
+pub fn parse_phases(s: &str) -> Result<[Phase; 3], String> {
+    let bad = || format!("invalid phase order '{s}' (use a permutation of new,due,lapsed)");
+    let phases: Vec<Phase> = s
+        .split(',')
+        .map(|p| match p.trim().to_lowercase().as_str() {
+            "new" => Ok(Phase::New),
+            "due" => Ok(Phase::Due),
+            "lapsed" => Ok(Phase::Lapsed),
+            _ => Err(bad()),
+        })
+        .collect::<Result<_, _>>()?;
+    let arr: [Phase; 3] = phases.try_into().map_err(|_| bad())?;
+    if arr[0] == arr[1] || arr[0] == arr[2] || arr[1] == arr[2] {
+        return Err(bad());
+    }
+    Ok(arr)
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ReviewOrder {
+    /// Earliest due first
+    Due,
+    /// Oldest card first
+    Created,
+    /// Lowest estimated recall probability first
+    Retrievability,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct StatsCmd {
+    #[arg(long)]
+    pub deck: Option<String>,
+    /// Also show cards added per day
+    #[arg(long)]
+    pub growth: bool,
+    /// Also show review count and accuracy per hour of day (local time)
+    #[arg(long)]
+    pub by_hour: bool,
+    /// Also show review count and accuracy per tag
+    #[arg(long)]
+    pub by_tag: bool,
+    #[command(flatten)]
+    pub range: DateRange,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct BackupCmd {
+    /// Where backups go (default: the JSON store's backups dir, or `<db>.backups` for SQLite)
+    #[arg(long)]
+    pub dir: Option<PathBuf>,
+    /// Keep only the newest N backups in the directory
+    #[arg(long, default_value_t = 10)]
+    pub keep: usize,
+    /// Compress the copy (`.json.gz` / `.sqlite3.gz`)
+    #[arg(long)]
+    pub gzip: bool,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct DoctorCmd {
+    /// Open the store and ping it; exits non-zero if it doesn't answer
+    #[arg(long)]
+    pub check_store: bool,
 }
 
 #[derive(Debug, Subcommand, Clone)]
 pub enum ExportCmd {
-    Json { path: PathBuf },
-    Csv { path: PathBuf, #[arg(long)] deck: Option<String> },
+    Json {
+        path: PathBuf,
+        #[command(flatten)]
+        range: DateRange,
+        /// No indentation (smaller file; `import json` reads either)
+        #[arg(long)]
+        compact: bool,
+    },
+    Csv {
+        path: PathBuf,
+        #[arg(long)]
+        deck: Option<String>,
+        #[command(flatten)]
+        range: DateRange,
+    },
+    /// Review history, one CSV row per graded review (not card state like `csv`)
+    RevlogCsv {
+        path: PathBuf,
+        #[arg(long)]
+        deck: Option<String>,
+    },
+    /// Due-card forecast as an iCalendar file (one all-day event per day)
+    Ics {
+        path: PathBuf,
+        #[arg(long)]
+        deck: Option<String>,
+        #[arg(long, default_value_t = 30)]
+        days: u32,
+    },
 }
 
 #[derive(Debug, Subcommand, Clone)]
 pub enum ImportCmd {
-    Json { path: PathBuf },
-    Csv { path: PathBuf, #[arg(long)] deck: Option<String> },
+    Json {
+        path: PathBuf,
+        /// JSON store only: write the data file once at the end, with no backup
+        #[arg(long)]
+        no_backup: bool,
+    },
+    Csv {
+        path: PathBuf,
+        #[arg(long)]
+        deck: Option<String>,
+        /// JSON store only: write the data file once at the end, with no backup
+        #[arg(long)]
+        no_backup: bool,
+    },
+}
+
+impl ImportCmd {
+    pub fn no_backup(&self) -> bool {
+        match self {
+            ImportCmd::Json { no_backup, .. } | ImportCmd::Csv { no_backup, .. } => *no_backup,
+        }
+    }
 }
 
 #[derive(Debug, Args, Clone)]
@@ -124,3 +733,77 @@ pub struct ApiCmd {
     #[arg(long, default_value = "127.0.0.1:8080")]
     pub addr: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn fixed_now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 3, 15, 18, 30, 0).unwrap()
+    }
+
+    #[test]
+    fn absolute_date_is_midnight_utc() {
+        let t = parse_date_spec("2025-01-02", fixed_now()).unwrap();
+        assert_eq!(t, Utc.with_ymd_and_hms(2025, 1, 2, 0, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn relative_days_and_weeks() {
+        assert_eq!(
+            parse_date_spec("7d", fixed_now()).unwrap(),
+            fixed_now() - Duration::days(7)
+        );
+        assert_eq!(
+            parse_date_spec("2w", fixed_now()).unwrap(),
+            fixed_now() - Duration::days(14)
+        );
+    }
+
+    #[test]
+    fn today_is_start_of_day() {
+        let t = parse_date_spec("today", fixed_now()).unwrap();
+        assert_eq!(t, Utc.with_ymd_and_hms(2025, 3, 15, 0, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn due_spec_plus_is_from_now() {
+        assert_eq!(
+            parse_due_spec("+7d", fixed_now()).unwrap(),
+            fixed_now() + Duration::days(7)
+        );
+        assert_eq!(
+            parse_due_spec("+2W", fixed_now()).unwrap(),
+            fixed_now() + Duration::days(14)
+        );
+        assert_eq!(
+            parse_due_spec("2025-06-01", fixed_now()).unwrap(),
+            Utc.with_ymd_and_hms(2025, 6, 1, 0, 0, 0).unwrap()
+        );
+        assert!(parse_due_spec("+today", fixed_now()).is_err());
+        assert!(parse_due_spec("+7y", fixed_now()).is_err());
+    }
+
+    #[test]
+    fn phase_order_must_be_a_permutation() {
+        assert_eq!(
+            parse_phases("new, due,lapsed").unwrap(),
+            [Phase::New, Phase::Due, Phase::Lapsed]
+        );
+        assert!(parse_phases("new,new,due").is_err());
+        assert!(parse_phases("new,due").is_err());
+    }
+
+    #[test]
+    fn garbage_is_rejected() {
+        assert!(parse_date_spec("soon", fixed_now()).is_err());
+        assert!(parse_date_spec("7y", fixed_now()).is_err());
+        assert!(parse_date_spec("-7d", fixed_now()).is_err());
+        assert!(parse_date_spec("+7d", fixed_now()).is_err());
+        assert!(parse_date_spec("d", fixed_now()).is_err());
+        assert!(parse_date_spec("9223372036854775807m", fixed_now()).is_err());
+        assert!(parse_date_spec("99999999999d", fixed_now()).is_err());
+        assert!(parse_due_spec("+99999999999d", fixed_now()).is_err());
+    }
+}
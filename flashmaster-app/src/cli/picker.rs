@@ -0,0 +1,144 @@
+//! Minimal fuzzy picker behind `--pick`, used when a deck or card selector is
+//! omitted. The matching lives in `filter_items` so it can be tested without a
+//! terminal.
+
+use anyhow::Result;
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::Print,
+    terminal::{self, disable_raw_mode, enable_raw_mode, Clear, ClearType},
+};
+use std::io::{stdout, Stdout, Write};
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct PickItem {
+    pub id: Uuid,
+    pub label: String,
+}
+
+/// Items whose label contains the query's characters in order, ignoring case.
+/// Tighter matches come first, then earlier ones; ties keep the input order.
+pub fn filter_items<'a>(items: &'a [PickItem], query: &str) -> Vec<&'a PickItem> {
+    let mut hits: Vec<((usize, usize), &PickItem)> = items
+        .iter()
+        .filter_map(|it| match_score(&it.label, query).map(|s| (s, it)))
+        .collect();
+    hits.sort_by_key(|(s, _)| *s);
+    hits.into_iter().map(|(_, it)| it).collect()
+}
+
+/// `(span, start)` of a subsequence match of `query` in `label`.
+fn match_score(label: &str, query: &str) -> Option<(usize, usize)> {
+    let label: Vec<char> = label.to_lowercase().chars().collect();
+    let mut start = None;
+    let mut pos = 0;
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = label[pos..].iter().position(|&c| c == q)? + pos;
+        start.get_or_insert(found);
+        pos = found + 1;
+    }
+    match start {
+        Some(s) => Some((pos - s, s)),
+        None => Some((0, 0)),
+    }
+}
+
+/// Shows the picker on the alternate screen; `None` when cancelled with Esc.
+pub fn pick(prompt: &str, items: &[PickItem]) -> Result<Option<Uuid>> {
+    let mut out = stdout();
+    enable_raw_mode()?;
+    execute!(out, terminal::EnterAlternateScreen, cursor::Hide)?;
+    let res = pick_loop(&mut out, prompt, items);
+    execute!(out, cursor::Show, terminal::LeaveAlternateScreen).ok();
+    disable_raw_mode().ok();
+    res
+}
+
+fn pick_loop(out: &mut Stdout, prompt: &str, items: &[PickItem]) -> Result<Option<Uuid>> {
+    let mut query = String::new();
+    let mut sel = 0usize;
+    loop {
+        let (_, rows) = terminal::size()?;
+        let visible = rows.saturating_sub(2) as usize;
+        let matches = filter_items(items, &query);
+        sel = sel.min(matches.len().min(visible).saturating_sub(1));
+
+        queue!(out, Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+        queue!(out, Print(format!("{prompt}> {query}")))?;
+        for (i, it) in matches.iter().take(visible).enumerate() {
+            let marker = if i == sel { ">" } else { " " };
+            queue!(
+                out,
+                cursor::MoveTo(0, i as u16 + 1),
+                Print(format!("{marker} {}", it.label))
+            )?;
+        }
+        queue!(
+            out,
+            cursor::MoveTo(0, rows.saturating_sub(1)),
+            Print("type to filter · ↑/↓ move · enter select · esc cancel")
+        )?;
+        out.flush()?;
+
+        let Event::Key(k) = event::read()? else {
+            continue;
+        };
+        if k.kind != KeyEventKind::Press {
+            continue;
+        }
+        match k.code {
+            KeyCode::Esc => return Ok(None),
+            KeyCode::Char('c') if k.modifiers.contains(KeyModifiers::CONTROL) => return Ok(None),
+            KeyCode::Enter => return Ok(matches.get(sel).map(|it| it.id)),
+            KeyCode::Up => sel = sel.saturating_sub(1),
+            KeyCode::Down => sel += 1,
+            KeyCode::Backspace => {
+                query.pop();
+                sel = 0;
+            }
+            KeyCode::Char(c) => {
+                query.push(c);
+                sel = 0;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(labels: &[&str]) -> Vec<PickItem> {
+        labels
+            .iter()
+            .map(|l| PickItem {
+                id: Uuid::new_v4(),
+                label: l.to_string(),
+            })
+            .collect()
+    }
+
+    fn labels(v: Vec<&PickItem>) -> Vec<&str> {
+        v.into_iter().map(|it| it.label.as_str()).collect()
+    }
+
+    #[test]
+    fn empty_query_keeps_everything_in_order() {
+        let all = items(&["Spanish", "German", "Chemistry"]);
+        assert_eq!(
+            labels(filter_items(&all, "")),
+            ["Spanish", "German", "Chemistry"]
+        );
+    }
+
+    #[test]
+    fn fuzzy_query_filters_and_ranks_tighter_matches_first() {
+        let all = items(&["Spanish verbs", "Chemistry", "spn", "German"]);
+        assert_eq!(labels(filter_items(&all, "SPN")), ["spn", "Spanish verbs"]);
+        assert!(filter_items(&all, "xyz").is_empty());
+    }
+}
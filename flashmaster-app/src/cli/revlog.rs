@@ -0,0 +1,72 @@
+//! Review-log CSV for `export revlog-csv`: one row per graded review, like
+//! Anki's revlog, for analysis in spreadsheets or notebooks.
+
+use flashmaster_core::{CardId, Review};
+use std::collections::HashMap;
+
+pub const REVLOG_HEADER: [&str; 7] = [
+    "reviewed_at",
+    "card_id",
+    "deck",
+    "grade",
+    "interval_applied",
+    "ef_after",
+    "duration_ms",
+];
+
+/// Rows oldest first. `grade` is the 0-3 score (0 = Again), `deck` the name looked up in
+/// `deck_of` (empty if the card is unknown). `duration_ms` is empty for
+/// reviews that weren't timed.
+pub fn reviews_to_revlog_csv(
+    reviews: &[Review],
+    deck_of: &HashMap<CardId, String>,
+) -> anyhow::Result<String> {
+    let mut rows: Vec<&Review> = reviews.iter().collect();
+    rows.sort_by_key(|r| r.reviewed_at);
+    let mut wtr = csv::Writer::from_writer(Vec::new());
+    wtr.write_record(REVLOG_HEADER)?;
+    for r in rows {
+        wtr.write_record([
+            r.reviewed_at.to_rfc3339(),
+            r.card_id.to_string(),
+            deck_of.get(&r.card_id).cloned().unwrap_or_default(),
+            r.grade.as_score().to_string(),
+            r.interval_applied.to_string(),
+            r.ef_after.to_string(),
+            r.duration_ms.map(|ms| ms.to_string()).unwrap_or_default(),
+        ])?;
+    }
+    Ok(String::from_utf8(wtr.into_inner()?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+    use flashmaster_core::Grade;
+    use uuid::Uuid;
+
+    #[test]
+    fn one_row_per_review_oldest_first() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let t0 = Utc.with_ymd_and_hms(2025, 5, 1, 9, 0, 0).unwrap();
+        let reviews = vec![
+            Review::new_timed(a, Grade::Easy, t0 + Duration::days(3), 6, 2.6, Some(4200)),
+            Review::new(a, Grade::Hard, t0, 1, 2.3),
+            Review::new(b, Grade::Medium, t0 + Duration::days(1), 1, 2.5),
+        ];
+        let deck_of = HashMap::from([(a, "Spanish".to_string()), (b, "French".to_string())]);
+
+        let csv = reviews_to_revlog_csv(&reviews, &deck_of).unwrap();
+        let mut rdr = csv::Reader::from_reader(csv.as_bytes());
+        assert_eq!(rdr.headers().unwrap(), REVLOG_HEADER.as_slice());
+        let rows: Vec<csv::StringRecord> = rdr.records().map(Result::unwrap).collect();
+        assert_eq!(rows.len(), reviews.len());
+        assert_eq!(&rows[0][0], t0.to_rfc3339());
+        assert_eq!(&rows[0][1], a.to_string());
+        assert_eq!(&rows[0][2], "Spanish");
+        assert_eq!((&rows[0][3], &rows[0][4]), ("1", "1"));
+        assert_eq!((&rows[2][3], &rows[2][4]), ("3", "6"));
+        assert_eq!((&rows[0][6], &rows[2][6]), ("", "4200"));
+    }
+}
@@ -0,0 +1,55 @@
+//! Built-in starter decks for `deck init`, compiled into the binary.
+
+use serde::Deserialize;
+
+pub struct Template {
+    pub name: &'static str,
+    pub description: &'static str,
+    json: &'static str,
+}
+
+pub const TEMPLATES: &[Template] = &[
+    Template {
+        name: "us-states",
+        description: "Capitals of the 50 US states",
+        json: include_str!("templates/us-states.json"),
+    },
+    Template {
+        name: "periodic-table-symbols",
+        description: "Element symbols for the first four periods",
+        json: include_str!("templates/periodic-table-symbols.json"),
+    },
+];
+
+/// Parsed template: a deck name, tags applied to every card, and
+/// `[front, back]` pairs.
+#[derive(Debug, Deserialize)]
+pub struct TemplateData {
+    pub deck: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub cards: Vec<(String, String)>,
+}
+
+pub fn find(name: &str) -> Option<&'static Template> {
+    TEMPLATES.iter().find(|t| t.name.eq_ignore_ascii_case(name))
+}
+
+impl Template {
+    pub fn data(&self) -> TemplateData {
+        serde_json::from_str(self.json).expect("built-in template is valid JSON")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_template_parses() {
+        for t in TEMPLATES {
+            let d = t.data();
+            assert!(!d.cards.is_empty(), "{} has no cards", t.name);
+        }
+    }
+}
@@ -0,0 +1,192 @@
+//! Typed client for the HTTP API, for integration tests and tooling.
+
+use crate::api::dto::{CardOut, DeckIn, DeckOut, ErrorOut};
+use flashmaster_core::{Grade, PoolCounts};
+use reqwest::{header, RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use std::fmt;
+use uuid::Uuid;
+
+/// Pool options for [`ApiClient::due`] and [`ApiClient::due_count`]; `None`
+/// leaves the server default.
+#[derive(Clone, Debug, Default)]
+pub struct DueOpts {
+    pub include_new: Option<bool>,
+    pub include_lapsed: Option<bool>,
+    pub max: Option<usize>,
+}
+
+impl DueOpts {
+    fn query(&self, deck: Option<&str>) -> Vec<(&'static str, String)> {
+        let mut q = Vec::new();
+        if let Some(d) = deck {
+            q.push(("deck", d.to_string()));
+        }
+        if let Some(b) = self.include_new {
+            q.push(("include_new", b.to_string()));
+        }
+        if let Some(b) = self.include_lapsed {
+            q.push(("include_lapsed", b.to_string()));
+        }
+        if let Some(n) = self.max {
+            q.push(("max", n.to_string()));
+        }
+        q
+    }
+}
+
+#[derive(Debug)]
+pub enum ClientError {
+    /// No usable response: connection failure, timeout, or an unreadable body.
+    Transport(reqwest::Error),
+    /// The server answered with an error status and `{"error": message}`.
+    Api {
+        status: StatusCode,
+        message: String,
+        /// Seconds from `Retry-After`, sent with 503s.
+        retry_after: Option<u64>,
+    },
+}
+
+impl ClientError {
+    /// Whether the same request may succeed if retried later.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, ClientError::Api { status, .. } if *status == StatusCode::SERVICE_UNAVAILABLE)
+    }
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Transport(e) => write!(f, "request failed: {e}"),
+            ClientError::Api {
+                status, message, ..
+            } => write!(f, "{status}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ClientError::Transport(e) => Some(e),
+            ClientError::Api { .. } => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(e: reqwest::Error) -> Self {
+        ClientError::Transport(e)
+    }
+}
+
+/// Client for a running `flashmaster api` server. Cloning is cheap and shares
+/// the connection pool.
+#[derive(Clone, Debug)]
+pub struct ApiClient {
+    base: String,
+    http: reqwest::Client,
+}
+
+impl ApiClient {
+    /// `base_url` is the server root, e.g. `http://127.0.0.1:8080`.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_client(base_url, reqwest::Client::new())
+    }
+
+    /// Like [`ApiClient::new`], reusing a configured `reqwest` client.
+    pub fn with_client(base_url: impl Into<String>, http: reqwest::Client) -> Self {
+        let base = base_url.into().trim_end_matches('/').to_string();
+        Self { base, http }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.base)
+    }
+
+    pub async fn list_decks(&self) -> Result<Vec<DeckOut>, ClientError> {
+        json(self.http.get(self.url("/decks"))).await
+    }
+
+    pub async fn create_deck(&self, name: &str) -> Result<DeckOut, ClientError> {
+        let body = DeckIn {
+            name: name.to_string(),
+        };
+        json(self.http.post(self.url("/decks")).json(&body)).await
+    }
+
+    /// The review pool for `deck` (name or id; all decks if `None`).
+    pub async fn due(
+        &self,
+        deck: Option<&str>,
+        opts: &DueOpts,
+    ) -> Result<Vec<CardOut>, ClientError> {
+        json(self.http.get(self.url("/due")).query(&opts.query(deck))).await
+    }
+
+    /// Per-phase size of the pool [`ApiClient::due`] would return.
+    pub async fn due_count(
+        &self,
+        deck: Option<&str>,
+        opts: &DueOpts,
+    ) -> Result<PoolCounts, ClientError> {
+        json(
+            self.http
+                .get(self.url("/due/count"))
+                .query(&opts.query(deck)),
+        )
+        .await
+    }
+
+    /// One page of cards in `deck` (name or id; all decks if `None`), oldest
+    /// first. `None` for `limit` takes the server's default page size.
+    pub async fn list_cards(
+        &self,
+        deck: Option<&str>,
+        offset: u64,
+        limit: Option<u64>,
+    ) -> Result<Vec<CardOut>, ClientError> {
+        let mut q = vec![("offset", offset.to_string())];
+        if let Some(d) = deck {
+            q.push(("deck", d.to_string()));
+        }
+        if let Some(n) = limit {
+            q.push(("limit", n.to_string()));
+        }
+        json(self.http.get(self.url("/cards")).query(&q)).await
+    }
+
+    pub async fn post_review(&self, card_id: Uuid, grade: Grade) -> Result<(), ClientError> {
+        let body = serde_json::json!({ "card_id": card_id, "grade": grade.as_score() });
+        send(self.http.post(self.url("/review")).json(&body)).await?;
+        Ok(())
+    }
+}
+
+/// Sends `req`, turning error statuses into [`ClientError::Api`].
+async fn send(req: RequestBuilder) -> Result<reqwest::Response, ClientError> {
+    let res = req.send().await?;
+    let status = res.status();
+    if status.is_success() {
+        return Ok(res);
+    }
+    let retry_after = res
+        .headers()
+        .get(header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok());
+    let text = res.text().await.unwrap_or_default();
+    let message = serde_json::from_str::<ErrorOut>(&text)
+        .map(|e| e.error)
+        .unwrap_or(text);
+    Err(ClientError::Api {
+        status,
+        message,
+        retry_after,
+    })
+}
+
+async fn json<T: DeserializeOwned>(req: RequestBuilder) -> Result<T, ClientError> {
+    Ok(send(req).await?.json().await?)
+}
@@ -0,0 +1,6 @@
+//! The HTTP API (server and DTOs), shared by the `flashmaster-app` binary and,
+//! with the `client` feature, a typed client for tests and tooling.
+
+pub mod api;
+#[cfg(feature = "client")]
+pub mod client;
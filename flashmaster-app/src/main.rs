@@ -1,25 +1,35 @@
 mod cli;
 pub mod tui;
-pub mod api;
+
+use flashmaster_app::api;
 
 use anyhow::Result;
 use clap::Parser; // needed for Cli::parse()
+use flashmaster_core::CardRules;
 use std::sync::Arc;
 use tokio::runtime::Runtime;
 
+use cli::commands::{open_repo, run_cli};
 use cli::opts::{Cli, Command};
-use cli::commands::{run_cli, open_repo};
 use tui::app::TuiApp;
+use tui::theme::Theme;
 
 fn main() -> Result<()> {
     let args = Cli::parse();
 
     match &args.cmd {
         // Run TUI on its own thread/runtime (no nested Tokio)
-        Command::Tui => {
+        Command::Tui(cmd) => {
             let rt = Arc::new(Runtime::new()?);
-            let repo = rt.block_on(open_repo(&args.store, args.db_path.clone()))?;
+            let repo = rt.block_on(open_repo(&args))?;
             let mut app = TuiApp::new(repo, rt);
+            app.grade_keys = args.grade_keys.into();
+            (app.goal, app.stop_at_goal) = (cmd.goal.goal, cmd.goal.stop_at_goal);
+            app.theme = Theme::from_env();
+            app.rules = CardRules {
+                auto_suspend_tags: args.auto_suspend_tags.clone(),
+                max_field_len: args.max_field_len,
+            };
             app.run()
         }
         // Everything else uses a single runtime here
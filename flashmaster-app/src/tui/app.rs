@@ -1,57 +1,581 @@
-use crate::tui::{inputs::{map_event, Action}, views::{self, RightPane}};
+use crate::tui::{
+    inputs::{
+        map_browse_event, map_event, map_mouse_event, map_text_event, Action, CardForm, FormStep,
+        LineEditor, TextKey,
+    },
+    theme::Theme,
+    views::{self, RightPane, ScreenLayout},
+};
+use chrono::{DateTime, Duration, TimeZone, Utc};
 use crossterm::{
-    event::{self},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use flashmaster_core::{
-    filters::{filter_by_due, filter_not_suspended},
-    scheduler::apply_grade,
-    Card, Deck, DueStatus, Grade, Repository,
+    add_card_with_rules, edit_tags,
+    filters::filter_by_text_ref,
+    pool::{build_cram_pool, build_review_pool, PoolOpts},
+    scheduler::{humanize_interval, preview_intervals},
+    session::{elapsed_ms, record_grade_timed, ReviewMode},
+    stats::{daily_streak_tz, sort_decks_by_recent, summarize_tz},
+    Card, CardId, CardPatch, CardRules, Deck, DeckCounts, DeckId, FailedCards, Grade, GradeScheme,
+    Repository, Review, ReviewSession, SessionSummary, StruggleTracker,
 };
+use flashmaster_json::paths::sessions_dir;
 use ratatui::{backend::CrosstermBackend, Terminal};
+use std::collections::{HashMap, HashSet};
 use std::io::{stdout, Stdout};
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver, Sender};
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::runtime::Runtime;
 
+/// Results of repo calls spawned on the runtime, delivered to the UI thread.
+pub enum Msg {
+    Decks(Vec<(Deck, DeckCounts)>),
+    Queue { deck_id: DeckId, cards: Vec<Card> },
+    Browser(Vec<Card>),
+    Stats(DeckStats),
+}
+
+/// Days of reviews the stats pane charts, ending today.
+const STATS_DAYS: i64 = 14;
+/// Lines PageUp/PageDown (and Ctrl-u/Ctrl-d) move the card pane.
+const SCROLL_STEP: u16 = 5;
+
+/// The stats pane's figures for one deck, computed off the UI thread and
+/// kept until the pane is reopened or a grade changes them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeckStats {
+    pub deck_id: DeckId,
+    pub counts: DeckCounts,
+    pub streak: u32,
+    /// Reviews per local day, oldest first, the last entry today.
+    pub per_day: Vec<u64>,
+}
+
+impl DeckStats {
+    /// Figures for `cards` (one deck's) as of `now`, days counted in `now`'s
+    /// timezone. `reviews` may span every deck. "Due" means due by the end of
+    /// today.
+    pub fn compute<Tz: TimeZone>(
+        deck_id: DeckId,
+        cards: &[Card],
+        reviews: &[Review],
+        now: DateTime<Tz>,
+    ) -> Self {
+        let today = now.date_naive();
+        let tz = now.timezone();
+        let end_of_day = (today + Duration::days(1))
+            .and_hms_opt(0, 0, 0)
+            .expect("midnight");
+        let end_of_day = tz
+            .from_local_datetime(&end_of_day)
+            .earliest()
+            .map_or(now.with_timezone(&Utc), |t| t.with_timezone(&Utc));
+        let ids: HashSet<CardId> = cards.iter().map(|c| c.id).collect();
+        let reviews: Vec<Review> = reviews
+            .iter()
+            .filter(|r| ids.contains(&r.card_id))
+            .cloned()
+            .collect();
+        let per_day = summarize_tz(&reviews, tz.clone()).per_day;
+        Self {
+            deck_id,
+            counts: DeckCounts::of(cards, end_of_day),
+            streak: daily_streak_tz(&reviews, today, tz),
+            per_day: (0..STATS_DAYS)
+                .rev()
+                .map(|ago| {
+                    per_day
+                        .get(&(today - Duration::days(ago)))
+                        .map_or(0, |t| t.total as u64)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The card browser: the selected deck's cards, narrowed as `filter` is typed.
+struct Browser {
+    cards: Vec<Card>,
+    filter: LineEditor,
+    cursor: usize,
+}
+
+impl Browser {
+    fn matches(&self) -> Vec<&Card> {
+        filter_by_text_ref(&self.cards, self.filter.text())
+    }
+}
+
 pub struct TuiApp {
     pub repo: Arc<dyn Repository>,
     pub rt: Arc<Runtime>,
-    decks: Vec<Deck>,
+    pub grade_keys: GradeScheme,
+    pub theme: Theme,
+    pub goal: Option<u32>,
+    pub stop_at_goal: bool,
+    /// Field limits and auto-suspend tags for cards added or edited here.
+    pub rules: CardRules,
+    decks: Vec<(Deck, DeckCounts)>,
     sel: usize,
     queue: Vec<Card>,
     idx: usize,
     reveal: bool,
+    /// When the current answer was last revealed, to time the grade.
+    revealed_at: Option<Instant>,
+    /// Card pane scroll offset, kept within the last draw's `max_scroll`.
+    scroll: u16,
+    /// The last frame's clickable areas, for mouse events.
+    layout: ScreenLayout,
     in_review: bool,
+    session: Option<ReviewSession>,
+    resume: Option<ReviewSession>,
+    sessions_dir: PathBuf,
+    status: Option<String>,
+    loading: bool,
+    struggles: StruggleTracker,
+    failed: FailedCards,
+    summary: SessionSummary,
+    offer_restudy: bool,
+    mode: ReviewMode,
+    sort_recent: bool,
+    /// Text typed after `+` so far; while set, keys go here instead of grading.
+    tag_input: Option<String>,
+    /// The open add/edit card form; while set, keys go to it.
+    form: Option<CardForm>,
+    /// The open card browser; while set (and no form is), keys go to it.
+    browser: Option<Browser>,
+    /// Whether the deck list shows the stats pane (`t`) instead of the idle one.
+    show_stats: bool,
+    /// Last computed stats; redraws reuse them rather than refetching.
+    stats: Option<DeckStats>,
+    tx: Sender<Msg>,
+    rx: Receiver<Msg>,
 }
 
 impl TuiApp {
     pub fn new(repo: Arc<dyn Repository>, rt: Arc<Runtime>) -> Self {
-        Self { repo, rt, decks: vec![], sel: 0, queue: vec![], idx: 0, reveal: false, in_review: false }
+        let (tx, rx) = channel();
+        Self {
+            repo,
+            rt,
+            grade_keys: GradeScheme::default(),
+            theme: Theme::default(),
+            goal: None,
+            stop_at_goal: false,
+            rules: CardRules::default(),
+            decks: vec![],
+            sel: 0,
+            queue: vec![],
+            idx: 0,
+            reveal: false,
+            revealed_at: None,
+            scroll: 0,
+            layout: ScreenLayout::default(),
+            in_review: false,
+            session: None,
+            resume: None,
+            sessions_dir: sessions_dir(),
+            status: None,
+            loading: false,
+            struggles: StruggleTracker::default(),
+            failed: FailedCards::default(),
+            summary: SessionSummary::default(),
+            offer_restudy: false,
+            mode: ReviewMode::Scheduled,
+            sort_recent: false,
+            tag_input: None,
+            form: None,
+            browser: None,
+            show_stats: false,
+            stats: None,
+            tx,
+            rx,
+        }
     }
 
     fn load_decks(&mut self) {
-        let mut v = self.rt.block_on(self.repo.list_decks()).unwrap_or_default();
-        v.sort_by_key(|d| d.created_at);
-        self.decks = v;
-        self.sel = self.sel.min(self.decks.len().saturating_sub(1));
+        self.loading = true;
+        let (repo, tx, recent) = (self.repo.clone(), self.tx.clone(), self.sort_recent);
+        self.rt.spawn(async move {
+            let mut v = repo
+                .list_decks_with_counts(chrono::Utc::now())
+                .await
+                .unwrap_or_default();
+            if recent {
+                let cards = repo.list_cards(None).await.unwrap_or_default();
+                let (mut decks, counts): (Vec<Deck>, Vec<DeckCounts>) = v.into_iter().unzip();
+                let counts: HashMap<DeckId, DeckCounts> =
+                    decks.iter().map(|d| d.id).zip(counts).collect();
+                sort_decks_by_recent(&mut decks, &cards);
+                v = decks
+                    .into_iter()
+                    .map(|d| {
+                        let c = counts[&d.id];
+                        (d, c)
+                    })
+                    .collect();
+            }
+            tx.send(Msg::Decks(v)).ok();
+        });
+    }
+
+    /// Hides the answer and scrolls back to the top for the card at `idx`.
+    fn show_card(&mut self) {
+        self.reveal = false;
+        self.scroll = 0;
     }
 
     fn build_queue(&mut self) {
         self.queue.clear();
         self.idx = 0;
-        self.reveal = false;
-        if self.decks.is_empty() { return; }
-        let did = self.decks[self.sel].id;
-        let mut cards = self.rt.block_on(self.repo.list_cards(Some(did))).unwrap_or_default();
-        cards = filter_not_suspended(&cards);
-        let now = chrono::Utc::now();
-        let mut pool = Vec::new();
-        pool.extend(filter_by_due(&cards, now, DueStatus::DueToday));
-        pool.extend(filter_by_due(&cards, now, DueStatus::New));
-        pool.extend(filter_by_due(&cards, now, DueStatus::Lapsed));
-        pool.sort_by_key(|c| (c.due_at, c.created_at));
-        self.queue = pool;
+        self.show_card();
+        if self.decks.is_empty() {
+            return;
+        }
+        self.loading = true;
+        let did = self.decks[self.sel].0.id;
+        let (repo, tx) = (self.repo.clone(), self.tx.clone());
+        self.rt.spawn(async move {
+            let cards = repo.list_cards(Some(did)).await.unwrap_or_default();
+            tx.send(Msg::Queue {
+                deck_id: did,
+                cards,
+            })
+            .ok();
+        });
+    }
+
+    /// Applies a finished background load; the render loop never blocks on the repo.
+    fn handle_msg(&mut self, msg: Msg) {
+        // Stats refresh quietly, even mid-review, so they never showed as loading.
+        self.loading &= matches!(msg, Msg::Stats(_));
+        match msg {
+            Msg::Decks(v) => {
+                self.decks = v;
+                self.sel = self.sel.min(self.decks.len().saturating_sub(1));
+            }
+            Msg::Queue { deck_id, cards } => self.apply_queue(deck_id, cards),
+            Msg::Browser(cards) => {
+                self.browser = Some(Browser {
+                    cards,
+                    filter: LineEditor::default(),
+                    cursor: 0,
+                })
+            }
+            Msg::Stats(s) => self.stats = Some(s),
+        }
+    }
+
+    /// Recomputes the selected deck's stats in the background.
+    fn load_stats(&mut self) {
+        let Some((deck, _)) = self.decks.get(self.sel) else {
+            return;
+        };
+        let (repo, tx, did) = (self.repo.clone(), self.tx.clone(), deck.id);
+        self.rt.spawn(async move {
+            let cards = repo.list_cards(Some(did)).await.unwrap_or_default();
+            let reviews = repo.list_reviews().await.unwrap_or_default();
+            tx.send(Msg::Stats(DeckStats::compute(
+                did,
+                &cards,
+                &reviews,
+                chrono::Local::now(),
+            )))
+            .ok();
+        });
+    }
+
+    fn open_browser(&mut self) {
+        let Some((deck, _)) = self.decks.get(self.sel) else {
+            return;
+        };
+        self.loading = true;
+        let (repo, tx, did) = (self.repo.clone(), self.tx.clone(), deck.id);
+        self.rt.spawn(async move {
+            let cards = repo.list_cards(Some(did)).await.unwrap_or_default();
+            tx.send(Msg::Browser(cards)).ok();
+        });
+    }
+
+    fn apply_queue(&mut self, did: DeckId, cards: Vec<Card>) {
+        self.queue = match self.mode {
+            ReviewMode::Cram => build_cram_pool(&cards),
+            ReviewMode::Scheduled => {
+                let opts = PoolOpts {
+                    include_new: true,
+                    include_lapsed: true,
+                    ..Default::default()
+                };
+                build_review_pool(&cards, chrono::Utc::now(), &opts)
+            }
+        };
+        self.idx = 0;
+        self.show_card();
+        self.in_review = true;
+        self.failed = FailedCards::default();
+        self.summary = SessionSummary::with_goal(self.goal);
+        // Cram runs don't leave a session behind to resume.
+        self.session = (self.mode == ReviewMode::Scheduled)
+            .then(|| ReviewSession::new(Some(did), self.queue.iter().map(|c| c.id).collect()));
+        self.save_session();
+    }
+
+    fn session_path(&self) -> Option<PathBuf> {
+        let did = self.decks.get(self.sel)?.0.id;
+        Some(ReviewSession::file_for(&self.sessions_dir, Some(did)))
+    }
+
+    /// Restores a saved session's remaining cards (deleted or since-suspended cards are dropped).
+    fn resume_session(&mut self, s: ReviewSession) {
+        self.queue = s
+            .remaining()
+            .iter()
+            .filter_map(|id| self.rt.block_on(self.repo.get_card(*id)).ok())
+            .filter(|c| !c.suspended)
+            .collect();
+        self.idx = 0;
+        self.show_card();
+        self.failed = FailedCards::default();
+        self.summary = SessionSummary::with_goal(self.goal);
+        self.session = Some(s);
+    }
+
+    /// Re-drills this session's failed cards, fetched fresh, as a cram run:
+    /// their due dates were already set by the grades that failed them.
+    fn start_restudy(&mut self) {
+        self.queue = self
+            .failed
+            .take()
+            .into_iter()
+            .filter_map(|id| self.rt.block_on(self.repo.get_card(id)).ok())
+            .filter(|c| !c.suspended)
+            .collect();
+        self.idx = 0;
+        self.show_card();
+        self.mode = ReviewMode::Cram;
+        self.session = None;
+        self.in_review = !self.queue.is_empty();
+    }
+
+    fn save_session(&self) {
+        if let (Some(s), Some(path)) = (&self.session, self.session_path()) {
+            if s.is_finished() {
+                ReviewSession::clear(&path).ok();
+            } else {
+                s.save(&path).ok();
+            }
+        }
+    }
+
+    fn advance_session(&mut self, grade: Option<&Grade>) {
+        if let Some(s) = self.session.as_mut() {
+            s.advance(grade);
+        }
+        self.save_session();
+    }
+
+    fn right_pane(&self) -> RightPane<'_> {
+        if let Some(form) = &self.form {
+            RightPane::Edit(form)
+        } else if self.loading {
+            RightPane::Empty("Loading…")
+        } else if let Some(b) = &self.browser {
+            RightPane::Browser {
+                cards: b.matches(),
+                filter: &b.filter,
+                cursor: b.cursor,
+            }
+        } else if let Some(s) = &self.resume {
+            RightPane::Resume {
+                remaining: s.remaining().len(),
+            }
+        } else if self.offer_restudy {
+            RightPane::Restudy {
+                failed: self.failed.len(),
+            }
+        } else if self.in_review {
+            // Every card in the queue comes from the selected deck.
+            let prompt = self
+                .decks
+                .get(self.sel)
+                .map(|(d, _)| d.prompt_with)
+                .unwrap_or_default();
+            if let Some(card) = self.queue.get(self.idx) {
+                let intervals = (self.mode == ReviewMode::Scheduled)
+                    .then(|| self.grade_keys.interval_hint(&preview_intervals(card)));
+                RightPane::Card {
+                    card,
+                    prompt,
+                    reveal: self.reveal,
+                    scroll: self.scroll,
+                    struggling: self.struggles.is_flagged(card.id),
+                    intervals,
+                }
+            } else {
+                RightPane::Empty("No cards in queue.")
+            }
+        } else if self.show_stats {
+            let deck = self.decks.get(self.sel).map(|(d, _)| d);
+            match (deck, &self.stats) {
+                (Some(d), Some(s)) if s.deck_id == d.id => RightPane::Stats {
+                    deck: &d.name,
+                    counts: s.counts,
+                    streak: s.streak,
+                    per_day: &s.per_day,
+                },
+                _ => RightPane::Empty("Loading…"),
+            }
+        } else {
+            RightPane::Idle
+        }
+    }
+
+    /// Feeds a key to the open tag input; Enter adds the tag, Esc drops it.
+    fn tag_input_event(&mut self, ev: Event) {
+        let Some(buf) = self.tag_input.as_mut() else {
+            return;
+        };
+        match map_text_event(ev) {
+            TextKey::Char(c) => buf.push(c),
+            TextKey::Backspace => {
+                buf.pop();
+            }
+            TextKey::Submit => {
+                let tag = self.tag_input.take().unwrap_or_default();
+                self.add_tag(&tag);
+            }
+            TextKey::Cancel => self.tag_input = None,
+            _ => {}
+        }
+    }
+
+    /// Feeds a key to the open browser: text edits the filter, Up/Down move,
+    /// Enter edits the card under the cursor, Esc closes.
+    fn browser_event(&mut self, ev: Event) {
+        let Some(b) = self.browser.as_mut() else {
+            return;
+        };
+        let key = map_text_event(ev);
+        if b.filter.apply(&key) {
+            b.cursor = 0;
+            return;
+        }
+        match key {
+            TextKey::Next if b.cursor + 1 < b.matches().len() => b.cursor += 1,
+            TextKey::Prev => b.cursor = b.cursor.saturating_sub(1),
+            TextKey::Submit => {
+                if let Some(card) = b.matches().get(b.cursor) {
+                    self.form = Some(CardForm::edit(card));
+                }
+            }
+            TextKey::Cancel => self.browser = None,
+            _ => {}
+        }
+    }
+
+    /// Feeds a key to the open card form; Enter saves, Esc closes it.
+    fn form_event(&mut self, ev: Event) {
+        let Some(form) = self.form.as_mut() else {
+            return;
+        };
+        match form.key(map_text_event(ev)) {
+            FormStep::Editing => {}
+            FormStep::Submit => self.submit_form(),
+            FormStep::Cancel => self.form = None,
+        }
+    }
+
+    /// Adds or updates the form's card. A refused save (empty front or back,
+    /// a field over the length limit) keeps the form open with the reason.
+    fn submit_form(&mut self) {
+        let Some(form) = self.form.as_mut() else {
+            return;
+        };
+        if let Err(msg) = form.validate() {
+            form.error = Some(msg.to_string());
+            return;
+        }
+        let (front, back, hint, tags) = (
+            form.front().to_string(),
+            form.back().to_string(),
+            form.hint(),
+            form.tags(),
+        );
+        let saved = match form.card_id {
+            None => self.rt.block_on(add_card_with_rules(
+                &*self.repo,
+                &self.rules,
+                form.deck_id,
+                &front,
+                &back,
+                hint.as_deref(),
+                &tags,
+            )),
+            Some(id) => {
+                // Only the form's fields are written, so scheduling is left as it is.
+                let patch = CardPatch {
+                    front: Some(front),
+                    back: Some(back),
+                    hint: Some(hint),
+                    tags: Some(tags),
+                    ..Default::default()
+                };
+                self.rules
+                    .check_patch(&patch)
+                    .and_then(|_| self.rt.block_on(self.repo.patch_card(id, patch)))
+            }
+        };
+        match saved {
+            Ok(card) => {
+                let added = form.card_id.is_none();
+                self.form = None;
+                if added {
+                    self.status = Some(format!("added {}", card.front));
+                    self.load_decks();
+                } else {
+                    self.status = Some("card saved".to_string());
+                    if let Some(b) = self.browser.as_mut() {
+                        if let Some(c) = b.cards.iter_mut().find(|c| c.id == card.id) {
+                            *c = card.clone();
+                        }
+                    }
+                    if let Some(c) = self.queue.iter_mut().find(|c| c.id == card.id) {
+                        *c = card;
+                    }
+                }
+            }
+            Err(e) => form.error = Some(e.to_string()),
+        }
+    }
+
+    /// Adds `tag` (a leading `#` is dropped) to the card under review unless it
+    /// already has it, ignoring case.
+    fn add_tag(&mut self, tag: &str) {
+        let tag = tag.trim().trim_start_matches('#');
+        let Some(card) = self.queue.get(self.idx) else {
+            return;
+        };
+        if tag.is_empty() {
+            return;
+        }
+        let Some(tags) = edit_tags(&card.tags, &[tag.to_string()], &[]) else {
+            self.status = Some(format!("already tagged {tag}"));
+            return;
+        };
+        let mut card = card.clone();
+        card.tags = tags;
+        self.status = Some(match self.rt.block_on(self.repo.update_card(&card)) {
+            Ok(stored) => {
+                self.queue[self.idx] = stored;
+                format!("tagged {tag}")
+            }
+            Err(e) => format!("tag not saved: {e}"),
+        });
     }
 
     pub fn run(&mut self) -> anyhow::Result<()> {
@@ -59,7 +583,7 @@ impl TuiApp {
 
         enable_raw_mode()?;
         let mut stdout = stdout();
-        execute!(stdout, EnterAlternateScreen)?;
+        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
         let backend = CrosstermBackend::new(stdout);
         let mut terminal = Terminal::new(backend)?;
 
@@ -67,54 +591,266 @@ impl TuiApp {
 
         disable_raw_mode().ok();
         let mut out: Stdout = std::io::stdout();
-        execute!(out, LeaveAlternateScreen).ok();
+        execute!(out, LeaveAlternateScreen, DisableMouseCapture).ok();
         terminal.show_cursor().ok();
 
-        res
+        let closed = self.rt.block_on(self.repo.close());
+        res.and(closed.map_err(Into::into))
     }
 
-    fn mainloop(&mut self, terminal: &mut Terminal<CrosstermBackend<Stdout>>) -> anyhow::Result<()> {
+    fn mainloop(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    ) -> anyhow::Result<()> {
         loop {
+            let prompt = self.tag_input.as_ref().map(|b| format!("add tag: {b}▏"));
+            let mut layout = ScreenLayout::default();
             terminal.draw(|f| {
-                let right = if self.in_review {
-                    if let Some(card) = self.queue.get(self.idx) { RightPane::Card { card, reveal: self.reveal } }
-                    else { RightPane::Empty("No cards in queue.") }
-                } else { RightPane::Idle };
-                views::draw_ui(f, f.size(), &self.decks, self.sel, right);
+                let status = prompt.as_deref().or(self.status.as_deref());
+                layout = views::draw_ui(
+                    f,
+                    &self.decks,
+                    self.sel,
+                    self.right_pane(),
+                    status,
+                    self.grade_keys,
+                    &self.theme,
+                );
             })?;
+            self.scroll = self.scroll.min(layout.max_scroll);
+            self.layout = layout;
+
+            while let Ok(msg) = self.rx.try_recv() {
+                self.handle_msg(msg);
+            }
 
             if event::poll(std::time::Duration::from_millis(100))? {
                 let ev = event::read()?;
-                let action = map_event(ev);
+                if self.form.is_some() {
+                    self.form_event(ev);
+                    continue;
+                }
+                if self.browser.is_some() {
+                    self.browser_event(ev);
+                    continue;
+                }
+                if self.tag_input.is_some() {
+                    self.tag_input_event(ev);
+                    continue;
+                }
+                let browse = if self.in_review {
+                    None
+                } else {
+                    map_browse_event(&ev)
+                };
+                let action = browse
+                    .or_else(|| map_mouse_event(&ev, &self.layout))
+                    .unwrap_or_else(|| map_event(ev, self.grade_keys));
                 match action {
                     Action::Quit => break,
-                    Action::Up   => { if !self.in_review { self.sel = self.sel.saturating_sub(1); } }
-                    Action::Down => { if !self.in_review && self.sel + 1 < self.decks.len() { self.sel += 1; } }
-                    Action::Enter => {
+                    Action::Up => {
                         if !self.in_review {
+                            self.sel = self.sel.saturating_sub(1);
+                            if self.show_stats {
+                                self.load_stats();
+                            }
+                        }
+                    }
+                    Action::Down => {
+                        if !self.in_review && self.sel + 1 < self.decks.len() {
+                            self.sel += 1;
+                            if self.show_stats {
+                                self.load_stats();
+                            }
+                        }
+                    }
+                    Action::Select(i) => {
+                        if !self.in_review && i < self.decks.len() {
+                            self.sel = i;
+                            if self.show_stats {
+                                self.load_stats();
+                            }
+                        }
+                    }
+                    Action::Enter => {
+                        if !self.in_review
+                            && !self.loading
+                            && self.resume.is_none()
+                            && !self.offer_restudy
+                        {
+                            self.mode = ReviewMode::Scheduled;
+                            let saved = self
+                                .session_path()
+                                .and_then(|p| ReviewSession::load(&p).ok().flatten());
+                            match saved {
+                                Some(s) if !s.is_finished() => self.resume = Some(s),
+                                _ => self.build_queue(),
+                            }
+                        }
+                    }
+                    Action::Cram => {
+                        if !self.in_review
+                            && !self.loading
+                            && self.resume.is_none()
+                            && !self.offer_restudy
+                        {
+                            self.mode = ReviewMode::Cram;
+                            self.status = Some("cram: grades are not recorded".to_string());
                             self.build_queue();
+                        }
+                    }
+                    Action::ToggleSort => {
+                        if !self.in_review && !self.loading {
+                            self.sort_recent = !self.sort_recent;
+                            self.status = Some(
+                                if self.sort_recent {
+                                    "decks: most recently reviewed first"
+                                } else {
+                                    "decks: oldest first"
+                                }
+                                .to_string(),
+                            );
+                            self.load_decks();
+                        }
+                    }
+                    Action::Yes => {
+                        if let Some(s) = self.resume.take() {
+                            self.resume_session(s);
                             self.in_review = true;
-                            self.idx = 0;
-                            self.reveal = false;
+                        } else if self.offer_restudy {
+                            self.offer_restudy = false;
+                            self.start_restudy();
+                        }
+                    }
+                    Action::No => {
+                        if self.resume.take().is_some() {
+                            self.build_queue();
+                        } else if self.offer_restudy {
+                            self.offer_restudy = false;
+                            self.failed = FailedCards::default();
+                        }
+                    }
+                    Action::AddCard => {
+                        if !self.in_review
+                            && !self.loading
+                            && self.resume.is_none()
+                            && !self.offer_restudy
+                        {
+                            if let Some((deck, _)) = self.decks.get(self.sel) {
+                                self.form = Some(CardForm::add(deck.id));
+                            }
+                        }
+                    }
+                    Action::EditCard => {
+                        if self.in_review {
+                            if let Some(card) = self.queue.get(self.idx) {
+                                self.form = Some(CardForm::edit(card));
+                            }
+                        }
+                    }
+                    Action::ScrollUp => self.scroll = self.scroll.saturating_sub(SCROLL_STEP),
+                    Action::ScrollDown => {
+                        self.scroll = self
+                            .scroll
+                            .saturating_add(SCROLL_STEP)
+                            .min(self.layout.max_scroll)
+                    }
+                    Action::Stats => {
+                        if !self.in_review {
+                            self.show_stats = !self.show_stats;
+                            if self.show_stats {
+                                self.load_stats();
+                            }
+                        }
+                    }
+                    Action::Browse => {
+                        if !self.in_review
+                            && !self.loading
+                            && self.resume.is_none()
+                            && !self.offer_restudy
+                        {
+                            self.open_browser();
+                        }
+                    }
+                    Action::AddTag => {
+                        if self.in_review && self.queue.get(self.idx).is_some() {
+                            self.tag_input = Some(String::new());
+                        }
+                    }
+                    Action::ToggleReveal => {
+                        if self.in_review {
+                            self.reveal = !self.reveal;
+                            self.revealed_at = self.reveal.then(Instant::now);
+                            self.scroll = 0;
                         }
                     }
-                    Action::ToggleReveal => { if self.in_review { self.reveal = !self.reveal; } }
                     Action::Skip => {
-                        if self.in_review && self.idx + 1 < self.queue.len() { self.idx += 1; self.reveal = false; }
+                        if self.in_review && self.idx + 1 < self.queue.len() {
+                            self.idx += 1;
+                            self.show_card();
+                            self.advance_session(None);
+                        }
                     }
-                    Action::GradeHard | Action::GradeMedium | Action::GradeEasy => {
+                    Action::Grade(grade) => {
                         if self.in_review {
                             if let Some(card) = self.queue.get(self.idx).cloned() {
-                                let grade = match action {
-                                    Action::GradeHard => Grade::Hard,
-                                    Action::GradeMedium => Grade::Medium,
-                                    Action::GradeEasy => Grade::Easy,
-                                    _ => Grade::Medium,
-                                };
-                                let out = apply_grade(card, grade);
-                                self.rt.block_on(self.repo.update_card(&out.updated_card)).ok();
-                                self.rt.block_on(self.repo.insert_review(&out.review)).ok();
-                                if self.idx + 1 < self.queue.len() { self.idx += 1; self.reveal = false; } else { self.in_review = false; }
+                                self.advance_session(Some(&grade));
+                                self.struggles.record(card.id, &grade);
+                                self.failed.record(card.id, &grade);
+                                // Grading a hidden answer (or one revealed on an earlier card) isn't timed.
+                                let took = self
+                                    .revealed_at
+                                    .take()
+                                    .filter(|_| self.reveal)
+                                    .map(elapsed_ms);
+                                let next = self
+                                    .rt
+                                    .block_on(record_grade_timed(
+                                        &*self.repo,
+                                        card,
+                                        grade,
+                                        self.mode,
+                                        chrono::Utc::now(),
+                                        took,
+                                    ))
+                                    .ok()
+                                    .flatten();
+                                if self.show_stats && next.is_some() {
+                                    self.load_stats();
+                                }
+                                self.status = Some(match next {
+                                    Some(c) => {
+                                        format!("next due {}", humanize_interval(c.interval_days))
+                                    }
+                                    None => "cram: not rescheduled".to_string(),
+                                });
+                                let reached = self.summary.record();
+                                if reached {
+                                    self.status = Some(format!(
+                                        "🎉 goal reached: {} cards reviewed",
+                                        self.summary.reviewed
+                                    ));
+                                }
+                                if reached && self.stop_at_goal {
+                                    // The session file keeps the rest for a later resume.
+                                    self.in_review = false;
+                                } else if self.idx + 1 < self.queue.len() {
+                                    self.idx += 1;
+                                    self.show_card();
+                                } else {
+                                    self.in_review = false;
+                                    let fronts: Vec<&str> = self
+                                        .queue
+                                        .iter()
+                                        .filter(|c| self.struggles.is_flagged(c.id))
+                                        .map(|c| c.front.as_str())
+                                        .collect();
+                                    if !fronts.is_empty() {
+                                        self.status =
+                                            Some(format!("review these: {}", fronts.join(", ")));
+                                    }
+                                    self.offer_restudy = !self.failed.is_empty();
+                                }
                             }
                         }
                     }
@@ -125,3 +861,389 @@ impl TuiApp {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use flashmaster_core::{repo::memory::MemoryRepo, PromptWith};
+    use std::time::Duration;
+
+    #[test]
+    fn deck_load_goes_from_loading_to_loaded() {
+        let rt = Arc::new(Runtime::new().unwrap());
+        let repo = Arc::new(MemoryRepo::new());
+        rt.block_on(repo.create_deck("Spanish")).unwrap();
+
+        let mut app = TuiApp::new(repo, rt);
+        app.load_decks();
+        assert!(app.loading);
+        assert!(app.decks.is_empty());
+
+        let msg = app.rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        app.handle_msg(msg);
+        assert!(!app.loading);
+        assert_eq!(app.decks.len(), 1);
+        assert_eq!(app.decks[0].0.name, "Spanish");
+        assert_eq!(app.decks[0].1.total, 0);
+    }
+
+    #[test]
+    fn queue_message_starts_review() {
+        let rt = Arc::new(Runtime::new().unwrap());
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = TuiApp::new(Arc::new(MemoryRepo::new()), rt);
+        app.sessions_dir = dir.path().to_path_buf();
+        let deck = Deck::new("Spanish");
+        app.handle_msg(Msg::Decks(vec![(deck.clone(), DeckCounts::default())]));
+
+        app.loading = true;
+        app.handle_msg(Msg::Queue {
+            deck_id: deck.id,
+            cards: vec![Card::new(deck.id, "hola", "hello")],
+        });
+        assert!(!app.loading);
+        assert!(app.in_review);
+        assert_eq!(app.queue.len(), 1);
+    }
+
+    #[test]
+    fn new_queue_starts_at_the_top_of_the_card() {
+        let rt = Arc::new(Runtime::new().unwrap());
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = TuiApp::new(Arc::new(MemoryRepo::new()), rt);
+        app.sessions_dir = dir.path().to_path_buf();
+        let deck = Deck::new("Spanish");
+        app.handle_msg(Msg::Decks(vec![(deck.clone(), DeckCounts::default())]));
+        app.reveal = true;
+        app.scroll = 7;
+
+        app.handle_msg(Msg::Queue {
+            deck_id: deck.id,
+            cards: vec![Card::new(deck.id, "hola", "hello")],
+        });
+        assert!(!app.reveal);
+        assert_eq!(app.scroll, 0);
+        let RightPane::Card { scroll, .. } = app.right_pane() else {
+            panic!("expected a card")
+        };
+        assert_eq!(scroll, 0);
+    }
+
+    #[test]
+    fn quick_tag_merges_into_the_current_card() {
+        let rt = Arc::new(Runtime::new().unwrap());
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Arc::new(MemoryRepo::new());
+        let deck = rt.block_on(repo.create_deck("Spanish")).unwrap();
+        let card = rt
+            .block_on(repo.add_card(deck.id, "hola", "hello", None, &["Verbs".to_string()]))
+            .unwrap();
+        let mut app = TuiApp::new(repo.clone(), rt.clone());
+        app.sessions_dir = dir.path().to_path_buf();
+        app.handle_msg(Msg::Decks(vec![(deck.clone(), DeckCounts::default())]));
+        app.handle_msg(Msg::Queue {
+            deck_id: deck.id,
+            cards: vec![card.clone()],
+        });
+
+        let key = |code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        app.tag_input = Some(String::new());
+        for c in "#confusing1".chars() {
+            app.tag_input_event(key(KeyCode::Char(c)));
+        }
+        app.tag_input_event(key(KeyCode::Enter));
+        assert!(app.tag_input.is_none());
+        assert_eq!(app.queue[app.idx].tags, ["Verbs", "confusing1"]);
+        assert_eq!(
+            rt.block_on(repo.get_card(card.id)).unwrap().tags,
+            ["Verbs", "confusing1"]
+        );
+        assert_eq!(rt.block_on(repo.list_reviews()).unwrap().len(), 0);
+
+        app.tag_input = Some(String::new());
+        for c in "verbs".chars() {
+            app.tag_input_event(key(KeyCode::Char(c)));
+        }
+        app.tag_input_event(key(KeyCode::Enter));
+        assert_eq!(app.queue[app.idx].tags, ["Verbs", "confusing1"]);
+        assert_eq!(app.status.as_deref(), Some("already tagged verbs"));
+    }
+
+    #[test]
+    fn back_prompted_deck_asks_with_the_back() {
+        let rt = Arc::new(Runtime::new().unwrap());
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = TuiApp::new(Arc::new(MemoryRepo::new()), rt);
+        app.sessions_dir = dir.path().to_path_buf();
+        let deck = Deck::builder("Spanish")
+            .prompt_with(PromptWith::Back)
+            .build();
+        app.handle_msg(Msg::Decks(vec![(deck.clone(), DeckCounts::default())]));
+        app.handle_msg(Msg::Queue {
+            deck_id: deck.id,
+            cards: vec![Card::new(deck.id, "hola", "hello")],
+        });
+
+        let RightPane::Card { card, prompt, .. } = app.right_pane() else {
+            panic!("expected a card")
+        };
+        assert_eq!(card.question(prompt), "hello");
+        assert_eq!(card.answer(prompt), "hola");
+    }
+
+    #[test]
+    fn cram_queue_takes_every_card_without_a_session() {
+        let rt = Arc::new(Runtime::new().unwrap());
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = TuiApp::new(Arc::new(MemoryRepo::new()), rt);
+        app.sessions_dir = dir.path().to_path_buf();
+        let deck = Deck::new("Exam");
+        app.handle_msg(Msg::Decks(vec![(deck.clone(), DeckCounts::default())]));
+
+        let mut later = Card::new(deck.id, "later", "x");
+        later.reps = 3;
+        later.due_at = chrono::Utc::now() + chrono::Duration::days(30);
+        app.mode = ReviewMode::Cram;
+        app.handle_msg(Msg::Queue {
+            deck_id: deck.id,
+            cards: vec![Card::new(deck.id, "new", "x"), later],
+        });
+        assert_eq!(app.queue.len(), 2);
+        assert!(app.session.is_none());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn card_form_adds_to_the_selected_deck() {
+        let rt = Arc::new(Runtime::new().unwrap());
+        let repo = Arc::new(MemoryRepo::new());
+        let deck = rt.block_on(repo.create_deck("Spanish")).unwrap();
+        let mut app = TuiApp::new(repo.clone(), rt.clone());
+        app.handle_msg(Msg::Decks(vec![(deck.clone(), DeckCounts::default())]));
+
+        let key = |code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        app.form = Some(CardForm::add(deck.id));
+        for c in "hola".chars() {
+            app.form_event(key(KeyCode::Char(c)));
+        }
+        app.form_event(key(KeyCode::Enter));
+        let form = app.form.as_ref().expect("refused without a back");
+        assert_eq!(form.error.as_deref(), Some("back can't be empty"));
+        assert!(rt.block_on(repo.list_cards(None)).unwrap().is_empty());
+        assert!(matches!(app.right_pane(), RightPane::Edit(_)));
+
+        app.form_event(key(KeyCode::Tab));
+        for c in "hello".chars() {
+            app.form_event(key(KeyCode::Char(c)));
+        }
+        // Up from the back wraps past the front to the tags.
+        app.form_event(key(KeyCode::Up));
+        app.form_event(key(KeyCode::Up));
+        for c in "greetings".chars() {
+            app.form_event(key(KeyCode::Char(c)));
+        }
+        app.form_event(key(KeyCode::Enter));
+        assert!(app.form.is_none());
+        let cards = rt.block_on(repo.list_cards(Some(deck.id))).unwrap();
+        assert_eq!(cards.len(), 1);
+        assert_eq!(
+            (cards[0].front.as_str(), cards[0].back.as_str()),
+            ("hola", "hello")
+        );
+        assert_eq!(cards[0].tags, ["greetings"]);
+    }
+
+    #[test]
+    fn card_form_edits_the_card_under_review() {
+        let rt = Arc::new(Runtime::new().unwrap());
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Arc::new(MemoryRepo::new());
+        let deck = rt.block_on(repo.create_deck("Spanish")).unwrap();
+        let card = rt
+            .block_on(repo.add_card(deck.id, "hola", "helo", Some("greeting"), &[]))
+            .unwrap();
+        let mut app = TuiApp::new(repo.clone(), rt.clone());
+        app.sessions_dir = dir.path().to_path_buf();
+        app.handle_msg(Msg::Decks(vec![(deck.clone(), DeckCounts::default())]));
+        app.handle_msg(Msg::Queue {
+            deck_id: deck.id,
+            cards: vec![card.clone()],
+        });
+
+        let key = |code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        app.form = Some(CardForm::edit(&app.queue[app.idx]));
+        app.form_event(key(KeyCode::Down));
+        app.form_event(key(KeyCode::Left));
+        app.form_event(key(KeyCode::Char('l')));
+        app.form_event(key(KeyCode::Down));
+        for _ in 0.."greeting".len() {
+            app.form_event(key(KeyCode::Backspace));
+        }
+        app.form_event(key(KeyCode::Enter));
+        assert!(app.form.is_none());
+        let stored = rt.block_on(repo.get_card(card.id)).unwrap();
+        assert_eq!(
+            (stored.back.as_str(), stored.hint.as_deref()),
+            ("hello", None)
+        );
+        assert_eq!(app.queue[app.idx].back, "hello");
+
+        app.form = Some(CardForm::edit(&app.queue[app.idx]));
+        for _ in 0.."hola".len() {
+            app.form_event(key(KeyCode::Backspace));
+        }
+        app.form_event(key(KeyCode::Enter));
+        assert_eq!(
+            app.form.as_ref().and_then(|f| f.error.as_deref()),
+            Some("front can't be empty")
+        );
+        app.form_event(key(KeyCode::Esc));
+        assert!(app.form.is_none());
+        assert_eq!(rt.block_on(repo.get_card(card.id)).unwrap().front, "hola");
+    }
+
+    #[test]
+    fn browser_filters_as_you_type_and_opens_the_card() {
+        let rt = Arc::new(Runtime::new().unwrap());
+        let repo = Arc::new(MemoryRepo::new());
+        let deck = rt.block_on(repo.create_deck("Spanish")).unwrap();
+        for (front, back) in [
+            ("hola", "hello"),
+            ("adiós", "goodbye"),
+            ("hasta luego", "see you"),
+        ] {
+            rt.block_on(repo.add_card(deck.id, front, back, None, &[]))
+                .unwrap();
+        }
+        let mut app = TuiApp::new(repo.clone(), rt.clone());
+        app.handle_msg(Msg::Decks(vec![(deck.clone(), DeckCounts::default())]));
+
+        app.open_browser();
+        let msg = app.rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        app.handle_msg(msg);
+        let RightPane::Browser { cards, .. } = app.right_pane() else {
+            panic!("expected the browser")
+        };
+        assert_eq!(cards.len(), 3);
+
+        let key = |code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        for c in "h".chars() {
+            app.browser_event(key(KeyCode::Char(c)));
+        }
+        app.browser_event(key(KeyCode::Down));
+        app.browser_event(key(KeyCode::Down));
+        let RightPane::Browser { cards, cursor, .. } = app.right_pane() else {
+            panic!("expected the browser")
+        };
+        let fronts: Vec<&str> = cards.iter().map(|c| c.front.as_str()).collect();
+        assert_eq!(fronts.len(), 2);
+        assert!(fronts.contains(&"hola") && fronts.contains(&"hasta luego"));
+        assert_eq!(cursor, 1);
+        let picked = fronts[cursor].to_string();
+
+        app.browser_event(key(KeyCode::Enter));
+        assert_eq!(app.form.as_ref().map(|f| f.front()), Some(picked.as_str()));
+        app.form_event(key(KeyCode::Char('!')));
+        app.form_event(key(KeyCode::Enter));
+        assert!(app.form.is_none());
+        let RightPane::Browser { cards, .. } = app.right_pane() else {
+            panic!("back to the browser")
+        };
+        assert!(cards.iter().any(|c| c.front == format!("{picked}!")));
+
+        app.browser_event(key(KeyCode::Esc));
+        assert!(app.browser.is_none());
+        assert!(matches!(app.right_pane(), RightPane::Idle));
+    }
+
+    #[test]
+    fn deck_stats_count_the_decks_cards_and_recent_days() {
+        let now = chrono::Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+        let deck = Deck::new("Spanish");
+        let mut due_tonight = Card::new(deck.id, "uno", "x");
+        due_tonight.reps = 1;
+        due_tonight.due_at = now + chrono::Duration::hours(6);
+        let mut later = Card::new(deck.id, "dos", "x");
+        later.reps = 1;
+        later.due_at = now + chrono::Duration::days(3);
+        let cards = vec![due_tonight.clone(), later, Card::new(deck.id, "tres", "x")];
+        let day = |ago: i64| now - chrono::Duration::days(ago);
+        let reviews = vec![
+            Review::new(due_tonight.id, Grade::Easy, day(0), 1, 2.5),
+            Review::new(due_tonight.id, Grade::Easy, day(1), 1, 2.5),
+            Review::new(due_tonight.id, Grade::Hard, day(1), 1, 2.5),
+            Review::new(due_tonight.id, Grade::Easy, day(13), 1, 2.5),
+            Review::new(due_tonight.id, Grade::Easy, day(14), 1, 2.5),
+            // Another deck's card.
+            Review::new(uuid::Uuid::new_v4(), Grade::Easy, day(2), 1, 2.5),
+        ];
+
+        let s = DeckStats::compute(deck.id, &cards, &reviews, now);
+        assert_eq!((s.counts.total, s.counts.due, s.counts.new), (3, 1, 1));
+        assert_eq!(s.streak, 2);
+        assert_eq!(s.per_day.len(), 14);
+        assert_eq!((s.per_day[0], s.per_day[12], s.per_day[13]), (1, 2, 1));
+        assert_eq!(s.per_day.iter().sum::<u64>(), 4);
+    }
+
+    #[test]
+    fn stats_pane_uses_the_cached_figures() {
+        let rt = Arc::new(Runtime::new().unwrap());
+        let repo = Arc::new(MemoryRepo::new());
+        let deck = rt.block_on(repo.create_deck("Spanish")).unwrap();
+        rt.block_on(repo.add_card(deck.id, "hola", "hello", None, &[]))
+            .unwrap();
+        let mut app = TuiApp::new(repo.clone(), rt.clone());
+        app.handle_msg(Msg::Decks(vec![(deck.clone(), DeckCounts::default())]));
+
+        app.show_stats = true;
+        app.load_stats();
+        assert!(matches!(app.right_pane(), RightPane::Empty("Loading…")));
+        let msg = app.rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        app.loading = true;
+        app.handle_msg(msg);
+        assert!(app.loading, "a stats refresh leaves other loads pending");
+        app.loading = false;
+
+        // Cards added after the refresh don't show until the next one.
+        rt.block_on(repo.add_card(deck.id, "adiós", "bye", None, &[]))
+            .unwrap();
+        let RightPane::Stats {
+            deck: name, counts, ..
+        } = app.right_pane()
+        else {
+            panic!("expected stats")
+        };
+        assert_eq!((name, counts.total, counts.new), ("Spanish", 1, 1));
+        assert!(app.rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn restudy_drills_only_failed_cards_as_cram() {
+        let rt = Arc::new(Runtime::new().unwrap());
+        let repo = Arc::new(MemoryRepo::new());
+        let deck = rt.block_on(repo.create_deck("Spanish")).unwrap();
+        let ids: Vec<_> = ["uno", "dos", "tres"]
+            .iter()
+            .map(|f| {
+                rt.block_on(repo.add_card(deck.id, f, "x", None, &[]))
+                    .unwrap()
+                    .id
+            })
+            .collect();
+
+        let mut app = TuiApp::new(repo, rt);
+        app.failed.record(ids[0], &Grade::Hard);
+        app.failed.record(ids[1], &Grade::Easy);
+        app.failed.record(ids[2], &Grade::Hard);
+        app.start_restudy();
+        assert!(app.in_review);
+        assert_eq!(app.mode, ReviewMode::Cram);
+        assert_eq!(
+            app.queue.iter().map(|c| c.id).collect::<Vec<_>>(),
+            vec![ids[0], ids[2]]
+        );
+        assert!(app.failed.is_empty());
+    }
+}
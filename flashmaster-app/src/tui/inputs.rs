@@ -1,37 +1,449 @@
-use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use crate::tui::views::ScreenLayout;
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use flashmaster_core::{parse_grade_input, Card, CardId, DeckId, Grade, GradeAction, GradeScheme};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub enum Action {
     Quit,
     Up,
     Down,
     Enter,
+    Cram,
+    ToggleSort,
     ToggleReveal,
-    GradeHard,
-    GradeMedium,
-    GradeEasy,
+    Grade(Grade),
     Skip,
+    AddTag,
+    AddCard,
+    EditCard,
+    Browse,
+    Stats,
+    /// Select the deck at this index (a click on the deck list).
+    Select(usize),
+    ScrollUp,
+    ScrollDown,
+    Yes,
+    No,
     None,
 }
 
-pub fn map_event(ev: Event) -> Action {
+/// Maps a terminal event to an action; digit and letter grade keys follow `keys`.
+pub fn map_event(ev: Event, keys: GradeScheme) -> Action {
     if let Event::Key(KeyEvent {
         code, modifiers, ..
     }) = ev
     {
         match (code, modifiers) {
-            (KeyCode::Char('q'), _) | (KeyCode::Esc, _) => Action::Quit,
+            (KeyCode::Esc, _) => Action::Quit,
             (KeyCode::Up, _) | (KeyCode::Char('k'), _) => Action::Up,
             (KeyCode::Down, _) | (KeyCode::Char('j'), _) => Action::Down,
             (KeyCode::Enter, _) => Action::Enter,
+            (KeyCode::PageUp, _) | (KeyCode::Char('u'), KeyModifiers::CONTROL) => Action::ScrollUp,
+            (KeyCode::PageDown, _) | (KeyCode::Char('d'), KeyModifiers::CONTROL) => {
+                Action::ScrollDown
+            }
+            (KeyCode::Char('c'), KeyModifiers::NONE) => Action::Cram,
+            (KeyCode::Char('r'), KeyModifiers::NONE) => Action::ToggleSort,
             (KeyCode::Char(' '), _) => Action::ToggleReveal,
-            (KeyCode::Char('1'), _) | (KeyCode::Char('h'), _) => Action::GradeHard,
-            (KeyCode::Char('2'), _) | (KeyCode::Char('m'), _) => Action::GradeMedium,
-            (KeyCode::Char('3'), _) | (KeyCode::Char('e'), _) => Action::GradeEasy,
-            (KeyCode::Char('s'), KeyModifiers::NONE) => Action::Skip,
+            (KeyCode::Char('+'), _) => Action::AddTag,
+            (KeyCode::Char('/'), _) => Action::Browse,
+            (KeyCode::Char('t'), KeyModifiers::NONE) => Action::Stats,
+            // Shifted, since `e` grades Easy.
+            (KeyCode::Char('E'), _) => Action::EditCard,
+            (KeyCode::Char('y'), _) => Action::Yes,
+            (KeyCode::Char('n'), _) => Action::No,
+            // Grades, skip and quit as the CLI prompt reads them.
+            (KeyCode::Char(c), _) => match parse_grade_input(c.encode_utf8(&mut [0; 4]), keys) {
+                Some(GradeAction::Grade(g)) => Action::Grade(g),
+                Some(GradeAction::Skip) => Action::Skip,
+                Some(GradeAction::Quit) => Action::Quit,
+                None => Action::None,
+            },
             _ => Action::None,
         }
     } else {
         Action::None
     }
 }
+
+/// Keys that only apply on the deck list, checked before [`map_event`]:
+/// there is nothing to grade yet, so `a` (Again in a review) adds a card.
+pub fn map_browse_event(ev: &Event) -> Option<Action> {
+    match ev {
+        Event::Key(KeyEvent {
+            code: KeyCode::Char('a'),
+            modifiers: KeyModifiers::NONE,
+            ..
+        }) => Some(Action::AddCard),
+        _ => None,
+    }
+}
+
+/// Left clicks, checked against the last frame's `layout` before
+/// [`map_event`]: a deck row selects that deck, a grade button grades.
+pub fn map_mouse_event(ev: &Event, layout: &ScreenLayout) -> Option<Action> {
+    let Event::Mouse(MouseEvent {
+        kind: MouseEventKind::Down(MouseButton::Left),
+        column,
+        row,
+        ..
+    }) = *ev
+    else {
+        return None;
+    };
+    let hit = |r: &ratatui::layout::Rect| {
+        (r.x..r.x + r.width).contains(&column) && (r.y..r.y + r.height).contains(&row)
+    };
+    if let Some((_, g)) = layout.grades.iter().find(|(r, _)| hit(r)) {
+        return Some(Action::Grade(g.clone()));
+    }
+    hit(&layout.decks).then(|| Action::Select(usize::from(row - layout.decks.y)))
+}
+
+/// A key while a one-line text input is open; every printable key is text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextKey {
+    Char(char),
+    Backspace,
+    Left,
+    Right,
+    /// Tab or Down: the next field of a form.
+    Next,
+    /// Shift-Tab or Up: the previous field of a form.
+    Prev,
+    Submit,
+    Cancel,
+    None,
+}
+
+pub fn map_text_event(ev: Event) -> TextKey {
+    match ev {
+        Event::Key(KeyEvent { code, .. }) => match code {
+            KeyCode::Char(c) => TextKey::Char(c),
+            KeyCode::Backspace => TextKey::Backspace,
+            KeyCode::Left => TextKey::Left,
+            KeyCode::Right => TextKey::Right,
+            KeyCode::Tab | KeyCode::Down => TextKey::Next,
+            KeyCode::BackTab | KeyCode::Up => TextKey::Prev,
+            KeyCode::Enter => TextKey::Submit,
+            KeyCode::Esc => TextKey::Cancel,
+            _ => TextKey::None,
+        },
+        _ => TextKey::None,
+    }
+}
+
+/// One line of editable text with a cursor, counted in characters.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LineEditor {
+    text: String,
+    cursor: usize,
+}
+
+impl LineEditor {
+    /// Starts with `text`, the cursor at its end.
+    pub fn new(text: impl Into<String>) -> Self {
+        let text = text.into();
+        let cursor = text.chars().count();
+        Self { text, cursor }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The text with a bar at the cursor, for the focused field.
+    pub fn with_cursor(&self) -> String {
+        let mut s = self.text.clone();
+        s.insert(self.byte_index(), '▏');
+        s
+    }
+
+    /// Applies an editing key; `false` for keys that aren't editing (submit,
+    /// cancel, moving between fields).
+    pub fn apply(&mut self, key: &TextKey) -> bool {
+        match key {
+            TextKey::Char(c) => {
+                self.text.insert(self.byte_index(), *c);
+                self.cursor += 1;
+            }
+            TextKey::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.text.remove(self.byte_index());
+            }
+            TextKey::Backspace => {}
+            TextKey::Left => self.cursor = self.cursor.saturating_sub(1),
+            TextKey::Right => self.cursor = (self.cursor + 1).min(self.text.chars().count()),
+            _ => return false,
+        }
+        true
+    }
+
+    fn byte_index(&self) -> usize {
+        self.text
+            .char_indices()
+            .nth(self.cursor)
+            .map_or(self.text.len(), |(i, _)| i)
+    }
+}
+
+/// Labels of the card form's fields, in the order they're shown.
+pub const CARD_FORM_FIELDS: [&str; 4] = ["Front", "Back", "Hint", "Tags"];
+
+/// What a key did to a [`CardForm`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormStep {
+    Editing,
+    Submit,
+    Cancel,
+}
+
+/// The TUI's add/edit card form. Tags are typed comma-separated; an empty
+/// hint clears it.
+#[derive(Debug, Clone)]
+pub struct CardForm {
+    /// The card being edited; `None` when adding a card to `deck_id`.
+    pub card_id: Option<CardId>,
+    pub deck_id: DeckId,
+    pub fields: [LineEditor; 4],
+    pub focus: usize,
+    /// Why the last submit was refused.
+    pub error: Option<String>,
+}
+
+impl CardForm {
+    pub fn add(deck_id: DeckId) -> Self {
+        Self {
+            card_id: None,
+            deck_id,
+            fields: Default::default(),
+            focus: 0,
+            error: None,
+        }
+    }
+
+    pub fn edit(card: &Card) -> Self {
+        Self {
+            card_id: Some(card.id),
+            deck_id: card.deck_id,
+            fields: [
+                LineEditor::new(card.front.as_str()),
+                LineEditor::new(card.back.as_str()),
+                LineEditor::new(card.hint.clone().unwrap_or_default()),
+                LineEditor::new(card.tags.join(", ")),
+            ],
+            focus: 0,
+            error: None,
+        }
+    }
+
+    /// Edits the focused field, moves focus, or reports Enter / Esc.
+    pub fn key(&mut self, key: TextKey) -> FormStep {
+        if self.fields[self.focus].apply(&key) {
+            return FormStep::Editing;
+        }
+        let n = self.fields.len();
+        match key {
+            TextKey::Next => self.focus = (self.focus + 1) % n,
+            TextKey::Prev => self.focus = (self.focus + n - 1) % n,
+            TextKey::Submit => return FormStep::Submit,
+            TextKey::Cancel => return FormStep::Cancel,
+            _ => {}
+        }
+        FormStep::Editing
+    }
+
+    pub fn front(&self) -> &str {
+        self.fields[0].text().trim()
+    }
+
+    pub fn back(&self) -> &str {
+        self.fields[1].text().trim()
+    }
+
+    pub fn hint(&self) -> Option<String> {
+        let h = self.fields[2].text().trim();
+        (!h.is_empty()).then(|| h.to_string())
+    }
+
+    pub fn tags(&self) -> Vec<String> {
+        self.fields[3]
+            .text()
+            .split(',')
+            .map(|t| t.trim().trim_start_matches('#'))
+            .filter(|t| !t.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// The message to show if the card can't be saved as typed.
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.front().is_empty() {
+            Err("front can't be empty")
+        } else if self.back().is_empty() {
+            Err("back can't be empty")
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(c: char) -> Event {
+        Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn grade_keys_follow_the_scheme() {
+        let grade = |c, keys| match map_event(key(c), keys) {
+            Action::Grade(g) => Some(g),
+            _ => None,
+        };
+        assert_eq!(grade('1', GradeScheme::Three), Some(Grade::Hard));
+        assert_eq!(grade('3', GradeScheme::Three), Some(Grade::Easy));
+        assert_eq!(grade('4', GradeScheme::Three), None);
+        assert_eq!(grade('3', GradeScheme::Four), Some(Grade::Medium));
+        assert_eq!(grade('4', GradeScheme::Four), Some(Grade::Easy));
+        assert!(matches!(
+            map_event(key('s'), GradeScheme::Four),
+            Action::Skip
+        ));
+        assert!(matches!(
+            map_event(key('q'), GradeScheme::Three),
+            Action::Quit
+        ));
+        assert_eq!(grade('e', GradeScheme::Three), Some(Grade::Easy));
+    }
+
+    #[test]
+    fn text_input_takes_grade_and_quit_keys_as_text() {
+        assert_eq!(map_text_event(key('1')), TextKey::Char('1'));
+        assert_eq!(map_text_event(key('q')), TextKey::Char('q'));
+        let esc = Event::Key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        assert_eq!(map_text_event(esc), TextKey::Cancel);
+    }
+
+    #[test]
+    fn add_and_edit_keys_leave_grading_alone() {
+        assert!(matches!(map_browse_event(&key('a')), Some(Action::AddCard)));
+        assert!(map_browse_event(&key('e')).is_none());
+        assert!(matches!(
+            map_event(key('a'), GradeScheme::Three),
+            Action::Grade(Grade::Again)
+        ));
+        assert!(matches!(
+            map_event(key('E'), GradeScheme::Three),
+            Action::EditCard
+        ));
+        assert!(matches!(
+            map_event(key('/'), GradeScheme::Four),
+            Action::Browse
+        ));
+        assert!(matches!(
+            map_event(key('t'), GradeScheme::Four),
+            Action::Stats
+        ));
+    }
+
+    #[test]
+    fn clicks_select_decks_and_grade() {
+        use ratatui::layout::Rect;
+        let click = |column, row| {
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Down(MouseButton::Left),
+                column,
+                row,
+                modifiers: KeyModifiers::NONE,
+            })
+        };
+        let layout = ScreenLayout {
+            decks: Rect::new(1, 2, 20, 5),
+            grades: vec![
+                (Rect::new(30, 10, 8, 1), Grade::Hard),
+                (Rect::new(39, 10, 10, 1), Grade::Medium),
+            ],
+            max_scroll: 0,
+        };
+        assert!(matches!(
+            map_mouse_event(&click(5, 2), &layout),
+            Some(Action::Select(0))
+        ));
+        assert!(matches!(
+            map_mouse_event(&click(20, 6), &layout),
+            Some(Action::Select(4))
+        ));
+        assert!(map_mouse_event(&click(21, 4), &layout).is_none());
+        assert!(map_mouse_event(&click(5, 7), &layout).is_none());
+        assert!(matches!(
+            map_mouse_event(&click(40, 10), &layout),
+            Some(Action::Grade(Grade::Medium))
+        ));
+        assert!(map_mouse_event(&click(38, 10), &layout).is_none());
+        assert!(map_mouse_event(&key('j'), &layout).is_none());
+    }
+
+    #[test]
+    fn page_keys_scroll() {
+        let ctrl = |c| Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL));
+        let plain = |code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        let keys = GradeScheme::Four;
+        assert!(matches!(
+            map_event(plain(KeyCode::PageDown), keys),
+            Action::ScrollDown
+        ));
+        assert!(matches!(
+            map_event(plain(KeyCode::PageUp), keys),
+            Action::ScrollUp
+        ));
+        assert!(matches!(map_event(ctrl('d'), keys), Action::ScrollDown));
+        assert!(matches!(map_event(ctrl('u'), keys), Action::ScrollUp));
+        assert!(matches!(map_event(key('u'), keys), Action::None));
+    }
+
+    #[test]
+    fn line_editor_inserts_at_the_cursor() {
+        let mut line = LineEditor::new("hla");
+        for k in [TextKey::Left, TextKey::Left, TextKey::Char('o')] {
+            assert!(line.apply(&k));
+        }
+        assert_eq!(line.text(), "hola");
+        assert_eq!(line.with_cursor(), "ho▏la");
+        line.apply(&TextKey::Backspace);
+        line.apply(&TextKey::Right);
+        line.apply(&TextKey::Right);
+        line.apply(&TextKey::Right);
+        line.apply(&TextKey::Char('é'));
+        assert_eq!(line.text(), "hlaé");
+        assert!(!line.apply(&TextKey::Submit));
+    }
+
+    #[test]
+    fn card_form_cycles_fields_and_checks_front_and_back() {
+        let mut form = CardForm::add(uuid::Uuid::new_v4());
+        for c in " hola ".chars() {
+            form.key(TextKey::Char(c));
+        }
+        assert_eq!(form.validate(), Err("back can't be empty"));
+        assert_eq!(form.key(TextKey::Prev), FormStep::Editing);
+        assert_eq!(form.focus, 3);
+        for c in "verbs, #a1,, ".chars() {
+            form.key(TextKey::Char(c));
+        }
+        form.key(TextKey::Next);
+        form.key(TextKey::Next);
+        for c in "hello".chars() {
+            form.key(TextKey::Char(c));
+        }
+        assert_eq!(form.validate(), Ok(()));
+        assert_eq!((form.front(), form.back()), ("hola", "hello"));
+        assert_eq!(form.hint(), None);
+        assert_eq!(form.tags(), ["verbs", "a1"]);
+        assert_eq!(form.key(TextKey::Submit), FormStep::Submit);
+        assert_eq!(form.key(TextKey::Cancel), FormStep::Cancel);
+    }
+}
@@ -1,7 +1,122 @@
-use ratatui::style::{Color, Style};
 use ratatui::style::Stylize;
+use ratatui::style::{Color, Modifier, Style};
 
-pub fn title_style() -> Style { Style::default().fg(Color::Cyan).bold() }
-pub fn hint_style() -> Style { Style::default().fg(Color::DarkGray) }
-pub fn selected_style() -> Style { Style::default().fg(Color::Yellow).bold() }
-pub fn footer_style() -> Style { Style::default().fg(Color::Gray) }
+/// How many colors the terminal can show, as far as the environment says.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorSupport {
+    None,
+    Basic,
+    Ansi256,
+    TrueColor,
+}
+
+impl ColorSupport {
+    /// Reads `NO_COLOR`, `COLORTERM` and `TERM` from the process environment.
+    pub fn from_env() -> Self {
+        let var = |k| std::env::var(k).ok();
+        Self::detect(
+            var("NO_COLOR").as_deref(),
+            var("COLORTERM").as_deref(),
+            var("TERM").as_deref(),
+        )
+    }
+
+    /// A non-empty `NO_COLOR` wins (see no-color.org); a missing or `dumb`
+    /// `TERM` gets no color; anything unrecognised gets the 16-color palette.
+    pub fn detect(no_color: Option<&str>, colorterm: Option<&str>, term: Option<&str>) -> Self {
+        if no_color.is_some_and(|v| !v.is_empty()) {
+            return ColorSupport::None;
+        }
+        if matches!(colorterm, Some("truecolor" | "24bit")) {
+            return ColorSupport::TrueColor;
+        }
+        match term {
+            None | Some("" | "dumb") => ColorSupport::None,
+            Some(t) if t.contains("256color") => ColorSupport::Ansi256,
+            Some(_) => ColorSupport::Basic,
+        }
+    }
+}
+
+/// Styles for the TUI, picked once at startup from [`ColorSupport`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Theme {
+    pub title: Style,
+    pub hint: Style,
+    pub selected: Style,
+    pub footer: Style,
+}
+
+impl Theme {
+    pub fn for_support(support: ColorSupport) -> Self {
+        match support {
+            ColorSupport::TrueColor | ColorSupport::Ansi256 => Self {
+                title: Style::default().fg(Color::Indexed(45)).bold(),
+                hint: Style::default().fg(Color::Indexed(244)),
+                selected: Style::default().fg(Color::Indexed(220)).bold(),
+                footer: Style::default().fg(Color::Indexed(250)),
+            },
+            ColorSupport::Basic => Self {
+                title: Style::default().fg(Color::Cyan).bold(),
+                hint: Style::default().fg(Color::DarkGray),
+                selected: Style::default().fg(Color::Yellow).bold(),
+                footer: Style::default().fg(Color::Gray),
+            },
+            // No colors or emphasis, except reversing the selected deck so it stays visible.
+            ColorSupport::None => Self {
+                title: Style::default(),
+                hint: Style::default(),
+                selected: Style::default().add_modifier(Modifier::REVERSED),
+                footer: Style::default(),
+            },
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::for_support(ColorSupport::from_env())
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::for_support(ColorSupport::Basic)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn palette_follows_env() {
+        let support = |nc, ct, t| ColorSupport::detect(nc, ct, t);
+        assert_eq!(
+            support(None, Some("truecolor"), Some("xterm-256color")),
+            ColorSupport::TrueColor
+        );
+        assert_eq!(
+            support(None, None, Some("xterm-256color")),
+            ColorSupport::Ansi256
+        );
+        assert_eq!(support(None, None, Some("xterm")), ColorSupport::Basic);
+        assert_eq!(support(None, None, Some("dumb")), ColorSupport::None);
+        assert_eq!(
+            support(Some("1"), Some("truecolor"), Some("xterm-256color")),
+            ColorSupport::None
+        );
+        assert_eq!(support(Some(""), None, Some("xterm")), ColorSupport::Basic);
+
+        assert_eq!(
+            Theme::for_support(ColorSupport::Basic).title.fg,
+            Some(Color::Cyan)
+        );
+        assert_eq!(
+            Theme::for_support(ColorSupport::Ansi256).title.fg,
+            Some(Color::Indexed(45))
+        );
+        let plain = Theme::for_support(ColorSupport::None);
+        assert!([plain.title, plain.hint, plain.selected, plain.footer]
+            .iter()
+            .all(|s| s.fg.is_none() && s.bg.is_none()));
+    }
+}
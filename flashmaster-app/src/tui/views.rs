@@ -1,35 +1,124 @@
-use crate::tui::theme::*;
-use flashmaster_core::{Card, Deck};
+use crate::tui::inputs::{CardForm, LineEditor, CARD_FORM_FIELDS};
+use crate::tui::theme::Theme;
+use flashmaster_core::{Card, Deck, DeckCounts, Grade, GradeScheme, PromptWith};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
+    widgets::{Block, Borders, List, ListItem, Paragraph, Sparkline, Wrap},
     Frame,
 };
+use std::borrow::Cow;
+use std::ops::Range;
+
+/// Most characters drawn for one card field. Longer text is clipped on
+/// screen only; the stored card keeps its full content.
+pub const MAX_DISPLAY_CHARS: usize = 2000;
+
+/// `s` cut to `max` characters plus an ellipsis, or unchanged if it fits.
+pub fn clip(s: &str, max: usize) -> Cow<'_, str> {
+    match s.char_indices().nth(max) {
+        Some((end, _)) => Cow::Owned(format!("{}…", &s[..end])),
+        None => Cow::Borrowed(s),
+    }
+}
 
 pub enum RightPane<'a> {
     Idle,
-    Card { card: &'a Card, reveal: bool },
+    Card {
+        card: &'a Card,
+        /// The deck's question side.
+        prompt: PromptWith,
+        reveal: bool,
+        /// Lines scrolled past the top of the card text.
+        scroll: u16,
+        struggling: bool,
+        /// Projected interval per grade key; `None` when grades don't
+        /// reschedule (cram).
+        intervals: Option<String>,
+    },
     Empty(&'a str),
+    Resume {
+        remaining: usize,
+    },
+    Restudy {
+        failed: usize,
+    },
+    /// The add/edit card form.
+    Edit(&'a CardForm),
+    /// The selected deck's figures; `per_day` holds review counts, oldest
+    /// day first, ending today.
+    Stats {
+        deck: &'a str,
+        counts: DeckCounts,
+        streak: u32,
+        per_day: &'a [u64],
+    },
+    /// The card browser: cards matching `filter`, `cursor` indexing them.
+    Browser {
+        cards: Vec<&'a Card>,
+        filter: &'a LineEditor,
+        cursor: usize,
+    },
+}
+
+/// Where the last frame put what the mouse can click.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ScreenLayout {
+    /// The deck list's rows, deck 0 on the top one.
+    pub decks: Rect,
+    /// Grade buttons under a revealed card.
+    pub grades: Vec<(Rect, Grade)>,
+    /// How far the card pane can scroll before its text runs out.
+    pub max_scroll: u16,
 }
 
-pub fn draw_ui(f: &mut Frame, area: Rect, decks: &[Deck], sel: usize, right: RightPane) {
+/// Draws the whole screen and returns its clickable layout.
+pub fn draw_ui(
+    f: &mut Frame,
+    decks: &[(Deck, DeckCounts)],
+    sel: usize,
+    right: RightPane,
+    status: Option<&str>,
+    keys: GradeScheme,
+    theme: &Theme,
+) -> ScreenLayout {
+    let area = f.size();
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
         .split(area);
-    draw_decks(f, chunks[0], decks, sel);
-    draw_right(f, chunks[1], right);
+    let mut layout = ScreenLayout {
+        decks: draw_decks(f, chunks[0], decks, sel, theme),
+        ..Default::default()
+    };
+    draw_right(f, chunks[1], right, keys, theme, &mut layout);
+
+    if let Some(msg) = status {
+        let r = chunks[1];
+        let sh = Rect {
+            x: r.x + 1,
+            y: r.y + r.height.saturating_sub(2),
+            width: r.width.saturating_sub(2),
+            height: 1,
+        };
+        f.render_widget(Paragraph::new(msg).style(theme.hint), sh);
+    }
 
     let foot = Paragraph::new(Line::from(vec![
         Span::raw(" ↑/k ↓/j select  "),
         Span::raw(" Enter start  "),
         Span::raw(" space reveal  "),
-        Span::raw(" 1/2/3 grade  "),
+        Span::raw(format!(" {} grade  ", keys.keys())),
         Span::raw(" s skip  "),
+        Span::raw(" PgUp/PgDn scroll  "),
+        Span::raw(" + tag  "),
+        Span::raw(" a add  "),
+        Span::raw(" E edit  "),
+        Span::raw(" / browse  "),
+        Span::raw(" t stats  "),
         Span::raw(" q quit "),
     ]))
-    .style(footer_style())
+    .style(theme.footer)
     .block(Block::default().borders(Borders::TOP));
     let fh = Rect {
         x: area.x,
@@ -38,23 +127,70 @@ pub fn draw_ui(f: &mut Frame, area: Rect, decks: &[Deck], sel: usize, right: Rig
         height: 1,
     };
     f.render_widget(foot, fh);
+    layout
+}
+
+/// The rows of a `len`-row list shown in `height` lines: the first page, or
+/// the page ending at `cursor` once it scrolls past that.
+pub fn visible_rows(len: usize, cursor: usize, height: usize) -> Range<usize> {
+    let start = (cursor + 1).saturating_sub(height);
+    start..len.min(start + height)
+}
+
+/// Rows `line` takes word-wrapped to `width` columns, the way [`Wrap`] lays
+/// it out; words wider than the pane are broken across rows.
+pub fn wrapped_rows(line: &str, width: usize) -> usize {
+    if width == 0 {
+        return 0;
+    }
+    let mut rows = 1;
+    let mut col = 0;
+    for word in line.split_whitespace() {
+        let w = word.chars().count();
+        if col > 0 && col + 1 + w <= width {
+            col += 1 + w;
+            continue;
+        }
+        if col > 0 {
+            rows += 1;
+        }
+        rows += (w - 1) / width;
+        col = (w - 1) % width + 1;
+    }
+    rows
+}
+
+/// Deck list entry: the name, then how many cards are due and new if any.
+pub fn deck_label(deck: &Deck, counts: &DeckCounts) -> String {
+    match (counts.due, counts.new) {
+        (0, 0) => deck.name.clone(),
+        (due, new) => format!("{}  ({due} due · {new} new)", deck.name),
+    }
 }
 
-fn draw_decks(f: &mut Frame, area: Rect, decks: &[Deck], sel: usize) {
+/// Returns the rows the decks are listed in.
+fn draw_decks(
+    f: &mut Frame,
+    area: Rect,
+    decks: &[(Deck, DeckCounts)],
+    sel: usize,
+    theme: &Theme,
+) -> Rect {
     let items: Vec<_> = decks
         .iter()
         .enumerate()
-        .map(|(i, d)| {
+        .map(|(i, (d, counts))| {
+            let label = deck_label(d, counts);
             let s = if i == sel {
-                Line::from(d.name.clone()).style(selected_style())
+                Line::from(label).style(theme.selected)
             } else {
-                Line::from(d.name.clone())
+                Line::from(label)
             };
             ListItem::new(s)
         })
         .collect();
 
-    let title = Paragraph::new(Line::from(vec![Span::raw("Decks").style(title_style())]));
+    let title = Paragraph::new(Line::from(vec![Span::raw("Decks").style(theme.title)]));
     let th = Rect {
         x: area.x,
         y: area.y,
@@ -69,11 +205,20 @@ fn draw_decks(f: &mut Frame, area: Rect, decks: &[Deck], sel: usize) {
         width: area.width,
         height: area.height.saturating_sub(1),
     };
-    let list = List::new(items).block(Block::default().borders(Borders::ALL));
-    f.render_widget(list, list_area);
+    let block = Block::default().borders(Borders::ALL);
+    let rows = block.inner(list_area);
+    f.render_widget(List::new(items).block(block), list_area);
+    rows
 }
 
-fn draw_right(f: &mut Frame, area: Rect, pane: RightPane) {
+fn draw_right(
+    f: &mut Frame,
+    area: Rect,
+    pane: RightPane,
+    keys: GradeScheme,
+    theme: &Theme,
+    layout: &mut ScreenLayout,
+) {
     match pane {
         RightPane::Idle => {
             let p = Paragraph::new("Press Enter to start reviewing the selected deck.")
@@ -81,13 +226,156 @@ fn draw_right(f: &mut Frame, area: Rect, pane: RightPane) {
                 .block(Block::default().title("Review").borders(Borders::ALL));
             f.render_widget(p, area);
         }
+        RightPane::Resume { remaining } => {
+            let msg = format!(
+                "Unfinished session for this deck ({remaining} left).\n\ny = resume   n = start fresh"
+            );
+            let p = Paragraph::new(msg)
+                .wrap(Wrap { trim: true })
+                .block(Block::default().title("Review").borders(Borders::ALL));
+            f.render_widget(p, area);
+        }
+        RightPane::Restudy { failed } => {
+            let msg = format!("Session done. Re-study {failed} failed cards?\n\ny = yes   n = no");
+            let p = Paragraph::new(msg)
+                .wrap(Wrap { trim: true })
+                .block(Block::default().title("Review").borders(Borders::ALL));
+            f.render_widget(p, area);
+        }
+        RightPane::Edit(form) => {
+            let title = if form.card_id.is_some() {
+                "Edit card"
+            } else {
+                "Add card"
+            };
+            let mut text: Vec<Line> = CARD_FORM_FIELDS
+                .iter()
+                .zip(&form.fields)
+                .enumerate()
+                .map(|(i, (label, field))| {
+                    let value = if i == form.focus {
+                        Span::raw(field.with_cursor()).style(theme.selected)
+                    } else {
+                        Span::raw(clip(field.text(), MAX_DISPLAY_CHARS).into_owned())
+                    };
+                    Line::from(vec![
+                        Span::raw(format!("{label}: ")).style(theme.title),
+                        value,
+                    ])
+                })
+                .collect();
+            text.push(Line::from(""));
+            if let Some(err) = &form.error {
+                text.push(Line::from(Span::raw(err.as_str()).style(theme.selected)));
+            }
+            text.push(Line::from(
+                Span::raw("Tab/↓ next field · Enter save · Esc cancel · tags: comma-separated")
+                    .style(theme.hint),
+            ));
+            let p = Paragraph::new(text)
+                .wrap(Wrap { trim: false })
+                .block(Block::default().title(title).borders(Borders::ALL));
+            f.render_widget(p, area);
+        }
+        RightPane::Stats {
+            deck,
+            counts,
+            streak,
+            per_day,
+        } => {
+            let block = Block::default()
+                .title(format!("Stats: {deck}"))
+                .borders(Borders::ALL);
+            let inner = block.inner(area);
+            f.render_widget(block, area);
+
+            let row = |label: &str, value: String| {
+                Line::from(vec![
+                    Span::raw(format!("{label:<11}")).style(theme.title),
+                    Span::raw(value),
+                ])
+            };
+            let busiest = per_day.iter().copied().max().unwrap_or(0);
+            let text = vec![
+                row("cards", counts.total.to_string()),
+                row("due today", counts.due.to_string()),
+                row("new", counts.new.to_string()),
+                row("streak", format!("{streak} day(s)")),
+                Line::from(""),
+                Line::from(
+                    Span::raw(format!(
+                        "reviews, last {} days (busiest: {busiest})",
+                        per_day.len()
+                    ))
+                    .style(theme.hint),
+                ),
+            ];
+            let text_height = text.len() as u16;
+            f.render_widget(Paragraph::new(text), inner);
+
+            let spark = Rect {
+                y: inner.y + text_height,
+                width: inner.width.min(per_day.len() as u16),
+                height: inner.height.saturating_sub(text_height + 1).min(4),
+                ..inner
+            };
+            f.render_widget(
+                Sparkline::default().data(per_day).style(theme.selected),
+                spark,
+            );
+        }
+        RightPane::Browser {
+            cards,
+            filter,
+            cursor,
+        } => {
+            let block = Block::default()
+                .title(format!("Cards ({})", cards.len()))
+                .borders(Borders::ALL);
+            let inner = block.inner(area);
+            f.render_widget(block, area);
+
+            let mut text = vec![
+                Line::from(vec![
+                    Span::raw("/ ").style(theme.title),
+                    Span::raw(filter.with_cursor()),
+                ]),
+                Line::from(
+                    Span::raw("type to filter · ↑/↓ move · Enter edit · Esc close")
+                        .style(theme.hint),
+                ),
+            ];
+            // Room for the filter, the help line, and the status line below.
+            let height = (inner.height as usize).saturating_sub(3);
+            // The "> " marker takes two columns and clip's ellipsis one more.
+            let width = (inner.width as usize).saturating_sub(3);
+            for i in visible_rows(cards.len(), cursor, height) {
+                let front = clip(cards[i].front.lines().next().unwrap_or(""), width).into_owned();
+                text.push(if i == cursor {
+                    Line::from(format!("> {front}")).style(theme.selected)
+                } else {
+                    Line::from(format!("  {front}"))
+                });
+            }
+            if cards.is_empty() {
+                text.push(Line::from(Span::raw("no matching cards").style(theme.hint)));
+            }
+            f.render_widget(Paragraph::new(text), inner);
+        }
         RightPane::Empty(msg) => {
             let p = Paragraph::new(msg)
                 .wrap(Wrap { trim: true })
                 .block(Block::default().title("Review").borders(Borders::ALL));
             f.render_widget(p, area);
         }
-        RightPane::Card { card, reveal } => {
+        RightPane::Card {
+            card,
+            prompt,
+            reveal,
+            scroll,
+            struggling,
+            intervals,
+        } => {
             let title = Block::default().title("Review").borders(Borders::ALL);
             let inner = Rect {
                 x: area.x + 1,
@@ -97,34 +385,194 @@ fn draw_right(f: &mut Frame, area: Rect, pane: RightPane) {
             };
             f.render_widget(title, area);
 
-            let q = Paragraph::new(Line::from(vec![
-                Span::raw("Q: ").style(title_style()),
-                Span::raw(&card.front),
-            ]))
-            .wrap(Wrap { trim: true });
-            f.render_widget(q, inner);
+            // Question and answer scroll together, so a long front never
+            // runs underneath the answer.
+            let (question, answer) = (card.question(prompt), card.answer(prompt));
+            let mut text = vec![Line::from(vec![
+                Span::raw("Q: ").style(theme.title),
+                Span::raw(clip(&question, MAX_DISPLAY_CHARS)),
+            ])];
 
             if reveal {
-                let ans_y = inner.y + 2;
-                let ans_area = Rect {
-                    x: inner.x,
-                    y: ans_y,
-                    width: inner.width,
-                    height: inner.height.saturating_sub(2),
-                };
-                let mut text = vec![Line::from(vec![
-                    Span::raw("A: ").style(title_style()),
-                    Span::raw(&card.back),
-                ])];
+                text.push(Line::default());
+                text.push(Line::from(vec![
+                    Span::raw("A: ").style(theme.title),
+                    Span::raw(clip(&answer, MAX_DISPLAY_CHARS)),
+                ]));
                 if let Some(h) = &card.hint {
                     text.push(Line::from(vec![
-                        Span::raw("hint: ").style(hint_style()),
-                        Span::raw(h),
+                        Span::raw("hint: ").style(theme.hint),
+                        Span::raw(clip(h, MAX_DISPLAY_CHARS)),
+                    ]));
+                }
+                if let Some(s) = &card.source {
+                    text.push(Line::from(vec![
+                        Span::raw("source: ").style(theme.hint),
+                        Span::raw(clip(s, MAX_DISPLAY_CHARS)),
                     ]));
                 }
-                let a = Paragraph::new(text).wrap(Wrap { trim: true });
-                f.render_widget(a, ans_area);
+                if let Some(next) = intervals {
+                    text.push(Line::from(vec![
+                        Span::raw("next: ").style(theme.hint),
+                        Span::raw(next),
+                    ]));
+                }
+                if struggling {
+                    text.push(Line::from(
+                        Span::raw("struggling — consider editing this card").style(theme.selected),
+                    ));
+                }
             }
+            // A revealed card keeps its last two rows for the grade buttons
+            // and the status line.
+            let mut text_area = inner;
+            if reveal && inner.height >= 3 {
+                text_area.height -= 2;
+                let bar = Rect {
+                    y: inner.y + text_area.height,
+                    height: 1,
+                    ..inner
+                };
+                layout.grades = grade_buttons(keys, bar);
+                let mut spans = vec![];
+                for (i, l) in keys.labels().iter().enumerate().take(layout.grades.len()) {
+                    spans.push(Span::raw(format!("[{} {l}]", i + 1)).style(theme.title));
+                    spans.push(Span::raw(" "));
+                }
+                f.render_widget(Paragraph::new(Line::from(spans)), bar);
+            }
+            let rows: usize = text
+                .iter()
+                .map(|l| {
+                    let s: String = l.spans.iter().map(|s| s.content.as_ref()).collect();
+                    wrapped_rows(&s, text_area.width as usize)
+                })
+                .sum();
+            layout.max_scroll =
+                u16::try_from(rows.saturating_sub(text_area.height as usize)).unwrap_or(u16::MAX);
+            let p = Paragraph::new(text)
+                .wrap(Wrap { trim: true })
+                .scroll((scroll.min(layout.max_scroll), 0));
+            f.render_widget(p, text_area);
         }
     }
 }
+
+/// Where each `[1 Hard]`-style button lands along `bar`, in key order,
+/// one space apart; buttons that don't fit are left off.
+pub fn grade_buttons(keys: GradeScheme, bar: Rect) -> Vec<(Rect, Grade)> {
+    let mut x = bar.x;
+    let mut out = vec![];
+    for (i, l) in keys.labels().iter().enumerate() {
+        let key = char::from_digit(i as u32 + 1, 10).unwrap_or('0');
+        let width = format!("[{key} {l}]").chars().count() as u16;
+        let Some(grade) = keys.grade_for_key(key) else {
+            break;
+        };
+        if x + width > bar.x + bar.width {
+            break;
+        }
+        out.push((Rect { x, width, ..bar }, grade));
+        x += width + 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clip_shortens_only_the_display() {
+        let card = Card::new(
+            uuid::Uuid::new_v4(),
+            "é".repeat(MAX_DISPLAY_CHARS + 10),
+            "x",
+        );
+        let shown = clip(&card.front, MAX_DISPLAY_CHARS);
+        assert_eq!(shown.chars().count(), MAX_DISPLAY_CHARS + 1);
+        assert!(shown.ends_with('…'));
+        assert_eq!(card.front.chars().count(), MAX_DISPLAY_CHARS + 10);
+        assert!(matches!(
+            clip("short", MAX_DISPLAY_CHARS),
+            Cow::Borrowed("short")
+        ));
+    }
+
+    #[test]
+    fn visible_rows_scroll_to_keep_the_cursor_shown() {
+        assert_eq!(visible_rows(3, 0, 10), 0..3);
+        assert_eq!(visible_rows(50, 4, 10), 0..10);
+        assert_eq!(visible_rows(50, 9, 10), 0..10);
+        assert_eq!(visible_rows(50, 10, 10), 1..11);
+        assert_eq!(visible_rows(50, 49, 10), 40..50);
+        assert_eq!(visible_rows(5, 2, 0), 3..3);
+    }
+
+    #[test]
+    fn wrapped_rows_break_at_words() {
+        assert_eq!(wrapped_rows("", 10), 1);
+        assert_eq!(wrapped_rows("short", 10), 1);
+        assert_eq!(wrapped_rows("one two three", 9), 2);
+        assert_eq!(wrapped_rows("abcdefghijkl", 5), 3);
+        assert_eq!(wrapped_rows("anything", 0), 0);
+    }
+
+    #[test]
+    fn grade_buttons_follow_the_scheme_and_fit_the_bar() {
+        let bar = Rect::new(2, 9, 40, 1);
+        let buttons = grade_buttons(GradeScheme::Three, bar);
+        // `[1 Hard] [2 Medium] [3 Easy]`
+        assert_eq!(
+            buttons,
+            vec![
+                (Rect::new(2, 9, 8, 1), Grade::Hard),
+                (Rect::new(11, 9, 10, 1), Grade::Medium),
+                (Rect::new(22, 9, 8, 1), Grade::Easy),
+            ]
+        );
+        let narrow = grade_buttons(GradeScheme::Four, Rect::new(0, 0, 18, 1));
+        assert_eq!(narrow.len(), 2);
+        assert_eq!(narrow[0].1, Grade::Again);
+    }
+
+    #[test]
+    fn card_scroll_is_clamped_to_the_text() {
+        let deck = Deck::new("Spanish");
+        let draw = |back: &str, scroll| {
+            let card = Card::new(deck.id, "hola", back);
+            let mut term =
+                ratatui::Terminal::new(ratatui::backend::TestBackend::new(40, 12)).unwrap();
+            let mut layout = ScreenLayout::default();
+            term.draw(|f| {
+                let pane = RightPane::Card {
+                    card: &card,
+                    prompt: PromptWith::Front,
+                    reveal: true,
+                    scroll,
+                    struggling: false,
+                    intervals: None,
+                };
+                layout = draw_ui(f, &[], 0, pane, None, GradeScheme::Four, &Theme::default());
+            })
+            .unwrap();
+            layout.max_scroll
+        };
+        // Shorter than the pane: nothing to scroll, however far asked.
+        assert_eq!(draw("hello", u16::MAX), 0);
+        assert!(draw(&"word ".repeat(200), 3) > 0);
+    }
+
+    #[test]
+    fn deck_label_shows_due_and_new() {
+        let deck = Deck::new("Spanish");
+        let counts = DeckCounts {
+            total: 9,
+            due: 2,
+            new: 5,
+            ..Default::default()
+        };
+        assert_eq!(deck_label(&deck, &counts), "Spanish  (2 due · 5 new)");
+        assert_eq!(deck_label(&deck, &DeckCounts::default()), "Spanish");
+    }
+}
@@ -0,0 +1,97 @@
+use flashmaster_app::{
+    api::server::router,
+    client::{ApiClient, ClientError, DueOpts},
+};
+use flashmaster_core::{repo::memory::MemoryRepo, Grade, Repository};
+use reqwest::StatusCode;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+/// Serves `repo` on an ephemeral port and returns a client for it.
+async fn serve(repo: Arc<MemoryRepo>) -> ApiClient {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router(repo, None)).await.unwrap() });
+    ApiClient::new(format!("http://{addr}/"))
+}
+
+#[tokio::test]
+async fn deck_create_and_review_round_trip() {
+    let repo = Arc::new(MemoryRepo::new());
+    let client = serve(repo.clone()).await;
+
+    let deck = client.create_deck("Spanish").await.unwrap();
+    assert_eq!(client.list_decks().await.unwrap()[0].id, deck.id);
+    let card = repo
+        .add_card(deck.id, "hola", "hello", None, &[])
+        .await
+        .unwrap();
+
+    let opts = DueOpts {
+        include_new: Some(true),
+        ..Default::default()
+    };
+    let due = client.due(Some("spanish"), &opts).await.unwrap();
+    assert_eq!(due.len(), 1);
+    assert_eq!(
+        client.due_count(Some("spanish"), &opts).await.unwrap().new,
+        1
+    );
+
+    client.post_review(due[0].id, Grade::Easy).await.unwrap();
+    let reviews = repo.list_reviews_for_card(card.id).await.unwrap();
+    assert_eq!(reviews.len(), 1);
+    assert_eq!(reviews[0].grade, Grade::Easy);
+    assert!(client.due(Some("spanish"), &opts).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn error_envelope_becomes_client_error() {
+    let client = serve(Arc::new(MemoryRepo::new())).await;
+    client.create_deck("Spanish").await.unwrap();
+
+    match client.create_deck("spanish").await {
+        Err(ClientError::Api {
+            status, message, ..
+        }) => {
+            assert_eq!(status, StatusCode::CONFLICT);
+            assert_eq!(message, "deck name already exists");
+        }
+        other => panic!("expected conflict, got {other:?}"),
+    }
+    let err = client
+        .post_review(uuid::Uuid::new_v4(), Grade::Hard)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ClientError::Api {
+            status: StatusCode::BAD_REQUEST,
+            ..
+        }
+    ));
+    assert!(!err.is_transient());
+}
+
+#[tokio::test]
+async fn cards_are_served_a_page_at_a_time() {
+    let repo = Arc::new(MemoryRepo::new());
+    let client = serve(repo.clone()).await;
+    let deck = repo.create_deck("Spanish").await.unwrap();
+    for i in 0..50 {
+        repo.add_card(deck.id, &format!("q{i}"), "a", None, &[])
+            .await
+            .unwrap();
+    }
+
+    let all = repo.list_cards(None).await.unwrap();
+    let page = client
+        .list_cards(Some("spanish"), 20, Some(20))
+        .await
+        .unwrap();
+    let ids: Vec<_> = page.iter().map(|c| c.id).collect();
+    let want: Vec<_> = all[20..40].iter().map(|c| c.id).collect();
+    assert_eq!(ids, want);
+    assert_eq!(client.list_cards(None, 0, None).await.unwrap().len(), 50);
+    assert!(client.list_cards(Some("nope"), 0, None).await.is_err());
+}
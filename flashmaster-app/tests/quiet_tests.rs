@@ -0,0 +1,36 @@
+use std::process::{Command, Output};
+
+/// Runs the CLI with its data dir (and so the JSON store) inside `dir`.
+fn cli(dir: &std::path::Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_flashmaster-app"))
+        .args(args)
+        .env("XDG_DATA_HOME", dir)
+        .env("HOME", dir)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn quiet_keeps_ids_and_drops_confirmations() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let add = cli(dir.path(), &["deck", "add", "Spanish", "--quiet"]);
+    assert!(add.status.success());
+    let stdout = String::from_utf8(add.stdout).unwrap();
+    let id = stdout.trim();
+    assert!(uuid::Uuid::parse_str(id).is_ok(), "{stdout:?}");
+
+    let rm = cli(dir.path(), &["-q", "deck", "rm", id]);
+    assert!(rm.status.success());
+    assert!(
+        rm.stdout.is_empty(),
+        "{:?}",
+        String::from_utf8_lossy(&rm.stdout)
+    );
+
+    // Errors still reach stderr.
+    let missing = cli(dir.path(), &["deck", "rm", "Spanish", "--quiet"]);
+    assert!(!missing.status.success());
+    assert!(missing.stdout.is_empty());
+    assert!(!missing.stderr.is_empty());
+}
@@ -0,0 +1,52 @@
+//! Answer checking for typed review.
+
+use crate::models::{Card, PromptWith};
+
+/// How typed answers are compared with a card's backs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AnswerNorm {
+    pub ignore_case: bool,
+    /// Collapse runs of whitespace and trim the ends.
+    pub squash_whitespace: bool,
+}
+
+impl Default for AnswerNorm {
+    fn default() -> Self {
+        Self {
+            ignore_case: true,
+            squash_whitespace: true,
+        }
+    }
+}
+
+impl AnswerNorm {
+    pub fn apply(&self, s: &str) -> String {
+        let s = if self.squash_whitespace {
+            s.split_whitespace().collect::<Vec<_>>().join(" ")
+        } else {
+            s.to_string()
+        };
+        if self.ignore_case {
+            s.to_lowercase()
+        } else {
+            s
+        }
+    }
+}
+
+/// True when `input` matches `card.back` or any of `card.alt_backs` under `norm`.
+pub fn check_answer(card: &Card, input: &str, norm: &AnswerNorm) -> bool {
+    let input = norm.apply(input);
+    std::iter::once(&card.back)
+        .chain(&card.alt_backs)
+        .any(|b| norm.apply(b) == input)
+}
+
+/// Like [`check_answer`], but for a deck prompting with `prompt`: when the
+/// back is the question, the front is the only accepted answer.
+pub fn check_answer_for(card: &Card, prompt: PromptWith, input: &str, norm: &AnswerNorm) -> bool {
+    match prompt {
+        PromptWith::Front => check_answer(card, input, norm),
+        PromptWith::Back => norm.apply(&card.front) == norm.apply(input),
+    }
+}
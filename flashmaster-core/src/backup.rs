@@ -0,0 +1,38 @@
+use chrono::{DateTime, Local};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// File name prefix shared by every backend's backups.
+pub const BACKUP_PREFIX: &str = "flashmaster";
+
+/// `{prefix}-YYYYmmdd-HHMMSS-nnnnnnnnn-{random}.{ext}`. Names of one prefix
+/// sort chronologically, which is what [`rotate_backups`] relies on; the
+/// nanoseconds and random suffix keep two backups taken at once apart.
+pub fn backup_file_name(prefix: &str, at: DateTime<Local>, ext: &str) -> String {
+    let suffix = uuid::Uuid::new_v4().simple().to_string();
+    format!(
+        "{prefix}-{}-{}.{ext}",
+        at.format("%Y%m%d-%H%M%S-%9f"),
+        &suffix[..6]
+    )
+}
+
+/// Deletes all but the newest `keep` files in `dir` whose name starts with
+/// `{prefix}-`, returning the removed paths. Files that fail to delete are
+/// left in place and not reported.
+pub fn rotate_backups(dir: &Path, prefix: &str, keep: usize) -> io::Result<Vec<PathBuf>> {
+    let lead = format!("{prefix}-");
+    let mut backups: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().map(|t| t.is_file()).unwrap_or(false))
+        .filter(|e| e.file_name().to_string_lossy().starts_with(&lead))
+        .map(|e| e.path())
+        .collect();
+    backups.sort();
+    let excess = backups.len().saturating_sub(keep);
+    Ok(backups
+        .drain(..excess)
+        .filter(|p| fs::remove_file(p).is_ok())
+        .collect())
+}
@@ -0,0 +1,214 @@
+//! Fluent construction of domain values. Every field not set explicitly keeps
+//! the value the type's `::new` gives it, so call sites keep compiling (and
+//! keep sensible defaults) as fields are added.
+
+use crate::{Card, CardId, Deck, DeckId, Grade, PromptWith, Review, ReviewId};
+use chrono::{DateTime, Utc};
+
+/// Builder for [`Deck`]; see [`Deck::builder`].
+#[derive(Clone, Debug)]
+pub struct DeckBuilder {
+    deck: Deck,
+}
+
+impl Deck {
+    pub fn builder(name: impl Into<String>) -> DeckBuilder {
+        DeckBuilder {
+            deck: Deck::new(name),
+        }
+    }
+}
+
+impl DeckBuilder {
+    pub fn id(mut self, id: DeckId) -> Self {
+        self.deck.id = id;
+        self
+    }
+
+    pub fn created_at(mut self, at: DateTime<Utc>) -> Self {
+        self.deck.created_at = at;
+        self
+    }
+
+    pub fn position(mut self, position: i32) -> Self {
+        self.deck.position = position;
+        self
+    }
+
+    pub fn prompt_with(mut self, prompt: PromptWith) -> Self {
+        self.deck.prompt_with = prompt;
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> Deck {
+        self.deck
+    }
+}
+
+/// Builder for [`Card`]; see [`Card::builder`].
+#[derive(Clone, Debug)]
+pub struct CardBuilder {
+    card: Card,
+}
+
+impl Card {
+    pub fn builder(
+        deck_id: DeckId,
+        front: impl Into<String>,
+        back: impl Into<String>,
+    ) -> CardBuilder {
+        CardBuilder {
+            card: Card::new(deck_id, front, back),
+        }
+    }
+}
+
+impl CardBuilder {
+    pub fn id(mut self, id: CardId) -> Self {
+        self.card.id = id;
+        self
+    }
+
+    pub fn alt_backs<I, S>(mut self, alts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.card.alt_backs = alts.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn hint(mut self, hint: impl Into<String>) -> Self {
+        self.card.hint = Some(hint.into());
+        self
+    }
+
+    pub fn source(mut self, source: impl Into<String>) -> Self {
+        self.card.source = Some(source.into());
+        self
+    }
+
+    pub fn tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.card.tags = tags.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn reps(mut self, reps: u32) -> Self {
+        self.card.reps = reps;
+        self
+    }
+
+    pub fn lapses(mut self, lapses: u32) -> Self {
+        self.card.lapses = lapses;
+        self
+    }
+
+    pub fn relearning_step(mut self, step: u8) -> Self {
+        self.card.relearning_step = Some(step);
+        self
+    }
+
+    pub fn interval_days(mut self, days: u32) -> Self {
+        self.card.interval_days = days;
+        self
+    }
+
+    pub fn ef(mut self, ef: f32) -> Self {
+        self.card.ef = ef;
+        self
+    }
+
+    /// FSRS memory state, as [`crate::apply_grade_fsrs`] leaves it.
+    pub fn fsrs_state(mut self, stability: f32, difficulty: f32) -> Self {
+        self.card.stability = stability;
+        self.card.difficulty = difficulty;
+        self
+    }
+
+    pub fn due_at(mut self, at: DateTime<Utc>) -> Self {
+        self.card.due_at = at;
+        self
+    }
+
+    pub fn last_grade(mut self, grade: Grade) -> Self {
+        self.card.last_grade = Some(grade);
+        self
+    }
+
+    pub fn last_reviewed_at(mut self, at: DateTime<Utc>) -> Self {
+        self.card.last_reviewed_at = Some(at);
+        self
+    }
+
+    pub fn suspended(mut self, suspended: bool) -> Self {
+        self.card.suspended = suspended;
+        self
+    }
+
+    pub fn created_at(mut self, at: DateTime<Utc>) -> Self {
+        self.card.created_at = at;
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> Card {
+        self.card
+    }
+}
+
+/// Builder for [`Review`]; see [`Review::builder`]. Defaults to a review
+/// made now that scheduled nothing (`interval_applied` 0, default ef).
+#[derive(Clone, Debug)]
+pub struct ReviewBuilder {
+    review: Review,
+}
+
+impl Review {
+    pub fn builder(card_id: CardId, grade: Grade) -> ReviewBuilder {
+        ReviewBuilder {
+            review: Review::new(card_id, grade, Utc::now(), 0, crate::EF_DEFAULT),
+        }
+    }
+}
+
+impl ReviewBuilder {
+    pub fn id(mut self, id: ReviewId) -> Self {
+        self.review.id = id;
+        self
+    }
+
+    pub fn reviewed_at(mut self, at: DateTime<Utc>) -> Self {
+        self.review.reviewed_at = at;
+        self
+    }
+
+    pub fn interval_applied(mut self, days: i32) -> Self {
+        self.review.interval_applied = days;
+        self
+    }
+
+    pub fn ef_after(mut self, ef: f32) -> Self {
+        self.review.ef_after = ef;
+        self
+    }
+
+    pub fn scheduler(mut self, name: impl Into<String>) -> Self {
+        self.review.scheduler = name.into();
+        self
+    }
+
+    pub fn duration_ms(mut self, ms: u32) -> Self {
+        self.review.duration_ms = Some(ms);
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> Review {
+        self.review
+    }
+}
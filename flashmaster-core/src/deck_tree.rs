@@ -0,0 +1,63 @@
+use crate::{Deck, DeckId};
+use std::collections::{HashMap, HashSet};
+
+/// `root` and every deck below it, at any depth.
+pub fn deck_subtree<'a>(
+    decks: impl IntoIterator<Item = &'a Deck>,
+    root: DeckId,
+) -> HashSet<DeckId> {
+    let mut children: HashMap<DeckId, Vec<DeckId>> = HashMap::new();
+    for d in decks {
+        if let Some(p) = d.parent_id {
+            children.entry(p).or_default().push(d.id);
+        }
+    }
+    let mut out = HashSet::from([root]);
+    let mut todo = vec![root];
+    while let Some(id) = todo.pop() {
+        for &child in children.get(&id).into_iter().flatten() {
+            if out.insert(child) {
+                todo.push(child);
+            }
+        }
+    }
+    out
+}
+
+/// Orders `items` so each deck is followed by its subdecks, pairing every
+/// item with its depth (0 for top-level decks). Siblings keep their order in
+/// `items`; a deck whose parent isn't listed counts as top-level.
+pub fn nest_decks<T>(items: Vec<T>, deck: impl Fn(&T) -> &Deck) -> Vec<(usize, T)> {
+    let index: HashMap<DeckId, usize> = items
+        .iter()
+        .enumerate()
+        .map(|(i, t)| (deck(t).id, i))
+        .collect();
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); items.len()];
+    let mut roots = Vec::new();
+    for (i, t) in items.iter().enumerate() {
+        match deck(t).parent_id.and_then(|p| index.get(&p)) {
+            Some(&p) if p != i => children[p].push(i),
+            _ => roots.push(i),
+        }
+    }
+
+    let mut order = Vec::with_capacity(items.len());
+    let mut seen = vec![false; items.len()];
+    let mut stack: Vec<(usize, usize)> = roots.into_iter().rev().map(|i| (i, 0)).collect();
+    while let Some((i, depth)) = stack.pop() {
+        if std::mem::replace(&mut seen[i], true) {
+            continue;
+        }
+        order.push((i, depth));
+        stack.extend(children[i].iter().rev().map(|&c| (c, depth + 1)));
+    }
+    // Only decks whose parents loop back to them are unreachable from a root.
+    order.extend((0..items.len()).filter(|&i| !seen[i]).map(|i| (i, 0)));
+
+    let mut slots: Vec<Option<T>> = items.into_iter().map(Some).collect();
+    order
+        .into_iter()
+        .map(|(i, depth)| (depth, slots[i].take().expect("each index once")))
+        .collect()
+}
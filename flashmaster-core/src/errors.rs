@@ -10,4 +10,8 @@ pub enum CoreError {
     Conflict(&'static str),
     #[error("storage error: {0}")]
     Storage(&'static str),
+    /// The backend was too busy to serve the request in time (e.g. no pooled
+    /// connection became free). Transient: the same call may succeed later.
+    #[error("timed out: {0}")]
+    Timeout(&'static str),
 }
@@ -0,0 +1,111 @@
+use crate::{Card, CardId, CoreError, DeckId, Grade};
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+/// Appended lines between fsyncs of the event log.
+pub const EVENT_LOG_SYNC_EVERY: u32 = 16;
+
+/// One line of the review event log.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReviewEvent {
+    pub card_id: CardId,
+    pub deck_id: DeckId,
+    pub grade: Grade,
+    pub reviewed_at: DateTime<Utc>,
+    pub interval_applied: u32,
+    pub ef_after: f32,
+}
+
+impl ReviewEvent {
+    /// Event for a card that was just graded; `None` if it was never reviewed.
+    pub fn from_card(card: &Card) -> Option<Self> {
+        Some(Self {
+            card_id: card.id,
+            deck_id: card.deck_id,
+            grade: card.last_grade.clone()?,
+            reviewed_at: card.last_reviewed_at?,
+            interval_applied: card.interval_days,
+            ef_after: card.ef,
+        })
+    }
+
+    /// The event as a single JSON line, newline included.
+    pub fn to_line(&self) -> String {
+        let mut line = serde_json::to_string(self).expect("review event serializes");
+        line.push('\n');
+        line
+    }
+}
+
+struct LogFile {
+    file: File,
+    unsynced: u32,
+    error: Option<String>,
+}
+
+/// Append-only JSONL log of graded reviews, kept next to (not in) the store.
+/// Write failures never fail the review; callers pick them up with
+/// [`EventLog::take_error`] and report them their own way.
+pub struct EventLog {
+    inner: Mutex<LogFile>,
+}
+
+impl EventLog {
+    pub fn open(path: &Path) -> Result<Self, CoreError> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(|_| CoreError::Storage("event log io"))?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|_| CoreError::Storage("event log io"))?;
+        Ok(Self {
+            inner: Mutex::new(LogFile {
+                file,
+                unsynced: 0,
+                error: None,
+            }),
+        })
+    }
+
+    pub fn append(&self, event: &ReviewEvent) {
+        let mut log = self.inner.lock();
+        if let Err(e) = log.file.write_all(event.to_line().as_bytes()) {
+            log.error = Some(format!("write failed: {e}"));
+            return;
+        }
+        log.unsynced += 1;
+        if log.unsynced >= EVENT_LOG_SYNC_EVERY {
+            log.sync();
+        }
+    }
+
+    /// The latest write or sync failure since the last call, e.g.
+    /// `write failed: No space left on device`.
+    pub fn take_error(&self) -> Option<String> {
+        self.inner.lock().error.take()
+    }
+}
+
+impl LogFile {
+    fn sync(&mut self) {
+        if let Err(e) = self.file.sync_data() {
+            self.error = Some(format!("sync failed: {e}"));
+        }
+        self.unsynced = 0;
+    }
+}
+
+impl Drop for EventLog {
+    fn drop(&mut self) {
+        let log = self.inner.get_mut();
+        if log.unsynced > 0 {
+            log.sync();
+        }
+    }
+}
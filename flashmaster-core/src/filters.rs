@@ -1,43 +1,139 @@
-use crate::{Card, DueStatus};
-use chrono::{DateTime, Utc};
+use crate::{AnswerNorm, Card, CardId, DueStatus};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
 
 pub fn filter_by_text(cards: &[Card], query: &str) -> Vec<Card> {
+    filter_by_text_ref(cards, query)
+        .into_iter()
+        .cloned()
+        .collect()
+}
+
+/// Fields a query can be limited to with a `field:` prefix.
+pub const QUERY_FIELDS: [&str; 5] = ["front", "back", "hint", "tag", "source"];
+
+/// Borrowing form of [`filter_by_text`], for callers that only read the matches.
+///
+/// A plain query matches any text field. `field:text` (see [`QUERY_FIELDS`])
+/// matches only that field, e.g. `source:ch3`; `source:` alone matches every
+/// card that has a source.
+pub fn filter_by_text_ref<'a>(cards: &'a [Card], query: &str) -> Vec<&'a Card> {
     let q = query.trim().to_lowercase();
-    if q.is_empty() {
-        return cards.to_vec();
+    let (field, q) = match q.split_once(':') {
+        Some((f, rest)) if QUERY_FIELDS.contains(&f.trim()) => (Some(f.trim()), rest.trim()),
+        _ => (None, q.as_str()),
+    };
+    if field.is_none() && q.is_empty() {
+        return cards.iter().collect();
     }
+    let hit = |s: &str| s.to_lowercase().contains(q);
     cards
         .iter()
-        .filter(|c| {
-            c.front.to_lowercase().contains(&q)
-                || c.back.to_lowercase().contains(&q)
-                || c.hint
-                    .as_ref()
-                    .map(|h| h.to_lowercase().contains(&q))
-                    .unwrap_or(false)
-                || c.tags.iter().any(|t| t.to_lowercase().contains(&q))
+        .filter(|c| match field {
+            Some("front") => hit(&c.front),
+            Some("back") => hit(&c.back),
+            Some("hint") => c.hint.as_deref().is_some_and(hit),
+            Some("tag") => c.tags.iter().any(|t| hit(t)),
+            Some(_) => c.source.as_deref().is_some_and(hit),
+            None => {
+                hit(&c.front)
+                    || hit(&c.back)
+                    || c.hint.as_deref().is_some_and(hit)
+                    || c.tags.iter().any(|t| hit(t))
+                    || c.source.as_deref().is_some_and(hit)
+            }
         })
-        .cloned()
         .collect()
 }
 
 pub fn filter_by_tag(cards: &[Card], tag: &str) -> Vec<Card> {
+    filter_by_tag_ref(cards, tag).into_iter().cloned().collect()
+}
+
+pub fn filter_by_tag_ref<'a>(cards: &'a [Card], tag: &str) -> Vec<&'a Card> {
     let q = tag.trim().to_lowercase();
     cards
         .iter()
         .filter(|c| c.tags.iter().any(|t| t.to_lowercase() == q))
-        .cloned()
         .collect()
 }
 
 pub fn filter_by_due(cards: &[Card], now: DateTime<Utc>, want: DueStatus) -> Vec<Card> {
+    filter_by_due_ref(cards, now, want)
+        .into_iter()
+        .cloned()
+        .collect()
+}
+
+pub fn filter_by_due_ref(cards: &[Card], now: DateTime<Utc>, want: DueStatus) -> Vec<&Card> {
+    cards.iter().filter(|c| c.due_status(now) == want).collect()
+}
+
+/// Cards due by `now + window`: overdue and new cards as well as those
+/// coming up within the window.
+pub fn filter_due_within(cards: &[Card], now: DateTime<Utc>, window: Duration) -> Vec<Card> {
+    filter_due_within_ref(cards, now, window)
+        .into_iter()
+        .cloned()
+        .collect()
+}
+
+pub fn filter_due_within_ref(cards: &[Card], now: DateTime<Utc>, window: Duration) -> Vec<&Card> {
+    cards.iter().filter(|c| c.due_at <= now + window).collect()
+}
+
+/// Cards created in `[from, to)`; an unset bound is open.
+pub fn filter_created_between(
+    cards: &[Card],
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> Vec<Card> {
+    filter_created_between_ref(cards, from, to)
+        .into_iter()
+        .cloned()
+        .collect()
+}
+
+pub fn filter_created_between_ref(
+    cards: &[Card],
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> Vec<&Card> {
     cards
         .iter()
-        .filter(|c| c.due_status(now) == want)
-        .cloned()
+        .filter(|c| from.is_none_or(|f| c.created_at >= f) && to.is_none_or(|t| c.created_at < t))
         .collect()
 }
 
 pub fn filter_not_suspended(cards: &[Card]) -> Vec<Card> {
-    cards.iter().filter(|c| !c.suspended).cloned().collect()
+    filter_not_suspended_ref(cards)
+        .into_iter()
+        .cloned()
+        .collect()
+}
+
+pub fn filter_not_suspended_ref(cards: &[Card]) -> Vec<&Card> {
+    cards.iter().filter(|c| !c.suspended).collect()
+}
+
+/// Groups of cards with the same front and back (ignoring case and extra
+/// whitespace). Each group is ordered oldest first, so `group[0]` is the one
+/// to keep; groups are ordered by that keeper. Unique cards are left out.
+pub fn find_duplicates(cards: &[Card]) -> Vec<Vec<CardId>> {
+    let norm = AnswerNorm::default();
+    let mut groups: HashMap<(String, String), Vec<&Card>> = HashMap::new();
+    for c in cards {
+        groups
+            .entry((norm.apply(&c.front), norm.apply(&c.back)))
+            .or_default()
+            .push(c);
+    }
+    let mut dups: Vec<Vec<&Card>> = groups.into_values().filter(|g| g.len() > 1).collect();
+    for g in &mut dups {
+        g.sort_by_key(|c| (c.created_at, c.id));
+    }
+    dups.sort_by_key(|g| (g[0].created_at, g[0].id));
+    dups.into_iter()
+        .map(|g| g.into_iter().map(|c| c.id).collect())
+        .collect()
 }
@@ -0,0 +1,130 @@
+use crate::Grade;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Layout of the grade keys, shared by every frontend so they agree on what
+/// `1` means. `Three` is the original `1=Hard 2=Medium 3=Easy`; `Four` is the
+/// common `1=Again 2=Hard 3=Good 4=Easy`.
+///
+/// Under `Four`, Good records [`Grade::Medium`]. Both layouts also take `0`,
+/// `a` or `again` for [`Grade::Again`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GradeScheme {
+    #[default]
+    Three,
+    Four,
+}
+
+impl GradeScheme {
+    /// Button labels in key order (`labels()[0]` is key `1`).
+    pub fn labels(self) -> &'static [&'static str] {
+        match self {
+            GradeScheme::Three => &["Hard", "Medium", "Easy"],
+            GradeScheme::Four => &["Again", "Hard", "Good", "Easy"],
+        }
+    }
+
+    /// The grade recorded for digit `key`; `None` outside the layout.
+    pub fn grade_for_key(self, key: char) -> Option<Grade> {
+        let n = key.to_digit(10)? as usize;
+        let label = *self.labels().get(n.checked_sub(1)?)?;
+        Self::grade_for_label(label)
+    }
+
+    /// A typed answer: a digit key, or a label or its first letter
+    /// (`"easy"`, `"e"`), case-insensitive.
+    pub fn parse_input(self, input: &str) -> Option<Grade> {
+        let s = input.trim().to_lowercase();
+        if matches!(s.as_str(), "0" | "a" | "again") {
+            return Some(Grade::Again);
+        }
+        let mut chars = s.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            if c.is_ascii_digit() {
+                return self.grade_for_key(c);
+            }
+        }
+        if s == "med" && self == GradeScheme::Three {
+            return Some(Grade::Medium);
+        }
+        self.labels()
+            .iter()
+            .copied()
+            .find(|l| {
+                let l = l.to_lowercase();
+                l == s || (s.len() == 1 && l.starts_with(&s))
+            })
+            .and_then(Self::grade_for_label)
+    }
+
+    /// Key legend for prompts, e.g. `1=Hard, 2=Medium, 3=Easy`.
+    pub fn key_hint(self) -> String {
+        self.labels()
+            .iter()
+            .enumerate()
+            .map(|(i, l)| format!("{}={l}", i + 1))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Projected intervals under each key's label, in key order, e.g.
+    /// `Hard: 1d, Medium: 6d, Easy: 10d`; see [`crate::preview_intervals`].
+    pub fn interval_hint(self, intervals: &BTreeMap<Grade, u32>) -> String {
+        self.labels()
+            .iter()
+            .filter_map(|l| {
+                let days = intervals.get(&Self::grade_for_label(l)?)?;
+                Some(format!("{l}: {days}d"))
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// The digit keys, e.g. `1/2/3`.
+    pub fn keys(self) -> &'static str {
+        match self {
+            GradeScheme::Three => "1/2/3",
+            GradeScheme::Four => "1/2/3/4",
+        }
+    }
+
+    fn grade_for_label(label: &str) -> Option<Grade> {
+        match label {
+            "Again" => Some(Grade::Again),
+            "Hard" => Some(Grade::Hard),
+            "Medium" | "Good" => Some(Grade::Medium),
+            "Easy" => Some(Grade::Easy),
+            _ => None,
+        }
+    }
+}
+
+/// What a key or word typed during review asks for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GradeAction {
+    Grade(Grade),
+    Skip,
+    Quit,
+}
+
+/// The one parser behind the CLI prompt, the TUI keys and the API's grade
+/// names: `s`/`skip`, `q`/`quit`, or anything
+/// [`GradeScheme::parse_input`] takes. Case is ignored, and full-width
+/// characters (as typed with a CJK input method) count as their ASCII forms.
+pub fn parse_grade_input(input: &str, scheme: GradeScheme) -> Option<GradeAction> {
+    let s: String = input
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            _ => c,
+        })
+        .collect::<String>()
+        .to_lowercase();
+    match s.as_str() {
+        "s" | "skip" => Some(GradeAction::Skip),
+        "q" | "quit" => Some(GradeAction::Quit),
+        _ => scheme.parse_input(&s).map(GradeAction::Grade),
+    }
+}
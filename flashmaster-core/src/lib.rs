@@ -1,13 +1,35 @@
+pub mod answer;
+pub mod backup;
+pub mod builders;
+pub mod deck_tree;
 pub mod errors;
+pub mod events;
 pub mod filters;
+pub mod grade_keys;
 pub mod models;
+pub mod pool;
 pub mod repo;
+pub mod rules;
+pub mod saved_filters;
 pub mod scheduler;
+pub mod session;
 pub mod stats;
+pub mod tags;
 
+pub use answer::*;
+pub use backup::*;
+pub use builders::*;
+pub use deck_tree::*;
 pub use errors::*;
+pub use events::*;
 pub use filters::*;
+pub use grade_keys::*;
 pub use models::*;
+pub use pool::*;
 pub use repo::*;
+pub use rules::*;
+pub use saved_filters::*;
 pub use scheduler::*;
+pub use session::*;
 pub use stats::*;
+pub use tags::*;
@@ -10,9 +10,12 @@ pub const EF_MIN: f32 = 1.3;
 pub const EF_MAX: f32 = 2.8;
 pub const EF_DEFAULT: f32 = 2.5;
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+/// Ordered from worst (`Again`) to best (`Easy`).
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(rename_all = "snake_case")]
 pub enum Grade {
+    /// Not recalled at all; always a lapse.
+    Again,
     Hard,
     Medium,
     Easy,
@@ -21,11 +24,29 @@ pub enum Grade {
 impl Grade {
     pub fn as_score(&self) -> i32 {
         match self {
+            Grade::Again => 0,
             Grade::Hard => 1,
             Grade::Medium => 2,
             Grade::Easy => 3,
         }
     }
+
+    /// Inverse of [`Grade::as_score`]; `None` for out-of-range scores.
+    pub fn from_score(score: i32) -> Option<Grade> {
+        match score {
+            0 => Some(Grade::Again),
+            1 => Some(Grade::Hard),
+            2 => Some(Grade::Medium),
+            3 => Some(Grade::Easy),
+            _ => None,
+        }
+    }
+
+    /// Whether the answer counts as failed: Again, and Hard, which the
+    /// default scheduler also treats as a lapse.
+    pub fn is_failing(&self) -> bool {
+        matches!(self, Grade::Again | Grade::Hard)
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -37,11 +58,47 @@ pub enum DueStatus {
     Future,
 }
 
+/// Which side of a deck's cards reviews show as the question. `Back` drills
+/// production (answer-first) without separate reverse cards.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptWith {
+    #[default]
+    Front,
+    Back,
+}
+
+impl PromptWith {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PromptWith::Front => "front",
+            PromptWith::Back => "back",
+        }
+    }
+
+    /// Inverse of [`PromptWith::as_str`]; `None` for anything else.
+    pub fn from_name(name: &str) -> Option<PromptWith> {
+        match name {
+            "front" => Some(PromptWith::Front),
+            "back" => Some(PromptWith::Back),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Deck {
     pub id: DeckId,
     pub name: String,
     pub created_at: DateTime<Utc>,
+    /// User-chosen sort position; lower lists first, ties by `created_at`.
+    #[serde(default)]
+    pub position: i32,
+    #[serde(default)]
+    pub prompt_with: PromptWith,
+    /// The deck this one is nested under; `None` for top-level decks.
+    #[serde(default)]
+    pub parent_id: Option<DeckId>,
 }
 
 impl Deck {
@@ -50,6 +107,9 @@ impl Deck {
             id: Uuid::new_v4(),
             name: name.into(),
             created_at: Utc::now(),
+            position: 0,
+            prompt_with: PromptWith::Front,
+            parent_id: None,
         }
     }
 }
@@ -60,12 +120,31 @@ pub struct Card {
     pub deck_id: DeckId,
     pub front: String,
     pub back: String,
+    /// Other answers accepted in typed review (synonyms).
+    #[serde(default)]
+    pub alt_backs: Vec<String>,
     pub hint: Option<String>,
+    /// Where the card came from, e.g. "Chapter 3, p.42".
+    #[serde(default)]
+    pub source: Option<String>,
     pub tags: Vec<String>,
 
     pub reps: u32,
+    /// Times the card has lapsed (been graded below Medium).
+    #[serde(default)]
+    pub lapses: u32,
+    /// Index into [`crate::Sm2Config::relearn_steps`] while the card is
+    /// relearning after a lapse; `None` in normal review.
+    #[serde(default)]
+    pub relearning_step: Option<u8>,
     pub interval_days: u32,
     pub ef: f32,
+    /// FSRS memory state, kept alongside the SM-2 fields; both stay 0 until
+    /// [`crate::apply_grade_fsrs`] first grades the card.
+    #[serde(default)]
+    pub stability: f32,
+    #[serde(default)]
+    pub difficulty: f32,
     pub due_at: DateTime<Utc>,
     pub last_grade: Option<Grade>,
     pub last_reviewed_at: Option<DateTime<Utc>>,
@@ -81,11 +160,17 @@ impl Card {
             deck_id,
             front: front.into(),
             back: back.into(),
+            alt_backs: Vec::new(),
             hint: None,
+            source: None,
             tags: Vec::new(),
             reps: 0,
+            lapses: 0,
+            relearning_step: None,
             interval_days: 0,
             ef: EF_DEFAULT,
+            stability: 0.0,
+            difficulty: 0.0,
             due_at: Utc::now(),
             last_grade: None,
             last_reviewed_at: None,
@@ -94,6 +179,52 @@ impl Card {
         }
     }
 
+    /// The question side under `prompt`: the front, or every accepted back.
+    pub fn question(&self, prompt: PromptWith) -> String {
+        match prompt {
+            PromptWith::Front => self.front.clone(),
+            PromptWith::Back => self.display_back(),
+        }
+    }
+
+    /// The side revealed as the answer under `prompt`.
+    pub fn answer(&self, prompt: PromptWith) -> String {
+        match prompt {
+            PromptWith::Front => self.display_back(),
+            PromptWith::Back => self.front.clone(),
+        }
+    }
+
+    /// `back` followed by any `alt_backs`, e.g. "car (also: auto, automobile)".
+    pub fn display_back(&self) -> String {
+        if self.alt_backs.is_empty() {
+            self.back.clone()
+        } else {
+            format!("{} (also: {})", self.back, self.alt_backs.join(", "))
+        }
+    }
+
+    /// Forgets all scheduling progress so the card is `New` again. Content,
+    /// tags and the suspended flag are kept.
+    pub fn reset(&mut self) {
+        self.reps = 0;
+        self.lapses = 0;
+        self.relearning_step = None;
+        self.interval_days = 0;
+        self.ef = EF_DEFAULT;
+        self.stability = 0.0;
+        self.difficulty = 0.0;
+        self.due_at = Utc::now();
+        self.last_grade = None;
+        self.last_reviewed_at = None;
+    }
+
+    /// Whether the card has lapsed at least `threshold` times, so a UI can
+    /// flag or suspend it.
+    pub fn is_leech(&self, threshold: u32) -> bool {
+        self.lapses >= threshold
+    }
+
     pub fn is_new(&self) -> bool {
         self.reps == 0
     }
@@ -114,6 +245,48 @@ impl Card {
     }
 }
 
+/// Content fields to change on a card; `None` leaves a field as stored.
+/// Scheduling fields are deliberately absent so a patch can't clobber a
+/// concurrent review.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CardPatch {
+    pub front: Option<String>,
+    pub back: Option<String>,
+    /// `Some(None)` clears the hint.
+    pub hint: Option<Option<String>>,
+    /// `Some(None)` clears the source.
+    pub source: Option<Option<String>>,
+    pub tags: Option<Vec<String>>,
+    pub suspended: Option<bool>,
+}
+
+impl CardPatch {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn apply(&self, card: &mut Card) {
+        if let Some(f) = &self.front {
+            card.front = f.clone();
+        }
+        if let Some(b) = &self.back {
+            card.back = b.clone();
+        }
+        if let Some(h) = &self.hint {
+            card.hint = h.clone();
+        }
+        if let Some(s) = &self.source {
+            card.source = s.clone();
+        }
+        if let Some(t) = &self.tags {
+            card.tags = t.clone();
+        }
+        if let Some(s) = self.suspended {
+            card.suspended = s;
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Review {
     pub id: ReviewId,
@@ -122,6 +295,17 @@ pub struct Review {
     pub reviewed_at: DateTime<Utc>,
     pub interval_applied: i32,
     pub ef_after: f32,
+    /// Algorithm that produced `interval_applied`/`ef_after`, e.g. `"sm2"`.
+    /// Reviews stored before this was recorded all came from SM-2.
+    #[serde(default = "default_scheduler")]
+    pub scheduler: String,
+    /// Milliseconds from showing the answer to grading it, when measured.
+    #[serde(default)]
+    pub duration_ms: Option<u32>,
+}
+
+fn default_scheduler() -> String {
+    crate::SCHEDULER_NAME.to_string()
 }
 
 impl Review {
@@ -139,6 +323,23 @@ impl Review {
             reviewed_at,
             interval_applied,
             ef_after,
+            scheduler: default_scheduler(),
+            duration_ms: None,
+        }
+    }
+
+    /// [`Review::new`] for an answer that took `duration_ms` to grade.
+    pub fn new_timed(
+        card_id: CardId,
+        grade: Grade,
+        reviewed_at: DateTime<Utc>,
+        interval_applied: i32,
+        ef_after: f32,
+        duration_ms: Option<u32>,
+    ) -> Self {
+        Self {
+            duration_ms,
+            ..Self::new(card_id, grade, reviewed_at, interval_applied, ef_after)
         }
     }
 }
@@ -0,0 +1,136 @@
+use crate::{filters::filter_not_suspended_ref, retrievability, Card, DueStatus};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PoolOrder {
+    /// Earliest `due_at` first, ties broken by `created_at`.
+    #[default]
+    DueAt,
+    /// Oldest card first.
+    CreatedAt,
+    /// Lowest estimated recall probability (see [`retrievability`]) first.
+    Retrievability,
+}
+
+/// Groups of cards in a review pool, for [`PoolOpts::order_phases`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    New,
+    Due,
+    Lapsed,
+}
+
+impl Phase {
+    fn of(status: &DueStatus) -> Option<Phase> {
+        match status {
+            DueStatus::New => Some(Phase::New),
+            DueStatus::DueToday => Some(Phase::Due),
+            DueStatus::Lapsed => Some(Phase::Lapsed),
+            DueStatus::Future => None,
+        }
+    }
+}
+
+/// Options for [`build_review_pool`]. `DueToday` cards are always included.
+#[derive(Clone, Debug, Default)]
+pub struct PoolOpts {
+    pub include_new: bool,
+    pub include_lapsed: bool,
+    /// Cap on new cards (oldest first) before `max` is applied.
+    pub new_limit: Option<usize>,
+    /// Cap on the whole pool.
+    pub max: Option<usize>,
+    pub order: PoolOrder,
+    /// Serve whole phases in this order (e.g. new first to frontload
+    /// learning), each sorted by `order`. `None` interleaves all phases.
+    pub order_phases: Option<[Phase; 3]>,
+    /// Also serve cards that fall due within this long (studying ahead,
+    /// e.g. for an exam). They sort after every phase in `order_phases`.
+    pub due_ahead: Option<Duration>,
+}
+
+/// The review queue shared by the CLI, TUI and API: non-suspended cards that
+/// are due (plus new/lapsed if requested), ordered and capped per `opts`.
+/// Every order ends with the card id as a tie-breaker, so the same cards
+/// give the same pool on every run whatever order they were listed in.
+pub fn build_review_pool(cards: &[Card], now: DateTime<Utc>, opts: &PoolOpts) -> Vec<Card> {
+    let mut new_cards = Vec::new();
+    let mut pool = Vec::new();
+    // Work on references and clone only the cards that make the cut.
+    for c in filter_not_suspended_ref(cards) {
+        match c.due_status(now) {
+            DueStatus::New if opts.include_new => new_cards.push(c),
+            DueStatus::DueToday => pool.push(c),
+            DueStatus::Lapsed if opts.include_lapsed => pool.push(c),
+            DueStatus::Future if opts.due_ahead.is_some_and(|d| c.due_at <= now + d) => {
+                pool.push(c)
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(limit) = opts.new_limit {
+        new_cards.sort_by_key(|c| (c.created_at, c.id));
+        new_cards.truncate(limit);
+    }
+    pool.extend(new_cards);
+
+    match opts.order {
+        PoolOrder::DueAt => pool.sort_by_key(|c| (c.due_at, c.created_at, c.id)),
+        PoolOrder::CreatedAt => pool.sort_by_key(|c| (c.created_at, c.id)),
+        PoolOrder::Retrievability => pool.sort_by(|a, b| {
+            retrievability(a, now)
+                .total_cmp(&retrievability(b, now))
+                .then(a.id.cmp(&b.id))
+        }),
+    }
+    if let Some(phases) = opts.order_phases {
+        // Stable, so the `order` sort above is kept within each phase.
+        pool.sort_by_key(|c| {
+            Phase::of(&c.due_status(now))
+                .and_then(|p| phases.iter().position(|&q| q == p))
+                .unwrap_or(phases.len())
+        });
+    }
+    if let Some(max) = opts.max {
+        pool.truncate(max);
+    }
+    pool.into_iter().cloned().collect()
+}
+
+/// Every non-suspended card, oldest first, ignoring due dates (cram mode).
+pub fn build_cram_pool(cards: &[Card]) -> Vec<Card> {
+    let mut pool = filter_not_suspended_ref(cards);
+    pool.sort_by_key(|c| (c.created_at, c.id));
+    pool.into_iter().cloned().collect()
+}
+
+/// Size of a review pool per phase, for "37 due, 20 new" previews.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolCounts {
+    pub new: usize,
+    pub due: usize,
+    pub lapsed: usize,
+}
+
+impl PoolCounts {
+    pub fn total(&self) -> usize {
+        self.new + self.due + self.lapsed
+    }
+}
+
+/// Counts the cards of a pool built by [`build_review_pool`] by phase at
+/// `now`; cards in no phase (not yet due) are not counted.
+pub fn pool_counts(pool: &[Card], now: DateTime<Utc>) -> PoolCounts {
+    let mut counts = PoolCounts::default();
+    for c in pool {
+        match Phase::of(&c.due_status(now)) {
+            Some(Phase::New) => counts.new += 1,
+            Some(Phase::Due) => counts.due += 1,
+            Some(Phase::Lapsed) => counts.lapsed += 1,
+            None => {}
+        }
+    }
+    counts
+}
@@ -1,32 +1,69 @@
-use crate::{Card, CardId, CoreError, Deck, DeckId, Review};
+use crate::{deck_subtree, Card, CardId, CardPatch, CoreError, Deck, DeckId, PromptWith, Review};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use parking_lot::RwLock;
 use std::collections::HashMap;
 
-#[derive(Default)]
 pub struct MemoryRepo {
     decks: RwLock<HashMap<DeckId, Deck>>,
     cards: RwLock<HashMap<CardId, Card>>,
     reviews: RwLock<HashMap<CardId, Vec<Review>>>,
+    unique_deck_names: bool,
+}
+
+impl Default for MemoryRepo {
+    fn default() -> Self {
+        Self {
+            decks: RwLock::default(),
+            cards: RwLock::default(),
+            reviews: RwLock::default(),
+            unique_deck_names: true,
+        }
+    }
 }
 
 impl MemoryRepo {
     pub fn new() -> Self {
         Self::default()
     }
-}
 
-#[async_trait]
-impl crate::repo::Repository for MemoryRepo {
-    async fn create_deck(&self, name: &str) -> Result<Deck, CoreError> {
-        let deck = Deck::new(name);
+    /// Whether `create_deck` rejects a name already used by another deck,
+    /// ignoring case (the default).
+    pub fn with_unique_deck_names(mut self, unique: bool) -> Self {
+        self.unique_deck_names = unique;
+        self
+    }
+
+    fn insert_deck(&self, mut deck: Deck) -> Result<Deck, CoreError> {
         let mut m = self.decks.write();
-        if m.values().any(|d| d.name.eq_ignore_ascii_case(name)) {
+        if self.unique_deck_names && m.values().any(|d| d.name.eq_ignore_ascii_case(&deck.name)) {
             return Err(CoreError::Conflict("deck name already exists"));
         }
+        if deck.parent_id.is_some_and(|p| !m.contains_key(&p)) {
+            return Err(CoreError::NotFound("deck"));
+        }
+        // New decks go after any reordered ones.
+        deck.position = m.values().map(|d| d.position + 1).max().unwrap_or(0);
         m.insert(deck.id, deck.clone());
         Ok(deck)
     }
+}
+
+#[async_trait]
+impl crate::repo::Repository for MemoryRepo {
+    async fn ping(&self) -> Result<(), CoreError> {
+        Ok(())
+    }
+
+    async fn create_deck(&self, name: &str) -> Result<Deck, CoreError> {
+        self.insert_deck(Deck::new(name))
+    }
+
+    async fn create_subdeck(&self, name: &str, parent: DeckId) -> Result<Deck, CoreError> {
+        let mut deck = Deck::new(name);
+        deck.parent_id = Some(parent);
+        self.insert_deck(deck)
+    }
 
     async fn get_deck(&self, id: DeckId) -> Result<Deck, CoreError> {
         self.decks
@@ -36,19 +73,54 @@ impl crate::repo::Repository for MemoryRepo {
             .ok_or(CoreError::NotFound("deck"))
     }
 
+    async fn deck_exists(&self, id: DeckId) -> Result<bool, CoreError> {
+        Ok(self.decks.read().contains_key(&id))
+    }
+
     async fn list_decks(&self) -> Result<Vec<Deck>, CoreError> {
-        Ok(self.decks.read().values().cloned().collect())
+        let mut v: Vec<Deck> = self.decks.read().values().cloned().collect();
+        v.sort_by_key(|d| (d.position, d.created_at));
+        Ok(v)
+    }
+
+    async fn count_decks(&self) -> Result<u64, CoreError> {
+        Ok(self.decks.read().len() as u64)
+    }
+
+    async fn list_subdecks(&self, parent: DeckId) -> Result<Vec<Deck>, CoreError> {
+        let mut v = self.list_decks().await?;
+        v.retain(|d| d.parent_id == Some(parent));
+        Ok(v)
+    }
+
+    async fn set_deck_position(&self, id: DeckId, position: i32) -> Result<(), CoreError> {
+        let mut m = self.decks.write();
+        let deck = m.get_mut(&id).ok_or(CoreError::NotFound("deck"))?;
+        deck.position = position;
+        Ok(())
+    }
+
+    async fn set_deck_prompt(&self, id: DeckId, prompt: PromptWith) -> Result<(), CoreError> {
+        let mut m = self.decks.write();
+        let deck = m.get_mut(&id).ok_or(CoreError::NotFound("deck"))?;
+        deck.prompt_with = prompt;
+        Ok(())
     }
 
     async fn delete_deck(&self, id: DeckId) -> Result<(), CoreError> {
-        self.decks
-            .write()
-            .remove(&id)
-            .ok_or(CoreError::NotFound("deck"))?;
+        let doomed = {
+            let mut decks = self.decks.write();
+            if !decks.contains_key(&id) {
+                return Err(CoreError::NotFound("deck"));
+            }
+            let doomed = deck_subtree(decks.values(), id);
+            decks.retain(|did, _| !doomed.contains(did));
+            doomed
+        };
         let mut cards = self.cards.write();
         let ids: Vec<CardId> = cards
             .values()
-            .filter(|c| c.deck_id == id)
+            .filter(|c| doomed.contains(&c.deck_id))
             .map(|c| c.id)
             .collect();
         for cid in ids {
@@ -58,6 +130,49 @@ impl crate::repo::Repository for MemoryRepo {
         Ok(())
     }
 
+    async fn merge_decks(
+        &self,
+        from: DeckId,
+        into: DeckId,
+        drop: &[CardId],
+    ) -> Result<(), CoreError> {
+        let mut decks = self.decks.write();
+        if !decks.contains_key(&from) || !decks.contains_key(&into) {
+            return Err(CoreError::NotFound("deck"));
+        }
+        let mut cards = self.cards.write();
+        let mut reviews = self.reviews.write();
+        for cid in drop {
+            cards.remove(cid);
+            reviews.remove(cid);
+        }
+        for c in cards.values_mut().filter(|c| c.deck_id == from) {
+            c.deck_id = into;
+        }
+        let parent = decks.remove(&from).and_then(|d| d.parent_id);
+        for d in decks.values_mut().filter(|d| d.parent_id == Some(from)) {
+            d.parent_id = parent;
+        }
+        Ok(())
+    }
+
+    async fn reset_deck(&self, deck_id: DeckId, keep_history: bool) -> Result<u64, CoreError> {
+        if !self.decks.read().contains_key(&deck_id) {
+            return Err(CoreError::NotFound("deck"));
+        }
+        let mut cards = self.cards.write();
+        let mut reviews = self.reviews.write();
+        let mut n = 0;
+        for c in cards.values_mut().filter(|c| c.deck_id == deck_id) {
+            c.reset();
+            if !keep_history {
+                reviews.remove(&c.id);
+            }
+            n += 1;
+        }
+        Ok(n)
+    }
+
     async fn add_card(
         &self,
         deck_id: DeckId,
@@ -66,13 +181,21 @@ impl crate::repo::Repository for MemoryRepo {
         hint: Option<&str>,
         tags: &[String],
     ) -> Result<Card, CoreError> {
-        if !self.decks.read().contains_key(&deck_id) {
-            return Err(CoreError::NotFound("deck"));
-        }
         let mut card = Card::new(deck_id, front, back);
         card.hint = hint.map(|s| s.to_string());
         card.tags = tags.to_vec();
-        self.cards.write().insert(card.id, card.clone());
+        self.insert_card(card).await
+    }
+
+    async fn insert_card(&self, card: Card) -> Result<Card, CoreError> {
+        if !self.decks.read().contains_key(&card.deck_id) {
+            return Err(CoreError::NotFound("deck"));
+        }
+        let mut cards = self.cards.write();
+        if cards.contains_key(&card.id) {
+            return Err(CoreError::Conflict("card already exists"));
+        }
+        cards.insert(card.id, card.clone());
         Ok(card)
     }
 
@@ -84,15 +207,42 @@ impl crate::repo::Repository for MemoryRepo {
             .ok_or(CoreError::NotFound("card"))
     }
 
+    async fn card_exists(&self, id: CardId) -> Result<bool, CoreError> {
+        Ok(self.cards.read().contains_key(&id))
+    }
+
     async fn list_cards(&self, deck_id: Option<DeckId>) -> Result<Vec<Card>, CoreError> {
         let cards = self.cards.read();
         let mut v: Vec<Card> = cards.values().cloned().collect();
         if let Some(did) = deck_id {
             v.retain(|c| c.deck_id == did);
         }
+        v.sort_by_key(|c| (c.created_at, c.id));
         Ok(v)
     }
 
+    async fn count_cards(&self, deck_id: Option<DeckId>) -> Result<u64, CoreError> {
+        let cards = self.cards.read();
+        let n = match deck_id {
+            Some(did) => cards.values().filter(|c| c.deck_id == did).count(),
+            None => cards.len(),
+        };
+        Ok(n as u64)
+    }
+
+    async fn list_cards_paged(
+        &self,
+        deck_id: Option<DeckId>,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<Card>, CoreError> {
+        let v = self.list_cards(deck_id).await?;
+        Ok(v.into_iter()
+            .skip(usize::try_from(offset).unwrap_or(usize::MAX))
+            .take(usize::try_from(limit).unwrap_or(usize::MAX))
+            .collect())
+    }
+
     async fn update_card(&self, card: &Card) -> Result<Card, CoreError> {
         let mut m = self.cards.write();
         if !m.contains_key(&card.id) {
@@ -102,6 +252,24 @@ impl crate::repo::Repository for MemoryRepo {
         Ok(card.clone())
     }
 
+    async fn patch_card(&self, id: CardId, patch: CardPatch) -> Result<Card, CoreError> {
+        let mut m = self.cards.write();
+        let card = m.get_mut(&id).ok_or(CoreError::NotFound("card"))?;
+        patch.apply(card);
+        Ok(card.clone())
+    }
+
+    async fn patch_cards(&self, patches: &[(CardId, CardPatch)]) -> Result<(), CoreError> {
+        let mut m = self.cards.write();
+        if patches.iter().any(|(id, _)| !m.contains_key(id)) {
+            return Err(CoreError::NotFound("card"));
+        }
+        for (id, patch) in patches {
+            patch.apply(m.get_mut(id).unwrap());
+        }
+        Ok(())
+    }
+
     async fn delete_card(&self, id: CardId) -> Result<(), CoreError> {
         self.cards
             .write()
@@ -111,6 +279,29 @@ impl crate::repo::Repository for MemoryRepo {
         Ok(())
     }
 
+    async fn merge_cards(&self, keeper: CardId, dupes: &[CardId]) -> Result<(), CoreError> {
+        let mut cards = self.cards.write();
+        if !cards.contains_key(&keeper) {
+            return Err(CoreError::NotFound("card"));
+        }
+        let mut reviews = self.reviews.write();
+        for cid in dupes.iter().filter(|c| **c != keeper) {
+            cards.remove(cid);
+            let moved = reviews.remove(cid).unwrap_or_default();
+            reviews
+                .entry(keeper)
+                .or_default()
+                .extend(moved.into_iter().map(|r| Review {
+                    card_id: keeper,
+                    ..r
+                }));
+        }
+        if let Some(v) = reviews.get_mut(&keeper) {
+            v.sort_by_key(|r| r.reviewed_at);
+        }
+        Ok(())
+    }
+
     async fn set_suspended(&self, id: CardId, suspended: bool) -> Result<(), CoreError> {
         let mut m = self.cards.write();
         let Some(card) = m.get_mut(&id) else {
@@ -120,6 +311,18 @@ impl crate::repo::Repository for MemoryRepo {
         Ok(())
     }
 
+    async fn move_card(&self, card_id: CardId, new_deck: DeckId) -> Result<(), CoreError> {
+        if !self.decks.read().contains_key(&new_deck) {
+            return Err(CoreError::NotFound("deck"));
+        }
+        let mut m = self.cards.write();
+        let Some(card) = m.get_mut(&card_id) else {
+            return Err(CoreError::NotFound("card"));
+        };
+        card.deck_id = new_deck;
+        Ok(())
+    }
+
     async fn insert_review(&self, review: &Review) -> Result<(), CoreError> {
         let mut m = self.reviews.write();
         m.entry(review.card_id).or_default().push(review.clone());
@@ -134,4 +337,41 @@ impl crate::repo::Repository for MemoryRepo {
             .cloned()
             .unwrap_or_default())
     }
+
+    async fn list_reviews_for_card_paged(
+        &self,
+        card_id: CardId,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<Review>, CoreError> {
+        let mut v = self.list_reviews_for_card(card_id).await?;
+        v.sort_by_key(|r| std::cmp::Reverse(r.reviewed_at));
+        Ok(v.into_iter()
+            .skip(usize::try_from(offset).unwrap_or(usize::MAX))
+            .take(usize::try_from(limit).unwrap_or(usize::MAX))
+            .collect())
+    }
+
+    async fn list_reviews(&self) -> Result<Vec<Review>, CoreError> {
+        let mut v: Vec<Review> = self.reviews.read().values().flatten().cloned().collect();
+        v.sort_by_key(|r| r.reviewed_at);
+        Ok(v)
+    }
+
+    async fn list_reviews_in_range(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Review>, CoreError> {
+        let mut v: Vec<Review> = self
+            .reviews
+            .read()
+            .values()
+            .flatten()
+            .filter(|r| from <= r.reviewed_at && r.reviewed_at < to)
+            .cloned()
+            .collect();
+        v.sort_by_key(|r| r.reviewed_at);
+        Ok(v)
+    }
 }
@@ -1,15 +1,89 @@
-use crate::{Card, CardId, CoreError, Deck, DeckId, Review};
+use crate::{
+    filter_by_text, Card, CardId, CardPatch, CoreError, Deck, DeckCounts, DeckId, PromptWith,
+    Review,
+};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
 
 pub mod memory;
 
 #[async_trait]
 pub trait Repository: Send + Sync {
+    /// Cheap connectivity check for health probes; reads no data.
+    async fn ping(&self) -> Result<(), CoreError>;
+    /// Flushes pending writes and releases connections; call once before
+    /// exiting. Pool-backed stores refuse queries afterwards, from every
+    /// clone sharing the pool.
+    async fn close(&self) -> Result<(), CoreError> {
+        Ok(())
+    }
+
     // Decks
     async fn create_deck(&self, name: &str) -> Result<Deck, CoreError>;
+    /// [`Repository::create_deck`] nested under `parent`; `NotFound("deck")`
+    /// when `parent` doesn't exist.
+    async fn create_subdeck(&self, name: &str, parent: DeckId) -> Result<Deck, CoreError>;
     async fn get_deck(&self, id: DeckId) -> Result<Deck, CoreError>;
+    /// Existence check without loading the row.
+    async fn deck_exists(&self, id: DeckId) -> Result<bool, CoreError>;
+    /// All decks ordered by `(position, created_at)`.
     async fn list_decks(&self) -> Result<Vec<Deck>, CoreError>;
+    /// How many decks there are, without loading them.
+    async fn count_decks(&self) -> Result<u64, CoreError>;
+    /// Decks directly under `parent`, ordered like [`Repository::list_decks`].
+    async fn list_subdecks(&self, parent: DeckId) -> Result<Vec<Deck>, CoreError>;
+    /// [`DeckCounts`] for one deck as of `now`.
+    async fn deck_counts(&self, id: DeckId, now: DateTime<Utc>) -> Result<DeckCounts, CoreError> {
+        let cards = self.list_cards(Some(id)).await?;
+        Ok(DeckCounts::of(&cards, now))
+    }
+    /// [`Repository::list_decks`] with each deck's [`DeckCounts`], without a
+    /// query per deck.
+    async fn list_decks_with_counts(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<(Deck, DeckCounts)>, CoreError> {
+        let decks = self.list_decks().await?;
+        let mut counts: HashMap<DeckId, DeckCounts> = HashMap::new();
+        for c in self.list_cards(None).await? {
+            counts.entry(c.deck_id).or_default().add(&c, now);
+        }
+        Ok(decks
+            .into_iter()
+            .map(|d| {
+                let n = counts.remove(&d.id).unwrap_or_default();
+                (d, n)
+            })
+            .collect())
+    }
+    async fn set_deck_position(&self, id: DeckId, position: i32) -> Result<(), CoreError>;
+    async fn set_deck_prompt(&self, id: DeckId, prompt: PromptWith) -> Result<(), CoreError>;
+    /// Gives the listed decks positions 0, 1, 2, ... in the order given;
+    /// decks left out keep theirs.
+    async fn reorder_decks(&self, ids_in_order: &[DeckId]) -> Result<(), CoreError> {
+        for (i, id) in ids_in_order.iter().enumerate() {
+            self.set_deck_position(*id, i as i32).await?;
+        }
+        Ok(())
+    }
+    /// Deletes the deck and its subdecks at any depth, with all their cards
+    /// and reviews.
     async fn delete_deck(&self, id: DeckId) -> Result<(), CoreError>;
+    /// Moves every card of `from` into `into` (reviews follow their cards),
+    /// deletes the cards listed in `drop`, then deletes `from`; its subdecks
+    /// move up to `from`'s parent. Atomic where the backend supports
+    /// transactions.
+    async fn merge_decks(
+        &self,
+        from: DeckId,
+        into: DeckId,
+        drop: &[CardId],
+    ) -> Result<(), CoreError>;
+    /// Applies [`Card::reset`] to every card in the deck, also deleting their
+    /// reviews unless `keep_history`. Returns the number of cards reset.
+    /// Atomic where the backend supports transactions.
+    async fn reset_deck(&self, deck_id: DeckId, keep_history: bool) -> Result<u64, CoreError>;
 
     // Cards
     async fn add_card(
@@ -20,14 +94,82 @@ pub trait Repository: Send + Sync {
         hint: Option<&str>,
         tags: &[String],
     ) -> Result<Card, CoreError>;
+    /// Stores `card` as given, keeping its id and scheduling state; `Conflict`
+    /// when the id is taken, `NotFound` when its deck doesn't exist.
+    async fn insert_card(&self, card: Card) -> Result<Card, CoreError>;
 
     async fn get_card(&self, id: CardId) -> Result<Card, CoreError>;
+    /// Existence check without loading the row.
+    async fn card_exists(&self, id: CardId) -> Result<bool, CoreError>;
+    /// Oldest first, ties by id, on every backend.
     async fn list_cards(&self, deck_id: Option<DeckId>) -> Result<Vec<Card>, CoreError>;
+    /// How many cards `list_cards(deck_id)` would return, without loading
+    /// them.
+    async fn count_cards(&self, deck_id: Option<DeckId>) -> Result<u64, CoreError>;
+    /// One page of [`Repository::list_cards`] in the same order: skips
+    /// `offset` cards and returns at most `limit`.
+    async fn list_cards_paged(
+        &self,
+        deck_id: Option<DeckId>,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<Card>, CoreError>;
+    /// Cards matching `query` as in [`filter_by_text`], in one deck or, with
+    /// `None`, across all decks.
+    async fn search_cards(
+        &self,
+        query: &str,
+        deck_id: Option<DeckId>,
+    ) -> Result<Vec<Card>, CoreError> {
+        let cards = self.list_cards(deck_id).await?;
+        Ok(filter_by_text(&cards, query))
+    }
     async fn update_card(&self, card: &Card) -> Result<Card, CoreError>;
+    /// Changes only the fields set in `patch`, without a read-modify-write
+    /// race against other writers. Returns the card as stored afterwards.
+    async fn patch_card(&self, id: CardId, patch: CardPatch) -> Result<Card, CoreError>;
+    /// [`Repository::patch_card`] for many cards at once; fails with
+    /// `NotFound` if any id is missing. Atomic where the backend supports
+    /// transactions.
+    async fn patch_cards(&self, patches: &[(CardId, CardPatch)]) -> Result<(), CoreError> {
+        for (id, patch) in patches {
+            self.patch_card(*id, patch.clone()).await?;
+        }
+        Ok(())
+    }
     async fn delete_card(&self, id: CardId) -> Result<(), CoreError>;
+    /// Moves the reviews of every card in `dupes` onto `keeper`, then deletes
+    /// those cards. Atomic where the backend supports transactions.
+    async fn merge_cards(&self, keeper: CardId, dupes: &[CardId]) -> Result<(), CoreError>;
     async fn set_suspended(&self, id: CardId, suspended: bool) -> Result<(), CoreError>;
+    /// Moves the card into `new_deck`, keeping its scheduling and reviews;
+    /// `NotFound("deck")` when `new_deck` doesn't exist.
+    async fn move_card(&self, card_id: CardId, new_deck: DeckId) -> Result<(), CoreError>;
 
     // Reviews
     async fn insert_review(&self, review: &Review) -> Result<(), CoreError>;
     async fn list_reviews_for_card(&self, card_id: CardId) -> Result<Vec<Review>, CoreError>;
+    /// One page of a card's reviews, newest first.
+    async fn list_reviews_for_card_paged(
+        &self,
+        card_id: CardId,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<Review>, CoreError>;
+    /// The card's `most_recent` latest reviews, newest first.
+    async fn recent_reviews_for_card(
+        &self,
+        card_id: CardId,
+        most_recent: usize,
+    ) -> Result<Vec<Review>, CoreError> {
+        self.list_reviews_for_card_paged(card_id, 0, u64::try_from(most_recent).unwrap_or(u64::MAX))
+            .await
+    }
+    async fn list_reviews(&self) -> Result<Vec<Review>, CoreError>;
+    /// Reviews with `from <= reviewed_at < to`, oldest first.
+    async fn list_reviews_in_range(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Review>, CoreError>;
 }
@@ -0,0 +1,96 @@
+use crate::{Card, CardPatch, CoreError, DeckId, Repository};
+
+/// Default [`CardRules::max_field_len`]: 64 KiB.
+pub const MAX_FIELD_LEN: usize = 64 * 1024;
+
+/// Policies applied when a card is created. They only look at the card as
+/// it is added; tags attached to an existing card later don't trigger them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CardRules {
+    /// New cards carrying any of these tags (case-insensitive) start suspended.
+    pub auto_suspend_tags: Vec<String>,
+    /// Longest front, back, hint, alternative back or tag accepted, in bytes.
+    /// Checked when content is written (added, imported or edited); cards
+    /// already stored are never truncated.
+    pub max_field_len: usize,
+}
+
+impl Default for CardRules {
+    fn default() -> Self {
+        Self {
+            auto_suspend_tags: Vec::new(),
+            max_field_len: MAX_FIELD_LEN,
+        }
+    }
+}
+
+impl CardRules {
+    /// `Invalid` if any of `fields` is longer than [`CardRules::max_field_len`].
+    pub fn check_len<'a>(
+        &self,
+        fields: impl IntoIterator<Item = &'a str>,
+    ) -> Result<(), CoreError> {
+        if fields.into_iter().any(|f| f.len() > self.max_field_len) {
+            return Err(CoreError::Invalid("field too long"));
+        }
+        Ok(())
+    }
+
+    /// [`CardRules::check_len`] over every content field of `card`.
+    pub fn check_card(&self, card: &Card) -> Result<(), CoreError> {
+        let fields = [card.front.as_str(), card.back.as_str()].into_iter();
+        self.check_len(
+            fields
+                .chain(card.hint.as_deref())
+                .chain(card.source.as_deref())
+                .chain(card.alt_backs.iter().map(String::as_str))
+                .chain(card.tags.iter().map(String::as_str)),
+        )
+    }
+
+    /// [`CardRules::check_len`] over the fields `patch` sets.
+    pub fn check_patch(&self, patch: &CardPatch) -> Result<(), CoreError> {
+        self.check_len(
+            patch
+                .front
+                .as_deref()
+                .into_iter()
+                .chain(patch.back.as_deref())
+                .chain(patch.hint.as_ref().and_then(|h| h.as_deref()))
+                .chain(patch.source.as_ref().and_then(|s| s.as_deref()))
+                .chain(patch.tags.iter().flatten().map(String::as_str)),
+        )
+    }
+
+    pub fn should_suspend(&self, tags: &[String]) -> bool {
+        tags.iter().any(|t| {
+            self.auto_suspend_tags
+                .iter()
+                .any(|s| s.trim().eq_ignore_ascii_case(t.trim()))
+        })
+    }
+}
+
+/// [`Repository::add_card`] followed by the creation-time `rules`.
+pub async fn add_card_with_rules<R: Repository + ?Sized>(
+    repo: &R,
+    rules: &CardRules,
+    deck_id: DeckId,
+    front: &str,
+    back: &str,
+    hint: Option<&str>,
+    tags: &[String],
+) -> Result<Card, CoreError> {
+    rules.check_len(
+        [front, back]
+            .into_iter()
+            .chain(hint)
+            .chain(tags.iter().map(String::as_str)),
+    )?;
+    let mut card = repo.add_card(deck_id, front, back, hint, tags).await?;
+    if rules.should_suspend(&card.tags) {
+        repo.set_suspended(card.id, true).await?;
+        card.suspended = true;
+    }
+    Ok(card)
+}
@@ -0,0 +1,111 @@
+use crate::{
+    filters::{filter_by_tag, filter_by_text, filter_due_within},
+    Card, CoreError, DeckId,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// A named, reusable study set ("smart deck") such as "cards tagged exam due
+/// this week". Unset criteria match everything, so without a deck it spans
+/// every deck.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SavedFilter {
+    pub name: String,
+    #[serde(default)]
+    pub deck_id: Option<DeckId>,
+    #[serde(default)]
+    pub tag: Option<String>,
+    /// Text query as for `search_cards`, `field:` prefixes included.
+    #[serde(default)]
+    pub query: Option<String>,
+    /// Only cards due within this many days, overdue and new ones included.
+    #[serde(default)]
+    pub due_within_days: Option<u32>,
+}
+
+impl SavedFilter {
+    /// The cards matching every criterion that is set.
+    pub fn select(&self, cards: &[Card], now: DateTime<Utc>) -> Vec<Card> {
+        let mut hits: Vec<Card> = cards
+            .iter()
+            .filter(|c| self.deck_id.is_none_or(|d| c.deck_id == d))
+            .cloned()
+            .collect();
+        if let Some(tag) = &self.tag {
+            hits = filter_by_tag(&hits, tag);
+        }
+        if let Some(q) = &self.query {
+            hits = filter_by_text(&hits, q);
+        }
+        if let Some(window) = self.due_window() {
+            hits = filter_due_within(&hits, now, window);
+        }
+        hits
+    }
+
+    /// How far ahead a review of this filter may reach, for
+    /// [`crate::PoolOpts::due_ahead`].
+    pub fn due_window(&self) -> Option<Duration> {
+        self.due_within_days.map(|d| Duration::days(d.into()))
+    }
+}
+
+/// Every saved filter, kept in a small JSON sidecar file so it works the same
+/// for every storage backend.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SavedFilters {
+    pub filters: Vec<SavedFilter>,
+}
+
+impl SavedFilters {
+    /// Loads the filters; an absent file means none are saved yet.
+    pub fn load(path: &Path) -> Result<Self, CoreError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let data = fs::read(path).map_err(|_| CoreError::Storage("filters io"))?;
+        serde_json::from_slice(&data).map_err(|_| CoreError::Invalid("filters file"))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), CoreError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|_| CoreError::Storage("filters io"))?;
+        }
+        let json =
+            serde_json::to_vec_pretty(self).map_err(|_| CoreError::Storage("filters encode"))?;
+        fs::write(path, json).map_err(|_| CoreError::Storage("filters io"))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SavedFilter> {
+        self.filters.iter().find(|f| f.name == name)
+    }
+
+    /// Adds `filter`, replacing any filter of the same name. Names are
+    /// letters, digits, `-` and `_`, so they can key session files.
+    pub fn upsert(&mut self, filter: SavedFilter) -> Result<(), CoreError> {
+        let valid = !filter.name.is_empty()
+            && filter
+                .name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(CoreError::Invalid("filter name"));
+        }
+        match self.filters.iter_mut().find(|f| f.name == filter.name) {
+            Some(existing) => *existing = filter,
+            None => self.filters.push(filter),
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<(), CoreError> {
+        let before = self.filters.len();
+        self.filters.retain(|f| f.name != name);
+        if self.filters.len() == before {
+            return Err(CoreError::NotFound("filter"));
+        }
+        Ok(())
+    }
+}
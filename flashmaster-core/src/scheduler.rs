@@ -1,5 +1,14 @@
-use crate::{Card, Grade, Review, EF_MAX, EF_MIN};
-use chrono::{Duration, Utc};
+use crate::{Card, Grade, Review, EF_DEFAULT, EF_MAX, EF_MIN};
+use chrono::{DateTime, Duration, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub mod fsrs;
+
+pub use fsrs::{
+    apply_grade_fsrs, apply_grade_fsrs_at, fsrs_state_from_history, FsrsParams, FSRS_SCHEDULER_NAME,
+};
 
 pub struct ScheduleOutcome {
     pub updated_card: Card,
@@ -10,35 +19,174 @@ fn clamp_ef(x: f32) -> f32 {
     x.clamp(EF_MIN, EF_MAX)
 }
 
-pub fn apply_grade(mut card: Card, grade: Grade) -> ScheduleOutcome {
-    let now = Utc::now();
+/// Label recorded on every [`Review`] this scheduler produces.
+pub const SCHEDULER_NAME: &str = "sm2";
+
+/// Which algorithm grades cards. Both keep their state on the [`Card`]
+/// (`reps`/`interval_days`/`ef` for SM-2, `stability`/`difficulty` for
+/// FSRS) and record their name on each [`Review`], so a collection can
+/// switch between them; a card first graded by FSRS after SM-2 is seeded
+/// from its SM-2 state.
+///
+/// [`Scheduler::apply`] grades with the default [`Sm2Config`] or
+/// [`FsrsParams`]; call [`apply_grade_with`] or [`apply_grade_fsrs_at`]
+/// directly to tune them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scheduler {
+    #[default]
+    Sm2,
+    Fsrs,
+}
+
+impl Scheduler {
+    /// The label its reviews carry, e.g. `"sm2"`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scheduler::Sm2 => SCHEDULER_NAME,
+            Scheduler::Fsrs => FSRS_SCHEDULER_NAME,
+        }
+    }
+
+    /// Inverse of [`Scheduler::as_str`]; `None` for anything else.
+    pub fn from_name(name: &str) -> Option<Scheduler> {
+        match name {
+            SCHEDULER_NAME => Some(Scheduler::Sm2),
+            FSRS_SCHEDULER_NAME => Some(Scheduler::Fsrs),
+            _ => None,
+        }
+    }
+
+    pub fn apply(self, card: Card, grade: Grade, now: DateTime<Utc>) -> ScheduleOutcome {
+        match self {
+            Scheduler::Sm2 => apply_grade_at(card, grade, now),
+            Scheduler::Fsrs => apply_grade_fsrs_at(card, grade, &FsrsParams::default(), now),
+        }
+    }
+}
+
+/// Suggested [`Sm2Config::hard_interval_factor`].
+pub const HARD_INTERVAL_FACTOR: f32 = 1.2;
+/// Ease lost on a softened Hard, much less than the full lapse penalty.
+pub const HARD_EF_PENALTY: f32 = 0.15;
+
+/// Tunable SM-2 parameters; `Default` is what [`apply_grade`] does.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sm2Config {
+    /// Interval after the first successful review.
+    pub first_interval_days: u32,
+    /// Interval after the second; later ones multiply the last by the ease.
+    pub second_interval_days: u32,
+    /// Interval after a lapse (Again, or an unsoftened Hard).
+    pub relearn_interval_days: u32,
+    /// Waits a lapsed card must pass in turn (e.g. 10 minutes, then a day)
+    /// before it is back in review; a miss starts them over, and passing the
+    /// last schedules the card like one with no reps. Ease only changes on
+    /// the lapse and on leaving the steps. Empty goes straight to
+    /// `relearn_interval_days`.
+    pub relearn_steps: Vec<Duration>,
+    /// Longest interval ever scheduled; ease keeps growing past it.
+    pub max_interval_days: u32,
+    /// `[a, b, c]` in the ease change `a - (3 - g) * (b + (3 - g) * c)` for
+    /// grade score `g`, so Easy adds `a` and Again takes the most. The result
+    /// is clamped to `EF_MIN..=EF_MAX`.
+    pub ease_delta_coeffs: [f32; 3],
+    /// When set, Hard on a card already in review keeps its reps and divides
+    /// the interval by this factor (minus [`HARD_EF_PENALTY`] ease) instead of
+    /// resetting it. `None` keeps Hard as a full lapse like Again, which the
+    /// three-key layout needs: Hard is its only failing key.
+    pub hard_interval_factor: Option<f32>,
+    /// How far [`apply_grade_fuzzed`] may move an interval, in percent;
+    /// `0` turns fuzzing off.
+    pub fuzz_percent: f32,
+}
+
+impl Default for Sm2Config {
+    fn default() -> Self {
+        Self {
+            first_interval_days: 1,
+            second_interval_days: 6,
+            relearn_interval_days: 1,
+            relearn_steps: Vec::new(),
+            max_interval_days: 36500,
+            ease_delta_coeffs: [0.1, 0.08, 0.02],
+            hard_interval_factor: None,
+            fuzz_percent: 5.0,
+        }
+    }
+}
+
+pub fn apply_grade(card: Card, grade: Grade) -> ScheduleOutcome {
+    apply_grade_at(card, grade, Utc::now())
+}
+
+/// [`apply_grade`] as if graded at `now`, for replays and deterministic tests.
+pub fn apply_grade_at(card: Card, grade: Grade, now: DateTime<Utc>) -> ScheduleOutcome {
+    apply_grade_with(card, grade, &Sm2Config::default(), now)
+}
+
+pub fn apply_grade_with(
+    mut card: Card,
+    grade: Grade,
+    cfg: &Sm2Config,
+    now: DateTime<Utc>,
+) -> ScheduleOutcome {
+    if let Some(step) = card.relearning_step.take() {
+        let next = if grade.is_failing() {
+            0
+        } else {
+            step as usize + 1
+        };
+        if next < cfg.relearn_steps.len() {
+            return relearn_step(card, grade, cfg, next as u8, now);
+        }
+    }
     let g = grade.as_score();
 
-    let new_ef = {
-        let delta = 0.1 - (3 - g) as f32 * (0.08 + (3 - g) as f32 * 0.02);
+    let soft_hard = match cfg.hard_interval_factor {
+        Some(f) if grade == Grade::Hard && card.reps > 0 => Some(f.max(1.0)),
+        _ => None,
+    };
+
+    let new_ef = if soft_hard.is_some() {
+        clamp_ef(card.ef - HARD_EF_PENALTY)
+    } else {
+        let [a, b, c] = cfg.ease_delta_coeffs;
+        let miss = (3 - g) as f32;
+        let delta = a - miss * (b + miss * c);
         clamp_ef(card.ef + delta)
     };
 
     let new_reps;
     let new_interval;
 
-    if g < 2 {
+    if let Some(factor) = soft_hard {
+        new_reps = card.reps;
+        new_interval = (card.interval_days.max(1) as f32 / factor).round().max(1.0) as u32;
+    } else if g < 2 {
         new_reps = 0;
-        new_interval = 1;
+        new_interval = cfg.relearn_interval_days.max(1);
     } else {
         new_reps = card.reps + 1;
         new_interval = if new_reps == 1 {
-            1
+            cfg.first_interval_days.max(1)
         } else if new_reps == 2 {
-            6
+            cfg.second_interval_days.max(1)
         } else {
             let base = card.interval_days.max(1) as f32;
             (base * new_ef).round().max(1.0) as u32
         };
     }
+    let new_interval = new_interval.min(cfg.max_interval_days.max(1));
 
+    if g < 2 {
+        card.lapses += 1;
+    }
     card.ef = new_ef;
     card.reps = new_reps;
+    if g < 2 && soft_hard.is_none() && !cfg.relearn_steps.is_empty() {
+        return relearn_step(card, grade, cfg, 0, now);
+    }
     card.interval_days = new_interval;
     card.due_at = now + Duration::days(new_interval as i64);
     card.last_grade = Some(grade.clone());
@@ -46,5 +194,177 @@ pub fn apply_grade(mut card: Card, grade: Grade) -> ScheduleOutcome {
 
     let review = Review::new(card.id, grade, now, new_interval as i32, new_ef);
 
-    ScheduleOutcome { updated_card: card, review }
+    ScheduleOutcome {
+        updated_card: card,
+        review,
+    }
+}
+
+/// Puts `card` on relearning step `step`, due once that step's wait is up.
+fn relearn_step(
+    mut card: Card,
+    grade: Grade,
+    cfg: &Sm2Config,
+    step: u8,
+    now: DateTime<Utc>,
+) -> ScheduleOutcome {
+    let wait = cfg.relearn_steps[step as usize];
+    card.relearning_step = Some(step);
+    card.interval_days = wait.num_days().max(0) as u32;
+    card.due_at = now + wait;
+    card.last_grade = Some(grade.clone());
+    card.last_reviewed_at = Some(now);
+
+    let review = Review::new(card.id, grade, now, card.interval_days as i32, card.ef);
+
+    ScheduleOutcome {
+        updated_card: card,
+        review,
+    }
+}
+
+/// The interval and due date grading `card` with `grade` at `now` would give
+/// it, leaving the card untouched.
+pub fn preview_grade(card: &Card, grade: Grade, now: DateTime<Utc>) -> (u32, DateTime<Utc>) {
+    let after = apply_grade_at(card.clone(), grade, now).updated_card;
+    (after.interval_days, after.due_at)
+}
+
+/// The `interval_days` each grade would give `card`, by the same math as
+/// [`apply_grade`]. Intervals don't depend on when the card is graded, and
+/// nothing is changed or saved.
+pub fn preview_intervals(card: &Card) -> BTreeMap<Grade, u32> {
+    [Grade::Again, Grade::Hard, Grade::Medium, Grade::Easy]
+        .into_iter()
+        .map(|g| {
+            let (days, _) = preview_grade(card, g.clone(), card.due_at);
+            (g, days)
+        })
+        .collect()
+}
+
+/// `card` with its scheduling state rebuilt from `reviews` alone: reset, then
+/// each of the card's reviews replayed by `reviewed_at` through the scheduler
+/// that recorded it (SM-2 for unknown names). Only the log matters, so the
+/// same reviews always give the same card; without any it is due from
+/// `created_at`.
+pub fn reconstruct_card_state(card: &Card, reviews: &[Review]) -> Card {
+    let mut sorted: Vec<&Review> = reviews.iter().filter(|r| r.card_id == card.id).collect();
+    sorted.sort_by_key(|r| r.reviewed_at);
+    let mut rebuilt = card.clone();
+    rebuilt.reset();
+    rebuilt.due_at = rebuilt.created_at;
+    for r in sorted {
+        let scheduler = Scheduler::from_name(&r.scheduler).unwrap_or_default();
+        rebuilt = scheduler
+            .apply(rebuilt, r.grade.clone(), r.reviewed_at)
+            .updated_card;
+    }
+    rebuilt
+}
+
+/// [`apply_grade`] with the interval spread by up to ±5%, so cards added
+/// together don't keep coming due on the same day.
+pub fn apply_grade_fuzzed(card: Card, grade: Grade, rng: &mut impl Rng) -> ScheduleOutcome {
+    apply_grade_fuzzed_with(card, grade, &Sm2Config::default(), Utc::now(), rng)
+}
+
+/// [`apply_grade_with`] followed by [`fuzz_interval`] using
+/// `cfg.fuzz_percent`, still within `cfg.max_interval_days`.
+pub fn apply_grade_fuzzed_with(
+    card: Card,
+    grade: Grade,
+    cfg: &Sm2Config,
+    now: DateTime<Utc>,
+    rng: &mut impl Rng,
+) -> ScheduleOutcome {
+    let mut out = apply_grade_with(card, grade, cfg, now);
+    if out.updated_card.relearning_step.is_some() {
+        return out;
+    }
+    let days = fuzz_interval(out.updated_card.interval_days, cfg.fuzz_percent, rng)
+        .min(cfg.max_interval_days.max(1));
+    out.updated_card.interval_days = days;
+    out.updated_card.due_at = now + Duration::days(days as i64);
+    out.review.interval_applied = days as i32;
+    out
+}
+
+/// Moves `days` by a random amount up to `percent` of it either way, and at
+/// least one day for intervals of 3 days or more. Shorter intervals are kept
+/// as they are, and the result is never below 1.
+pub fn fuzz_interval(days: u32, percent: f32, rng: &mut impl Rng) -> u32 {
+    if days < 3 || percent <= 0.0 {
+        return days;
+    }
+    let spread = ((days as f32 * percent / 100.0).round() as i64).max(1);
+    let fuzzed = days as i64 + rng.gen_range(-spread..=spread);
+    fuzzed.max(1) as u32
+}
+
+/// Human-friendly "when is this due" for an interval in days:
+/// "today", "tomorrow", "in 3 days", "in 2 weeks", "in 4 months", "in 1.2 years".
+pub fn humanize_interval(days: u32) -> String {
+    match days {
+        0 => "today".to_string(),
+        1 => "tomorrow".to_string(),
+        _ => format!("in {}", humanize_days(days)),
+    }
+}
+
+/// The past counterpart of [`humanize_interval`]: "today", "yesterday",
+/// "3 days ago", "2 weeks ago", ...
+pub fn humanize_ago(days: u32) -> String {
+    match days {
+        0 => "today".to_string(),
+        1 => "yesterday".to_string(),
+        _ => format!("{} ago", humanize_days(days)),
+    }
+}
+
+/// "3 days", "2 weeks", "4 months", "1.2 years"; meant for `days >= 2`.
+fn humanize_days(days: u32) -> String {
+    fn plural(n: u32, unit: &str) -> String {
+        if n == 1 {
+            format!("1 {unit}")
+        } else {
+            format!("{n} {unit}s")
+        }
+    }
+    match days {
+        0..=13 => plural(days, "day"),
+        14..=59 => plural((days as f32 / 7.0).round() as u32, "week"),
+        60..=364 => plural((days as f32 / 30.0).round() as u32, "month"),
+        _ => {
+            let years = (days as f32 / 365.0 * 10.0).round() / 10.0;
+            if years.fract() == 0.0 {
+                plural(years as u32, "year")
+            } else {
+                format!("{years:.1} years")
+            }
+        }
+    }
+}
+
+/// Estimated probability (0..=1) of recalling `card` at `now`, using the
+/// FSRS power forgetting curve `R = (1 + t / (9 * S))^-1`, which is 0.9 when
+/// the elapsed time `t` equals the stability `S`.
+///
+/// Cards graded by FSRS use their own stability; for SM-2 cards it is
+/// approximated by the current interval scaled by `ef` relative to the
+/// default. `t` runs from the last review. Never-reviewed cards return 0.
+pub fn retrievability(card: &Card, now: DateTime<Utc>) -> f32 {
+    let last = match card.last_reviewed_at {
+        Some(t) => t,
+        None if card.is_new() => return 0.0,
+        // Imported without a review timestamp: infer it from the schedule.
+        None => card.due_at - Duration::days(card.interval_days as i64),
+    };
+    let elapsed_days = ((now - last).num_seconds().max(0) as f32) / 86_400.0;
+    let stability = if card.stability > 0.0 {
+        card.stability
+    } else {
+        card.interval_days.max(1) as f32 * (card.ef / EF_DEFAULT)
+    };
+    1.0 / (1.0 + elapsed_days / (9.0 * stability))
 }
@@ -0,0 +1,198 @@
+//! FSRS (Free Spaced Repetition Scheduler), v4.5 formulas.
+//!
+//! Each card carries a memory state: `stability`, the days until recall
+//! probability drops to 90%, and `difficulty`, from 1 (easy) to 10 (hard).
+//! A grade updates both from how likely the card was to be recalled when it
+//! was shown, and the next interval is the time until recall probability
+//! falls to [`FsrsParams::request_retention`].
+//!
+//! FSRS rates with four buttons, which map onto the grades as Again → Again,
+//! Hard → Hard, Medium → Good and Easy → Easy. Unlike SM-2, FSRS treats Hard
+//! as recalled; only Again is a lapse.
+
+use crate::{Card, Grade, Review, ScheduleOutcome};
+use chrono::{DateTime, Duration, Utc};
+
+/// Label recorded on every [`Review`] this scheduler produces.
+pub const FSRS_SCHEDULER_NAME: &str = "fsrs";
+
+const DECAY: f32 = -0.5;
+/// Chosen so that `R(S, S) = 0.9`.
+const FACTOR: f32 = 19.0 / 81.0;
+
+/// FSRS model weights and targets.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FsrsParams {
+    /// The 17 model weights `w0..w16`.
+    pub w: [f32; 17],
+    /// Recall probability the next interval aims for.
+    pub request_retention: f32,
+    /// Longest interval ever scheduled, in days.
+    pub maximum_interval: u32,
+}
+
+impl Default for FsrsParams {
+    /// The published FSRS v4.5 defaults.
+    fn default() -> Self {
+        Self {
+            w: [
+                0.4872, 1.4003, 3.7145, 13.8206, 5.1618, 1.2298, 0.8975, 0.031, 1.6474, 0.1367,
+                1.0461, 2.1072, 0.0793, 0.3246, 1.587, 0.2272, 2.8755,
+            ],
+            request_retention: 0.9,
+            maximum_interval: 36500,
+        }
+    }
+}
+
+/// FSRS's rating (1 = Again .. 4 = Easy) for a grade.
+pub fn fsrs_rating(grade: &Grade) -> u8 {
+    match grade {
+        Grade::Again => 1,
+        Grade::Hard => 2,
+        Grade::Medium => 3,
+        Grade::Easy => 4,
+    }
+}
+
+pub fn apply_grade_fsrs(card: Card, grade: Grade, params: &FsrsParams) -> ScheduleOutcome {
+    apply_grade_fsrs_at(card, grade, params, Utc::now())
+}
+
+/// [`apply_grade_fsrs`] as if graded at `now`, for replays and deterministic
+/// tests.
+///
+/// A card with no FSRS state yet starts from its first grade if it is new;
+/// a card already reviewed under SM-2 is seeded from its interval and ease
+/// (see [`fsrs_state_from_history`] for a seed that replays its reviews).
+pub fn apply_grade_fsrs_at(
+    mut card: Card,
+    grade: Grade,
+    params: &FsrsParams,
+    now: DateTime<Utc>,
+) -> ScheduleOutcome {
+    let rating = fsrs_rating(&grade);
+    let state = if card.stability > 0.0 {
+        Some((card.stability, card.difficulty))
+    } else if card.is_new() {
+        None
+    } else {
+        Some(seed_from_sm2(&card))
+    };
+    let (stability, difficulty) = match state {
+        None => initial_state(params, rating),
+        Some((s, d)) => {
+            let elapsed = elapsed_days(&card, now);
+            next_state(params, s, d, forgetting_curve(elapsed, s), rating)
+        }
+    };
+
+    let interval = next_interval(params, stability);
+    card.stability = stability;
+    card.difficulty = difficulty;
+    if rating == 1 {
+        card.reps = 0;
+        card.lapses += 1;
+    } else {
+        card.reps += 1;
+    }
+    // FSRS schedules lapses itself, without SM-2's relearning steps.
+    card.relearning_step = None;
+    card.interval_days = interval;
+    card.due_at = now + Duration::days(interval as i64);
+    card.last_grade = Some(grade.clone());
+    card.last_reviewed_at = Some(now);
+
+    // FSRS has no ease; the review records the card's unchanged SM-2 ease.
+    let mut review = Review::new(card.id, grade, now, interval as i32, card.ef);
+    review.scheduler = FSRS_SCHEDULER_NAME.to_string();
+
+    ScheduleOutcome {
+        updated_card: card,
+        review,
+    }
+}
+
+/// The `(stability, difficulty)` a card would have if every review in
+/// `reviews` had been graded by FSRS; `None` without reviews. Order does not
+/// matter, reviews are replayed by `reviewed_at`.
+pub fn fsrs_state_from_history(reviews: &[Review], params: &FsrsParams) -> Option<(f32, f32)> {
+    let mut sorted: Vec<&Review> = reviews.iter().collect();
+    sorted.sort_by_key(|r| r.reviewed_at);
+    let mut state: Option<(f32, f32, DateTime<Utc>)> = None;
+    for r in sorted {
+        let rating = fsrs_rating(&r.grade);
+        let (s, d) = match state {
+            None => initial_state(params, rating),
+            Some((s, d, last)) => {
+                let elapsed = days_between(last, r.reviewed_at);
+                next_state(params, s, d, forgetting_curve(elapsed, s), rating)
+            }
+        };
+        state = Some((s, d, r.reviewed_at));
+    }
+    state.map(|(s, d, _)| (s, d))
+}
+
+/// Probability of recall after `elapsed_days` at stability `stability`.
+pub fn forgetting_curve(elapsed_days: f32, stability: f32) -> f32 {
+    (1.0 + FACTOR * elapsed_days / stability).powf(DECAY)
+}
+
+fn initial_state(params: &FsrsParams, rating: u8) -> (f32, f32) {
+    let stability = params.w[rating as usize - 1].max(0.1);
+    (stability, initial_difficulty(params, rating))
+}
+
+fn initial_difficulty(params: &FsrsParams, rating: u8) -> f32 {
+    let w = &params.w;
+    (w[4] - (rating as f32 - 3.0) * w[5]).clamp(1.0, 10.0)
+}
+
+fn next_state(params: &FsrsParams, s: f32, d: f32, r: f32, rating: u8) -> (f32, f32) {
+    let w = &params.w;
+    let stability = if rating == 1 {
+        let forget =
+            w[11] * d.powf(-w[12]) * ((s + 1.0).powf(w[13]) - 1.0) * ((1.0 - r) * w[14]).exp();
+        // A lapse never leaves the memory more stable than before.
+        forget.min(s)
+    } else {
+        let hard_penalty = if rating == 2 { w[15] } else { 1.0 };
+        let easy_bonus = if rating == 4 { w[16] } else { 1.0 };
+        s * (1.0
+            + w[8].exp()
+                * (11.0 - d)
+                * s.powf(-w[9])
+                * (((1.0 - r) * w[10]).exp() - 1.0)
+                * hard_penalty
+                * easy_bonus)
+    };
+    let moved = d - w[6] * (rating as f32 - 3.0);
+    let difficulty = (w[7] * initial_difficulty(params, 3) + (1.0 - w[7]) * moved).clamp(1.0, 10.0);
+    (stability.max(0.1), difficulty)
+}
+
+fn next_interval(params: &FsrsParams, stability: f32) -> u32 {
+    let days = stability / FACTOR * (params.request_retention.powf(1.0 / DECAY) - 1.0);
+    (days.round() as u32).clamp(1, params.maximum_interval.max(1))
+}
+
+/// SM-2 state read as FSRS state: the interval scaled by ease (as
+/// [`crate::retrievability`] does) for stability, and ease mapped linearly
+/// from `EF_MAX..EF_MIN` onto difficulty `1..10`.
+fn seed_from_sm2(card: &Card) -> (f32, f32) {
+    let stability = card.interval_days.max(1) as f32 * (card.ef / crate::EF_DEFAULT);
+    let t = (crate::EF_MAX - card.ef) / (crate::EF_MAX - crate::EF_MIN);
+    (stability, (1.0 + 9.0 * t).clamp(1.0, 10.0))
+}
+
+fn elapsed_days(card: &Card, now: DateTime<Utc>) -> f32 {
+    match card.last_reviewed_at {
+        Some(last) => days_between(last, now),
+        None => card.interval_days as f32,
+    }
+}
+
+fn days_between(from: DateTime<Utc>, to: DateTime<Utc>) -> f32 {
+    (to - from).num_seconds().max(0) as f32 / 86_400.0
+}
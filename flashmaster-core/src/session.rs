@@ -0,0 +1,271 @@
+use crate::{apply_grade_at, Card, CardId, CoreError, DeckId, Grade, Repository, Totals};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// A review queue that can be saved mid-way and resumed later. Shared by the
+/// CLI review loop and the TUI; persisted as a small JSON sidecar file so it
+/// works the same for every storage backend.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReviewSession {
+    pub deck_id: Option<DeckId>,
+    pub card_ids: Vec<CardId>,
+    pub idx: usize,
+    pub tallies: Totals,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ReviewSession {
+    pub fn new(deck_id: Option<DeckId>, card_ids: Vec<CardId>) -> Self {
+        let now = Utc::now();
+        Self {
+            deck_id,
+            card_ids,
+            idx: 0,
+            tallies: Totals::default(),
+            started_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn current(&self) -> Option<CardId> {
+        self.card_ids.get(self.idx).copied()
+    }
+
+    pub fn remaining(&self) -> &[CardId] {
+        &self.card_ids[self.idx.min(self.card_ids.len())..]
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.idx >= self.card_ids.len()
+    }
+
+    /// Moves past the current card, recording the grade if it was graded
+    /// (`None` means skipped).
+    pub fn advance(&mut self, grade: Option<&Grade>) {
+        if let Some(g) = grade {
+            self.tallies.record(g);
+        }
+        self.idx = (self.idx + 1).min(self.card_ids.len());
+        self.updated_at = Utc::now();
+    }
+
+    /// Sidecar file for a deck's session (`None` = all decks) inside `dir`.
+    pub fn file_for(dir: &Path, deck_id: Option<DeckId>) -> PathBuf {
+        let key = deck_id
+            .map(|d| d.to_string())
+            .unwrap_or_else(|| "all".to_string());
+        dir.join(format!("session-{key}.json"))
+    }
+
+    /// Sidecar file for the session of a saved filter (see `SavedFilter`).
+    pub fn file_for_filter(dir: &Path, name: &str) -> PathBuf {
+        dir.join(format!("session-filter-{name}.json"))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), CoreError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|_| CoreError::Storage("session io"))?;
+        }
+        let json =
+            serde_json::to_vec_pretty(self).map_err(|_| CoreError::Storage("session encode"))?;
+        fs::write(path, json).map_err(|_| CoreError::Storage("session io"))
+    }
+
+    /// Loads a saved session; `Ok(None)` if there is none.
+    pub fn load(path: &Path) -> Result<Option<Self>, CoreError> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let data = fs::read(path).map_err(|_| CoreError::Storage("session io"))?;
+        serde_json::from_slice(&data)
+            .map(Some)
+            .map_err(|_| CoreError::Invalid("session file"))
+    }
+
+    pub fn clear(path: &Path) -> Result<(), CoreError> {
+        match fs::remove_file(path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(_) => Err(CoreError::Storage("session io")),
+        }
+    }
+}
+
+/// Failed grades within one session before a card is flagged as struggling.
+pub const STRUGGLE_THRESHOLD: u32 = 3;
+
+/// A card is struggling once it has failed more than `threshold` times.
+pub fn is_struggling(again_count: u32, threshold: u32) -> bool {
+    again_count > threshold
+}
+
+/// Per-session count of failed (Hard) grades per card. Not persisted.
+#[derive(Clone, Debug)]
+pub struct StruggleTracker {
+    threshold: u32,
+    again: HashMap<CardId, u32>,
+    flagged: Vec<CardId>,
+}
+
+impl Default for StruggleTracker {
+    fn default() -> Self {
+        Self::new(STRUGGLE_THRESHOLD)
+    }
+}
+
+impl StruggleTracker {
+    pub fn new(threshold: u32) -> Self {
+        Self {
+            threshold,
+            again: HashMap::new(),
+            flagged: Vec::new(),
+        }
+    }
+
+    /// Records a grade; returns true when this grade newly flags the card.
+    pub fn record(&mut self, card_id: CardId, grade: &Grade) -> bool {
+        if !grade.is_failing() {
+            return false;
+        }
+        let n = self.again.entry(card_id).or_insert(0);
+        *n += 1;
+        if is_struggling(*n, self.threshold) && !self.flagged.contains(&card_id) {
+            self.flagged.push(card_id);
+            return true;
+        }
+        false
+    }
+
+    pub fn is_flagged(&self, card_id: CardId) -> bool {
+        self.flagged.contains(&card_id)
+    }
+
+    /// Flagged cards in the order they crossed the threshold.
+    pub fn flagged(&self) -> &[CardId] {
+        &self.flagged
+    }
+}
+
+/// Cards failed (graded Again or Hard) during one session, each listed once in the
+/// order it first failed. Backs the end-of-session "study again" offer; not
+/// persisted.
+#[derive(Clone, Debug, Default)]
+pub struct FailedCards {
+    ids: Vec<CardId>,
+}
+
+impl FailedCards {
+    pub fn record(&mut self, card_id: CardId, grade: &Grade) {
+        if grade.is_failing() && !self.ids.contains(&card_id) {
+            self.ids.push(card_id);
+        }
+    }
+
+    pub fn ids(&self) -> &[CardId] {
+        &self.ids
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Hands over the failed ids for a re-study round, leaving the set empty
+    /// to collect that round's failures.
+    pub fn take(&mut self) -> Vec<CardId> {
+        std::mem::take(&mut self.ids)
+    }
+}
+
+/// Cards graded during one session (re-study rounds included) and progress
+/// toward an optional goal set with `--goal`. Not persisted.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SessionSummary {
+    pub reviewed: u32,
+    pub goal: Option<u32>,
+}
+
+impl SessionSummary {
+    pub fn with_goal(goal: Option<u32>) -> Self {
+        Self { reviewed: 0, goal }
+    }
+
+    /// Counts one graded card; returns true only for the grade that reaches
+    /// the goal, so the caller announces it once.
+    pub fn record(&mut self) -> bool {
+        self.reviewed += 1;
+        self.goal == Some(self.reviewed)
+    }
+
+    pub fn goal_reached(&self) -> bool {
+        self.goal.is_some_and(|g| self.reviewed >= g)
+    }
+}
+
+/// Whether grading a card reschedules it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReviewMode {
+    #[default]
+    Scheduled,
+    /// Drill cards regardless of due dates; nothing is written.
+    Cram,
+}
+
+/// Records `grade` for `card` according to `mode`. Scheduled reviews update
+/// the card and log a review, returning the rescheduled card; cram reviews
+/// leave the repository untouched and return `None`.
+pub async fn record_grade<R: Repository + ?Sized>(
+    repo: &R,
+    card: Card,
+    grade: Grade,
+    mode: ReviewMode,
+) -> Result<Option<Card>, CoreError> {
+    record_grade_at(repo, card, grade, mode, Utc::now()).await
+}
+
+/// [`record_grade`] as if graded at `now`.
+pub async fn record_grade_at<R: Repository + ?Sized>(
+    repo: &R,
+    card: Card,
+    grade: Grade,
+    mode: ReviewMode,
+    now: DateTime<Utc>,
+) -> Result<Option<Card>, CoreError> {
+    record_grade_timed(repo, card, grade, mode, now, None).await
+}
+
+/// [`record_grade_at`], logging how long the answer took (see [`elapsed_ms`])
+/// on the review.
+pub async fn record_grade_timed<R: Repository + ?Sized>(
+    repo: &R,
+    card: Card,
+    grade: Grade,
+    mode: ReviewMode,
+    now: DateTime<Utc>,
+    duration_ms: Option<u32>,
+) -> Result<Option<Card>, CoreError> {
+    match mode {
+        ReviewMode::Cram => Ok(None),
+        ReviewMode::Scheduled => {
+            let mut out = apply_grade_at(card, grade, now);
+            out.review.duration_ms = duration_ms;
+            repo.update_card(&out.updated_card).await?;
+            repo.insert_review(&out.review).await?;
+            Ok(Some(out.updated_card))
+        }
+    }
+}
+
+/// Whole milliseconds since `since`, saturating at `u32::MAX`; the answer
+/// time review loops pass to [`record_grade_timed`].
+pub fn elapsed_ms(since: Instant) -> u32 {
+    u32::try_from(since.elapsed().as_millis()).unwrap_or(u32::MAX)
+}
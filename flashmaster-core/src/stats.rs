@@ -1,10 +1,13 @@
-use crate::{Grade, Review};
-use chrono::{Duration, NaiveDate};
+use crate::{Card, CardId, Deck, DeckId, Grade, Review};
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, TimeZone, Timelike, Utc};
+use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap};
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Totals {
     pub total: u32,
+    #[serde(default)]
+    pub again: u32,
     pub hard: u32,
     pub medium: u32,
     pub easy: u32,
@@ -14,6 +17,7 @@ impl Totals {
     pub fn record(&mut self, g: &Grade) {
         self.total += 1;
         match g {
+            Grade::Again => self.again += 1,
             Grade::Hard => self.hard += 1,
             Grade::Medium => self.medium += 1,
             Grade::Easy => self.easy += 1,
@@ -35,17 +39,50 @@ pub struct StatsSummary {
 }
 
 pub fn summarize(reviews: &[Review]) -> StatsSummary {
+    summarize_tz(reviews, Utc)
+}
+
+/// [`summarize`] with `per_day` keyed by the local date in `tz`, e.g. a
+/// `chrono_tz::Tz` or `chrono::Local`.
+pub fn summarize_tz<Tz: TimeZone>(reviews: &[Review], tz: Tz) -> StatsSummary {
     let mut summary = StatsSummary::default();
     for r in reviews {
         summary.totals.record(&r.grade);
-        let d = r.reviewed_at.date_naive();
+        let d = r.reviewed_at.with_timezone(&tz).date_naive();
         summary.per_day.entry(d).or_default().record(&r.grade);
     }
     summary
 }
 
+/// Grade totals per local hour of day, `tz` being the user's offset from UTC.
+fn totals_by_hour(reviews: &[Review], tz: FixedOffset) -> [Totals; 24] {
+    let mut hours: [Totals; 24] = std::array::from_fn(|_| Totals::default());
+    for r in reviews {
+        let hour = r.reviewed_at.with_timezone(&tz).hour() as usize;
+        hours[hour].record(&r.grade);
+    }
+    hours
+}
+
+/// Number of reviews in each local hour of day (index 0 is midnight to 1am).
+pub fn reviews_by_hour(reviews: &[Review], tz: FixedOffset) -> [u32; 24] {
+    totals_by_hour(reviews, tz).map(|t| t.total)
+}
+
+/// [`Totals::accuracy`] for each local hour of day; `None` for hours without
+/// reviews.
+pub fn accuracy_by_hour(reviews: &[Review], tz: FixedOffset) -> [Option<f32>; 24] {
+    totals_by_hour(reviews, tz).map(|t| (t.total > 0).then(|| t.accuracy()))
+}
+
 pub fn daily_streak(reviews: &[Review], today: NaiveDate) -> u32 {
-    let per_day = summarize(reviews).per_day;
+    daily_streak_tz(reviews, today, Utc)
+}
+
+/// [`daily_streak`] counting local days in `tz`; `today` is a date in `tz`
+/// too, so late-evening reviews stay on the day they were made.
+pub fn daily_streak_tz<Tz: TimeZone>(reviews: &[Review], today: NaiveDate, tz: Tz) -> u32 {
+    let per_day = summarize_tz(reviews, tz).per_day;
     let mut streak = 0u32;
     let mut day = today;
     loop {
@@ -71,3 +108,235 @@ pub fn per_deck_totals(
     }
     map
 }
+
+/// [`Totals::accuracy`] over reviews of cards already seen before: each
+/// card's earliest review in `reviews` is left out, so pass the full log
+/// rather than a date window. 0.0 when nothing remains.
+pub fn true_retention(reviews: &[Review]) -> f32 {
+    let mut first: HashMap<CardId, &Review> = HashMap::new();
+    for r in reviews {
+        let f = first.entry(r.card_id).or_insert(r);
+        if r.reviewed_at < f.reviewed_at {
+            *f = r;
+        }
+    }
+    let mut totals = Totals::default();
+    for r in reviews.iter().filter(|r| first[&r.card_id].id != r.id) {
+        totals.record(&r.grade);
+    }
+    totals.accuracy()
+}
+
+/// Mean answer time over the reviews that recorded one; `None` if none did.
+pub fn average_duration_ms(reviews: &[Review]) -> Option<f32> {
+    let timed: Vec<u32> = reviews.iter().filter_map(|r| r.duration_ms).collect();
+    if timed.is_empty() {
+        return None;
+    }
+    Some(timed.iter().map(|&ms| ms as f64).sum::<f64>() as f32 / timed.len() as f32)
+}
+
+/// Grade totals per tag, each review counting towards every tag on its card.
+/// Tags compare case-insensitively and are keyed in lowercase; reviews of
+/// untagged cards count towards nothing.
+pub fn per_tag_totals(cards: &[Card], reviews: &[Review]) -> HashMap<String, Totals> {
+    let card_tags: HashMap<CardId, Vec<String>> = cards
+        .iter()
+        .map(|c| {
+            let mut tags: Vec<String> = c.tags.iter().map(|t| t.to_lowercase()).collect();
+            tags.sort();
+            tags.dedup();
+            (c.id, tags)
+        })
+        .collect();
+    let mut map: HashMap<String, Totals> = HashMap::new();
+    for r in reviews {
+        for tag in card_tags.get(&r.card_id).into_iter().flatten() {
+            map.entry(tag.clone()).or_default().record(&r.grade);
+        }
+    }
+    map
+}
+
+/// Number of cards created on each day (UTC), for deck growth charts.
+pub fn additions_per_day(cards: &[Card]) -> BTreeMap<NaiveDate, u32> {
+    let mut map = BTreeMap::new();
+    for c in cards {
+        *map.entry(c.created_at.date_naive()).or_insert(0) += 1;
+    }
+    map
+}
+
+/// Scheduled (non-new, non-suspended) cards due on each of the `days` days
+/// starting at `today`. Overdue cards count towards `today`.
+pub fn due_forecast(cards: &[Card], today: NaiveDate, days: u32) -> BTreeMap<NaiveDate, u32> {
+    let end = today + Duration::days(days as i64);
+    let mut map = BTreeMap::new();
+    for c in cards.iter().filter(|c| !c.suspended && !c.is_new()) {
+        let day = c.due_at.date_naive().max(today);
+        if day < end {
+            *map.entry(day).or_insert(0) += 1;
+        }
+    }
+    map
+}
+
+/// The `limit` scheduled (non-new, non-suspended) cards furthest past their
+/// `due_at`, most overdue first, with whole days overdue.
+pub fn most_overdue(cards: &[Card], now: DateTime<Utc>, limit: usize) -> Vec<(CardId, i64)> {
+    let mut overdue: Vec<&Card> = cards
+        .iter()
+        .filter(|c| !c.suspended && !c.is_new() && c.due_at < now)
+        .collect();
+    overdue.sort_by_key(|c| c.due_at);
+    overdue
+        .into_iter()
+        .take(limit)
+        .map(|c| (c.id, (now - c.due_at).num_days()))
+        .collect()
+}
+
+/// Card counts for one deck, as shown next to it in deck lists. `new` and
+/// `due` leave out suspended cards; `total` counts every card.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeckCounts {
+    pub total: u32,
+    pub new: u32,
+    pub due: u32,
+    pub suspended: u32,
+    /// Latest `last_reviewed_at` among the deck's cards, as in
+    /// [`deck_last_reviewed`]. Not serialized: the API reports it on the deck.
+    #[serde(skip)]
+    pub last_reviewed_at: Option<DateTime<Utc>>,
+}
+
+impl DeckCounts {
+    /// Adds `card` as of `now`: new if never reviewed, due if reviewed and
+    /// `due_at <= now`.
+    pub fn add(&mut self, card: &Card, now: DateTime<Utc>) {
+        self.total += 1;
+        self.last_reviewed_at = self.last_reviewed_at.max(card.last_reviewed_at);
+        if card.suspended {
+            self.suspended += 1;
+        } else if card.is_new() {
+            self.new += 1;
+        } else if card.due_at <= now {
+            self.due += 1;
+        }
+    }
+
+    pub fn of<'a>(cards: impl IntoIterator<Item = &'a Card>, now: DateTime<Utc>) -> Self {
+        let mut counts = Self::default();
+        for c in cards {
+            counts.add(c, now);
+        }
+        counts
+    }
+}
+
+/// Interval, in days, from which a card counts as mature.
+pub const MATURE_INTERVAL_DAYS: u32 = 21;
+
+/// Cards by how well they are learned. Suspended cards count only as
+/// suspended.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaturityCounts {
+    pub new: u32,
+    pub young: u32,
+    pub mature: u32,
+    pub suspended: u32,
+}
+
+/// [`maturity_breakdown_with`] at [`MATURE_INTERVAL_DAYS`].
+pub fn maturity_breakdown(cards: &[Card]) -> MaturityCounts {
+    maturity_breakdown_with(cards, MATURE_INTERVAL_DAYS)
+}
+
+/// Counts never-reviewed cards as new, and reviewed ones as mature once
+/// their interval reaches `mature_days`, young before that.
+pub fn maturity_breakdown_with(cards: &[Card], mature_days: u32) -> MaturityCounts {
+    let mut counts = MaturityCounts::default();
+    for c in cards {
+        if c.suspended {
+            counts.suspended += 1;
+        } else if c.is_new() {
+            counts.new += 1;
+        } else if c.interval_days >= mature_days {
+            counts.mature += 1;
+        } else {
+            counts.young += 1;
+        }
+    }
+    counts
+}
+
+/// Most recent `last_reviewed_at` among `cards`, i.e. when a deck was last
+/// studied if given that deck's cards.
+pub fn deck_last_reviewed(cards: &[Card]) -> Option<DateTime<Utc>> {
+    cards.iter().filter_map(|c| c.last_reviewed_at).max()
+}
+
+/// Sorts decks most recently studied first; never-studied decks go last,
+/// oldest first. `cards` may span all decks.
+pub fn sort_decks_by_recent(decks: &mut [Deck], cards: &[Card]) {
+    let mut last: HashMap<DeckId, DateTime<Utc>> = HashMap::new();
+    for c in cards {
+        if let Some(t) = c.last_reviewed_at {
+            let e = last.entry(c.deck_id).or_insert(t);
+            *e = (*e).max(t);
+        }
+    }
+    decks.sort_by_key(|d| (std::cmp::Reverse(last.get(&d.id).copied()), d.created_at));
+}
+
+/// Reviews needed in each window before [`accuracy_trend`] calls a direction.
+pub const TREND_MIN_REVIEWS: u32 = 5;
+/// Accuracy changes smaller than this (either way) count as flat.
+pub const TREND_FLAT_BAND: f32 = 0.02;
+
+/// Direction of accuracy between two consecutive windows. The payload is
+/// the change in accuracy (recent minus previous, as a fraction).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub enum Trend {
+    Improving(f32),
+    Flat(f32),
+    Declining(f32),
+    /// Too few reviews in one of the windows to say.
+    Unknown,
+}
+
+/// Accuracy over the last `window_days` compared with the `window_days`
+/// before that.
+pub fn accuracy_trend(reviews: &[Review], window_days: u32) -> Trend {
+    accuracy_trend_at(reviews, window_days, Utc::now())
+}
+
+/// [`accuracy_trend`] with the windows ending at `now`.
+pub fn accuracy_trend_at(reviews: &[Review], window_days: u32, now: DateTime<Utc>) -> Trend {
+    if window_days == 0 {
+        return Trend::Unknown;
+    }
+    let w = Duration::days(window_days as i64);
+    let (mut recent, mut previous) = (Totals::default(), Totals::default());
+    for r in reviews {
+        let age = now - r.reviewed_at;
+        if age < Duration::zero() {
+            continue;
+        } else if age < w {
+            recent.record(&r.grade);
+        } else if age < w * 2 {
+            previous.record(&r.grade);
+        }
+    }
+    if recent.total < TREND_MIN_REVIEWS || previous.total < TREND_MIN_REVIEWS {
+        return Trend::Unknown;
+    }
+    let delta = recent.accuracy() - previous.accuracy();
+    if delta >= TREND_FLAT_BAND {
+        Trend::Improving(delta)
+    } else if delta <= -TREND_FLAT_BAND {
+        Trend::Declining(delta)
+    } else {
+        Trend::Flat(delta)
+    }
+}
@@ -0,0 +1,15 @@
+//! Tag edits shared by `card edit`, `tag apply` and the TUI's quick tag.
+
+/// Appends each of `add` not already present, then drops every tag matching
+/// one of `rm`; both comparisons ignore ASCII case. Returns `None` when the
+/// result equals `tags`, so callers can skip the write.
+pub fn edit_tags(tags: &[String], add: &[String], rm: &[String]) -> Option<Vec<String>> {
+    let mut out = tags.to_vec();
+    for t in add {
+        if !out.iter().any(|x| x.eq_ignore_ascii_case(t)) {
+            out.push(t.clone());
+        }
+    }
+    out.retain(|x| !rm.iter().any(|r| x.eq_ignore_ascii_case(r)));
+    (out != tags).then_some(out)
+}
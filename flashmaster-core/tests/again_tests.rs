@@ -0,0 +1,53 @@
+use chrono::Utc;
+use flashmaster_core::{
+    apply_grade, apply_grade_with, Card, FailedCards, Grade, Sm2Config, Totals,
+};
+
+fn mature_card() -> Card {
+    let mut card = Card::new(uuid::Uuid::new_v4(), "q", "a");
+    card.reps = 5;
+    card.interval_days = 30;
+    card.ef = 2.5;
+    card
+}
+
+#[test]
+fn again_scores_zero_and_round_trips() {
+    assert_eq!(Grade::Again.as_score(), 0);
+    assert_eq!(Grade::from_score(0), Some(Grade::Again));
+    for score in 1..=3 {
+        assert_eq!(Grade::from_score(score).unwrap().as_score(), score);
+    }
+    let legacy: Grade = serde_json::from_str("\"hard\"").unwrap();
+    assert_eq!(legacy, Grade::Hard);
+    assert_eq!(serde_json::to_string(&Grade::Again).unwrap(), "\"again\"");
+}
+
+#[test]
+fn again_is_a_lapse_with_the_steepest_ease_penalty() {
+    let again = apply_grade(mature_card(), Grade::Again).updated_card;
+    let hard = apply_grade(mature_card(), Grade::Hard).updated_card;
+    assert_eq!((again.reps, again.interval_days), (0, 1));
+    assert!(again.ef < hard.ef, "{} vs {}", again.ef, hard.ef);
+
+    let cfg = Sm2Config {
+        relearn_interval_days: 2,
+        ..Default::default()
+    };
+    let relearn = apply_grade_with(mature_card(), Grade::Again, &cfg, Utc::now()).updated_card;
+    assert_eq!(relearn.interval_days, 2);
+}
+
+#[test]
+fn again_counts_as_failed() {
+    let id = uuid::Uuid::new_v4();
+    let mut failed = FailedCards::default();
+    failed.record(id, &Grade::Again);
+    assert_eq!(failed.ids(), [id]);
+
+    let mut totals = Totals::default();
+    totals.record(&Grade::Again);
+    totals.record(&Grade::Easy);
+    assert_eq!((totals.again, totals.total), (1, 2));
+    assert_eq!(totals.accuracy(), 0.5);
+}
@@ -0,0 +1,33 @@
+use flashmaster_core::{check_answer, check_answer_for, AnswerNorm, Card, PromptWith};
+use uuid::Uuid;
+
+fn car() -> Card {
+    let mut c = Card::new(Uuid::new_v4(), "Wagen", "car");
+    c.alt_backs = vec!["automobile".into(), "auto".into()];
+    c
+}
+
+#[test]
+fn alternative_back_is_accepted() {
+    let norm = AnswerNorm::default();
+    assert!(check_answer(&car(), "car", &norm));
+    assert!(check_answer(&car(), "  Automobile ", &norm));
+}
+
+#[test]
+fn answer_outside_alternatives_fails() {
+    assert!(!check_answer(&car(), "truck", &AnswerNorm::default()));
+}
+
+#[test]
+fn display_back_lists_alternatives() {
+    assert_eq!(car().display_back(), "car (also: automobile, auto)");
+}
+
+#[test]
+fn back_prompt_expects_the_front() {
+    let norm = AnswerNorm::default();
+    assert!(check_answer_for(&car(), PromptWith::Back, "wagen", &norm));
+    assert!(!check_answer_for(&car(), PromptWith::Back, "car", &norm));
+    assert_eq!(car().question(PromptWith::Back), car().display_back());
+}
@@ -0,0 +1,44 @@
+use chrono::{Duration, Local, TimeZone};
+use flashmaster_core::{backup_file_name, rotate_backups, BACKUP_PREFIX};
+
+#[test]
+fn rotation_keeps_newest_n() {
+    let dir = tempfile::tempdir().unwrap();
+    let start = Local.with_ymd_and_hms(2030, 1, 1, 9, 0, 0).unwrap();
+    let names: Vec<String> = (0..5)
+        .map(|i| backup_file_name(BACKUP_PREFIX, start + Duration::seconds(i), "sqlite3"))
+        .collect();
+    for n in &names {
+        std::fs::write(dir.path().join(n), b"x").unwrap();
+    }
+    std::fs::write(dir.path().join("notes.txt"), b"keep me").unwrap();
+
+    let removed = rotate_backups(dir.path(), BACKUP_PREFIX, 3).unwrap();
+    assert_eq!(removed.len(), 2);
+    let mut left: Vec<String> = std::fs::read_dir(dir.path())
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    left.sort();
+    assert_eq!(left[..3], names[2..]);
+    assert_eq!(left[3], "notes.txt");
+}
+
+#[test]
+fn names_sort_chronologically() {
+    let t = Local.with_ymd_and_hms(2030, 1, 1, 9, 0, 0).unwrap();
+    let a = backup_file_name("fm", t + Duration::milliseconds(999), "json");
+    let b = backup_file_name("fm", t + Duration::seconds(1), "json");
+    assert!(a.starts_with("fm-20300101-090000-999000000-"), "{a}");
+    assert!(a.ends_with(".json"));
+    assert!(a < b);
+}
+
+#[test]
+fn same_instant_gets_distinct_names() {
+    let t = Local.with_ymd_and_hms(2030, 1, 1, 9, 0, 0).unwrap();
+    assert_ne!(
+        backup_file_name("fm", t, "json"),
+        backup_file_name("fm", t, "json")
+    );
+}
@@ -0,0 +1,41 @@
+use chrono::{Duration, Utc};
+use flashmaster_core::{Card, Deck, DueStatus, Grade, Review, EF_DEFAULT};
+use uuid::Uuid;
+
+#[test]
+fn card_builder_overrides_only_what_is_set() {
+    let deck = Deck::builder("Spanish").build();
+    let due = Utc::now() + Duration::days(3);
+    let card = Card::builder(deck.id, "hola", "hello")
+        .tags(["greeting"])
+        .due_at(due)
+        .build();
+
+    assert_eq!(card.deck_id, deck.id);
+    assert_eq!((card.front.as_str(), card.back.as_str()), ("hola", "hello"));
+    assert_eq!(card.tags, vec!["greeting".to_string()]);
+    assert_eq!(card.due_at, due);
+
+    assert!(card.hint.is_none() && card.alt_backs.is_empty());
+    assert_eq!((card.reps, card.interval_days, card.ef), (0, 0, EF_DEFAULT));
+    assert!(card.last_grade.is_none() && card.last_reviewed_at.is_none());
+    assert!(!card.suspended);
+    assert_eq!(card.due_status(Utc::now()), DueStatus::New);
+}
+
+#[test]
+fn deck_and_review_builders_default_like_new() {
+    let id = Uuid::new_v4();
+    let deck = Deck::builder("French").id(id).build();
+    assert_eq!((deck.id, deck.name.as_str()), (id, "French"));
+
+    let card_id = Uuid::new_v4();
+    let review = Review::builder(card_id, Grade::Easy)
+        .interval_applied(6)
+        .build();
+    assert_eq!(review.card_id, card_id);
+    assert_eq!(review.grade, Grade::Easy);
+    assert_eq!(review.interval_applied, 6);
+    assert_eq!(review.ef_after, EF_DEFAULT);
+    assert!(Utc::now() - review.reviewed_at < Duration::seconds(5));
+}
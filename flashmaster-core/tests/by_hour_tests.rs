@@ -0,0 +1,29 @@
+use chrono::{FixedOffset, TimeZone, Utc};
+use flashmaster_core::{accuracy_by_hour, reviews_by_hour, Grade, Review};
+use uuid::Uuid;
+
+#[test]
+fn reviews_land_in_local_hours() {
+    // UTC-5: 13:20 UTC is 08:20 local, 03:10 UTC is 22:10 local the day before.
+    let tz = FixedOffset::west_opt(5 * 3600).unwrap();
+    let morning = Utc.with_ymd_and_hms(2025, 3, 10, 13, 20, 0).unwrap();
+    let night = Utc.with_ymd_and_hms(2025, 3, 11, 3, 10, 0).unwrap();
+    let review = |at, g| Review::new(Uuid::new_v4(), g, at, 1, 2.5);
+    let reviews = vec![
+        review(morning, Grade::Easy),
+        review(morning, Grade::Medium),
+        review(morning, Grade::Easy),
+        review(night, Grade::Hard),
+        review(night, Grade::Easy),
+    ];
+
+    let counts = reviews_by_hour(&reviews, tz);
+    assert_eq!(counts[8], 3);
+    assert_eq!(counts[22], 2);
+    assert_eq!(counts.iter().sum::<u32>(), 5);
+
+    let accuracy = accuracy_by_hour(&reviews, tz);
+    assert_eq!(accuracy[8], Some(1.0));
+    assert_eq!(accuracy[22], Some(0.5));
+    assert_eq!(accuracy[13], None);
+}
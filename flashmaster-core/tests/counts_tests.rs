@@ -0,0 +1,51 @@
+use chrono::{Duration, Utc};
+use flashmaster_core::{repo::memory::MemoryRepo, DeckCounts, Repository};
+
+#[tokio::test]
+async fn list_counts_match_per_deck_counts() {
+    let repo = MemoryRepo::new();
+    let now = Utc::now();
+    let a = repo.create_deck("A").await.unwrap();
+    repo.create_deck("B").await.unwrap();
+    repo.add_card(a.id, "new", "x", None, &[]).await.unwrap();
+    let mut due = repo.add_card(a.id, "due", "x", None, &[]).await.unwrap();
+    due.reps = 2;
+    due.due_at = now - Duration::hours(1);
+    repo.update_card(&due).await.unwrap();
+
+    let listed = repo.list_decks_with_counts(now).await.unwrap();
+    assert_eq!(listed.len(), 2);
+    for (deck, counts) in &listed {
+        assert_eq!(*counts, repo.deck_counts(deck.id, now).await.unwrap());
+    }
+    assert_eq!(
+        listed[0].1,
+        DeckCounts {
+            total: 2,
+            new: 1,
+            due: 1,
+            suspended: 0,
+            last_reviewed_at: None,
+        }
+    );
+    assert_eq!(listed[1].1, DeckCounts::default());
+}
+
+#[tokio::test]
+async fn last_reviewed_comes_with_the_counts() {
+    let repo = MemoryRepo::new();
+    let now = Utc::now();
+    let studied = repo.create_deck("Studied").await.unwrap();
+    let fresh = repo.create_deck("Fresh").await.unwrap();
+    repo.add_card(fresh.id, "q", "a", None, &[]).await.unwrap();
+    let mut c = repo
+        .add_card(studied.id, "q", "a", None, &[])
+        .await
+        .unwrap();
+    c.last_reviewed_at = Some(now - Duration::hours(2));
+    repo.update_card(&c).await.unwrap();
+
+    let listed = repo.list_decks_with_counts(now).await.unwrap();
+    assert_eq!(listed[0].1.last_reviewed_at, Some(now - Duration::hours(2)));
+    assert_eq!(listed[1].1.last_reviewed_at, None);
+}
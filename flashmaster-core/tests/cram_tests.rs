@@ -0,0 +1,55 @@
+use chrono::{Duration, Utc};
+use flashmaster_core::repo::memory::MemoryRepo;
+use flashmaster_core::{build_cram_pool, record_grade, Grade, Repository, ReviewMode};
+
+#[tokio::test]
+async fn cram_session_leaves_scheduling_untouched() {
+    let repo = MemoryRepo::new();
+    let deck = repo.create_deck("Exam").await.unwrap();
+    let mut scheduled = repo.add_card(deck.id, "q1", "a1", None, &[]).await.unwrap();
+    scheduled.reps = 4;
+    scheduled.interval_days = 20;
+    scheduled.due_at = Utc::now() + Duration::days(20);
+    repo.update_card(&scheduled).await.unwrap();
+    repo.add_card(deck.id, "q2", "a2", None, &[]).await.unwrap();
+    let mut suspended = repo.add_card(deck.id, "q3", "a3", None, &[]).await.unwrap();
+    suspended.suspended = true;
+    repo.update_card(&suspended).await.unwrap();
+
+    let before = repo.list_cards(Some(deck.id)).await.unwrap();
+    let pool = build_cram_pool(&before);
+    assert_eq!(
+        pool.len(),
+        2,
+        "future and new cards are crammed, suspended are not"
+    );
+
+    for card in pool {
+        let out = record_grade(&repo, card, Grade::Easy, ReviewMode::Cram)
+            .await
+            .unwrap();
+        assert!(out.is_none());
+    }
+
+    for b in before {
+        let after = repo.get_card(b.id).await.unwrap();
+        assert_eq!(after.due_at, b.due_at);
+        assert_eq!(after.reps, b.reps);
+        assert!(repo.list_reviews_for_card(b.id).await.unwrap().is_empty());
+    }
+}
+
+#[tokio::test]
+async fn scheduled_grade_reschedules_and_logs() {
+    let repo = MemoryRepo::new();
+    let deck = repo.create_deck("Exam").await.unwrap();
+    let card = repo.add_card(deck.id, "q", "a", None, &[]).await.unwrap();
+
+    let out = record_grade(&repo, card.clone(), Grade::Easy, ReviewMode::Scheduled)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(out.reps, 1);
+    assert_eq!(repo.get_card(card.id).await.unwrap().reps, 1);
+    assert_eq!(repo.list_reviews_for_card(card.id).await.unwrap().len(), 1);
+}
@@ -0,0 +1,37 @@
+use chrono::{Duration, TimeZone, Utc};
+use flashmaster_core::{filter_created_between, Card};
+
+#[test]
+fn created_range_includes_from_and_excludes_to() {
+    let from = Utc.with_ymd_and_hms(2025, 2, 1, 0, 0, 0).unwrap();
+    let to = Utc.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap();
+    let deck = uuid::Uuid::new_v4();
+    let at = |t| Card::builder(deck, "q", "a").created_at(t).build();
+    let before = at(from - Duration::seconds(1));
+    let first = at(from);
+    let inside = at(from + Duration::days(10));
+    let last_moment = at(to - Duration::seconds(1));
+    let after = at(to);
+    let cards = [
+        before.clone(),
+        first.clone(),
+        inside.clone(),
+        last_moment.clone(),
+        after.clone(),
+    ];
+
+    let ids = |v: Vec<Card>| v.into_iter().map(|c| c.id).collect::<Vec<_>>();
+    assert_eq!(
+        ids(filter_created_between(&cards, Some(from), Some(to))),
+        [first.id, inside.id, last_moment.id]
+    );
+    assert_eq!(
+        ids(filter_created_between(&cards, None, Some(from))),
+        [before.id]
+    );
+    assert_eq!(
+        ids(filter_created_between(&cards, Some(to), None)),
+        [after.id]
+    );
+    assert_eq!(filter_created_between(&cards, None, None).len(), 5);
+}
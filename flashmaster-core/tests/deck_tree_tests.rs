@@ -0,0 +1,109 @@
+use flashmaster_core::{
+    apply_grade, deck_subtree, nest_decks, repo::memory::MemoryRepo, CoreError, Deck, Grade,
+    Repository,
+};
+
+fn child(name: &str, parent: &Deck) -> Deck {
+    let mut d = Deck::new(name);
+    d.parent_id = Some(parent.id);
+    d
+}
+
+#[test]
+fn nesting_puts_subdecks_under_their_parent() {
+    let spanish = Deck::new("Spanish");
+    let french = Deck::new("French");
+    let verbs = child("Verbs", &spanish);
+    let irregular = child("Irregular", &verbs);
+    let nouns = child("Nouns", &spanish);
+    let listed = vec![
+        irregular.clone(),
+        french.clone(),
+        nouns.clone(),
+        spanish.clone(),
+        verbs.clone(),
+    ];
+
+    let nested = nest_decks(listed, |d| d);
+    let names: Vec<(usize, &str)> = nested.iter().map(|(n, d)| (*n, d.name.as_str())).collect();
+    assert_eq!(
+        names,
+        [
+            (0, "French"),
+            (0, "Spanish"),
+            (1, "Nouns"),
+            (1, "Verbs"),
+            (2, "Irregular"),
+        ]
+    );
+
+    let all = [
+        spanish.clone(),
+        french,
+        verbs.clone(),
+        irregular.clone(),
+        nouns.clone(),
+    ];
+    let below = deck_subtree(&all, spanish.id);
+    assert_eq!(below.len(), 4);
+    assert_eq!(deck_subtree(&all, verbs.id).len(), 2);
+    assert!(!below.contains(&all[1].id));
+}
+
+#[test]
+fn orphans_and_loops_still_show_up_once() {
+    let mut a = Deck::new("a");
+    let b = child("b", &a);
+    a.parent_id = Some(b.id);
+    let lost = child("lost", &Deck::new("gone"));
+    let nested = nest_decks(vec![a, b, lost], |d| d);
+    assert_eq!(nested.len(), 3);
+    assert_eq!((nested[0].0, nested[0].1.name.as_str()), (0, "lost"));
+}
+
+#[tokio::test]
+async fn deleting_a_deck_takes_its_subdecks_with_it() {
+    let repo = MemoryRepo::new();
+    let spanish = repo.create_deck("Spanish").await.unwrap();
+    let verbs = repo.create_subdeck("Verbs", spanish.id).await.unwrap();
+    let irregular = repo.create_subdeck("Irregular", verbs.id).await.unwrap();
+    let french = repo.create_deck("French").await.unwrap();
+    let card = repo
+        .add_card(irregular.id, "ser", "to be", None, &[])
+        .await
+        .unwrap();
+    repo.insert_review(&apply_grade(card.clone(), Grade::Easy).review)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        repo.list_subdecks(spanish.id).await.unwrap()[0].id,
+        verbs.id
+    );
+    assert!(matches!(
+        repo.create_subdeck("x", uuid::Uuid::new_v4()).await,
+        Err(CoreError::NotFound("deck"))
+    ));
+
+    repo.delete_deck(spanish.id).await.unwrap();
+    let left: Vec<_> = repo.list_decks().await.unwrap();
+    assert_eq!(left.len(), 1);
+    assert_eq!(left[0].id, french.id);
+    assert!(!repo.card_exists(card.id).await.unwrap());
+    assert!(repo.list_reviews().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn merging_a_parent_lifts_its_subdecks() {
+    let repo = MemoryRepo::new();
+    let lang = repo.create_deck("Languages").await.unwrap();
+    let spanish = repo.create_subdeck("Spanish", lang.id).await.unwrap();
+    let verbs = repo.create_subdeck("Verbs", spanish.id).await.unwrap();
+    let other = repo.create_deck("Other").await.unwrap();
+
+    repo.merge_decks(spanish.id, other.id, &[]).await.unwrap();
+    assert_eq!(
+        repo.get_deck(verbs.id).await.unwrap().parent_id,
+        Some(lang.id)
+    );
+}
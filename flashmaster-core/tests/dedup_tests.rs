@@ -0,0 +1,17 @@
+use chrono::{Duration, Utc};
+use flashmaster_core::{find_duplicates, Card};
+use uuid::Uuid;
+
+#[test]
+fn identical_cards_form_one_group_oldest_first() {
+    let deck = Uuid::new_v4();
+    let now = Utc::now();
+    let mut newer = Card::new(deck, "Hola ", "hello");
+    newer.created_at = now;
+    let mut older = Card::new(deck, "hola", "Hello");
+    older.created_at = now - Duration::days(3);
+    let other = Card::new(deck, "adios", "bye");
+
+    let groups = find_duplicates(&[newer.clone(), other, older.clone()]);
+    assert_eq!(groups, vec![vec![older.id, newer.id]]);
+}
@@ -0,0 +1,43 @@
+use chrono::Utc;
+use flashmaster_core::{
+    average_duration_ms, repo::memory::MemoryRepo, session::record_grade_timed, Grade, Repository,
+    Review, ReviewMode,
+};
+use uuid::Uuid;
+
+#[test]
+fn average_skips_untimed_reviews() {
+    let card = Uuid::new_v4();
+    let now = Utc::now();
+    assert_eq!(average_duration_ms(&[]), None);
+    let untimed = Review::new(card, Grade::Easy, now, 1, 2.5);
+    assert_eq!(average_duration_ms(std::slice::from_ref(&untimed)), None);
+
+    let reviews = vec![
+        untimed,
+        Review::new_timed(card, Grade::Easy, now, 1, 2.5, Some(1000)),
+        Review::builder(card, Grade::Hard).duration_ms(4000).build(),
+    ];
+    assert_eq!(average_duration_ms(&reviews), Some(2500.0));
+}
+
+#[tokio::test]
+async fn timed_grade_is_logged_on_the_review() {
+    let repo = MemoryRepo::new();
+    let deck = repo.create_deck("Lang").await.unwrap();
+    let card = repo.add_card(deck.id, "q", "a", None, &[]).await.unwrap();
+
+    record_grade_timed(
+        &repo,
+        card.clone(),
+        Grade::Medium,
+        ReviewMode::Scheduled,
+        Utc::now(),
+        Some(3210),
+    )
+    .await
+    .unwrap();
+    let reviews = repo.list_reviews_for_card(card.id).await.unwrap();
+    assert_eq!(reviews.len(), 1);
+    assert_eq!(reviews[0].duration_ms, Some(3210));
+}
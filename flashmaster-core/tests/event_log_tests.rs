@@ -0,0 +1,72 @@
+use flashmaster_core::repo::memory::MemoryRepo;
+use flashmaster_core::{
+    apply_grade, record_grade, Card, EventLog, Grade, Repository, ReviewEvent, ReviewMode,
+};
+
+#[test]
+fn event_line_is_one_json_object() {
+    let card = Card::new(uuid::Uuid::new_v4(), "q", "a");
+    assert!(ReviewEvent::from_card(&card).is_none(), "never graded");
+
+    let out = apply_grade(card, Grade::Easy);
+    let ev = ReviewEvent::from_card(&out.updated_card).unwrap();
+    let line = ev.to_line();
+    assert!(line.ends_with('\n'));
+    assert_eq!(line.matches('\n').count(), 1);
+    let v: serde_json::Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(v["grade"], "easy");
+    assert_eq!(
+        v["interval_applied"].as_u64().unwrap() as i32,
+        out.review.interval_applied
+    );
+    assert_eq!(v["ef_after"].as_f64().unwrap() as f32, out.review.ef_after);
+}
+
+#[tokio::test]
+async fn grading_two_cards_appends_two_lines() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("events.jsonl");
+    std::fs::write(&path, "{\"earlier\":true}\n").unwrap();
+
+    let repo = MemoryRepo::new();
+    let deck = repo.create_deck("Lang").await.unwrap();
+    let log = EventLog::open(&path).unwrap();
+    let mut ids = Vec::new();
+    for front in ["q1", "q2"] {
+        let card = repo.add_card(deck.id, front, "a", None, &[]).await.unwrap();
+        ids.push(card.id);
+        let updated = record_grade(&repo, card, Grade::Medium, ReviewMode::Scheduled)
+            .await
+            .unwrap()
+            .unwrap();
+        log.append(&ReviewEvent::from_card(&updated).unwrap());
+    }
+    drop(log);
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    let mut lines = text.lines();
+    assert_eq!(
+        lines.next(),
+        Some("{\"earlier\":true}"),
+        "opened in append mode"
+    );
+    let events: Vec<ReviewEvent> = lines.map(|l| serde_json::from_str(l).unwrap()).collect();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].card_id, ids[0]);
+    assert_eq!(events[1].card_id, ids[1]);
+    assert!(events.iter().all(|e| e.deck_id == deck.id));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn write_failure_is_kept_until_taken() {
+    let log = EventLog::open(std::path::Path::new("/dev/full")).unwrap();
+    let ev = ReviewEvent::from_card(
+        &apply_grade(Card::new(uuid::Uuid::new_v4(), "q", "a"), Grade::Easy).updated_card,
+    )
+    .unwrap();
+    assert!(log.take_error().is_none());
+    log.append(&ev);
+    assert!(log.take_error().unwrap().starts_with("write failed:"));
+    assert!(log.take_error().is_none(), "taken once");
+}
@@ -0,0 +1,17 @@
+use flashmaster_core::{FailedCards, Grade};
+use uuid::Uuid;
+
+#[test]
+fn two_failed_cards_are_offered_for_restudy() {
+    let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    let mut failed = FailedCards::default();
+    failed.record(a, &Grade::Hard);
+    failed.record(c, &Grade::Easy);
+    failed.record(b, &Grade::Hard);
+    failed.record(a, &Grade::Hard);
+    failed.record(c, &Grade::Medium);
+
+    assert_eq!(failed.ids(), &[a, b]);
+    assert_eq!(failed.take(), vec![a, b]);
+    assert!(failed.is_empty());
+}
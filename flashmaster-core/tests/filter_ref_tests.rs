@@ -0,0 +1,43 @@
+use chrono::{Duration, Utc};
+use flashmaster_core::{
+    filter_by_due, filter_by_due_ref, filter_by_tag, filter_by_tag_ref, filter_by_text,
+    filter_by_text_ref, filter_not_suspended, filter_not_suspended_ref, Card, DueStatus,
+};
+use uuid::Uuid;
+
+fn ids<'a>(cards: impl IntoIterator<Item = &'a Card>) -> Vec<Uuid> {
+    cards.into_iter().map(|c| c.id).collect()
+}
+
+#[test]
+fn ref_filters_select_the_same_cards_as_owned() {
+    let deck = Uuid::new_v4();
+    let now = Utc::now();
+    let mut a = Card::new(deck, "hola", "hello");
+    a.tags = vec!["Spanish".into()];
+    let mut b = Card::new(deck, "adios", "bye");
+    b.reps = 2;
+    b.due_at = now + Duration::days(3);
+    b.suspended = true;
+    let mut c = Card::new(deck, "gracias", "thanks");
+    c.tags = vec!["spanish".into()];
+    let cards = vec![a, b, c];
+
+    assert_eq!(
+        ids(filter_by_text_ref(&cards, "a")),
+        ids(&filter_by_text(&cards, "a"))
+    );
+    assert_eq!(
+        ids(filter_by_tag_ref(&cards, "SPANISH")),
+        ids(&filter_by_tag(&cards, "SPANISH"))
+    );
+    assert_eq!(
+        ids(filter_by_due_ref(&cards, now, DueStatus::New)),
+        ids(&filter_by_due(&cards, now, DueStatus::New))
+    );
+    assert_eq!(
+        ids(filter_not_suspended_ref(&cards)),
+        ids(&filter_not_suspended(&cards))
+    );
+    assert_eq!(filter_not_suspended_ref(&cards).len(), 2);
+}
@@ -0,0 +1,31 @@
+use chrono::{Duration, NaiveDate, TimeZone, Utc};
+use flashmaster_core::{due_forecast, Card};
+use uuid::Uuid;
+
+fn scheduled(due_days: i64) -> Card {
+    let mut c = Card::new(Uuid::new_v4(), "q", "a");
+    c.reps = 1;
+    c.due_at = Utc.with_ymd_and_hms(2025, 3, 10, 9, 0, 0).unwrap() + Duration::days(due_days);
+    c
+}
+
+#[test]
+fn forecast_buckets_by_day_within_window() {
+    let today = NaiveDate::from_ymd_opt(2025, 3, 10).unwrap();
+    let mut suspended = scheduled(1);
+    suspended.suspended = true;
+    let cards = vec![
+        scheduled(-3), // overdue -> today
+        scheduled(0),
+        scheduled(2),
+        scheduled(2),
+        scheduled(7), // outside a 7-day window
+        suspended,
+        Card::new(Uuid::new_v4(), "new", "card"),
+    ];
+
+    let f = due_forecast(&cards, today, 7);
+    assert_eq!(f.len(), 2);
+    assert_eq!(f[&today], 2);
+    assert_eq!(f[&(today + Duration::days(2))], 2);
+}
@@ -0,0 +1,113 @@
+use chrono::{Duration, TimeZone, Utc};
+use flashmaster_core::{
+    apply_grade_at, apply_grade_fsrs_at, fsrs_state_from_history, Card, FsrsParams, Grade, Review,
+    Scheduler, FSRS_SCHEDULER_NAME,
+};
+
+fn card() -> Card {
+    Card::new(uuid::Uuid::new_v4(), "q", "a")
+}
+
+#[test]
+fn first_grade_uses_the_initial_weights() {
+    let params = FsrsParams::default();
+    let now = Utc.with_ymd_and_hms(2025, 3, 1, 9, 0, 0).unwrap();
+
+    let good = apply_grade_fsrs_at(card(), Grade::Medium, &params, now);
+    assert_eq!(good.updated_card.stability, params.w[2]);
+    assert_eq!(good.updated_card.difficulty, params.w[4]);
+    assert_eq!(good.updated_card.interval_days, 4);
+    assert_eq!(good.updated_card.due_at, now + Duration::days(4));
+    assert_eq!(good.review.scheduler, FSRS_SCHEDULER_NAME);
+
+    let easy = apply_grade_fsrs_at(card(), Grade::Easy, &params, now).updated_card;
+    assert_eq!((easy.stability, easy.interval_days), (params.w[3], 14));
+    assert!(easy.difficulty < good.updated_card.difficulty);
+
+    let again = apply_grade_fsrs_at(card(), Grade::Again, &params, now).updated_card;
+    assert_eq!((again.reps, again.interval_days), (0, 1));
+    assert!(again.difficulty > good.updated_card.difficulty);
+
+    // FSRS's Hard is a pass, not a lapse.
+    let hard = apply_grade_fsrs_at(card(), Grade::Hard, &params, now).updated_card;
+    assert_eq!((hard.stability, hard.reps), (params.w[1], 1));
+}
+
+#[test]
+fn recall_grows_stability_and_a_lapse_shrinks_it() {
+    let params = FsrsParams::default();
+    let t0 = Utc.with_ymd_and_hms(2025, 3, 1, 9, 0, 0).unwrap();
+    let first = apply_grade_fsrs_at(card(), Grade::Medium, &params, t0).updated_card;
+    let t1 = first.due_at;
+
+    let good = apply_grade_fsrs_at(first.clone(), Grade::Medium, &params, t1).updated_card;
+    let easy = apply_grade_fsrs_at(first.clone(), Grade::Easy, &params, t1).updated_card;
+    assert!(good.stability > first.stability);
+    assert!(easy.interval_days > good.interval_days);
+    assert_eq!(good.reps, 2);
+
+    let lapse = apply_grade_fsrs_at(good.clone(), Grade::Again, &params, good.due_at).updated_card;
+    assert!(lapse.stability < good.stability);
+    assert_eq!(lapse.reps, 0);
+    // The SM-2 ease is left alone.
+    assert_eq!(lapse.ef, first.ef);
+}
+
+#[test]
+fn sm2_cards_are_seeded_from_their_schedule() {
+    let params = FsrsParams::default();
+    let now = Utc::now();
+    let mut mature = card();
+    mature.reps = 5;
+    mature.interval_days = 30;
+    mature.last_reviewed_at = Some(now - Duration::days(30));
+
+    let out = apply_grade_fsrs_at(mature, Grade::Medium, &params, now).updated_card;
+    assert!(out.interval_days > 30, "got {}", out.interval_days);
+}
+
+#[test]
+fn history_replay_matches_grading_in_turn() {
+    let params = FsrsParams::default();
+    let t0 = Utc.with_ymd_and_hms(2025, 3, 1, 9, 0, 0).unwrap();
+    let grades = [Grade::Medium, Grade::Easy, Grade::Again, Grade::Hard];
+
+    let mut c = card();
+    let mut reviews = Vec::new();
+    let mut at = t0;
+    for g in grades {
+        let out = apply_grade_fsrs_at(c, g, &params, at);
+        c = out.updated_card;
+        reviews.push(out.review);
+        at = c.due_at;
+    }
+    reviews.reverse();
+    assert_eq!(
+        fsrs_state_from_history(&reviews, &params),
+        Some((c.stability, c.difficulty))
+    );
+    assert_eq!(fsrs_state_from_history(&[] as &[Review], &params), None);
+}
+
+#[test]
+fn scheduler_enum_dispatches_by_name() {
+    assert_eq!(Scheduler::default(), Scheduler::Sm2);
+    for s in [Scheduler::Sm2, Scheduler::Fsrs] {
+        assert_eq!(Scheduler::from_name(s.as_str()), Some(s));
+    }
+    assert_eq!(Scheduler::from_name("anki"), None);
+
+    let now = Utc::now();
+    let c = card();
+    let sm2 = Scheduler::Sm2
+        .apply(c.clone(), Grade::Easy, now)
+        .updated_card;
+    let direct = apply_grade_at(c.clone(), Grade::Easy, now).updated_card;
+    assert_eq!(
+        (sm2.interval_days, sm2.ef),
+        (direct.interval_days, direct.ef)
+    );
+    let fsrs = Scheduler::Fsrs.apply(c, Grade::Easy, now);
+    assert_eq!(fsrs.review.scheduler, "fsrs");
+    assert!(fsrs.updated_card.stability > 0.0);
+}
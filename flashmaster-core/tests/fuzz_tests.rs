@@ -0,0 +1,47 @@
+use chrono::{Duration, Utc};
+use flashmaster_core::{
+    apply_grade_fuzzed_with, apply_grade_with, fuzz_interval, Card, Grade, Sm2Config,
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+fn mature_card() -> Card {
+    let mut card = Card::new(uuid::Uuid::new_v4(), "q", "a");
+    card.reps = 5;
+    card.interval_days = 40;
+    card
+}
+
+#[test]
+fn same_seed_same_schedule() {
+    let cfg = Sm2Config::default();
+    let now = Utc::now();
+    let run = |seed| {
+        let mut rng = StdRng::seed_from_u64(seed);
+        apply_grade_fuzzed_with(mature_card(), Grade::Medium, &cfg, now, &mut rng)
+    };
+    let a = run(7);
+    let b = run(7);
+    assert_eq!(a.updated_card.interval_days, b.updated_card.interval_days);
+    let days = a.updated_card.interval_days;
+    assert_eq!(a.updated_card.due_at, now + Duration::days(days as i64));
+    assert_eq!(a.review.interval_applied, days as i32);
+
+    // 40 * 2.5 = 100 days, moved by at most 5.
+    let plain = apply_grade_with(mature_card(), Grade::Medium, &cfg, now).updated_card;
+    assert_eq!(plain.interval_days, 100);
+    assert!((95..=105).contains(&days), "got {days}");
+}
+
+#[test]
+fn fuzz_spreads_within_bounds() {
+    let mut rng = StdRng::seed_from_u64(42);
+    let spread: Vec<u32> = (0..200).map(|_| fuzz_interval(3, 5.0, &mut rng)).collect();
+    // 5% of 3 rounds to 0, so the one-day minimum applies.
+    assert!(spread.iter().all(|d| (2..=4).contains(d)));
+    assert!(spread.contains(&2) && spread.contains(&4));
+
+    assert_eq!(fuzz_interval(2, 5.0, &mut rng), 2);
+    assert_eq!(fuzz_interval(1, 50.0, &mut rng), 1);
+    assert_eq!(fuzz_interval(100, 0.0, &mut rng), 100);
+    assert!((0..200).all(|_| fuzz_interval(4, 100.0, &mut rng) >= 1));
+}
@@ -0,0 +1,24 @@
+use flashmaster_core::SessionSummary;
+
+#[test]
+fn goal_is_reached_on_the_fifth_grade_only() {
+    let mut s = SessionSummary::with_goal(Some(5));
+    for _ in 0..4 {
+        assert!(!s.record());
+        assert!(!s.goal_reached());
+    }
+    assert!(s.record());
+    assert!(s.goal_reached());
+
+    // Further grades keep the goal met without announcing it again.
+    assert!(!s.record());
+    assert!(s.goal_reached());
+    assert_eq!(s.reviewed, 6);
+}
+
+#[test]
+fn no_goal_is_never_reached() {
+    let mut s = SessionSummary::default();
+    assert!(!s.record());
+    assert!(!s.goal_reached());
+}
@@ -0,0 +1,108 @@
+use flashmaster_core::{Grade, GradeScheme};
+
+#[test]
+fn three_key_layout_starts_at_hard() {
+    let k = GradeScheme::Three;
+    assert_eq!(k.grade_for_key('1'), Some(Grade::Hard));
+    assert_eq!(k.grade_for_key('2'), Some(Grade::Medium));
+    assert_eq!(k.grade_for_key('3'), Some(Grade::Easy));
+    assert_eq!(k.grade_for_key('4'), None);
+    assert_eq!(k.key_hint(), "1=Hard, 2=Medium, 3=Easy");
+    assert_eq!(k.parse_input(" Med "), Some(Grade::Medium));
+    assert_eq!(k.parse_input("e"), Some(Grade::Easy));
+}
+
+#[test]
+fn four_key_layout_starts_at_again() {
+    let k = GradeScheme::Four;
+    assert_eq!(k.labels()[0], "Again");
+    assert_eq!(k.grade_for_key('1'), Some(Grade::Again));
+    assert_eq!(k.grade_for_key('2'), Some(Grade::Hard));
+    assert_eq!(k.grade_for_key('3'), Some(Grade::Medium));
+    assert_eq!(k.grade_for_key('4'), Some(Grade::Easy));
+    assert_eq!(k.grade_for_key('0'), None);
+    assert_eq!(k.key_hint(), "1=Again, 2=Hard, 3=Good, 4=Easy");
+    assert_eq!(k.parse_input("good"), Some(Grade::Medium));
+    assert_eq!(k.parse_input("a"), Some(Grade::Again));
+    assert_eq!(k.parse_input("m"), None);
+}
+
+#[test]
+fn every_alias_parses_the_same_everywhere() {
+    use flashmaster_core::{parse_grade_input, GradeAction};
+    let grade = |s, k| parse_grade_input(s, k);
+    let g = |g| Some(GradeAction::Grade(g));
+
+    let three = GradeScheme::Three;
+    for s in ["1", "h", "H", "hard", " Hard "] {
+        assert_eq!(grade(s, three), g(Grade::Hard), "{s}");
+    }
+    for s in ["2", "m", "med", "medium", "MEDIUM"] {
+        assert_eq!(grade(s, three), g(Grade::Medium), "{s}");
+    }
+    for s in ["3", "e", "easy", "Easy"] {
+        assert_eq!(grade(s, three), g(Grade::Easy), "{s}");
+    }
+    for s in ["0", "a", "again", "AGAIN"] {
+        assert_eq!(grade(s, three), g(Grade::Again), "{s}");
+    }
+
+    let four = GradeScheme::Four;
+    for (s, want) in [
+        ("0", Grade::Again),
+        ("1", Grade::Again),
+        ("a", Grade::Again),
+        ("again", Grade::Again),
+        ("2", Grade::Hard),
+        ("h", Grade::Hard),
+        ("hard", Grade::Hard),
+        ("3", Grade::Medium),
+        ("g", Grade::Medium),
+        ("good", Grade::Medium),
+        ("4", Grade::Easy),
+        ("e", Grade::Easy),
+        ("easy", Grade::Easy),
+    ] {
+        assert_eq!(grade(s, four), g(want), "{s}");
+    }
+
+    for k in [three, four] {
+        for (s, want) in [
+            ("s", GradeAction::Skip),
+            ("skip", GradeAction::Skip),
+            ("q", GradeAction::Quit),
+            ("QUIT", GradeAction::Quit),
+        ] {
+            assert_eq!(grade(s, k), Some(want), "{s}");
+        }
+        assert_eq!(grade("x", k), None);
+        assert_eq!(grade("undo", k), None);
+        assert_eq!(grade("", k), None);
+    }
+    assert_eq!(grade("4", three), None);
+    assert_eq!(grade("m", four), None);
+
+    // Full-width input from CJK keyboards.
+    assert_eq!(grade("３", three), g(Grade::Easy));
+    assert_eq!(grade("ｓ", three), Some(GradeAction::Skip));
+}
+
+#[test]
+fn interval_hint_follows_the_key_labels() {
+    let intervals = [
+        (Grade::Again, 1),
+        (Grade::Hard, 1),
+        (Grade::Medium, 6),
+        (Grade::Easy, 10),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(
+        GradeScheme::Three.interval_hint(&intervals),
+        "Hard: 1d, Medium: 6d, Easy: 10d"
+    );
+    assert_eq!(
+        GradeScheme::Four.interval_hint(&intervals),
+        "Again: 1d, Hard: 1d, Good: 6d, Easy: 10d"
+    );
+}
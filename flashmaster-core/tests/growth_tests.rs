@@ -0,0 +1,18 @@
+use chrono::{Duration, Utc};
+use flashmaster_core::{additions_per_day, Card, Deck};
+
+#[test]
+fn additions_bucket_by_creation_day() {
+    let deck = Deck::new("Lang");
+    let now = Utc::now();
+    let a = Card::new(deck.id, "hola", "hello");
+    let mut b = Card::new(deck.id, "adios", "goodbye");
+    b.created_at = a.created_at;
+    let mut c = Card::new(deck.id, "gracias", "thanks");
+    c.created_at = now - Duration::days(3);
+
+    let per_day = additions_per_day(&[a.clone(), b, c.clone()]);
+    assert_eq!(per_day.get(&a.created_at.date_naive()), Some(&2));
+    assert_eq!(per_day.get(&c.created_at.date_naive()), Some(&1));
+    assert_eq!(per_day.len(), 2);
+}
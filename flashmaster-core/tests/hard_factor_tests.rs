@@ -0,0 +1,50 @@
+use chrono::Utc;
+use flashmaster_core::{
+    apply_grade, apply_grade_with, Card, Grade, Sm2Config, HARD_INTERVAL_FACTOR,
+};
+
+fn mature_card() -> Card {
+    let mut card = Card::new(uuid::Uuid::new_v4(), "q", "a");
+    card.reps = 5;
+    card.interval_days = 30;
+    card.ef = 2.5;
+    card
+}
+
+#[test]
+fn softened_hard_shortens_without_reset() {
+    let cfg = Sm2Config {
+        hard_interval_factor: Some(HARD_INTERVAL_FACTOR),
+        ..Default::default()
+    };
+    let out = apply_grade_with(mature_card(), Grade::Hard, &cfg, Utc::now()).updated_card;
+    assert_eq!(out.reps, 5);
+    assert_eq!(out.interval_days, 25);
+    assert!(
+        out.ef < 2.5 && out.ef > 2.3,
+        "small ease penalty, got {}",
+        out.ef
+    );
+}
+
+#[test]
+fn default_hard_still_resets() {
+    let out = apply_grade(mature_card(), Grade::Hard).updated_card;
+    assert_eq!(out.reps, 0);
+    assert_eq!(out.interval_days, 1);
+}
+
+#[test]
+fn softened_hard_on_new_card_matches_reset() {
+    let cfg = Sm2Config {
+        hard_interval_factor: Some(HARD_INTERVAL_FACTOR),
+        ..Default::default()
+    };
+    let card = Card::new(uuid::Uuid::new_v4(), "q", "a");
+    let soft = apply_grade_with(card.clone(), Grade::Hard, &cfg, Utc::now()).updated_card;
+    let plain = apply_grade(card, Grade::Hard).updated_card;
+    assert_eq!(
+        (soft.reps, soft.interval_days),
+        (plain.reps, plain.interval_days)
+    );
+}
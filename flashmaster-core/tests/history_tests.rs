@@ -0,0 +1,32 @@
+use chrono::{Duration, Utc};
+use flashmaster_core::repo::memory::MemoryRepo;
+use flashmaster_core::{Grade, Repository, Review};
+
+#[tokio::test]
+async fn last_five_of_twenty_newest_first() {
+    let repo = MemoryRepo::new();
+    let deck = repo.create_deck("Lang").await.unwrap();
+    let card = repo.add_card(deck.id, "q", "a", None, &[]).await.unwrap();
+    let start = Utc::now() - Duration::days(30);
+    for i in (0..20).rev() {
+        let r = Review::new(
+            card.id,
+            Grade::Easy,
+            start + Duration::days(i),
+            i as i32,
+            2.5,
+        );
+        repo.insert_review(&r).await.unwrap();
+    }
+
+    let last5 = repo.recent_reviews_for_card(card.id, 5).await.unwrap();
+    let days: Vec<i32> = last5.iter().map(|r| r.interval_applied).collect();
+    assert_eq!(days, vec![19, 18, 17, 16, 15]);
+    assert_eq!(
+        repo.list_reviews_for_card_paged(card.id, 18, 5)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+}
@@ -0,0 +1,26 @@
+use flashmaster_core::{humanize_ago, humanize_interval};
+
+#[test]
+fn humanize_common_intervals() {
+    assert_eq!(humanize_interval(0), "today");
+    assert_eq!(humanize_interval(1), "tomorrow");
+    assert_eq!(humanize_interval(13), "in 13 days");
+    assert_eq!(humanize_interval(60), "in 2 months");
+    assert_eq!(humanize_interval(800), "in 2.2 years");
+}
+
+#[test]
+fn humanize_pluralizes_units() {
+    assert_eq!(humanize_interval(3), "in 3 days");
+    assert_eq!(humanize_interval(14), "in 2 weeks");
+    assert_eq!(humanize_interval(365), "in 1 year");
+}
+
+#[test]
+fn humanize_ago_mirrors_intervals() {
+    assert_eq!(humanize_ago(0), "today");
+    assert_eq!(humanize_ago(1), "yesterday");
+    assert_eq!(humanize_ago(3), "3 days ago");
+    assert_eq!(humanize_ago(14), "2 weeks ago");
+    assert_eq!(humanize_ago(800), "2.2 years ago");
+}
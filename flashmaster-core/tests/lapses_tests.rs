@@ -0,0 +1,21 @@
+use flashmaster_core::{apply_grade, Card, Grade};
+
+#[test]
+fn only_failing_grades_count_as_lapses() {
+    let mut card = Card::new(uuid::Uuid::new_v4(), "q", "a");
+    for g in [
+        Grade::Medium,
+        Grade::Hard,
+        Grade::Easy,
+        Grade::Again,
+        Grade::Medium,
+    ] {
+        card = apply_grade(card, g).updated_card;
+    }
+    assert_eq!(card.lapses, 2);
+    assert!(card.is_leech(2));
+    assert!(!card.is_leech(3));
+
+    card.reset();
+    assert_eq!(card.lapses, 0);
+}
@@ -0,0 +1,43 @@
+use flashmaster_core::{maturity_breakdown, maturity_breakdown_with, Card, MaturityCounts};
+use uuid::Uuid;
+
+fn with_interval(days: u32) -> Card {
+    let mut c = Card::new(Uuid::new_v4(), "q", "a");
+    if days > 0 {
+        c.reps = 2;
+        c.interval_days = days;
+    }
+    c
+}
+
+#[test]
+fn cards_split_by_interval_and_suspension() {
+    let mut suspended = with_interval(100);
+    suspended.suspended = true;
+    let cards = vec![
+        with_interval(0),
+        with_interval(5),
+        with_interval(21),
+        with_interval(100),
+        suspended,
+    ];
+
+    assert_eq!(
+        maturity_breakdown(&cards),
+        MaturityCounts {
+            new: 1,
+            young: 1,
+            mature: 2,
+            suspended: 1
+        }
+    );
+    assert_eq!(
+        maturity_breakdown_with(&cards, 30),
+        MaturityCounts {
+            new: 1,
+            young: 2,
+            mature: 1,
+            suspended: 1
+        }
+    );
+}
@@ -0,0 +1,21 @@
+use flashmaster_core::{repo::memory::MemoryRepo, CoreError, Repository};
+
+#[tokio::test]
+async fn move_card_checks_the_target_deck() {
+    let repo = MemoryRepo::new();
+    let spanish = repo.create_deck("Spanish").await.unwrap();
+    let verbs = repo.create_deck("Verbs").await.unwrap();
+    let card = repo
+        .add_card(spanish.id, "ser", "to be", None, &[])
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        repo.move_card(card.id, uuid::Uuid::new_v4()).await,
+        Err(CoreError::NotFound("deck"))
+    ));
+    assert_eq!(repo.get_card(card.id).await.unwrap().deck_id, spanish.id);
+
+    repo.move_card(card.id, verbs.id).await.unwrap();
+    assert_eq!(repo.list_cards(Some(verbs.id)).await.unwrap().len(), 1);
+}
@@ -0,0 +1,27 @@
+use chrono::{Duration, Utc};
+use flashmaster_core::{most_overdue, Card};
+use uuid::Uuid;
+
+fn scheduled(due_days_ago: i64) -> Card {
+    Card::builder(Uuid::new_v4(), "q", "a")
+        .reps(2)
+        .due_at(Utc::now() - Duration::days(due_days_ago))
+        .build()
+}
+
+#[test]
+fn longest_overdue_ranks_first() {
+    let two = scheduled(2);
+    let ten = scheduled(10);
+    let future = scheduled(-3);
+    let new = Card::new(Uuid::new_v4(), "new", "x");
+    let suspended = Card {
+        suspended: true,
+        ..scheduled(30)
+    };
+    let cards = [two.clone(), future, ten.clone(), new, suspended];
+
+    let top = most_overdue(&cards, Utc::now(), 10);
+    assert_eq!(top, vec![(ten.id, 10), (two.id, 2)]);
+    assert_eq!(most_overdue(&cards, Utc::now(), 1), vec![(ten.id, 10)]);
+}
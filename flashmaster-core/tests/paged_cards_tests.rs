@@ -0,0 +1,19 @@
+use flashmaster_core::{repo::memory::MemoryRepo, Repository};
+
+#[tokio::test]
+async fn second_page_is_the_next_slice() {
+    let repo = MemoryRepo::new();
+    let deck = repo.create_deck("Spanish").await.unwrap();
+    for i in 0..50 {
+        repo.add_card(deck.id, &format!("q{i}"), "a", None, &[])
+            .await
+            .unwrap();
+    }
+
+    let all = repo.list_cards(Some(deck.id)).await.unwrap();
+    let page = repo.list_cards_paged(Some(deck.id), 20, 20).await.unwrap();
+    let ids: Vec<_> = page.iter().map(|c| c.id).collect();
+    let want: Vec<_> = all[20..40].iter().map(|c| c.id).collect();
+    assert_eq!(ids, want);
+    assert_eq!(repo.list_cards_paged(None, 45, 20).await.unwrap().len(), 5);
+}
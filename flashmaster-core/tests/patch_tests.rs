@@ -0,0 +1,56 @@
+use flashmaster_core::repo::memory::MemoryRepo;
+use flashmaster_core::{record_grade, CardPatch, Grade, Repository, ReviewMode};
+
+#[tokio::test]
+async fn patch_keeps_scheduling_written_after_the_read() {
+    let repo = MemoryRepo::new();
+    let deck = repo.create_deck("Lang").await.unwrap();
+    let card = repo
+        .add_card(deck.id, "hola", "hello", Some("greeting"), &[])
+        .await
+        .unwrap();
+
+    // An editor read the card, then a review landed before the edit is saved.
+    let stale = repo.get_card(card.id).await.unwrap();
+    let reviewed = record_grade(&repo, card, Grade::Easy, ReviewMode::Scheduled)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let patch = CardPatch {
+        front: Some("¡hola!".into()),
+        ..Default::default()
+    };
+    let out = repo.patch_card(stale.id, patch).await.unwrap();
+    assert_eq!(out.front, "¡hola!");
+    assert_eq!(out.back, "hello");
+    assert_eq!(out.hint.as_deref(), Some("greeting"));
+    assert_eq!(out.reps, reviewed.reps);
+    assert_eq!(out.due_at, reviewed.due_at);
+    assert_eq!(out.ef, reviewed.ef);
+    assert_eq!(repo.get_card(stale.id).await.unwrap().reps, 1);
+}
+
+#[tokio::test]
+async fn patch_can_clear_hint_and_rejects_unknown_cards() {
+    let repo = MemoryRepo::new();
+    let deck = repo.create_deck("Lang").await.unwrap();
+    let card = repo
+        .add_card(deck.id, "q", "a", Some("h"), &[])
+        .await
+        .unwrap();
+
+    let patch = CardPatch {
+        hint: Some(None),
+        suspended: Some(true),
+        ..Default::default()
+    };
+    let out = repo.patch_card(card.id, patch).await.unwrap();
+    assert!(out.hint.is_none());
+    assert!(out.suspended);
+
+    assert!(repo
+        .patch_card(uuid::Uuid::new_v4(), CardPatch::default())
+        .await
+        .is_err());
+}
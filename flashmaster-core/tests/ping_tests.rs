@@ -0,0 +1,7 @@
+use flashmaster_core::repo::memory::MemoryRepo;
+use flashmaster_core::Repository;
+
+#[tokio::test]
+async fn memory_repo_always_answers_ping() {
+    MemoryRepo::new().ping().await.unwrap();
+}
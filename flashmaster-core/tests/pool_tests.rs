@@ -0,0 +1,131 @@
+use chrono::{Duration, Utc};
+use flashmaster_core::{build_review_pool, Card, Deck, Phase, PoolOpts, PoolOrder};
+
+fn reviewed(deck: &Deck, front: &str, due_offset_hours: i64) -> Card {
+    let mut c = Card::new(deck.id, front, "x");
+    c.reps = 2;
+    c.interval_days = 3;
+    c.due_at = Utc::now() + Duration::hours(due_offset_hours);
+    c
+}
+
+fn fronts(pool: &[Card]) -> Vec<&str> {
+    pool.iter().map(|c| c.front.as_str()).collect()
+}
+
+#[test]
+fn pool_orders_by_due_and_respects_inclusion() {
+    let deck = Deck::new("Lang");
+    let due_late = reviewed(&deck, "due-late", -1);
+    let due_early = reviewed(&deck, "due-early", -5);
+    let lapsed = reviewed(&deck, "lapsed", -48);
+    let future = reviewed(&deck, "future", 48);
+    let mut suspended = reviewed(&deck, "suspended", -2);
+    suspended.suspended = true;
+    let mut new = Card::new(deck.id, "new", "x");
+    new.due_at = Utc::now() - Duration::hours(3);
+    let cards = vec![due_late, due_early, lapsed, future, suspended, new];
+    let now = Utc::now();
+
+    let only_due = build_review_pool(&cards, now, &PoolOpts::default());
+    assert_eq!(fronts(&only_due), ["due-early", "due-late"]);
+
+    let all = PoolOpts {
+        include_new: true,
+        include_lapsed: true,
+        ..Default::default()
+    };
+    let pool = build_review_pool(&cards, now, &all);
+    assert_eq!(fronts(&pool), ["lapsed", "due-early", "new", "due-late"]);
+}
+
+#[test]
+fn new_limit_applies_before_max() {
+    let deck = Deck::new("Lang");
+    let now = Utc::now();
+    let mut cards = Vec::new();
+    for i in 0..4 {
+        let mut c = Card::new(deck.id, format!("new{i}"), "x");
+        c.created_at = now - Duration::days(10 - i);
+        c.due_at = now - Duration::hours(1);
+        cards.push(c);
+    }
+    cards.push(reviewed(&deck, "due", -2));
+
+    let opts = PoolOpts {
+        include_new: true,
+        new_limit: Some(2),
+        max: Some(2),
+        order: PoolOrder::CreatedAt,
+        ..Default::default()
+    };
+    let pool = build_review_pool(&cards, now, &opts);
+    // Two oldest new cards survive new_limit; `max` then keeps the two oldest overall.
+    assert_eq!(fronts(&pool), ["new0", "new1"]);
+
+    let opts = PoolOpts {
+        include_new: true,
+        new_limit: Some(1),
+        ..Default::default()
+    };
+    let pool = build_review_pool(&cards, now, &opts);
+    assert_eq!(pool.len(), 2);
+    assert!(fronts(&pool).contains(&"due"));
+    assert!(fronts(&pool).contains(&"new0"));
+}
+
+#[test]
+fn phase_order_groups_the_pool() {
+    let deck = Deck::new("Lang");
+    let mut new = Card::new(deck.id, "new", "x");
+    new.due_at = Utc::now() - Duration::hours(3);
+    let cards = vec![
+        reviewed(&deck, "due", -1),
+        reviewed(&deck, "lapsed", -48),
+        new,
+    ];
+    let now = Utc::now();
+    let opts = |phases| PoolOpts {
+        include_new: true,
+        include_lapsed: true,
+        order_phases: Some(phases),
+        ..Default::default()
+    };
+
+    let new_first = build_review_pool(&cards, now, &opts([Phase::New, Phase::Due, Phase::Lapsed]));
+    assert_eq!(fronts(&new_first), ["new", "due", "lapsed"]);
+
+    let lapsed_first =
+        build_review_pool(&cards, now, &opts([Phase::Lapsed, Phase::Due, Phase::New]));
+    assert_eq!(fronts(&lapsed_first), ["lapsed", "due", "new"]);
+}
+
+#[test]
+fn identical_timestamps_fall_back_to_id_order() {
+    let deck = Deck::new("Lang");
+    let now = Utc::now();
+    let twin = |front: &str| {
+        let mut c = reviewed(&deck, front, -1);
+        c.due_at = now - Duration::hours(1);
+        c.created_at = now - Duration::days(3);
+        c
+    };
+    let (a, b) = (twin("a"), twin("b"));
+    let mut by_id = vec![a.id, b.id];
+    by_id.sort();
+
+    for order in [
+        PoolOrder::DueAt,
+        PoolOrder::CreatedAt,
+        PoolOrder::Retrievability,
+    ] {
+        let opts = PoolOpts {
+            order,
+            ..Default::default()
+        };
+        for cards in [vec![a.clone(), b.clone()], vec![b.clone(), a.clone()]] {
+            let pool = build_review_pool(&cards, now, &opts);
+            assert_eq!(pool.iter().map(|c| c.id).collect::<Vec<_>>(), by_id);
+        }
+    }
+}
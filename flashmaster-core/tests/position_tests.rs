@@ -0,0 +1,37 @@
+use flashmaster_core::repo::memory::MemoryRepo;
+use flashmaster_core::Repository;
+
+async fn names(repo: &MemoryRepo) -> Vec<String> {
+    repo.list_decks()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|d| d.name)
+        .collect()
+}
+
+#[tokio::test]
+async fn positions_order_the_deck_list() {
+    let repo = MemoryRepo::new();
+    let a = repo.create_deck("A").await.unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(2));
+    let b = repo.create_deck("B").await.unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(2));
+    let c = repo.create_deck("C").await.unwrap();
+    assert_eq!(names(&repo).await, ["A", "B", "C"], "new decks list last");
+
+    repo.set_deck_position(c.id, -1).await.unwrap();
+    assert_eq!(names(&repo).await, ["C", "A", "B"]);
+
+    repo.reorder_decks(&[b.id, c.id, a.id]).await.unwrap();
+    assert_eq!(names(&repo).await, ["B", "C", "A"]);
+    assert_eq!(repo.get_deck(a.id).await.unwrap().position, 2);
+
+    repo.create_deck("D").await.unwrap();
+    assert_eq!(names(&repo).await, ["B", "C", "A", "D"]);
+
+    assert!(repo
+        .set_deck_position(uuid::Uuid::new_v4(), 0)
+        .await
+        .is_err());
+}
@@ -0,0 +1,33 @@
+use flashmaster_core::{apply_grade, preview_intervals, Card, Grade};
+
+fn card() -> Card {
+    let mut card = Card::new(uuid::Uuid::new_v4(), "q", "a");
+    card.reps = 2;
+    card.interval_days = 6;
+    card
+}
+
+#[test]
+fn preview_matches_grading_and_leaves_the_card_alone() {
+    let card = card();
+    let before = serde_json::to_value(&card).unwrap();
+    let preview = preview_intervals(&card);
+    assert_eq!(serde_json::to_value(&card).unwrap(), before);
+
+    let grades: Vec<_> = preview.keys().cloned().collect();
+    assert_eq!(
+        grades,
+        [Grade::Again, Grade::Hard, Grade::Medium, Grade::Easy]
+    );
+    for (grade, days) in preview {
+        let graded = apply_grade(card.clone(), grade.clone()).updated_card;
+        assert_eq!(graded.interval_days, days, "{grade:?}");
+    }
+}
+
+#[test]
+fn better_grades_never_preview_shorter() {
+    let preview = preview_intervals(&card());
+    let days: Vec<_> = preview.values().collect();
+    assert!(days.windows(2).all(|w| w[0] <= w[1]), "{days:?}");
+}
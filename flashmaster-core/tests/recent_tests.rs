@@ -0,0 +1,26 @@
+use chrono::{Duration, Utc};
+use flashmaster_core::{deck_last_reviewed, sort_decks_by_recent, Card, Deck};
+
+#[test]
+fn most_recently_reviewed_deck_sorts_first() {
+    let now = Utc::now();
+    let old = Deck::new("Old");
+    let fresh = Deck::new("Fresh");
+    let unseen = Deck::new("Unseen");
+
+    let mut a = Card::new(old.id, "a", "x");
+    a.last_reviewed_at = Some(now - Duration::days(5));
+    let mut b = Card::new(fresh.id, "b", "x");
+    b.last_reviewed_at = Some(now - Duration::days(9));
+    let mut c = Card::new(fresh.id, "c", "x");
+    c.last_reviewed_at = Some(now - Duration::hours(1));
+    let d = Card::new(unseen.id, "d", "x");
+    let cards = vec![a, b, c.clone(), d];
+
+    assert_eq!(deck_last_reviewed(&cards[1..3]), c.last_reviewed_at);
+
+    let mut decks = vec![unseen, old, fresh];
+    sort_decks_by_recent(&mut decks, &cards);
+    let names: Vec<&str> = decks.iter().map(|d| d.name.as_str()).collect();
+    assert_eq!(names, ["Fresh", "Old", "Unseen"]);
+}
@@ -0,0 +1,96 @@
+use chrono::{Duration, TimeZone, Utc};
+use flashmaster_core::{
+    apply_grade_at, apply_grade_fsrs_at, reconstruct_card_state, Card, FsrsParams, Grade, Review,
+};
+
+const GRADES: [Grade; 6] = [
+    Grade::Medium,
+    Grade::Easy,
+    Grade::Again,
+    Grade::Hard,
+    Grade::Medium,
+    Grade::Easy,
+];
+
+/// Grades `card` live through `GRADES`, a few days apart, returning the final
+/// card and the review log it left behind.
+fn live(card: Card) -> (Card, Vec<Review>) {
+    let start = Utc.with_ymd_and_hms(2025, 1, 1, 9, 0, 0).unwrap();
+    let mut card = card;
+    let mut reviews = Vec::new();
+    for (i, grade) in GRADES.iter().enumerate() {
+        let out = apply_grade_at(card, grade.clone(), start + Duration::days(3 * i as i64));
+        card = out.updated_card;
+        reviews.push(out.review);
+    }
+    (card, reviews)
+}
+
+fn scheduling(c: &Card) -> impl PartialEq + std::fmt::Debug {
+    (
+        c.reps,
+        c.lapses,
+        c.interval_days,
+        c.ef,
+        c.due_at,
+        c.last_grade.clone(),
+        c.last_reviewed_at,
+    )
+}
+
+#[test]
+fn replay_matches_live_grading() {
+    let (graded, reviews) = live(Card::new(uuid::Uuid::new_v4(), "q", "a"));
+    let rebuilt = reconstruct_card_state(&graded, &reviews);
+    assert_eq!(scheduling(&rebuilt), scheduling(&graded));
+    assert_eq!((rebuilt.front.as_str(), rebuilt.id), ("q", graded.id));
+}
+
+#[test]
+fn replay_ignores_order_and_current_state() {
+    let (graded, mut reviews) = live(Card::new(uuid::Uuid::new_v4(), "q", "a"));
+    reviews.reverse();
+    let mut drifted = graded.clone();
+    drifted.reps = 40;
+    drifted.ef = 1.3;
+    let rebuilt = reconstruct_card_state(&drifted, &reviews);
+    assert_eq!(scheduling(&rebuilt), scheduling(&graded));
+    assert_eq!(
+        scheduling(&reconstruct_card_state(&drifted, &reviews)),
+        scheduling(&rebuilt)
+    );
+}
+
+#[test]
+fn other_cards_reviews_are_skipped() {
+    let card = Card::new(uuid::Uuid::new_v4(), "q", "a");
+    let (_, mut reviews) = live(card.clone());
+    let (_, strangers) = live(Card::new(uuid::Uuid::new_v4(), "x", "y"));
+    reviews.truncate(2);
+    reviews.extend(strangers);
+    let rebuilt = reconstruct_card_state(&card, &reviews);
+    assert_eq!((rebuilt.reps, rebuilt.interval_days), (2, 6));
+}
+
+#[test]
+fn no_reviews_is_a_fresh_card_due_at_creation() {
+    let (graded, _) = live(Card::new(uuid::Uuid::new_v4(), "q", "a"));
+    let rebuilt = reconstruct_card_state(&graded, &[]);
+    assert!(rebuilt.is_new());
+    assert_eq!(rebuilt.due_at, graded.created_at);
+    assert_eq!(rebuilt.last_grade, None);
+}
+
+#[test]
+fn fsrs_reviews_replay_through_fsrs() {
+    let now = Utc.with_ymd_and_hms(2025, 1, 1, 9, 0, 0).unwrap();
+    let out = apply_grade_fsrs_at(
+        Card::new(uuid::Uuid::new_v4(), "q", "a"),
+        Grade::Medium,
+        &FsrsParams::default(),
+        now,
+    );
+    let rebuilt = reconstruct_card_state(&out.updated_card, &[out.review]);
+    assert_eq!(rebuilt.stability, out.updated_card.stability);
+    assert_eq!(rebuilt.interval_days, out.updated_card.interval_days);
+}
@@ -0,0 +1,70 @@
+use chrono::{Duration, TimeZone, Utc};
+use flashmaster_core::{apply_grade_with, Card, Grade, Sm2Config};
+
+fn cfg() -> Sm2Config {
+    Sm2Config {
+        relearn_steps: vec![Duration::minutes(10), Duration::days(1)],
+        ..Default::default()
+    }
+}
+
+fn mature_card() -> Card {
+    let mut card = Card::new(uuid::Uuid::new_v4(), "q", "a");
+    card.reps = 5;
+    card.interval_days = 30;
+    card.ef = 2.5;
+    card
+}
+
+#[test]
+fn two_step_relearn_then_back_to_review() {
+    let cfg = cfg();
+    let t0 = Utc.with_ymd_and_hms(2025, 1, 1, 9, 0, 0).unwrap();
+
+    let lapsed = apply_grade_with(mature_card(), Grade::Again, &cfg, t0).updated_card;
+    assert_eq!(lapsed.relearning_step, Some(0));
+    assert_eq!((lapsed.reps, lapsed.lapses), (0, 1));
+    assert_eq!(lapsed.due_at, t0 + Duration::minutes(10));
+    let ef = lapsed.ef;
+
+    let t1 = lapsed.due_at;
+    let step_two = apply_grade_with(lapsed, Grade::Medium, &cfg, t1).updated_card;
+    assert_eq!(step_two.relearning_step, Some(1));
+    assert_eq!(step_two.due_at, t1 + Duration::days(1));
+    assert_eq!((step_two.ef, step_two.interval_days), (ef, 1));
+
+    let t2 = step_two.due_at;
+    let graduated = apply_grade_with(step_two, Grade::Medium, &cfg, t2).updated_card;
+    assert_eq!(graduated.relearning_step, None);
+    assert_eq!(graduated.reps, 1);
+    assert_eq!(graduated.interval_days, cfg.first_interval_days);
+    assert_eq!(graduated.lapses, 1);
+}
+
+#[test]
+fn a_miss_while_relearning_restarts_the_steps() {
+    let cfg = cfg();
+    let t0 = Utc.with_ymd_and_hms(2025, 1, 1, 9, 0, 0).unwrap();
+    let card = apply_grade_with(mature_card(), Grade::Again, &cfg, t0).updated_card;
+    let card = apply_grade_with(card, Grade::Medium, &cfg, t0).updated_card;
+    assert_eq!(card.relearning_step, Some(1));
+
+    let out = apply_grade_with(card, Grade::Hard, &cfg, t0);
+    assert_eq!(out.updated_card.relearning_step, Some(0));
+    assert_eq!(out.updated_card.lapses, 1);
+    assert_eq!(out.updated_card.due_at, t0 + Duration::minutes(10));
+    assert_eq!(out.review.interval_applied, 0);
+}
+
+#[test]
+fn no_steps_keeps_the_one_day_relearn() {
+    let card = apply_grade_with(
+        mature_card(),
+        Grade::Again,
+        &Sm2Config::default(),
+        Utc::now(),
+    )
+    .updated_card;
+    assert_eq!(card.relearning_step, None);
+    assert_eq!(card.interval_days, 1);
+}
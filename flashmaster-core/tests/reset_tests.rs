@@ -0,0 +1,47 @@
+use flashmaster_core::repo::memory::MemoryRepo;
+use flashmaster_core::{record_grade, Grade, Repository, ReviewMode, EF_DEFAULT};
+
+#[tokio::test]
+async fn reset_deck_sends_every_card_back_to_new() {
+    let repo = MemoryRepo::new();
+    let deck = repo.create_deck("Lang").await.unwrap();
+    let other = repo.create_deck("Other").await.unwrap();
+    for i in 0..3 {
+        let card = repo
+            .add_card(deck.id, &format!("q{i}"), "a", None, &[])
+            .await
+            .unwrap();
+        record_grade(&repo, card, Grade::Medium, ReviewMode::Scheduled)
+            .await
+            .unwrap();
+    }
+    let kept = repo.add_card(other.id, "x", "y", None, &[]).await.unwrap();
+    record_grade(&repo, kept.clone(), Grade::Medium, ReviewMode::Scheduled)
+        .await
+        .unwrap();
+
+    let n = repo.reset_deck(deck.id, false).await.unwrap();
+    assert_eq!(n, 3);
+    for c in repo.list_cards(Some(deck.id)).await.unwrap() {
+        assert!(c.is_new());
+        assert_eq!((c.reps, c.interval_days, c.ef), (0, 0, EF_DEFAULT));
+        assert!(c.last_reviewed_at.is_none());
+        assert!(repo.list_reviews_for_card(c.id).await.unwrap().is_empty());
+    }
+    assert_eq!(repo.get_card(kept.id).await.unwrap().reps, 1);
+    assert_eq!(repo.list_reviews_for_card(kept.id).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn reset_deck_can_keep_history() {
+    let repo = MemoryRepo::new();
+    let deck = repo.create_deck("Lang").await.unwrap();
+    let card = repo.add_card(deck.id, "q", "a", None, &[]).await.unwrap();
+    record_grade(&repo, card.clone(), Grade::Easy, ReviewMode::Scheduled)
+        .await
+        .unwrap();
+
+    assert_eq!(repo.reset_deck(deck.id, true).await.unwrap(), 1);
+    assert_eq!(repo.get_card(card.id).await.unwrap().reps, 0);
+    assert_eq!(repo.list_reviews_for_card(card.id).await.unwrap().len(), 1);
+}
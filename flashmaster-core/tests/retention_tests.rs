@@ -0,0 +1,33 @@
+use chrono::{Duration, TimeZone, Utc};
+use flashmaster_core::{true_retention, Grade, Review};
+use uuid::Uuid;
+
+fn review(card: Uuid, day: i64, grade: Grade) -> Review {
+    let at = Utc.with_ymd_and_hms(2025, 3, 1, 9, 0, 0).unwrap() + Duration::days(day);
+    Review::new(card, grade, at, 1, 2.5)
+}
+
+#[test]
+fn first_review_of_each_card_is_left_out() {
+    let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    // Out of order: each card's first review is found by time, not position.
+    let reviews = vec![
+        review(a, 3, Grade::Easy),
+        review(a, 0, Grade::Again),
+        review(a, 1, Grade::Medium),
+        review(b, 2, Grade::Hard),
+        review(b, 0, Grade::Easy),
+        review(a, 5, Grade::Again),
+        review(c, 4, Grade::Again), // only review of c
+    ];
+
+    // Counted: a Easy, a Medium, a Again, b Hard -> 2 of 4 passed.
+    assert_eq!(true_retention(&reviews), 0.5);
+}
+
+#[test]
+fn nothing_to_count_is_zero() {
+    assert_eq!(true_retention(&[]), 0.0);
+    let single = [review(Uuid::new_v4(), 0, Grade::Easy)];
+    assert_eq!(true_retention(&single), 0.0);
+}
@@ -0,0 +1,40 @@
+use chrono::{Duration, Utc};
+use flashmaster_core::{build_review_pool, retrievability, Card, PoolOpts, PoolOrder, EF_DEFAULT};
+use uuid::Uuid;
+
+fn reviewed(days_ago: i64, interval: u32) -> Card {
+    let now = Utc::now();
+    let mut c = Card::new(Uuid::new_v4(), format!("{days_ago}d"), "x");
+    c.reps = 3;
+    c.interval_days = interval;
+    c.ef = EF_DEFAULT;
+    c.last_reviewed_at = Some(now - Duration::days(days_ago));
+    c.due_at = now - Duration::days(days_ago) + Duration::days(interval as i64);
+    c
+}
+
+#[test]
+fn fresh_review_is_high_and_long_overdue_is_low() {
+    let now = Utc::now();
+    assert!(retrievability(&reviewed(0, 10), now) > 0.99);
+    // Due exactly now: the curve is calibrated to 90% at t == S.
+    assert!((retrievability(&reviewed(10, 10), now) - 0.9).abs() < 0.01);
+    assert!(retrievability(&reviewed(400, 5), now) < 0.2);
+    assert_eq!(
+        retrievability(&Card::new(Uuid::new_v4(), "q", "a"), now),
+        0.0
+    );
+}
+
+#[test]
+fn pool_can_order_by_lowest_retrievability() {
+    let cards = vec![reviewed(2, 1), reviewed(40, 1), reviewed(10, 1)];
+    let opts = PoolOpts {
+        include_lapsed: true,
+        order: PoolOrder::Retrievability,
+        ..Default::default()
+    };
+    let pool = build_review_pool(&cards, Utc::now(), &opts);
+    let fronts: Vec<&str> = pool.iter().map(|c| c.front.as_str()).collect();
+    assert_eq!(fronts, ["40d", "10d", "2d"]);
+}
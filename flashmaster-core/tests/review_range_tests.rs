@@ -0,0 +1,35 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use flashmaster_core::{repo::memory::MemoryRepo, Grade, Repository, Review};
+
+#[tokio::test]
+async fn range_includes_from_and_excludes_to() {
+    let repo = MemoryRepo::new();
+    let deck = repo.create_deck("Lang").await.unwrap();
+    let card = repo.add_card(deck.id, "q", "a", None, &[]).await.unwrap();
+    let from = Utc.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap();
+    let to = from + Duration::days(1);
+    // Tagged by interval_applied; inserted newest first.
+    let stamps = [
+        (to + Duration::milliseconds(1), 5),
+        (to, 4),
+        (to - Duration::milliseconds(1), 3),
+        (from + Duration::milliseconds(500), 2),
+        (from, 1),
+        (from - Duration::seconds(1), 0),
+    ];
+    for (at, tag) in stamps {
+        let r = Review::new(card.id, Grade::Medium, at, tag, 2.5);
+        repo.insert_review(&r).await.unwrap();
+    }
+
+    let tags = |v: Vec<Review>| v.iter().map(|r| r.interval_applied).collect::<Vec<_>>();
+    let got = repo.list_reviews_in_range(from, to).await.unwrap();
+    assert_eq!(tags(got), vec![1, 2, 3]);
+
+    assert!(repo.list_reviews_in_range(to, to).await.unwrap().is_empty());
+    let all = repo
+        .list_reviews_in_range(DateTime::<Utc>::MIN_UTC, DateTime::<Utc>::MAX_UTC)
+        .await
+        .unwrap();
+    assert_eq!(tags(all), vec![0, 1, 2, 3, 4, 5]);
+}
@@ -0,0 +1,63 @@
+use flashmaster_core::repo::memory::MemoryRepo;
+use flashmaster_core::{
+    add_card_with_rules, CardPatch, CardRules, CoreError, Repository, MAX_FIELD_LEN,
+};
+
+#[tokio::test]
+async fn auto_suspend_tag_starts_card_suspended() {
+    let repo = MemoryRepo::new();
+    let deck = repo.create_deck("Lang").await.unwrap();
+    let rules = CardRules {
+        auto_suspend_tags: vec!["someday".into()],
+        ..Default::default()
+    };
+
+    let tagged = add_card_with_rules(
+        &repo,
+        &rules,
+        deck.id,
+        "q1",
+        "a1",
+        None,
+        &["verbs".into(), "Someday".into()],
+    )
+    .await
+    .unwrap();
+    let plain = add_card_with_rules(&repo, &rules, deck.id, "q2", "a2", None, &["verbs".into()])
+        .await
+        .unwrap();
+
+    assert!(tagged.suspended);
+    assert!(repo.get_card(tagged.id).await.unwrap().suspended);
+    assert!(!plain.suspended);
+    assert!(!repo.get_card(plain.id).await.unwrap().suspended);
+}
+
+#[test]
+fn no_rules_suspend_nothing() {
+    assert!(!CardRules::default().should_suspend(&["someday".into()]));
+}
+
+#[tokio::test]
+async fn over_limit_fields_are_rejected_on_write() {
+    let repo = MemoryRepo::new();
+    let deck = repo.create_deck("Lang").await.unwrap();
+    let rules = CardRules {
+        max_field_len: 8,
+        ..Default::default()
+    };
+
+    let long = "x".repeat(9);
+    let res = add_card_with_rules(&repo, &rules, deck.id, &long, "a", None, &[]).await;
+    assert!(matches!(res, Err(CoreError::Invalid(_))));
+    assert!(repo.list_cards(None).await.unwrap().is_empty());
+
+    let ok = add_card_with_rules(&repo, &rules, deck.id, "12345678", "a", None, &[]).await;
+    assert!(ok.is_ok());
+    let patch = CardPatch {
+        tags: Some(vec![long]),
+        ..Default::default()
+    };
+    assert!(rules.check_patch(&patch).is_err());
+    assert_eq!(CardRules::default().max_field_len, MAX_FIELD_LEN);
+}
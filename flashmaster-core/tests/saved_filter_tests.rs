@@ -0,0 +1,79 @@
+use chrono::{Duration, TimeZone, Utc};
+use flashmaster_core::{
+    build_review_pool, repo::memory::MemoryRepo, PoolOpts, Repository, SavedFilter, SavedFilters,
+};
+
+#[tokio::test]
+async fn tag_and_due_filter_spans_decks() {
+    let repo = MemoryRepo::new();
+    let now = Utc.with_ymd_and_hms(2031, 5, 1, 9, 0, 0).unwrap();
+    let bio = repo.create_deck("Biology").await.unwrap();
+    let chem = repo.create_deck("Chemistry").await.unwrap();
+
+    let add = |deck, front: &'static str, tags: &[&str], due_in_days: i64| {
+        let repo = &repo;
+        let tags: Vec<String> = tags.iter().map(|t| t.to_string()).collect();
+        async move {
+            let mut c = repo.add_card(deck, front, "x", None, &tags).await.unwrap();
+            c.reps = 1;
+            c.due_at = now + Duration::days(due_in_days);
+            repo.update_card(&c).await.unwrap();
+            c
+        }
+    };
+    let cell = add(bio.id, "cell", &["exam"], 3).await;
+    let acid = add(chem.id, "acid", &["Exam"], 0).await;
+    add(bio.id, "fern", &["exam"], 30).await; // outside the window
+    add(chem.id, "base", &[], 1).await; // not tagged
+
+    let filter = SavedFilter {
+        name: "exam".into(),
+        tag: Some("exam".into()),
+        due_within_days: Some(7),
+        ..Default::default()
+    };
+    let cards = repo.list_cards(None).await.unwrap();
+    let mut picked: Vec<_> = filter.select(&cards, now).iter().map(|c| c.id).collect();
+    picked.sort();
+    let mut want = vec![cell.id, acid.id];
+    want.sort();
+    assert_eq!(picked, want);
+
+    // Reviewing the filter studies the card due in 3 days ahead of time.
+    let opts = PoolOpts {
+        due_ahead: filter.due_window(),
+        ..Default::default()
+    };
+    let pool = build_review_pool(&filter.select(&cards, now), now, &opts);
+    assert_eq!(
+        pool.iter().map(|c| c.id).collect::<Vec<_>>(),
+        [acid.id, cell.id]
+    );
+}
+
+#[test]
+fn saved_filters_round_trip_and_validate_names() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("filters.json");
+    assert_eq!(SavedFilters::load(&path).unwrap(), SavedFilters::default());
+
+    let mut saved = SavedFilters::default();
+    let exam = SavedFilter {
+        name: "exam".into(),
+        tag: Some("exam".into()),
+        ..Default::default()
+    };
+    saved.upsert(exam.clone()).unwrap();
+    assert!(saved
+        .upsert(SavedFilter {
+            name: "../x".into(),
+            ..Default::default()
+        })
+        .is_err());
+    saved.save(&path).unwrap();
+
+    let mut loaded = SavedFilters::load(&path).unwrap();
+    assert_eq!(loaded.get("exam"), Some(&exam));
+    loaded.remove("exam").unwrap();
+    assert!(loaded.remove("exam").is_err());
+}
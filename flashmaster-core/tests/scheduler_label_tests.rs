@@ -0,0 +1,9 @@
+use flashmaster_core::Review;
+
+#[test]
+fn reviews_saved_without_a_label_are_sm2() {
+    let legacy = r#"{"id":"6f1c2a3e-6c1e-4f0e-9d7e-0a8b5c3d2e1f","card_id":"1b2c3d4e-5f60-4718-8293-a4b5c6d7e8f9",
+        "grade":"easy","reviewed_at":"2025-01-01T00:00:00Z","interval_applied":1,"ef_after":2.6}"#;
+    let r: Review = serde_json::from_str(legacy).unwrap();
+    assert_eq!(r.scheduler, "sm2");
+}
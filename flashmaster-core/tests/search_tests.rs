@@ -0,0 +1,72 @@
+use flashmaster_core::repo::memory::MemoryRepo;
+use flashmaster_core::Repository;
+
+#[tokio::test]
+async fn search_without_deck_spans_all_decks() {
+    let repo = MemoryRepo::new();
+    let spanish = repo.create_deck("Spanish").await.unwrap();
+    let french = repo.create_deck("French").await.unwrap();
+    let es = repo
+        .add_card(spanish.id, "casa", "house", None, &[])
+        .await
+        .unwrap();
+    let fr = repo
+        .add_card(french.id, "maison", "House", None, &[])
+        .await
+        .unwrap();
+    repo.add_card(french.id, "chat", "cat", None, &[])
+        .await
+        .unwrap();
+
+    let mut hits = repo.search_cards("house", None).await.unwrap();
+    hits.sort_by_key(|c| c.front.clone());
+    let got: Vec<_> = hits.iter().map(|c| (c.id, c.deck_id)).collect();
+    assert_eq!(got, vec![(es.id, spanish.id), (fr.id, french.id)]);
+
+    let only_fr = repo.search_cards("house", Some(french.id)).await.unwrap();
+    assert_eq!(only_fr.len(), 1);
+    assert_eq!(only_fr[0].deck_id, french.id);
+}
+
+#[tokio::test]
+async fn field_prefix_limits_the_match() {
+    let repo = MemoryRepo::new();
+    let deck = repo.create_deck("Biology").await.unwrap();
+    let mut sourced = repo
+        .add_card(deck.id, "mitosis", "cell division", None, &["cells".into()])
+        .await
+        .unwrap();
+    sourced.source = Some("Chapter 3, p.42".into());
+    repo.update_card(&sourced).await.unwrap();
+    let other = repo
+        .add_card(deck.id, "chapter", "cells", None, &[])
+        .await
+        .unwrap();
+
+    let ids = |q: &'static str| {
+        let repo = &repo;
+        async move {
+            let mut v: Vec<_> = repo
+                .search_cards(q, None)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|c| c.id)
+                .collect();
+            v.sort();
+            v
+        }
+    };
+    let mut both = vec![sourced.id, other.id];
+    both.sort();
+
+    assert_eq!(ids("chapter").await, both);
+    assert_eq!(ids("source:chapter 3").await, [sourced.id]);
+    assert_eq!(ids("SOURCE: p.42").await, [sourced.id]);
+    assert_eq!(ids("source:").await, [sourced.id]);
+    assert_eq!(ids("front:chapter").await, [other.id]);
+    assert_eq!(ids("tag:cells").await, [sourced.id]);
+    assert_eq!(ids("back:cells").await, [other.id]);
+    // Not a known field, so the colon is plain text.
+    assert!(ids("page:42").await.is_empty());
+}
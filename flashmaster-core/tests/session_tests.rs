@@ -0,0 +1,23 @@
+use flashmaster_core::{Grade, ReviewSession};
+use uuid::Uuid;
+
+#[test]
+fn save_and_load_restores_position() {
+    let dir = tempfile::tempdir().unwrap();
+    let deck_id = Uuid::new_v4();
+    let ids: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+
+    let mut s = ReviewSession::new(Some(deck_id), ids.clone());
+    s.advance(Some(&Grade::Easy));
+    let path = ReviewSession::file_for(dir.path(), Some(deck_id));
+    s.save(&path).unwrap();
+
+    let loaded = ReviewSession::load(&path).unwrap().expect("session saved");
+    assert_eq!(loaded.idx, 1);
+    assert_eq!(loaded.remaining(), &ids[1..]);
+    assert_eq!(loaded.current(), Some(ids[1]));
+    assert_eq!(loaded.tallies.easy, 1);
+
+    ReviewSession::clear(&path).unwrap();
+    assert!(ReviewSession::load(&path).unwrap().is_none());
+}
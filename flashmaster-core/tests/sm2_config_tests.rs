@@ -0,0 +1,84 @@
+use chrono::Utc;
+use flashmaster_core::{apply_grade_at, apply_grade_with, Card, Grade, Sm2Config, EF_MAX, EF_MIN};
+
+fn grade_twice(cfg: &Sm2Config, grade: Grade) -> Card {
+    let now = Utc::now();
+    let card = Card::new(uuid::Uuid::new_v4(), "q", "a");
+    let once = apply_grade_with(card, grade.clone(), cfg, now).updated_card;
+    apply_grade_with(once, grade, cfg, now).updated_card
+}
+
+#[test]
+fn custom_second_interval_is_used() {
+    let cfg = Sm2Config {
+        second_interval_days: 4,
+        ..Default::default()
+    };
+    let card = grade_twice(&cfg, Grade::Medium);
+    assert_eq!((card.reps, card.interval_days), (2, 4));
+}
+
+#[test]
+fn default_config_matches_apply_grade() {
+    let now = Utc::now();
+    let card = Card::new(uuid::Uuid::new_v4(), "q", "a");
+    for grade in [Grade::Hard, Grade::Medium, Grade::Easy] {
+        let plain = apply_grade_at(card.clone(), grade.clone(), now).updated_card;
+        let with = apply_grade_with(card.clone(), grade, &Sm2Config::default(), now).updated_card;
+        assert_eq!(
+            (plain.interval_days, plain.ef),
+            (with.interval_days, with.ef)
+        );
+    }
+    assert_eq!(
+        grade_twice(&Sm2Config::default(), Grade::Medium).interval_days,
+        6
+    );
+}
+
+#[test]
+fn ease_coefficients_are_still_clamped() {
+    let steep = Sm2Config {
+        first_interval_days: 2,
+        ease_delta_coeffs: [1.0, 1.0, 1.0],
+        ..Default::default()
+    };
+    let easy = grade_twice(&steep, Grade::Easy);
+    assert_eq!(easy.ef, EF_MAX);
+    let hard = grade_twice(&steep, Grade::Hard);
+    assert_eq!(hard.ef, EF_MIN);
+
+    let first = apply_grade_with(
+        Card::new(uuid::Uuid::new_v4(), "q", "a"),
+        Grade::Easy,
+        &steep,
+        Utc::now(),
+    );
+    assert_eq!(first.updated_card.interval_days, 2);
+}
+
+#[test]
+fn max_interval_caps_growth_but_not_ease() {
+    let cfg = Sm2Config {
+        max_interval_days: 30,
+        ..Default::default()
+    };
+    let now = Utc::now();
+    let mut card = Card::new(uuid::Uuid::new_v4(), "q", "a");
+    card.ef = EF_MIN;
+    let mut efs = Vec::new();
+    for _ in 0..8 {
+        card = apply_grade_with(card, Grade::Easy, &cfg, now).updated_card;
+        assert!(card.interval_days <= 30);
+        efs.push(card.ef);
+    }
+    assert_eq!(card.interval_days, 30);
+    assert_eq!(card.due_at, now + chrono::Duration::days(30));
+    // Ease kept climbing after the interval saturated.
+    assert!(efs.windows(2).all(|w| w[1] > w[0]), "{efs:?}");
+    assert!(card.ef < EF_MAX);
+    for _ in 0..10 {
+        card = apply_grade_with(card, Grade::Easy, &cfg, now).updated_card;
+    }
+    assert_eq!((card.interval_days, card.ef), (30, EF_MAX));
+}
@@ -0,0 +1,42 @@
+use chrono::{NaiveDate, TimeZone, Utc};
+use chrono_tz::America::New_York;
+use flashmaster_core::{daily_streak, daily_streak_tz, summarize, summarize_tz, Grade, Review};
+use uuid::Uuid;
+
+fn review_at(y: i32, m: u32, d: u32, h: u32, min: u32) -> Review {
+    let at = Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap();
+    Review::new(Uuid::new_v4(), Grade::Medium, at, 1, 2.5)
+}
+
+#[test]
+fn late_utc_review_lands_on_previous_local_day() {
+    // 23:30 UTC on March 11 is 19:30 the same day in New York (EDT), but
+    // 03:30 UTC on March 12 is 23:30 on March 11 there.
+    let reviews = vec![
+        review_at(2025, 3, 11, 23, 30),
+        review_at(2025, 3, 12, 3, 30),
+    ];
+    let mar11 = NaiveDate::from_ymd_opt(2025, 3, 11).unwrap();
+    let mar12 = NaiveDate::from_ymd_opt(2025, 3, 12).unwrap();
+
+    let utc = summarize(&reviews).per_day;
+    assert_eq!(utc[&mar11].total, 1);
+    assert_eq!(utc[&mar12].total, 1);
+
+    let local = summarize_tz(&reviews, New_York).per_day;
+    assert_eq!(local.len(), 1);
+    assert_eq!(local[&mar11].total, 2);
+}
+
+#[test]
+fn streak_follows_local_days() {
+    // Evenings in New York on March 10 and 11, which are past midnight UTC.
+    let reviews = vec![review_at(2025, 3, 11, 1, 0), review_at(2025, 3, 12, 1, 0)];
+    let mar11 = NaiveDate::from_ymd_opt(2025, 3, 11).unwrap();
+    let mar12 = NaiveDate::from_ymd_opt(2025, 3, 12).unwrap();
+
+    assert_eq!(daily_streak_tz(&reviews, mar11, New_York), 2);
+    assert_eq!(daily_streak_tz(&reviews, mar12, New_York), 0);
+    assert_eq!(daily_streak(&reviews, mar12), 2);
+    assert_eq!(daily_streak(&reviews, mar11), 1);
+}
@@ -0,0 +1,26 @@
+use flashmaster_core::{is_struggling, Grade, StruggleTracker};
+use uuid::Uuid;
+
+#[test]
+fn fourth_again_flags_card() {
+    let id = Uuid::new_v4();
+    let mut t = StruggleTracker::default();
+    for _ in 0..3 {
+        assert!(!t.record(id, &Grade::Hard));
+    }
+    assert!(!t.is_flagged(id));
+    assert!(t.record(id, &Grade::Hard));
+    assert!(t.is_flagged(id));
+    assert_eq!(t.flagged(), &[id]);
+    // Already flagged: no second notification.
+    assert!(!t.record(id, &Grade::Hard));
+}
+
+#[test]
+fn passing_grades_do_not_count() {
+    let id = Uuid::new_v4();
+    let mut t = StruggleTracker::new(0);
+    assert!(!t.record(id, &Grade::Easy));
+    assert!(is_struggling(1, 0));
+    assert!(!is_struggling(3, 3));
+}
@@ -0,0 +1,38 @@
+use chrono::Utc;
+use flashmaster_core::{per_tag_totals, Card, Grade, Review};
+use uuid::Uuid;
+
+fn tagged(tags: &[&str]) -> Card {
+    let mut c = Card::new(Uuid::new_v4(), "q", "a");
+    c.tags = tags.iter().map(|t| t.to_string()).collect();
+    c
+}
+
+fn review(card: &Card, grade: Grade) -> Review {
+    Review::new(card.id, grade, Utc::now(), 1, 2.5)
+}
+
+#[test]
+fn reviews_count_towards_every_tag_case_insensitively() {
+    let verbs = tagged(&["Verbs", "spanish"]);
+    let nouns = tagged(&["SPANISH", "nouns", "Nouns"]);
+    let untagged = tagged(&[]);
+    let reviews = vec![
+        review(&verbs, Grade::Easy),
+        review(&verbs, Grade::Again),
+        review(&nouns, Grade::Medium),
+        review(&untagged, Grade::Easy),
+    ];
+
+    let map = per_tag_totals(&[verbs, nouns, untagged], &reviews);
+    let mut keys: Vec<&str> = map.keys().map(String::as_str).collect();
+    keys.sort();
+    assert_eq!(keys, vec!["nouns", "spanish", "verbs"]);
+
+    assert_eq!(map["spanish"].total, 3);
+    assert_eq!(map["verbs"].total, 2);
+    assert_eq!(map["verbs"].accuracy(), 0.5);
+    // "nouns" and "Nouns" on one card count the review once.
+    assert_eq!(map["nouns"].total, 1);
+    assert_eq!(map["nouns"].accuracy(), 1.0);
+}
@@ -0,0 +1,22 @@
+use flashmaster_core::edit_tags;
+
+fn v(tags: &[&str]) -> Vec<String> {
+    tags.iter().map(|t| t.to_string()).collect()
+}
+
+#[test]
+fn adds_are_deduped_ignoring_case() {
+    let out = edit_tags(&v(&["Verbs"]), &v(&["verbs", "basics", "Basics"]), &[]);
+    assert_eq!(out, Some(v(&["Verbs", "basics"])));
+}
+
+#[test]
+fn removals_ignore_case_and_win_over_adds() {
+    let out = edit_tags(&v(&["verbs", "Old"]), &v(&["new"]), &v(&["old", "NEW"]));
+    assert_eq!(out, Some(v(&["verbs"])));
+}
+
+#[test]
+fn unchanged_tags_report_none() {
+    assert_eq!(edit_tags(&v(&["a"]), &v(&["A"]), &v(&["b"])), None);
+}
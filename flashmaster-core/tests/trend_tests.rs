@@ -0,0 +1,53 @@
+use chrono::{Duration, Utc};
+use flashmaster_core::{accuracy_trend, accuracy_trend_at, Grade, Review, Trend};
+use uuid::Uuid;
+
+/// `good` passing and `bad` failing reviews, `days_ago` days before `now`.
+fn batch(now: chrono::DateTime<Utc>, days_ago: i64, good: usize, bad: usize) -> Vec<Review> {
+    let at = now - Duration::days(days_ago) - Duration::hours(1);
+    let grades =
+        std::iter::repeat_n(Grade::Easy, good).chain(std::iter::repeat_n(Grade::Hard, bad));
+    grades
+        .map(|g| Review::new(Uuid::new_v4(), g, at, 1, 2.5))
+        .collect()
+}
+
+#[test]
+fn improving_when_recent_week_is_better() {
+    let now = Utc::now();
+    let mut reviews = batch(now, 10, 5, 5);
+    reviews.extend(batch(now, 2, 9, 1));
+    match accuracy_trend_at(&reviews, 7, now) {
+        Trend::Improving(d) => assert!((d - 0.4).abs() < 1e-4, "{d}"),
+        t => panic!("expected improving, got {t:?}"),
+    }
+}
+
+#[test]
+fn declining_when_recent_week_is_worse() {
+    let now = Utc::now();
+    let mut reviews = batch(now, 8, 10, 0);
+    reviews.extend(batch(now, 0, 6, 4));
+    match accuracy_trend_at(&reviews, 7, now) {
+        Trend::Declining(d) => assert!((d + 0.4).abs() < 1e-4, "{d}"),
+        t => panic!("expected declining, got {t:?}"),
+    }
+}
+
+#[test]
+fn flat_and_unknown() {
+    let now = Utc::now();
+    let mut reviews = batch(now, 9, 8, 2);
+    reviews.extend(batch(now, 1, 8, 2));
+    assert!(matches!(
+        accuracy_trend_at(&reviews, 7, now),
+        Trend::Flat(_)
+    ));
+
+    // Nothing in the previous window, or too few reviews overall.
+    assert_eq!(accuracy_trend(&batch(now, 1, 20, 0), 7), Trend::Unknown);
+    let mut sparse = batch(now, 9, 2, 0);
+    sparse.extend(batch(now, 1, 2, 0));
+    assert_eq!(accuracy_trend_at(&sparse, 7, now), Trend::Unknown);
+    assert_eq!(accuracy_trend_at(&reviews, 0, now), Trend::Unknown);
+}
@@ -0,0 +1,16 @@
+use flashmaster_core::{repo::memory::MemoryRepo, CoreError, Repository};
+
+#[tokio::test]
+async fn second_verbs_deck_needs_uniqueness_off() {
+    let strict = MemoryRepo::new();
+    strict.create_deck("Verbs").await.unwrap();
+    assert!(matches!(
+        strict.create_deck("Verbs").await,
+        Err(CoreError::Conflict(_))
+    ));
+
+    let loose = MemoryRepo::new().with_unique_deck_names(false);
+    loose.create_deck("Verbs").await.unwrap();
+    loose.create_deck("Verbs").await.unwrap();
+    assert_eq!(loose.list_decks().await.unwrap().len(), 2);
+}
@@ -1,17 +1,26 @@
 use chrono::{DateTime, Utc};
-use flashmaster_core::{repo::Repository, Card, CardId, CoreError, Deck, DeckId, Review};
+use flashmaster_core::{
+    backup_file_name, deck_subtree, repo::Repository, rotate_backups, Card, CardId, CardPatch,
+    CoreError, Deck, DeckId, PromptWith, Review, BACKUP_PREFIX,
+};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
+use std::future::Future;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use tempfile::NamedTempFile;
 use tokio::task;
 
+pub mod migrate;
 pub mod paths;
 
-const FILE_VERSION: u32 = 1;
+pub use migrate::{migrate_file, migrate_image, MigrationReport};
+
+/// Bump together with a step in `migrate::migrate_image`.
+const FILE_VERSION: u32 = 7;
 
 #[derive(Clone, Serialize, Deserialize)]
 struct FileImage {
@@ -86,6 +95,12 @@ pub struct JsonStore {
     path: PathBuf,
     backups_dir: PathBuf,
     max_backups: usize,
+    pretty: bool,
+    unique_deck_names: bool,
+    /// Set inside `with_backups_disabled`: saves only touch memory.
+    deferred: AtomicBool,
+    /// A deferred save hasn't reached the file yet.
+    dirty: AtomicBool,
     state: RwLock<State>,
 }
 
@@ -95,7 +110,11 @@ impl JsonStore {
         Self::open_with(file, backups, 10).await
     }
 
-    pub async fn open_with(path: PathBuf, backups_dir: PathBuf, max_backups: usize) -> Result<Self, CoreError> {
+    pub async fn open_with(
+        path: PathBuf,
+        backups_dir: PathBuf,
+        max_backups: usize,
+    ) -> Result<Self, CoreError> {
         ensure_parent_dirs(&path)?;
         ensure_dir(&backups_dir)?;
         let state = load_or_init(&path).await?;
@@ -103,11 +122,77 @@ impl JsonStore {
             path,
             backups_dir,
             max_backups: max_backups.max(1),
+            pretty: true,
+            unique_deck_names: true,
+            deferred: AtomicBool::new(false),
+            dirty: AtomicBool::new(false),
             state: RwLock::new(state),
         })
     }
 
+    /// Whether saves are indented (the default, easy to read and diff) or
+    /// compact, which is about half the size for large collections. Loading
+    /// accepts either, so this can be switched at any time.
+    pub fn with_pretty(mut self, pretty: bool) -> Self {
+        self.pretty = pretty;
+        self
+    }
+
+    /// Whether `create_deck` rejects a name already used by another deck,
+    /// ignoring case (the default).
+    pub fn with_unique_deck_names(mut self, unique: bool) -> Self {
+        self.unique_deck_names = unique;
+        self
+    }
+
+    /// Runs `f` as one batch, for one-off jobs like a big import: writes
+    /// made inside it stay in memory, and the data file is written once at
+    /// the end with no backup and no rotation. The file is written even if
+    /// `f` fails, so it always matches what the store holds in memory.
+    pub async fn with_backups_disabled<'a, F, Fut, T, E>(&'a self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&'a Self) -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: From<CoreError>,
+    {
+        self.deferred.store(true, Ordering::SeqCst);
+        let out = f(self).await;
+        self.deferred.store(false, Ordering::SeqCst);
+        self.write(false).await?;
+        out
+    }
+
+    async fn insert_deck(&self, mut deck: Deck) -> Result<Deck, CoreError> {
+        {
+            let mut s = self.state.write();
+            if self.unique_deck_names
+                && s.decks
+                    .values()
+                    .any(|d| d.name.eq_ignore_ascii_case(&deck.name))
+            {
+                return Err(CoreError::Conflict("deck name already exists"));
+            }
+            if deck.parent_id.is_some_and(|p| !s.decks.contains_key(&p)) {
+                return Err(CoreError::NotFound("deck"));
+            }
+            // New decks go after any reordered ones.
+            deck.position = s.decks.values().map(|d| d.position + 1).max().unwrap_or(0);
+            s.decks.insert(deck.id, deck.clone());
+        }
+        self.save().await?;
+        Ok(deck)
+    }
+
     async fn save(&self) -> Result<(), CoreError> {
+        if self.deferred.load(Ordering::SeqCst) {
+            self.dirty.store(true, Ordering::SeqCst);
+            return Ok(());
+        }
+        self.write(true).await
+    }
+
+    async fn write(&self, backup: bool) -> Result<(), CoreError> {
+        self.dirty.store(false, Ordering::SeqCst);
         let snapshot = {
             let mut s = self.state.write();
             s.updated_at = Utc::now();
@@ -116,12 +201,19 @@ impl JsonStore {
         let path = self.path.clone();
         let backups = self.backups_dir.clone();
         let keep = self.max_backups;
+        let pretty = self.pretty;
 
         // Join error -> CoreError, inner io::Error -> CoreError
-        task::spawn_blocking(move || write_with_backup(&path, &backups, keep, &snapshot))
-            .await
-            .map_err(|_| CoreError::Storage("io"))?
-            .map_err(|_| CoreError::Storage("io"))?;
+        task::spawn_blocking(move || {
+            if backup {
+                write_with_backup(&path, &backups, keep, pretty, &snapshot)
+            } else {
+                write_data(&path, pretty, &snapshot).map(drop)
+            }
+        })
+        .await
+        .map_err(|_| CoreError::Storage("io"))?
+        .map_err(|_| CoreError::Storage("io"))?;
         Ok(())
     }
 }
@@ -140,84 +232,102 @@ fn ensure_dir(path: &Path) -> Result<(), CoreError> {
 async fn load_or_init(path: &Path) -> Result<State, CoreError> {
     if path.exists() {
         let p = path.to_path_buf();
-        let img: FileImage = task::spawn_blocking(move || {
+        let raw: serde_json::Value = task::spawn_blocking(move || {
             let mut f = fs::File::open(&p)?;
             let mut buf = String::new();
             f.read_to_string(&mut buf)?;
-            let v = serde_json::from_str::<FileImage>(&buf)?;
-            Ok::<FileImage, std::io::Error>(v)
+            let v = serde_json::from_str(&buf)?;
+            Ok::<serde_json::Value, std::io::Error>(v)
         })
         .await
         .map_err(|_| CoreError::Storage("io"))
         .and_then(|r| r.map_err(|_| CoreError::Storage("io")))?;
+        // Older files are upgraded in memory exactly as `migrate_file` would.
+        let img: FileImage =
+            serde_json::from_value(migrate_image(raw)?.0).map_err(|_| CoreError::Storage("io"))?;
         let mut st = State::from_image(img);
         st.updated_at = Utc::now();
         Ok(st)
     } else {
         let st = State::new_empty();
         let img = st.to_image();
-        write_with_backup(path, &path.with_extension("backups"), 1, &img).map_err(|_| CoreError::Storage("io"))?;
+        write_with_backup(path, &path.with_extension("backups"), 1, true, &img)
+            .map_err(|_| CoreError::Storage("io"))?;
         Ok(st)
     }
 }
 
-fn write_with_backup(path: &Path, backups_dir: &Path, max_backups: usize, img: &FileImage) -> Result<(), std::io::Error> {
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)?;
-    }
+fn write_with_backup(
+    path: &Path,
+    backups_dir: &Path,
+    max_backups: usize,
+    pretty: bool,
+    img: &FileImage,
+) -> Result<(), std::io::Error> {
     fs::create_dir_all(backups_dir)?;
-
-    let json = serde_json::to_vec_pretty(img).expect("serialize");
-    let mut tmp = NamedTempFile::new_in(path.parent().unwrap_or_else(|| Path::new(".")))?;
-    tmp.write_all(&json)?;
-    tmp.flush()?;
-    let _ = fs::remove_file(path);
-    tmp.persist(path)?;
+    let json = write_data(path, pretty, img)?;
 
     // Backup rotation
-    let ts = chrono::Local::now().format("%Y%m%d-%H%M%S");
-    let backup_name = format!("flashmaster-{ts}.json");
-    let backup_path = backups_dir.join(backup_name);
+    let backup_path = backups_dir.join(backup_file_name(
+        BACKUP_PREFIX,
+        chrono::Local::now(),
+        "json",
+    ));
     let mut btmp = NamedTempFile::new_in(backups_dir)?;
     btmp.write_all(&json)?;
     btmp.flush()?;
-    let _ = fs::remove_file(&backup_path);
-    btmp.persist(&backup_path)?;
+    btmp.persist_noclobber(&backup_path)?;
 
-    rotate_backups(backups_dir, max_backups)?;
+    rotate_backups(backups_dir, BACKUP_PREFIX, max_backups)?;
 
     Ok(())
 }
 
-fn rotate_backups(dir: &Path, keep: usize) -> Result<(), std::io::Error> {
-    let mut entries: Vec<_> = fs::read_dir(dir)?
-        .filter_map(|e| e.ok())
-        .filter(|e| e.path().extension().and_then(|s| s.to_str()) == Some("json"))
-        .collect();
-    entries.sort_by_key(|e| e.metadata().and_then(|m| m.modified()).ok());
-    if entries.len() > keep {
-        for e in &entries[0..entries.len() - keep] {
-            let _ = fs::remove_file(e.path());
-        }
+/// Atomically replaces the data file; returns the bytes written.
+fn write_data(path: &Path, pretty: bool, img: &FileImage) -> Result<Vec<u8>, std::io::Error> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
     }
-    Ok(())
+    let json = if pretty {
+        serde_json::to_vec_pretty(img)
+    } else {
+        serde_json::to_vec(img)
+    }
+    .expect("serialize");
+    let mut tmp = NamedTempFile::new_in(path.parent().unwrap_or_else(|| Path::new(".")))?;
+    tmp.write_all(&json)?;
+    tmp.flush()?;
+    // persist renames over the old file, so readers never see it missing.
+    tmp.persist(path)?;
+    Ok(json)
 }
 
 use async_trait::async_trait;
 
 #[async_trait]
 impl Repository for JsonStore {
-    async fn create_deck(&self, name: &str) -> Result<Deck, CoreError> {
-        let deck = Deck::new(name);
-        {
-            let mut s = self.state.write();
-            if s.decks.values().any(|d| d.name.eq_ignore_ascii_case(name)) {
-                return Err(CoreError::Conflict("deck name already exists"));
-            }
-            s.decks.insert(deck.id, deck.clone());
+    async fn ping(&self) -> Result<(), CoreError> {
+        Ok(())
+    }
+
+    /// Writes out saves held back by [`JsonStore::with_backups_disabled`]
+    /// (without a backup); every other save is already on disk. The store
+    /// stays usable.
+    async fn close(&self) -> Result<(), CoreError> {
+        if self.dirty.load(Ordering::SeqCst) {
+            self.write(false).await?;
         }
-        self.save().await?;
-        Ok(deck)
+        Ok(())
+    }
+
+    async fn create_deck(&self, name: &str) -> Result<Deck, CoreError> {
+        self.insert_deck(Deck::new(name)).await
+    }
+
+    async fn create_subdeck(&self, name: &str, parent: DeckId) -> Result<Deck, CoreError> {
+        let mut deck = Deck::new(name);
+        deck.parent_id = Some(parent);
+        self.insert_deck(deck).await
     }
 
     async fn get_deck(&self, id: DeckId) -> Result<Deck, CoreError> {
@@ -225,18 +335,78 @@ impl Repository for JsonStore {
         s.decks.get(&id).cloned().ok_or(CoreError::NotFound("deck"))
     }
 
+    async fn deck_exists(&self, id: DeckId) -> Result<bool, CoreError> {
+        Ok(self.state.read().decks.contains_key(&id))
+    }
+
     async fn list_decks(&self) -> Result<Vec<Deck>, CoreError> {
         let s = self.state.read();
-        Ok(s.decks.values().cloned().collect())
+        let mut v: Vec<Deck> = s.decks.values().cloned().collect();
+        v.sort_by_key(|d| (d.position, d.created_at));
+        Ok(v)
+    }
+
+    async fn count_decks(&self) -> Result<u64, CoreError> {
+        Ok(self.state.read().decks.len() as u64)
+    }
+
+    async fn list_subdecks(&self, parent: DeckId) -> Result<Vec<Deck>, CoreError> {
+        let mut v = self.list_decks().await?;
+        v.retain(|d| d.parent_id == Some(parent));
+        Ok(v)
+    }
+
+    async fn set_deck_position(&self, id: DeckId, position: i32) -> Result<(), CoreError> {
+        {
+            let mut s = self.state.write();
+            let Some(d) = s.decks.get_mut(&id) else {
+                return Err(CoreError::NotFound("deck"));
+            };
+            d.position = position;
+        }
+        self.save().await
+    }
+
+    async fn set_deck_prompt(&self, id: DeckId, prompt: PromptWith) -> Result<(), CoreError> {
+        {
+            let mut s = self.state.write();
+            let Some(d) = s.decks.get_mut(&id) else {
+                return Err(CoreError::NotFound("deck"));
+            };
+            d.prompt_with = prompt;
+        }
+        self.save().await
+    }
+
+    async fn reorder_decks(&self, ids_in_order: &[DeckId]) -> Result<(), CoreError> {
+        {
+            let mut s = self.state.write();
+            if ids_in_order.iter().any(|id| !s.decks.contains_key(id)) {
+                return Err(CoreError::NotFound("deck"));
+            }
+            for (i, id) in ids_in_order.iter().enumerate() {
+                if let Some(d) = s.decks.get_mut(id) {
+                    d.position = i as i32;
+                }
+            }
+        }
+        self.save().await
     }
 
     async fn delete_deck(&self, id: DeckId) -> Result<(), CoreError> {
         {
             let mut s = self.state.write();
-            if s.decks.remove(&id).is_none() {
+            if !s.decks.contains_key(&id) {
                 return Err(CoreError::NotFound("deck"));
             }
-            let to_remove: Vec<CardId> = s.cards.values().filter(|c| c.deck_id == id).map(|c| c.id).collect();
+            let doomed = deck_subtree(s.decks.values(), id);
+            s.decks.retain(|did, _| !doomed.contains(did));
+            let to_remove: Vec<CardId> = s
+                .cards
+                .values()
+                .filter(|c| doomed.contains(&c.deck_id))
+                .map(|c| c.id)
+                .collect();
             for cid in to_remove {
                 s.cards.remove(&cid);
                 s.reviews.remove(&cid);
@@ -245,6 +415,58 @@ impl Repository for JsonStore {
         self.save().await
     }
 
+    async fn merge_decks(
+        &self,
+        from: DeckId,
+        into: DeckId,
+        drop: &[CardId],
+    ) -> Result<(), CoreError> {
+        {
+            let mut s = self.state.write();
+            if !s.decks.contains_key(&from) || !s.decks.contains_key(&into) {
+                return Err(CoreError::NotFound("deck"));
+            }
+            for cid in drop {
+                s.cards.remove(cid);
+                s.reviews.remove(cid);
+            }
+            for c in s.cards.values_mut().filter(|c| c.deck_id == from) {
+                c.deck_id = into;
+            }
+            let parent = s.decks.remove(&from).and_then(|d| d.parent_id);
+            for d in s.decks.values_mut().filter(|d| d.parent_id == Some(from)) {
+                d.parent_id = parent;
+            }
+        }
+        self.save().await
+    }
+
+    async fn reset_deck(&self, deck_id: DeckId, keep_history: bool) -> Result<u64, CoreError> {
+        let n = {
+            let mut s = self.state.write();
+            if !s.decks.contains_key(&deck_id) {
+                return Err(CoreError::NotFound("deck"));
+            }
+            let ids: Vec<CardId> = s
+                .cards
+                .values()
+                .filter(|c| c.deck_id == deck_id)
+                .map(|c| c.id)
+                .collect();
+            for id in &ids {
+                if let Some(c) = s.cards.get_mut(id) {
+                    c.reset();
+                }
+                if !keep_history {
+                    s.reviews.remove(id);
+                }
+            }
+            ids.len() as u64
+        };
+        self.save().await?;
+        Ok(n)
+    }
+
     async fn add_card(
         &self,
         deck_id: DeckId,
@@ -253,18 +475,21 @@ impl Repository for JsonStore {
         hint: Option<&str>,
         tags: &[String],
     ) -> Result<Card, CoreError> {
-        let card = {
-            let s = self.state.read();
-            if !s.decks.contains_key(&deck_id) {
-                return Err(CoreError::NotFound("deck"));
-            }
-            let mut c = Card::new(deck_id, front, back);
-            c.hint = hint.map(|s| s.to_string());
-            c.tags = tags.to_vec();
-            c
-        };
+        let mut card = Card::new(deck_id, front, back);
+        card.hint = hint.map(|s| s.to_string());
+        card.tags = tags.to_vec();
+        self.insert_card(card).await
+    }
+
+    async fn insert_card(&self, card: Card) -> Result<Card, CoreError> {
         {
             let mut s = self.state.write();
+            if !s.decks.contains_key(&card.deck_id) {
+                return Err(CoreError::NotFound("deck"));
+            }
+            if s.cards.contains_key(&card.id) {
+                return Err(CoreError::Conflict("card already exists"));
+            }
             s.cards.insert(card.id, card.clone());
         }
         self.save().await?;
@@ -276,15 +501,42 @@ impl Repository for JsonStore {
         s.cards.get(&id).cloned().ok_or(CoreError::NotFound("card"))
     }
 
+    async fn card_exists(&self, id: CardId) -> Result<bool, CoreError> {
+        Ok(self.state.read().cards.contains_key(&id))
+    }
+
     async fn list_cards(&self, deck_id: Option<DeckId>) -> Result<Vec<Card>, CoreError> {
         let s = self.state.read();
         let mut v: Vec<Card> = s.cards.values().cloned().collect();
         if let Some(did) = deck_id {
             v.retain(|c| c.deck_id == did);
         }
+        v.sort_by_key(|c| (c.created_at, c.id));
         Ok(v)
     }
 
+    async fn count_cards(&self, deck_id: Option<DeckId>) -> Result<u64, CoreError> {
+        let s = self.state.read();
+        let n = match deck_id {
+            Some(did) => s.cards.values().filter(|c| c.deck_id == did).count(),
+            None => s.cards.len(),
+        };
+        Ok(n as u64)
+    }
+
+    async fn list_cards_paged(
+        &self,
+        deck_id: Option<DeckId>,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<Card>, CoreError> {
+        let v = self.list_cards(deck_id).await?;
+        Ok(v.into_iter()
+            .skip(usize::try_from(offset).unwrap_or(usize::MAX))
+            .take(usize::try_from(limit).unwrap_or(usize::MAX))
+            .collect())
+    }
+
     async fn update_card(&self, card: &Card) -> Result<Card, CoreError> {
         {
             let mut s = self.state.write();
@@ -297,6 +549,30 @@ impl Repository for JsonStore {
         Ok(card.clone())
     }
 
+    async fn patch_card(&self, id: CardId, patch: CardPatch) -> Result<Card, CoreError> {
+        let card = {
+            let mut s = self.state.write();
+            let card = s.cards.get_mut(&id).ok_or(CoreError::NotFound("card"))?;
+            patch.apply(card);
+            card.clone()
+        };
+        self.save().await?;
+        Ok(card)
+    }
+
+    async fn patch_cards(&self, patches: &[(CardId, CardPatch)]) -> Result<(), CoreError> {
+        {
+            let mut s = self.state.write();
+            if patches.iter().any(|(id, _)| !s.cards.contains_key(id)) {
+                return Err(CoreError::NotFound("card"));
+            }
+            for (id, patch) in patches {
+                patch.apply(s.cards.get_mut(id).unwrap());
+            }
+        }
+        self.save().await
+    }
+
     async fn delete_card(&self, id: CardId) -> Result<(), CoreError> {
         {
             let mut s = self.state.write();
@@ -308,6 +584,30 @@ impl Repository for JsonStore {
         self.save().await
     }
 
+    async fn merge_cards(&self, keeper: CardId, dupes: &[CardId]) -> Result<(), CoreError> {
+        {
+            let mut s = self.state.write();
+            if !s.cards.contains_key(&keeper) {
+                return Err(CoreError::NotFound("card"));
+            }
+            for cid in dupes.iter().filter(|c| **c != keeper) {
+                s.cards.remove(cid);
+                let moved = s.reviews.remove(cid).unwrap_or_default();
+                s.reviews
+                    .entry(keeper)
+                    .or_default()
+                    .extend(moved.into_iter().map(|r| Review {
+                        card_id: keeper,
+                        ..r
+                    }));
+            }
+            if let Some(v) = s.reviews.get_mut(&keeper) {
+                v.sort_by_key(|r| r.reviewed_at);
+            }
+        }
+        self.save().await
+    }
+
     async fn set_suspended(&self, id: CardId, suspended: bool) -> Result<(), CoreError> {
         {
             let mut s = self.state.write();
@@ -319,10 +619,27 @@ impl Repository for JsonStore {
         self.save().await
     }
 
+    async fn move_card(&self, card_id: CardId, new_deck: DeckId) -> Result<(), CoreError> {
+        {
+            let mut s = self.state.write();
+            if !s.decks.contains_key(&new_deck) {
+                return Err(CoreError::NotFound("deck"));
+            }
+            let Some(c) = s.cards.get_mut(&card_id) else {
+                return Err(CoreError::NotFound("card"));
+            };
+            c.deck_id = new_deck;
+        }
+        self.save().await
+    }
+
     async fn insert_review(&self, review: &Review) -> Result<(), CoreError> {
         {
             let mut s = self.state.write();
-            s.reviews.entry(review.card_id).or_default().push(review.clone());
+            s.reviews
+                .entry(review.card_id)
+                .or_default()
+                .push(review.clone());
         }
         self.save().await
     }
@@ -331,4 +648,42 @@ impl Repository for JsonStore {
         let s = self.state.read();
         Ok(s.reviews.get(&card_id).cloned().unwrap_or_default())
     }
+
+    async fn list_reviews_for_card_paged(
+        &self,
+        card_id: CardId,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<Review>, CoreError> {
+        let mut v = self.list_reviews_for_card(card_id).await?;
+        v.sort_by_key(|r| std::cmp::Reverse(r.reviewed_at));
+        Ok(v.into_iter()
+            .skip(usize::try_from(offset).unwrap_or(usize::MAX))
+            .take(usize::try_from(limit).unwrap_or(usize::MAX))
+            .collect())
+    }
+
+    async fn list_reviews(&self) -> Result<Vec<Review>, CoreError> {
+        let s = self.state.read();
+        let mut v: Vec<Review> = s.reviews.values().flatten().cloned().collect();
+        v.sort_by_key(|r| r.reviewed_at);
+        Ok(v)
+    }
+
+    async fn list_reviews_in_range(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Review>, CoreError> {
+        let s = self.state.read();
+        let mut v: Vec<Review> = s
+            .reviews
+            .values()
+            .flatten()
+            .filter(|r| from <= r.reviewed_at && r.reviewed_at < to)
+            .cloned()
+            .collect();
+        v.sort_by_key(|r| r.reviewed_at);
+        Ok(v)
+    }
 }
@@ -0,0 +1,151 @@
+use crate::{write_data, FileImage, FILE_VERSION};
+use flashmaster_core::{backup_file_name, CoreError};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Prefix of the copy `migrate_file` keeps of the original file. It differs
+/// from `BACKUP_PREFIX`, so backup rotation never deletes it.
+pub const MIGRATE_BACKUP_PREFIX: &str = "pre-migrate";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MigrationReport {
+    pub from: u32,
+    pub to: u32,
+    /// One line per change, e.g. `cards: set source = null on 3`.
+    pub changes: Vec<String>,
+    /// Copy of the original; `None` when the file was already current.
+    pub backup: Option<PathBuf>,
+}
+
+/// Upgrades a parsed store file one version at a time to `FILE_VERSION`,
+/// returning it with a line per change made. Files from a newer version
+/// are rejected rather than guessed at.
+pub fn migrate_image(mut img: Value) -> Result<(Value, Vec<String>), CoreError> {
+    let from = file_version(&img)?;
+    let mut changes = Vec::new();
+    for v in from..FILE_VERSION {
+        match v {
+            1 => changes.extend(v1_to_v2(&mut img)),
+            2 => changes.extend(v2_to_v3(&mut img)),
+            3 => changes.extend(v3_to_v4(&mut img)),
+            4 => changes.extend(v4_to_v5(&mut img)),
+            5 => changes.extend(v5_to_v6(&mut img)),
+            6 => changes.extend(v6_to_v7(&mut img)),
+            _ => unreachable!("no migration from version {v}"),
+        }
+        img["version"] = json!(v + 1);
+    }
+    Ok((img, changes))
+}
+
+fn file_version(img: &Value) -> Result<u32, CoreError> {
+    img.get("version")
+        .and_then(Value::as_u64)
+        .and_then(|v| u32::try_from(v).ok())
+        .filter(|v| (1..=FILE_VERSION).contains(v))
+        .ok_or(CoreError::Invalid("file version"))
+}
+
+/// Version 2 writes out the fields added since version 1, which older
+/// files only got through serde defaults.
+fn v1_to_v2(img: &mut Value) -> Vec<String> {
+    [
+        fill(img, "decks", "position", json!(0)),
+        fill(img, "decks", "prompt_with", json!("front")),
+        fill(img, "cards", "alt_backs", json!([])),
+        fill(img, "cards", "source", Value::Null),
+        fill(img, "reviews", "scheduler", json!("sm2")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Version 3 adds the FSRS memory state to cards.
+fn v2_to_v3(img: &mut Value) -> Vec<String> {
+    [
+        fill(img, "cards", "stability", json!(0.0)),
+        fill(img, "cards", "difficulty", json!(0.0)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Version 4 adds the lapse count to cards.
+fn v3_to_v4(img: &mut Value) -> Vec<String> {
+    fill(img, "cards", "lapses", json!(0)).into_iter().collect()
+}
+
+/// Version 5 adds the relearning step to cards.
+fn v4_to_v5(img: &mut Value) -> Vec<String> {
+    fill(img, "cards", "relearning_step", Value::Null)
+        .into_iter()
+        .collect()
+}
+
+/// Version 6 adds subdecks.
+fn v5_to_v6(img: &mut Value) -> Vec<String> {
+    fill(img, "decks", "parent_id", Value::Null)
+        .into_iter()
+        .collect()
+}
+
+/// Version 7 adds answer times to reviews.
+fn v6_to_v7(img: &mut Value) -> Vec<String> {
+    fill(img, "reviews", "duration_ms", Value::Null)
+        .into_iter()
+        .collect()
+}
+
+/// Sets `key` to `default` on every entry of `img[list]` that lacks it.
+fn fill(img: &mut Value, list: &str, key: &str, default: Value) -> Option<String> {
+    let entries = img.get_mut(list)?.as_array_mut()?;
+    let mut n = 0;
+    for entry in entries.iter_mut().filter_map(Value::as_object_mut) {
+        if !entry.contains_key(key) {
+            entry.insert(key.to_string(), default.clone());
+            n += 1;
+        }
+    }
+    (n > 0).then(|| format!("{list}: set {key} = {default} on {n}"))
+}
+
+/// Upgrades the store file at `path` in place, first copying the original
+/// into `backups_dir`. A file already at `FILE_VERSION` is left untouched.
+pub fn migrate_file(
+    path: &Path,
+    backups_dir: &Path,
+    pretty: bool,
+) -> Result<MigrationReport, CoreError> {
+    let raw = fs::read(path).map_err(|_| CoreError::Storage("io"))?;
+    let old: Value = serde_json::from_slice(&raw).map_err(|_| CoreError::Invalid("store file"))?;
+    let from = file_version(&old)?;
+    let (new, changes) = migrate_image(old)?;
+    if from == FILE_VERSION {
+        return Ok(MigrationReport {
+            from,
+            to: FILE_VERSION,
+            changes,
+            backup: None,
+        });
+    }
+    let img: FileImage =
+        serde_json::from_value(new).map_err(|_| CoreError::Invalid("store file"))?;
+
+    fs::create_dir_all(backups_dir).map_err(|_| CoreError::Storage("io"))?;
+    let backup = backups_dir.join(backup_file_name(
+        MIGRATE_BACKUP_PREFIX,
+        chrono::Local::now(),
+        "json",
+    ));
+    fs::write(&backup, &raw).map_err(|_| CoreError::Storage("io"))?;
+    write_data(path, pretty, &img).map_err(|_| CoreError::Storage("io"))?;
+    Ok(MigrationReport {
+        from,
+        to: FILE_VERSION,
+        changes,
+        backup: Some(backup),
+    })
+}
@@ -17,3 +17,12 @@ pub fn default_store_file() -> (PathBuf, PathBuf) {
     let backups = root.join("backups");
     (file, backups)
 }
+
+pub fn sessions_dir() -> PathBuf {
+    data_root().join("sessions")
+}
+
+/// Saved filters for `review --filter`, shared by every store.
+pub fn filters_file() -> PathBuf {
+    data_root().join("filters.json")
+}
@@ -0,0 +1,21 @@
+use flashmaster_core::Repository;
+use flashmaster_json::JsonStore;
+
+#[tokio::test]
+async fn quick_saves_keep_separate_backups() {
+    let dir = tempfile::tempdir().unwrap();
+    let backups = dir.path().join("backups");
+    let store = JsonStore::open_with(dir.path().join("store.json"), backups.clone(), 10)
+        .await
+        .unwrap();
+    store.create_deck("Spanish").await.unwrap();
+    store.create_deck("French").await.unwrap();
+
+    let mut names: Vec<String> = std::fs::read_dir(&backups)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names.dedup();
+    assert_eq!(names.len(), 2, "{names:?}");
+}
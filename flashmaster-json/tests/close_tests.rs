@@ -0,0 +1,39 @@
+use flashmaster_core::{CoreError, Repository};
+use flashmaster_json::JsonStore;
+
+#[tokio::test]
+async fn close_writes_pending_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("store.json");
+    let backups = dir.path().join("backups");
+    let store = JsonStore::open_with(file.clone(), backups.clone(), 5)
+        .await
+        .unwrap();
+
+    let on_disk = || async {
+        let reopened = JsonStore::open_with(file.clone(), backups.clone(), 5)
+            .await
+            .unwrap();
+        reopened.list_decks().await.unwrap()
+    };
+
+    store
+        .with_backups_disabled(|s| async move {
+            s.create_deck("Spanish").await?;
+            // Saves inside the batch are held back until it ends.
+            assert!(on_disk().await.is_empty());
+            s.close().await?;
+            assert_eq!(on_disk().await.len(), 1);
+            Ok::<_, CoreError>(())
+        })
+        .await
+        .unwrap();
+
+    // The store is still usable after closing; with nothing pending,
+    // closing leaves the file alone.
+    store.create_deck("French").await.unwrap();
+    let written = std::fs::read(&file).unwrap();
+    store.close().await.unwrap();
+    assert_eq!(std::fs::read(&file).unwrap(), written);
+    assert_eq!(on_disk().await.len(), 2);
+}
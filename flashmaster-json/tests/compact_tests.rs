@@ -0,0 +1,49 @@
+use flashmaster_core::Repository;
+use flashmaster_json::JsonStore;
+use std::path::Path;
+
+async fn open(dir: &Path, pretty: bool) -> JsonStore {
+    JsonStore::open_with(dir.join("store.json"), dir.join("backups"), 1)
+        .await
+        .unwrap()
+        .with_pretty(pretty)
+}
+
+#[tokio::test]
+async fn compact_store_reloads_and_is_smaller() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("store.json");
+
+    let compact = open(dir.path(), false).await;
+    let deck = compact.create_deck("Spanish").await.unwrap();
+    for i in 0..20 {
+        let tags = ["lang".to_string()];
+        compact
+            .add_card(deck.id, &format!("q{i}"), "a", Some("hint"), &tags)
+            .await
+            .unwrap();
+    }
+    let cards = compact.list_cards(None).await.unwrap();
+    let compact_len = std::fs::metadata(&file).unwrap().len();
+    drop(compact);
+
+    // Reloading a compact file and saving it pretty changes only the layout.
+    let pretty = open(dir.path(), true).await;
+    assert_eq!(pretty.list_decks().await.unwrap(), vec![deck]);
+    let reloaded = pretty.list_cards(None).await.unwrap();
+    let key = |cs: &[flashmaster_core::Card]| {
+        let mut v: Vec<_> = cs
+            .iter()
+            .map(|c| (c.id, c.front.clone(), c.hint.clone(), c.due_at))
+            .collect();
+        v.sort_by_key(|k| k.0);
+        v
+    };
+    assert_eq!(key(&reloaded), key(&cards));
+    pretty.set_suspended(cards[0].id, false).await.unwrap();
+    let pretty_len = std::fs::metadata(&file).unwrap().len();
+    assert!(compact_len < pretty_len, "{compact_len} !< {pretty_len}");
+
+    let again = open(dir.path(), false).await;
+    assert_eq!(key(&again.list_cards(None).await.unwrap()), key(&cards));
+}
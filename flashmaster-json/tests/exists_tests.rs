@@ -0,0 +1,21 @@
+use flashmaster_core::Repository;
+use flashmaster_json::JsonStore;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn exists_checks_match_stored_ids() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = JsonStore::open_with(dir.path().join("store.json"), dir.path().join("backups"), 1)
+        .await
+        .unwrap();
+    let deck = repo.create_deck("Lang").await.unwrap();
+    let card = repo.add_card(deck.id, "q", "a", None, &[]).await.unwrap();
+
+    assert!(repo.deck_exists(deck.id).await.unwrap());
+    assert!(repo.card_exists(card.id).await.unwrap());
+    assert!(!repo.deck_exists(Uuid::new_v4()).await.unwrap());
+    assert!(!repo.card_exists(Uuid::new_v4()).await.unwrap());
+
+    repo.delete_card(card.id).await.unwrap();
+    assert!(!repo.card_exists(card.id).await.unwrap());
+}
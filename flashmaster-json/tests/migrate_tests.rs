@@ -0,0 +1,90 @@
+use flashmaster_core::{PromptWith, Repository};
+use flashmaster_json::{migrate_file, migrate_image, JsonStore};
+use serde_json::json;
+
+fn v1_image() -> serde_json::Value {
+    json!({
+        "version": 1,
+        "created_at": "2024-01-01T00:00:00Z",
+        "updated_at": "2024-01-02T00:00:00Z",
+        "decks": [{
+            "id": "6f1c8a52-3f1e-4c7b-9a51-0d4c9b9e2a01",
+            "name": "Spanish",
+            "created_at": "2024-01-01T00:00:00Z"
+        }],
+        "cards": [{
+            "id": "0b6a9f0e-5d2c-4f7a-8e61-3c2d1b0a9f02",
+            "deck_id": "6f1c8a52-3f1e-4c7b-9a51-0d4c9b9e2a01",
+            "front": "hola",
+            "back": "hello",
+            "hint": null,
+            "tags": [],
+            "reps": 1,
+            "interval_days": 1,
+            "ef": 2.5,
+            "due_at": "2024-01-02T00:00:00Z",
+            "last_grade": "easy",
+            "last_reviewed_at": "2024-01-01T00:00:00Z",
+            "suspended": false,
+            "created_at": "2024-01-01T00:00:00Z"
+        }],
+        "reviews": [{
+            "id": "9d8c7b6a-5f4e-4d3c-8b2a-1f0e9d8c7b03",
+            "card_id": "0b6a9f0e-5d2c-4f7a-8e61-3c2d1b0a9f02",
+            "grade": "easy",
+            "reviewed_at": "2024-01-01T00:00:00Z",
+            "interval_applied": 1,
+            "ef_after": 2.6
+        }]
+    })
+}
+
+#[test]
+fn v1_image_gets_current_version_and_defaults() {
+    let (img, changes) = migrate_image(v1_image()).unwrap();
+    assert_eq!(img["version"], 7);
+    assert_eq!(img["decks"][0]["position"], 0);
+    assert_eq!(img["decks"][0]["prompt_with"], "front");
+    assert_eq!(img["cards"][0]["alt_backs"], json!([]));
+    assert!(img["cards"][0]["source"].is_null());
+    assert_eq!(img["reviews"][0]["scheduler"], "sm2");
+    assert_eq!(img["cards"][0]["stability"], 0.0);
+    assert_eq!(img["cards"][0]["lapses"], 0);
+    assert!(img["cards"][0]["relearning_step"].is_null());
+    assert!(img["decks"][0]["parent_id"].is_null());
+    assert!(img["reviews"][0]["duration_ms"].is_null());
+    assert_eq!(changes.len(), 11);
+    assert!(changes.contains(&"cards: set source = null on 1".to_string()));
+
+    // Already current: nothing to do.
+    let (again, none) = migrate_image(img.clone()).unwrap();
+    assert_eq!(again, img);
+    assert!(none.is_empty());
+
+    let mut future = v1_image();
+    future["version"] = json!(99);
+    assert!(migrate_image(future).is_err());
+}
+
+#[tokio::test]
+async fn migrate_file_backs_up_the_original() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("store.json");
+    let backups = dir.path().join("backups");
+    let original = serde_json::to_vec(&v1_image()).unwrap();
+    std::fs::write(&file, &original).unwrap();
+
+    let report = migrate_file(&file, &backups, true).unwrap();
+    assert_eq!((report.from, report.to), (1, 7));
+    assert_eq!(std::fs::read(report.backup.unwrap()).unwrap(), original);
+
+    let store = JsonStore::open_with(file.clone(), backups.clone(), 5)
+        .await
+        .unwrap();
+    let decks = store.list_decks().await.unwrap();
+    assert_eq!(decks[0].prompt_with, PromptWith::Front);
+    assert_eq!(store.list_cards(None).await.unwrap()[0].front, "hola");
+
+    let again = migrate_file(&file, &backups, true).unwrap();
+    assert_eq!((again.from, again.backup), (7, None));
+}
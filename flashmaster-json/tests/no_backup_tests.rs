@@ -0,0 +1,35 @@
+use flashmaster_core::{CoreError, Repository};
+use flashmaster_json::JsonStore;
+
+#[tokio::test]
+async fn batch_without_backups_writes_only_the_data_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("store.json");
+    let backups = dir.path().join("backups");
+    let store = JsonStore::open_with(file.clone(), backups.clone(), 5)
+        .await
+        .unwrap();
+
+    let deck = store
+        .with_backups_disabled(|s| async move {
+            let deck = s.create_deck("Spanish").await?;
+            for i in 0..50 {
+                s.add_card(deck.id, &format!("q{i}"), "a", None, &[])
+                    .await?;
+            }
+            Ok::<_, CoreError>(deck)
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(std::fs::read_dir(&backups).unwrap().count(), 0);
+    let reopened = JsonStore::open_with(file, backups.clone(), 5)
+        .await
+        .unwrap();
+    assert_eq!(reopened.list_decks().await.unwrap(), vec![deck.clone()]);
+    assert_eq!(reopened.list_cards(Some(deck.id)).await.unwrap().len(), 50);
+
+    // Outside the batch every save backs up again.
+    store.create_deck("French").await.unwrap();
+    assert_eq!(std::fs::read_dir(&backups).unwrap().count(), 1);
+}
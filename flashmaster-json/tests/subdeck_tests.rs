@@ -0,0 +1,27 @@
+use flashmaster_core::Repository;
+use flashmaster_json::JsonStore;
+
+#[tokio::test]
+async fn subdecks_survive_a_reopen_and_go_with_their_parent() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("store.json");
+    let backups = dir.path().join("backups");
+    let store = JsonStore::open_with(file.clone(), backups.clone(), 5)
+        .await
+        .unwrap();
+    let spanish = store.create_deck("Spanish").await.unwrap();
+    let verbs = store.create_subdeck("Verbs", spanish.id).await.unwrap();
+    store
+        .add_card(verbs.id, "ser", "to be", None, &[])
+        .await
+        .unwrap();
+
+    let reopened = JsonStore::open_with(file, backups, 5).await.unwrap();
+    let under = reopened.list_subdecks(spanish.id).await.unwrap();
+    assert_eq!(under.len(), 1);
+    assert_eq!(under[0].id, verbs.id);
+
+    reopened.delete_deck(spanish.id).await.unwrap();
+    assert!(reopened.list_decks().await.unwrap().is_empty());
+    assert!(reopened.list_cards(None).await.unwrap().is_empty());
+}
@@ -0,0 +1,20 @@
+use flashmaster_core::{CoreError, Repository};
+use flashmaster_json::JsonStore;
+
+#[tokio::test]
+async fn duplicate_deck_names_survive_a_reload() {
+    let dir = tempfile::tempdir().unwrap();
+    let open =
+        || JsonStore::open_with(dir.path().join("store.json"), dir.path().join("backups"), 1);
+
+    let loose = open().await.unwrap().with_unique_deck_names(false);
+    loose.create_deck("Verbs").await.unwrap();
+    loose.create_deck("Verbs").await.unwrap();
+
+    let strict = open().await.unwrap();
+    assert_eq!(strict.list_decks().await.unwrap().len(), 2);
+    assert!(matches!(
+        strict.create_deck("verbs").await,
+        Err(CoreError::Conflict(_))
+    ));
+}
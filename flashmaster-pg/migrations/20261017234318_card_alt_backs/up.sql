@@ -0,0 +1 @@
+ALTER TABLE cards ADD COLUMN IF NOT EXISTS alt_backs text[] NOT NULL DEFAULT '{}';
@@ -0,0 +1 @@
+ALTER TABLE decks ADD COLUMN IF NOT EXISTS prompt_with text NOT NULL DEFAULT 'front';
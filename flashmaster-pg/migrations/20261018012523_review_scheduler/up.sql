@@ -0,0 +1 @@
+ALTER TABLE reviews ADD COLUMN IF NOT EXISTS scheduler text NOT NULL DEFAULT 'sm2';
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_decks_name;
+ALTER TABLE decks ADD CONSTRAINT decks_name_key UNIQUE (name);
@@ -0,0 +1,3 @@
+ALTER TABLE decks DROP CONSTRAINT IF EXISTS decks_name_key;
+-- Still unique ignoring case; the app drops this when duplicates are allowed.
+CREATE UNIQUE INDEX IF NOT EXISTS idx_decks_name ON decks (lower(name));
@@ -0,0 +1,2 @@
+ALTER TABLE cards DROP COLUMN IF EXISTS difficulty;
+ALTER TABLE cards DROP COLUMN IF EXISTS stability;
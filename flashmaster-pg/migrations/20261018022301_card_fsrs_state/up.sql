@@ -0,0 +1,2 @@
+ALTER TABLE cards ADD COLUMN IF NOT EXISTS stability real NOT NULL DEFAULT 0;
+ALTER TABLE cards ADD COLUMN IF NOT EXISTS difficulty real NOT NULL DEFAULT 0;
@@ -0,0 +1 @@
+ALTER TABLE cards ADD COLUMN IF NOT EXISTS lapses integer NOT NULL DEFAULT 0;
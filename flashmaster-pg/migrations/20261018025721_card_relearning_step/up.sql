@@ -0,0 +1 @@
+ALTER TABLE cards ADD COLUMN IF NOT EXISTS relearning_step smallint;
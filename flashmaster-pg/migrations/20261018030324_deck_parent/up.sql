@@ -0,0 +1 @@
+ALTER TABLE decks ADD COLUMN IF NOT EXISTS parent_id uuid REFERENCES decks(id) ON DELETE CASCADE;
@@ -1,9 +1,21 @@
 use chrono::{DateTime, Utc};
-use flashmaster_core::{repo::Repository, Card, CardId, CoreError, Deck, DeckId, Grade, Review};
-use sqlx::{postgres::PgPoolOptions, PgPool, Row};
+use flashmaster_core::{
+    repo::Repository, Card, CardId, CardPatch, CoreError, Deck, DeckCounts, DeckId, Grade,
+    PromptWith, Review, EF_DEFAULT,
+};
+use sqlx::{postgres::PgPoolOptions, PgPool, Postgres, QueryBuilder, Row};
 
+pub mod conninfo;
+
+/// Unique index on `lower(name)`, dropped when duplicate deck names are allowed.
+const DECK_NAME_INDEX: &str = "idx_decks_name";
+
+/// PostgreSQL-backed repository. Cloning is cheap: clones share the same
+/// connection pool.
+#[derive(Clone)]
 pub struct PostgresRepo {
     pool: PgPool,
+    unique_deck_names: bool,
 }
 
 impl PostgresRepo {
@@ -12,12 +24,36 @@ impl PostgresRepo {
             .max_connections(10)
             .connect(url)
             .await
-            .map_err(|_| CoreError::Storage("pg connect"))?;
-        let repo = Self { pool };
+            .map_err(pg_err("pg connect"))?;
+        let repo = Self {
+            pool,
+            unique_deck_names: true,
+        };
         repo.ensure_schema().await?;
         Ok(repo)
     }
 
+    /// Whether `create_deck` rejects a name already used by another deck,
+    /// ignoring case (the default). A unique index on `lower(name)` backs
+    /// the check; it is created or dropped here, not on each insert.
+    pub async fn with_unique_deck_names(mut self, unique: bool) -> Result<Self, CoreError> {
+        if unique {
+            self.ensure_deck_name_index().await?;
+        } else {
+            sqlx::query(&format!("DROP INDEX IF EXISTS {DECK_NAME_INDEX}"))
+                .execute(&self.pool)
+                .await
+                .map_err(pg_err("pg schema"))?;
+        }
+        self.unique_deck_names = unique;
+        Ok(self)
+    }
+
+    /// Underlying connection pool (shared by all clones of this repo).
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
     async fn ensure_schema(&self) -> Result<(), CoreError> {
         // Mirrors migrations (id generation done in app; DB defaults still helpful)
         const STMT: &str = r#"
@@ -25,10 +61,19 @@ impl PostgresRepo {
 
         CREATE TABLE IF NOT EXISTS decks (
           id          uuid PRIMARY KEY,
-          name        text NOT NULL UNIQUE,
-          created_at  timestamptz NOT NULL
+          name        text NOT NULL,
+          created_at  timestamptz NOT NULL,
+          position    integer NOT NULL DEFAULT 0,
+          prompt_with text NOT NULL DEFAULT 'front',
+          parent_id   uuid REFERENCES decks(id) ON DELETE CASCADE
         );
 
+        ALTER TABLE decks ADD COLUMN IF NOT EXISTS position integer NOT NULL DEFAULT 0;
+        ALTER TABLE decks ADD COLUMN IF NOT EXISTS prompt_with text NOT NULL DEFAULT 'front';
+        ALTER TABLE decks ADD COLUMN IF NOT EXISTS parent_id uuid REFERENCES decks(id) ON DELETE CASCADE;
+        -- Replaced by a case-insensitive index that can be switched off (ensure_deck_name_index).
+        ALTER TABLE decks DROP CONSTRAINT IF EXISTS decks_name_key;
+
         CREATE TABLE IF NOT EXISTS cards (
           id                uuid PRIMARY KEY,
           deck_id           uuid NOT NULL REFERENCES decks(id) ON DELETE CASCADE,
@@ -43,20 +88,39 @@ impl PostgresRepo {
           last_grade        smallint,
           last_reviewed_at  timestamptz,
           suspended         boolean NOT NULL DEFAULT false,
-          created_at        timestamptz NOT NULL
+          created_at        timestamptz NOT NULL,
+          alt_backs         text[] NOT NULL DEFAULT '{}',
+          source            text,
+          stability         real NOT NULL DEFAULT 0,
+          difficulty        real NOT NULL DEFAULT 0,
+          lapses            integer NOT NULL DEFAULT 0,
+          relearning_step   smallint
         );
 
+        ALTER TABLE cards ADD COLUMN IF NOT EXISTS alt_backs text[] NOT NULL DEFAULT '{}';
+        ALTER TABLE cards ADD COLUMN IF NOT EXISTS source text;
+        ALTER TABLE cards ADD COLUMN IF NOT EXISTS stability real NOT NULL DEFAULT 0;
+        ALTER TABLE cards ADD COLUMN IF NOT EXISTS difficulty real NOT NULL DEFAULT 0;
+        ALTER TABLE cards ADD COLUMN IF NOT EXISTS lapses integer NOT NULL DEFAULT 0;
+        ALTER TABLE cards ADD COLUMN IF NOT EXISTS relearning_step smallint;
+
         CREATE TABLE IF NOT EXISTS reviews (
           id               uuid PRIMARY KEY,
           card_id          uuid NOT NULL REFERENCES cards(id) ON DELETE CASCADE,
           grade            smallint NOT NULL,
           reviewed_at      timestamptz NOT NULL,
           interval_applied integer NOT NULL,
-          ef_after         real NOT NULL
+          ef_after         real NOT NULL,
+          scheduler        text NOT NULL DEFAULT 'sm2',
+          duration_ms      bigint
         );
 
+        ALTER TABLE reviews ADD COLUMN IF NOT EXISTS scheduler text NOT NULL DEFAULT 'sm2';
+        ALTER TABLE reviews ADD COLUMN IF NOT EXISTS duration_ms bigint;
+
         CREATE INDEX IF NOT EXISTS idx_cards_deck_due ON cards (deck_id, due_at);
         CREATE INDEX IF NOT EXISTS idx_reviews_card_time ON reviews (card_id, reviewed_at);
+        CREATE INDEX IF NOT EXISTS idx_reviews_time ON reviews (reviewed_at);
         "#;
 
         for chunk in STMT.split(';') {
@@ -67,80 +131,334 @@ impl PostgresRepo {
             sqlx::query(sql)
                 .execute(&self.pool)
                 .await
-                .map_err(|_| CoreError::Storage("pg schema"))?;
+                .map_err(pg_err("pg schema"))?;
         }
-        Ok(())
+        self.ensure_deck_name_index().await
     }
-}
 
-#[async_trait::async_trait]
-impl Repository for PostgresRepo {
-    // ===== Decks =====
-    async fn create_deck(&self, name: &str) -> Result<Deck, CoreError> {
-        // unique name pre-check
-        let exists =
-            sqlx::query_scalar::<_, i64>("SELECT 1 FROM decks WHERE lower(name)=lower($1) LIMIT 1")
-                .bind(name)
+    /// Keeps deck names unique in the schema too, unless the database
+    /// already holds duplicates from a run that allowed them.
+    async fn ensure_deck_name_index(&self) -> Result<(), CoreError> {
+        let dupes =
+            sqlx::query("SELECT 1 FROM decks GROUP BY lower(name) HAVING COUNT(*) > 1 LIMIT 1")
                 .fetch_optional(&self.pool)
                 .await
-                .map_err(|_| CoreError::Storage("pg read deck"))?
+                .map_err(pg_err("pg schema"))?
                 .is_some();
-        if exists {
-            return Err(CoreError::Conflict("deck name already exists"));
+        if !dupes {
+            sqlx::query(&format!(
+                "CREATE UNIQUE INDEX IF NOT EXISTS {DECK_NAME_INDEX} ON decks (lower(name))"
+            ))
+            .execute(&self.pool)
+            .await
+            .map_err(pg_err("pg schema"))?;
         }
+        Ok(())
+    }
 
-        let deck = Deck::new(name);
-        sqlx::query("INSERT INTO decks (id,name,created_at) VALUES ($1,$2,$3)")
-            .bind(deck.id)
+    async fn insert_deck(&self, mut deck: Deck) -> Result<Deck, CoreError> {
+        // unique name pre-check
+        if self.unique_deck_names {
+            let exists = sqlx::query_scalar::<_, i64>(
+                "SELECT 1 FROM decks WHERE lower(name)=lower($1) LIMIT 1",
+            )
             .bind(&deck.name)
-            .bind(deck.created_at)
-            .execute(&self.pool)
+            .fetch_optional(&self.pool)
             .await
-            .map_err(|_| CoreError::Storage("pg insert deck"))?;
+            .map_err(pg_err("pg read deck"))?
+            .is_some();
+            if exists {
+                return Err(CoreError::Conflict("deck name already exists"));
+            }
+        }
+
+        if let Some(parent) = deck.parent_id {
+            if !self.deck_exists(parent).await? {
+                return Err(CoreError::NotFound("deck"));
+            }
+        }
+        // New decks go after any reordered ones.
+        deck.position = sqlx::query_scalar(
+            "INSERT INTO decks (id,name,created_at,position,prompt_with,parent_id)
+             SELECT $1,$2,$3,COALESCE(MAX(position), -1) + 1,$4,$5 FROM decks
+             RETURNING position",
+        )
+        .bind(deck.id)
+        .bind(&deck.name)
+        .bind(deck.created_at)
+        .bind(deck.prompt_with.as_str())
+        .bind(deck.parent_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| match &e {
+            // Lost a race with another insert of the same name.
+            sqlx::Error::Database(d) if d.constraint() == Some(DECK_NAME_INDEX) => {
+                CoreError::Conflict("deck name already exists")
+            }
+            _ => pg_err("pg insert deck")(e),
+        })?;
         Ok(deck)
     }
+}
+
+#[async_trait::async_trait]
+impl Repository for PostgresRepo {
+    async fn ping(&self) -> Result<(), CoreError> {
+        sqlx::query("SELECT 1")
+            .execute(&self.pool)
+            .await
+            .map_err(pg_err("pg ping"))?;
+        Ok(())
+    }
+
+    async fn close(&self) -> Result<(), CoreError> {
+        self.pool.close().await;
+        Ok(())
+    }
+
+    // ===== Decks =====
+    async fn create_deck(&self, name: &str) -> Result<Deck, CoreError> {
+        self.insert_deck(Deck::new(name)).await
+    }
+
+    async fn create_subdeck(&self, name: &str, parent: DeckId) -> Result<Deck, CoreError> {
+        let mut deck = Deck::new(name);
+        deck.parent_id = Some(parent);
+        self.insert_deck(deck).await
+    }
 
     async fn get_deck(&self, id: DeckId) -> Result<Deck, CoreError> {
-        let row = sqlx::query("SELECT id,name,created_at FROM decks WHERE id=$1")
+        let row = sqlx::query(
+            "SELECT id,name,created_at,position,prompt_with,parent_id FROM decks WHERE id=$1",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(pg_err("pg read deck"))?;
+        let row = row.ok_or(CoreError::NotFound("deck"))?;
+        row_into_deck(&row)
+    }
+
+    async fn deck_exists(&self, id: DeckId) -> Result<bool, CoreError> {
+        let row = sqlx::query("SELECT 1 FROM decks WHERE id=$1 LIMIT 1")
             .bind(id)
             .fetch_optional(&self.pool)
             .await
-            .map_err(|_| CoreError::Storage("pg read deck"))?;
-        let row = row.ok_or(CoreError::NotFound("deck"))?;
-        Ok(Deck {
-            id: row.get::<uuid::Uuid, _>("id"),
-            name: row.get::<String, _>("name"),
-            created_at: row.get::<DateTime<Utc>, _>("created_at"),
-        })
+            .map_err(pg_err("pg read deck"))?;
+        Ok(row.is_some())
     }
 
     async fn list_decks(&self) -> Result<Vec<Deck>, CoreError> {
-        let rows = sqlx::query("SELECT id,name,created_at FROM decks ORDER BY created_at ASC")
-            .fetch_all(&self.pool)
+        let rows = sqlx::query(
+            "SELECT id,name,created_at,position,prompt_with,parent_id FROM decks ORDER BY position ASC, created_at ASC",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(pg_err("pg list decks"))?;
+        rows.iter().map(row_into_deck).collect()
+    }
+
+    async fn count_decks(&self) -> Result<u64, CoreError> {
+        let n: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM decks")
+            .fetch_one(&self.pool)
             .await
-            .map_err(|_| CoreError::Storage("pg list decks"))?;
-        Ok(rows
-            .into_iter()
-            .map(|row| Deck {
-                id: row.get("id"),
-                name: row.get("name"),
-                created_at: row.get("created_at"),
-            })
-            .collect())
+            .map_err(pg_err("pg count decks"))?;
+        Ok(n as u64)
+    }
+
+    async fn list_subdecks(&self, parent: DeckId) -> Result<Vec<Deck>, CoreError> {
+        let rows = sqlx::query(
+            "SELECT id,name,created_at,position,prompt_with,parent_id FROM decks
+             WHERE parent_id=$1 ORDER BY position ASC, created_at ASC",
+        )
+        .bind(parent)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(pg_err("pg list decks"))?;
+        rows.iter().map(row_into_deck).collect()
+    }
+
+    async fn deck_counts(&self, id: DeckId, now: DateTime<Utc>) -> Result<DeckCounts, CoreError> {
+        let row = sqlx::query(
+            "SELECT COUNT(*) AS total,
+                COUNT(*) FILTER (WHERE suspended) AS suspended,
+                COUNT(*) FILTER (WHERE NOT suspended AND reps = 0) AS new,
+                COUNT(*) FILTER (WHERE NOT suspended AND reps > 0 AND due_at <= $1) AS due,
+                MAX(last_reviewed_at) AS last_reviewed_at
+             FROM cards WHERE deck_id=$2",
+        )
+        .bind(now)
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(pg_err("pg count cards"))?;
+        Ok(row_into_counts(&row))
+    }
+
+    async fn list_decks_with_counts(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<(Deck, DeckCounts)>, CoreError> {
+        let rows = sqlx::query(
+            "SELECT d.id, d.name, d.created_at, d.position, d.prompt_with, d.parent_id,
+                COUNT(c.id) AS total,
+                COUNT(c.id) FILTER (WHERE c.suspended) AS suspended,
+                COUNT(c.id) FILTER (WHERE NOT c.suspended AND c.reps = 0) AS new,
+                COUNT(c.id) FILTER (WHERE NOT c.suspended AND c.reps > 0 AND c.due_at <= $1) AS due,
+                MAX(c.last_reviewed_at) AS last_reviewed_at
+             FROM decks d LEFT JOIN cards c ON c.deck_id = d.id
+             GROUP BY d.id
+             ORDER BY d.position ASC, d.created_at ASC",
+        )
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(pg_err("pg list decks"))?;
+        rows.iter()
+            .map(|row| Ok((row_into_deck(row)?, row_into_counts(row))))
+            .collect()
+    }
+
+    async fn set_deck_position(&self, id: DeckId, position: i32) -> Result<(), CoreError> {
+        let res = sqlx::query("UPDATE decks SET position=$1 WHERE id=$2")
+            .bind(position)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(pg_err("pg update deck"))?;
+        if res.rows_affected() == 0 {
+            return Err(CoreError::NotFound("deck"));
+        }
+        Ok(())
+    }
+
+    async fn set_deck_prompt(&self, id: DeckId, prompt: PromptWith) -> Result<(), CoreError> {
+        let res = sqlx::query("UPDATE decks SET prompt_with=$1 WHERE id=$2")
+            .bind(prompt.as_str())
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(pg_err("pg update deck"))?;
+        if res.rows_affected() == 0 {
+            return Err(CoreError::NotFound("deck"));
+        }
+        Ok(())
+    }
+
+    async fn reorder_decks(&self, ids_in_order: &[DeckId]) -> Result<(), CoreError> {
+        let mut tx = self.pool.begin().await.map_err(pg_err("pg tx"))?;
+        for (i, id) in ids_in_order.iter().enumerate() {
+            let res = sqlx::query("UPDATE decks SET position=$1 WHERE id=$2")
+                .bind(i as i32)
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .map_err(pg_err("pg update deck"))?;
+            if res.rows_affected() == 0 {
+                tx.rollback().await.ok();
+                return Err(CoreError::NotFound("deck"));
+            }
+        }
+        tx.commit().await.map_err(pg_err("pg tx commit"))
     }
 
     async fn delete_deck(&self, id: DeckId) -> Result<(), CoreError> {
+        // Subdecks, cards and reviews go with it via ON DELETE CASCADE.
         let res = sqlx::query("DELETE FROM decks WHERE id=$1")
             .bind(id)
             .execute(&self.pool)
             .await
-            .map_err(|_| CoreError::Storage("pg del deck"))?;
+            .map_err(pg_err("pg del deck"))?;
         if res.rows_affected() == 0 {
             return Err(CoreError::NotFound("deck"));
         }
         Ok(())
     }
 
+    async fn merge_decks(
+        &self,
+        from: DeckId,
+        into: DeckId,
+        drop: &[CardId],
+    ) -> Result<(), CoreError> {
+        let mut tx = self.pool.begin().await.map_err(pg_err("pg tx"))?;
+
+        let found: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM decks WHERE id = ANY($1)")
+            .bind(vec![from, into])
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(pg_err("pg read deck"))?;
+        if found < 2 {
+            tx.rollback().await.ok();
+            return Err(CoreError::NotFound("deck"));
+        }
+
+        sqlx::query("DELETE FROM cards WHERE id = ANY($1)")
+            .bind(drop.to_vec())
+            .execute(&mut *tx)
+            .await
+            .map_err(pg_err("pg del card"))?;
+
+        sqlx::query("UPDATE cards SET deck_id=$1 WHERE deck_id=$2")
+            .bind(into)
+            .bind(from)
+            .execute(&mut *tx)
+            .await
+            .map_err(pg_err("pg move cards"))?;
+
+        sqlx::query("UPDATE decks SET parent_id=(SELECT parent_id FROM decks WHERE id=$1) WHERE parent_id=$1")
+            .bind(from)
+            .execute(&mut *tx)
+            .await
+            .map_err(pg_err("pg update deck"))?;
+
+        sqlx::query("DELETE FROM decks WHERE id=$1")
+            .bind(from)
+            .execute(&mut *tx)
+            .await
+            .map_err(pg_err("pg del deck"))?;
+
+        tx.commit().await.map_err(pg_err("pg tx commit"))
+    }
+
+    async fn reset_deck(&self, deck_id: DeckId, keep_history: bool) -> Result<u64, CoreError> {
+        let mut tx = self.pool.begin().await.map_err(pg_err("pg tx"))?;
+        let found = sqlx::query("SELECT 1 FROM decks WHERE id=$1 LIMIT 1")
+            .bind(deck_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(pg_err("pg read deck"))?;
+        if found.is_none() {
+            tx.rollback().await.ok();
+            return Err(CoreError::NotFound("deck"));
+        }
+
+        if !keep_history {
+            sqlx::query(
+                "DELETE FROM reviews WHERE card_id IN (SELECT id FROM cards WHERE deck_id=$1)",
+            )
+            .bind(deck_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(pg_err("pg del reviews"))?;
+        }
+        let res = sqlx::query(
+            r#"UPDATE cards SET reps=0, interval_days=0, ef=$1, due_at=$2,
+                      last_grade=NULL, last_reviewed_at=NULL, stability=0, difficulty=0,
+                      lapses=0, relearning_step=NULL
+               WHERE deck_id=$3"#,
+        )
+        .bind(EF_DEFAULT)
+        .bind(Utc::now())
+        .bind(deck_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(pg_err("pg reset cards"))?;
+
+        tx.commit().await.map_err(pg_err("pg tx commit"))?;
+        Ok(res.rows_affected())
+    }
+
     // ===== Cards =====
     async fn add_card(
         &self,
@@ -150,27 +468,27 @@ impl Repository for PostgresRepo {
         hint: Option<&str>,
         tags: &[String],
     ) -> Result<Card, CoreError> {
-        // ensure deck exists
-        let exists = sqlx::query_scalar::<_, i64>("SELECT 1 FROM decks WHERE id=$1 LIMIT 1")
-            .bind(deck_id)
-            .fetch_optional(&self.pool)
-            .await
-            .map_err(|_| CoreError::Storage("pg read deck"))?
-            .is_some();
-        if !exists {
-            return Err(CoreError::NotFound("deck"));
-        }
-
         let mut card = Card::new(deck_id, front, back);
         card.hint = hint.map(|s| s.to_string());
         card.tags = tags.to_vec();
+        self.insert_card(card).await
+    }
+
+    async fn insert_card(&self, card: Card) -> Result<Card, CoreError> {
+        if !self.deck_exists(card.deck_id).await? {
+            return Err(CoreError::NotFound("deck"));
+        }
+        if self.card_exists(card.id).await? {
+            return Err(CoreError::Conflict("card already exists"));
+        }
 
         sqlx::query(
             r#"
             INSERT INTO cards (
               id, deck_id, front, back, hint, tags, reps, interval_days, ef, due_at,
-              last_grade, last_reviewed_at, suspended, created_at
-            ) VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,$13,$14)
+              last_grade, last_reviewed_at, suspended, created_at, alt_backs, source,
+              stability, difficulty, lapses, relearning_step
+            ) VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,$13,$14,$15,$16,$17,$18,$19,$20)
             "#,
         )
         .bind(card.id)
@@ -187,9 +505,15 @@ impl Repository for PostgresRepo {
         .bind(card.last_reviewed_at)
         .bind(card.suspended)
         .bind(card.created_at)
+        .bind(&card.alt_backs)
+        .bind(card.source.clone())
+        .bind(card.stability as f64)
+        .bind(card.difficulty as f64)
+        .bind(card.lapses as i64)
+        .bind(card.relearning_step.map(i16::from))
         .execute(&self.pool)
         .await
-        .map_err(|_| CoreError::Storage("pg insert card"))?;
+        .map_err(pg_err("pg insert card"))?;
 
         Ok(card)
     }
@@ -197,37 +521,49 @@ impl Repository for PostgresRepo {
     async fn get_card(&self, id: CardId) -> Result<Card, CoreError> {
         let row = sqlx::query(
             r#"SELECT id,deck_id,front,back,hint,tags,reps,interval_days,ef,due_at,
-                       last_grade,last_reviewed_at,suspended,created_at
+                       last_grade,last_reviewed_at,suspended,created_at,alt_backs,source,
+                          stability,difficulty,lapses,relearning_step
                FROM cards WHERE id=$1"#,
         )
         .bind(id)
         .fetch_optional(&self.pool)
         .await
-        .map_err(|_| CoreError::Storage("pg read card"))?;
+        .map_err(pg_err("pg read card"))?;
         let row = row.ok_or(CoreError::NotFound("card"))?;
         row_into_card(row)
     }
 
+    async fn card_exists(&self, id: CardId) -> Result<bool, CoreError> {
+        let row = sqlx::query("SELECT 1 FROM cards WHERE id=$1 LIMIT 1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(pg_err("pg read card"))?;
+        Ok(row.is_some())
+    }
+
     async fn list_cards(&self, deck_id: Option<DeckId>) -> Result<Vec<Card>, CoreError> {
         let rows = if let Some(did) = deck_id {
             sqlx::query(
                 r#"SELECT id,deck_id,front,back,hint,tags,reps,interval_days,ef,due_at,
-                          last_grade,last_reviewed_at,suspended,created_at
-                   FROM cards WHERE deck_id=$1 ORDER BY created_at ASC"#,
+                          last_grade,last_reviewed_at,suspended,created_at,alt_backs,source,
+                          stability,difficulty,lapses,relearning_step
+                   FROM cards WHERE deck_id=$1 ORDER BY created_at ASC, id ASC"#,
             )
             .bind(did)
             .fetch_all(&self.pool)
             .await
-            .map_err(|_| CoreError::Storage("pg list cards"))?
+            .map_err(pg_err("pg list cards"))?
         } else {
             sqlx::query(
                 r#"SELECT id,deck_id,front,back,hint,tags,reps,interval_days,ef,due_at,
-                          last_grade,last_reviewed_at,suspended,created_at
-                   FROM cards ORDER BY created_at ASC"#,
+                          last_grade,last_reviewed_at,suspended,created_at,alt_backs,source,
+                          stability,difficulty,lapses,relearning_step
+                   FROM cards ORDER BY created_at ASC, id ASC"#,
             )
             .fetch_all(&self.pool)
             .await
-            .map_err(|_| CoreError::Storage("pg list cards"))?
+            .map_err(pg_err("pg list cards"))?
         };
         let mut v = Vec::with_capacity(rows.len());
         for row in rows {
@@ -236,12 +572,46 @@ impl Repository for PostgresRepo {
         Ok(v)
     }
 
+    async fn count_cards(&self, deck_id: Option<DeckId>) -> Result<u64, CoreError> {
+        let n: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM cards WHERE ($1::uuid IS NULL OR deck_id=$1)")
+                .bind(deck_id)
+                .fetch_one(&self.pool)
+                .await
+                .map_err(pg_err("pg count cards"))?;
+        Ok(n as u64)
+    }
+
+    async fn list_cards_paged(
+        &self,
+        deck_id: Option<DeckId>,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<Card>, CoreError> {
+        let rows = sqlx::query(
+            r#"SELECT id,deck_id,front,back,hint,tags,reps,interval_days,ef,due_at,
+                      last_grade,last_reviewed_at,suspended,created_at,alt_backs,source,
+                          stability,difficulty,lapses,relearning_step
+               FROM cards WHERE ($1::uuid IS NULL OR deck_id=$1)
+               ORDER BY created_at ASC, id ASC LIMIT $2 OFFSET $3"#,
+        )
+        .bind(deck_id)
+        .bind(i64::try_from(limit).unwrap_or(i64::MAX))
+        .bind(i64::try_from(offset).unwrap_or(i64::MAX))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(pg_err("pg list cards"))?;
+        rows.into_iter().map(row_into_card).collect()
+    }
+
     async fn update_card(&self, card: &Card) -> Result<Card, CoreError> {
         let res = sqlx::query(
             r#"
             UPDATE cards SET
               deck_id=$1, front=$2, back=$3, hint=$4, tags=$5, reps=$6, interval_days=$7,
-              ef=$8, due_at=$9, last_grade=$10, last_reviewed_at=$11, suspended=$12
+              ef=$8, due_at=$9, last_grade=$10, last_reviewed_at=$11, suspended=$12,
+              alt_backs=$14, source=$15, stability=$16, difficulty=$17, lapses=$18,
+              relearning_step=$19
             WHERE id=$13
             "#,
         )
@@ -258,34 +628,112 @@ impl Repository for PostgresRepo {
         .bind(card.last_reviewed_at)
         .bind(card.suspended)
         .bind(card.id)
+        .bind(&card.alt_backs)
+        .bind(card.source.clone())
+        .bind(card.stability as f64)
+        .bind(card.difficulty as f64)
+        .bind(card.lapses as i64)
+        .bind(card.relearning_step.map(i16::from))
         .execute(&self.pool)
         .await
-        .map_err(|_| CoreError::Storage("pg update card"))?;
+        .map_err(pg_err("pg update card"))?;
         if res.rows_affected() == 0 {
             return Err(CoreError::NotFound("card"));
         }
         Ok(card.clone())
     }
 
+    async fn patch_card(&self, id: CardId, patch: CardPatch) -> Result<Card, CoreError> {
+        if patch.is_empty() {
+            return self.get_card(id).await;
+        }
+        let row = patch_query(id, patch)
+            .build()
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(pg_err("pg patch card"))?;
+        row_into_card(row.ok_or(CoreError::NotFound("card"))?)
+    }
+
+    async fn patch_cards(&self, patches: &[(CardId, CardPatch)]) -> Result<(), CoreError> {
+        let mut tx = self.pool.begin().await.map_err(pg_err("pg tx"))?;
+        for (id, patch) in patches.iter().filter(|(_, p)| !p.is_empty()) {
+            patch_query(*id, patch.clone())
+                .build()
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(pg_err("pg patch card"))?
+                .ok_or(CoreError::NotFound("card"))?;
+        }
+        tx.commit().await.map_err(pg_err("pg patch card"))?;
+        Ok(())
+    }
+
     async fn delete_card(&self, id: CardId) -> Result<(), CoreError> {
         let res = sqlx::query("DELETE FROM cards WHERE id=$1")
             .bind(id)
             .execute(&self.pool)
             .await
-            .map_err(|_| CoreError::Storage("pg del card"))?;
+            .map_err(pg_err("pg del card"))?;
         if res.rows_affected() == 0 {
             return Err(CoreError::NotFound("card"));
         }
         Ok(())
     }
 
+    async fn merge_cards(&self, keeper: CardId, dupes: &[CardId]) -> Result<(), CoreError> {
+        let dupes: Vec<CardId> = dupes.iter().copied().filter(|c| *c != keeper).collect();
+        let mut tx = self.pool.begin().await.map_err(pg_err("pg tx"))?;
+
+        let found: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM cards WHERE id=$1")
+            .bind(keeper)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(pg_err("pg read card"))?;
+        if found == 0 {
+            tx.rollback().await.ok();
+            return Err(CoreError::NotFound("card"));
+        }
+
+        sqlx::query("UPDATE reviews SET card_id=$1 WHERE card_id = ANY($2)")
+            .bind(keeper)
+            .bind(&dupes)
+            .execute(&mut *tx)
+            .await
+            .map_err(pg_err("pg move reviews"))?;
+
+        sqlx::query("DELETE FROM cards WHERE id = ANY($1)")
+            .bind(&dupes)
+            .execute(&mut *tx)
+            .await
+            .map_err(pg_err("pg del card"))?;
+
+        tx.commit().await.map_err(pg_err("pg tx commit"))
+    }
+
     async fn set_suspended(&self, id: CardId, suspended: bool) -> Result<(), CoreError> {
         let res = sqlx::query("UPDATE cards SET suspended=$1 WHERE id=$2")
             .bind(suspended)
             .bind(id)
             .execute(&self.pool)
             .await
-            .map_err(|_| CoreError::Storage("pg suspend"))?;
+            .map_err(pg_err("pg suspend"))?;
+        if res.rows_affected() == 0 {
+            return Err(CoreError::NotFound("card"));
+        }
+        Ok(())
+    }
+
+    async fn move_card(&self, card_id: CardId, new_deck: DeckId) -> Result<(), CoreError> {
+        if !self.deck_exists(new_deck).await? {
+            return Err(CoreError::NotFound("deck"));
+        }
+        let res = sqlx::query("UPDATE cards SET deck_id=$1 WHERE id=$2")
+            .bind(new_deck)
+            .bind(card_id)
+            .execute(&self.pool)
+            .await
+            .map_err(pg_err("pg move card"))?;
         if res.rows_affected() == 0 {
             return Err(CoreError::NotFound("card"));
         }
@@ -295,8 +743,8 @@ impl Repository for PostgresRepo {
     // ===== Reviews =====
     async fn insert_review(&self, review: &Review) -> Result<(), CoreError> {
         sqlx::query(
-            r#"INSERT INTO reviews (id,card_id,grade,reviewed_at,interval_applied,ef_after)
-               VALUES ($1,$2,$3,$4,$5,$6)"#,
+            r#"INSERT INTO reviews (id,card_id,grade,reviewed_at,interval_applied,ef_after,scheduler,duration_ms)
+               VALUES ($1,$2,$3,$4,$5,$6,$7,$8)"#,
         )
         .bind(review.id)
         .bind(review.card_id)
@@ -304,52 +752,151 @@ impl Repository for PostgresRepo {
         .bind(review.reviewed_at)
         .bind(review.interval_applied as i64)
         .bind(review.ef_after as f64)
+        .bind(&review.scheduler)
+        .bind(review.duration_ms.map(i64::from))
         .execute(&self.pool)
         .await
-        .map_err(|_| CoreError::Storage("pg insert review"))?;
+        .map_err(pg_err("pg insert review"))?;
         Ok(())
     }
 
     async fn list_reviews_for_card(&self, card_id: CardId) -> Result<Vec<Review>, CoreError> {
         let rows = sqlx::query(
-            r#"SELECT id,card_id,grade,reviewed_at,interval_applied,ef_after
+            r#"SELECT id,card_id,grade,reviewed_at,interval_applied,ef_after,scheduler,duration_ms
                FROM reviews WHERE card_id=$1 ORDER BY reviewed_at ASC"#,
         )
         .bind(card_id)
         .fetch_all(&self.pool)
         .await
-        .map_err(|_| CoreError::Storage("pg list reviews"))?;
-        let mut v = Vec::with_capacity(rows.len());
-        for row in rows {
-            v.push(Review {
-                id: row.get::<uuid::Uuid, _>("id"),
-                card_id: row.get::<uuid::Uuid, _>("card_id"),
-                grade: grade_from_i16(row.get::<i16, _>("grade"))
-                    .ok_or(CoreError::Invalid("grade"))?,
-                reviewed_at: row.get::<DateTime<Utc>, _>("reviewed_at"),
-                interval_applied: row.get::<i32, _>("interval_applied"),
-                ef_after: row.get::<f32, _>("ef_after"),
-            });
-        }
-        Ok(v)
+        .map_err(pg_err("pg list reviews"))?;
+        rows.into_iter().map(row_into_review).collect()
+    }
+
+    async fn list_reviews_for_card_paged(
+        &self,
+        card_id: CardId,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<Review>, CoreError> {
+        let rows = sqlx::query(
+            r#"SELECT id,card_id,grade,reviewed_at,interval_applied,ef_after,scheduler,duration_ms
+               FROM reviews WHERE card_id=$1 ORDER BY reviewed_at DESC LIMIT $2 OFFSET $3"#,
+        )
+        .bind(card_id)
+        .bind(i64::try_from(limit).unwrap_or(i64::MAX))
+        .bind(i64::try_from(offset).unwrap_or(i64::MAX))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(pg_err("pg list reviews"))?;
+        rows.into_iter().map(row_into_review).collect()
+    }
+
+    async fn list_reviews(&self) -> Result<Vec<Review>, CoreError> {
+        let rows = sqlx::query(
+            r#"SELECT id,card_id,grade,reviewed_at,interval_applied,ef_after,scheduler,duration_ms
+               FROM reviews ORDER BY reviewed_at ASC"#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(pg_err("pg list reviews"))?;
+        rows.into_iter().map(row_into_review).collect()
+    }
+
+    async fn list_reviews_in_range(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Review>, CoreError> {
+        let rows = sqlx::query(
+            r#"SELECT id,card_id,grade,reviewed_at,interval_applied,ef_after,scheduler,duration_ms
+               FROM reviews
+               WHERE reviewed_at >= $1 AND reviewed_at < $2
+               ORDER BY reviewed_at ASC"#,
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(pg_err("pg list reviews in range"))?;
+        rows.into_iter().map(row_into_review).collect()
     }
 }
 
 // ===== helpers =====
+/// Maps a query failure to `Storage(ctx)`, or to `Timeout` when no pooled
+/// connection could be acquired in time.
+fn pg_err(ctx: &'static str) -> impl Fn(sqlx::Error) -> CoreError {
+    move |e| match e {
+        sqlx::Error::PoolTimedOut => CoreError::Timeout("pg pool acquire"),
+        _ => CoreError::Storage(ctx),
+    }
+}
+
 fn grade_to_i16(g: &Grade) -> i16 {
     match g {
+        Grade::Again => 0,
         Grade::Hard => 1,
         Grade::Medium => 2,
         Grade::Easy => 3,
     }
 }
 
+/// `UPDATE ... RETURNING` for the fields set in `patch` (which must not be empty).
+fn patch_query(id: CardId, patch: CardPatch) -> QueryBuilder<'static, Postgres> {
+    let mut qb = QueryBuilder::<Postgres>::new("UPDATE cards SET ");
+    let mut set = qb.separated(", ");
+    if let Some(f) = patch.front {
+        set.push("front=").push_bind_unseparated(f);
+    }
+    if let Some(b) = patch.back {
+        set.push("back=").push_bind_unseparated(b);
+    }
+    if let Some(h) = patch.hint {
+        set.push("hint=").push_bind_unseparated(h);
+    }
+    if let Some(s) = patch.source {
+        set.push("source=").push_bind_unseparated(s);
+    }
+    if let Some(t) = patch.tags {
+        set.push("tags=").push_bind_unseparated(t);
+    }
+    if let Some(s) = patch.suspended {
+        set.push("suspended=").push_bind_unseparated(s);
+    }
+    qb.push(" WHERE id=").push_bind(id).push(
+        " RETURNING id,deck_id,front,back,hint,tags,reps,interval_days,ef,due_at,\
+           last_grade,last_reviewed_at,suspended,created_at,alt_backs,source,\
+           stability,difficulty,lapses,relearning_step",
+    );
+    qb
+}
+
+fn prompt_from_str(s: &str) -> Result<PromptWith, CoreError> {
+    PromptWith::from_name(s).ok_or(CoreError::Invalid("prompt_with"))
+}
+
 fn grade_from_i16(i: i16) -> Option<Grade> {
-    match i {
-        1 => Some(Grade::Hard),
-        2 => Some(Grade::Medium),
-        3 => Some(Grade::Easy),
-        _ => None,
+    Grade::from_score(i as i32)
+}
+
+fn row_into_deck(row: &sqlx::postgres::PgRow) -> Result<Deck, CoreError> {
+    Ok(Deck {
+        id: row.get::<uuid::Uuid, _>("id"),
+        name: row.get::<String, _>("name"),
+        created_at: row.get::<DateTime<Utc>, _>("created_at"),
+        position: row.get::<i32, _>("position"),
+        prompt_with: prompt_from_str(&row.get::<String, _>("prompt_with"))?,
+        parent_id: row.get::<Option<uuid::Uuid>, _>("parent_id"),
+    })
+}
+
+fn row_into_counts(row: &sqlx::postgres::PgRow) -> DeckCounts {
+    DeckCounts {
+        total: row.get::<i64, _>("total") as u32,
+        new: row.get::<i64, _>("new") as u32,
+        due: row.get::<i64, _>("due") as u32,
+        suspended: row.get::<i64, _>("suspended") as u32,
+        last_reviewed_at: row.get::<Option<DateTime<Utc>>, _>("last_reviewed_at"),
     }
 }
 
@@ -359,7 +906,9 @@ fn row_into_card(row: sqlx::postgres::PgRow) -> Result<Card, CoreError> {
         deck_id: row.get::<uuid::Uuid, _>("deck_id"),
         front: row.get::<String, _>("front"),
         back: row.get::<String, _>("back"),
+        alt_backs: row.get::<Vec<String>, _>("alt_backs"),
         hint: row.get::<Option<String>, _>("hint"),
+        source: row.get::<Option<String>, _>("source"),
         tags: row.get::<Vec<String>, _>("tags"),
         reps: row.get::<i32, _>("reps") as u32,
         interval_days: row.get::<i32, _>("interval_days") as u32,
@@ -371,5 +920,27 @@ fn row_into_card(row: sqlx::postgres::PgRow) -> Result<Card, CoreError> {
         last_reviewed_at: row.get::<Option<DateTime<Utc>>, _>("last_reviewed_at"),
         suspended: row.get::<bool, _>("suspended"),
         created_at: row.get::<DateTime<Utc>, _>("created_at"),
+        stability: row.get::<f32, _>("stability"),
+        difficulty: row.get::<f32, _>("difficulty"),
+        lapses: u32::try_from(row.get::<i32, _>("lapses"))
+            .map_err(|_| CoreError::Invalid("lapses"))?,
+        relearning_step: row
+            .get::<Option<i16>, _>("relearning_step")
+            .map(u8::try_from)
+            .transpose()
+            .map_err(|_| CoreError::Invalid("relearning step"))?,
+    })
+}
+
+fn row_into_review(row: sqlx::postgres::PgRow) -> Result<Review, CoreError> {
+    Ok(Review {
+        id: row.get::<uuid::Uuid, _>("id"),
+        card_id: row.get::<uuid::Uuid, _>("card_id"),
+        grade: grade_from_i16(row.get::<i16, _>("grade")).ok_or(CoreError::Invalid("grade"))?,
+        reviewed_at: row.get::<DateTime<Utc>, _>("reviewed_at"),
+        interval_applied: row.get::<i32, _>("interval_applied"),
+        ef_after: row.get::<f32, _>("ef_after"),
+        scheduler: row.get::<String, _>("scheduler"),
+        duration_ms: row.get::<Option<i64>, _>("duration_ms").map(|ms| ms as u32),
     })
 }
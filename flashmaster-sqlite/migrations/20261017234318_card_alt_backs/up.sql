@@ -0,0 +1 @@
+ALTER TABLE cards ADD COLUMN alt_backs TEXT NOT NULL DEFAULT '[]';
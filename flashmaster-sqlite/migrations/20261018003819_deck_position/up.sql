@@ -0,0 +1 @@
+ALTER TABLE decks ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1 @@
+ALTER TABLE decks ADD COLUMN prompt_with TEXT NOT NULL DEFAULT 'front';
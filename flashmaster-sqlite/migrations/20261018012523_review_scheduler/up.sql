@@ -0,0 +1 @@
+ALTER TABLE reviews ADD COLUMN scheduler TEXT NOT NULL DEFAULT 'sm2';
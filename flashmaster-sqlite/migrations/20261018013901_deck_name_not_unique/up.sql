@@ -0,0 +1,20 @@
+PRAGMA foreign_keys = OFF;
+
+CREATE TABLE decks_new (
+  id          TEXT PRIMARY KEY,
+  name        TEXT NOT NULL,
+  created_at  TEXT NOT NULL,
+  position    INTEGER NOT NULL DEFAULT 0,
+  prompt_with TEXT NOT NULL DEFAULT 'front'
+);
+
+INSERT INTO decks_new (id,name,created_at,position,prompt_with)
+SELECT id,name,created_at,position,prompt_with FROM decks;
+
+DROP TABLE decks;
+ALTER TABLE decks_new RENAME TO decks;
+
+PRAGMA foreign_keys = ON;
+
+-- Still unique ignoring case; the app drops this when duplicates are allowed.
+CREATE UNIQUE INDEX IF NOT EXISTS idx_decks_name ON decks (lower(name));
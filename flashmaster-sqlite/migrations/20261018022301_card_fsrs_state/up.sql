@@ -0,0 +1,2 @@
+ALTER TABLE cards ADD COLUMN stability REAL NOT NULL DEFAULT 0;
+ALTER TABLE cards ADD COLUMN difficulty REAL NOT NULL DEFAULT 0;
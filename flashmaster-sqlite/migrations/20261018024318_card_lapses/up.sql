@@ -0,0 +1 @@
+ALTER TABLE cards ADD COLUMN lapses INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1 @@
+ALTER TABLE cards ADD COLUMN relearning_step INTEGER;
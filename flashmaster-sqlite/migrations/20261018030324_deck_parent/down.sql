@@ -0,0 +1,17 @@
+PRAGMA foreign_keys = OFF;
+
+CREATE TABLE decks_new (
+  id          TEXT PRIMARY KEY,
+  name        TEXT NOT NULL,
+  created_at  TEXT NOT NULL,
+  position    INTEGER NOT NULL DEFAULT 0,
+  prompt_with TEXT NOT NULL DEFAULT 'front'
+);
+
+INSERT INTO decks_new (id,name,created_at,position,prompt_with)
+SELECT id,name,created_at,position,prompt_with FROM decks;
+
+DROP TABLE decks;
+ALTER TABLE decks_new RENAME TO decks;
+
+PRAGMA foreign_keys = ON;
@@ -0,0 +1 @@
+ALTER TABLE decks ADD COLUMN parent_id TEXT REFERENCES decks(id) ON DELETE CASCADE;
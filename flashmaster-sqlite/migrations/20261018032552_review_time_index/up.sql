@@ -0,0 +1 @@
+CREATE INDEX IF NOT EXISTS idx_reviews_time ON reviews (reviewed_at);
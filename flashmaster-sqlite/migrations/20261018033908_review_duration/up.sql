@@ -0,0 +1 @@
+ALTER TABLE reviews ADD COLUMN duration_ms INTEGER;
@@ -1,36 +1,180 @@
-use chrono::{DateTime, Utc};
-use flashmaster_core::{repo::Repository, Card, CardId, CoreError, Deck, DeckId, Grade, Review};
-use sqlx::{sqlite::SqlitePoolOptions, Row, SqlitePool};
+use chrono::{DateTime, TimeZone, Utc};
+use flashmaster_core::{
+    repo::Repository, Card, CardId, CardPatch, CoreError, Deck, DeckCounts, DeckId, Grade,
+    PromptWith, Review, EF_DEFAULT,
+};
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqliteConnection, SqlitePoolOptions, SqliteSynchronous},
+    Connection, QueryBuilder, Row, Sqlite, SqlitePool,
+};
 use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
 
+/// Reported when a write hits a read-only database file or mount.
+pub const READ_ONLY_MSG: &str = "sqlite database is read-only";
+
+/// Reported (as `CoreError::Timeout`) when no pooled connection frees up
+/// within the acquire timeout.
+pub const POOL_TIMEOUT_MSG: &str = "sqlite pool acquire";
+
+/// `subtree(id)`: the bound deck and every deck below it. `UNION` rather
+/// than `UNION ALL` so a parent cycle can't recurse forever.
+const DECK_SUBTREE: &str = "WITH RECURSIVE subtree(id) AS (
+       SELECT ? UNION SELECT d.id FROM decks d JOIN subtree s ON d.parent_id = s.id
+     )";
+
+/// Unique index on `lower(name)`, dropped when duplicate deck names are allowed.
+const DECK_NAME_INDEX: &str = "idx_decks_name";
+
+/// `PRAGMA synchronous` level. `Full` (the default) syncs on every commit
+/// and survives power loss; `Normal` can lose the last commits on power loss
+/// (but not on an application crash); `Off` hands writes to the OS without
+/// syncing, so a power loss or OS crash can corrupt the database.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Synchronous {
+    Off,
+    Normal,
+    #[default]
+    Full,
+}
+
+impl Synchronous {
+    fn to_sqlx(self) -> SqliteSynchronous {
+        match self {
+            Synchronous::Off => SqliteSynchronous::Off,
+            Synchronous::Normal => SqliteSynchronous::Normal,
+            Synchronous::Full => SqliteSynchronous::Full,
+        }
+    }
+}
+
+/// Per-connection tuning for [`SqliteRepo::open_file_with`]. The defaults
+/// match [`SqliteRepo::open_file`].
+#[derive(Clone, Debug, Default)]
+pub struct SqliteOpts {
+    pub synchronous: Synchronous,
+    /// Page cache size in KiB (`PRAGMA cache_size = -N`); SQLite's default if `None`.
+    pub cache_size_kb: Option<u32>,
+    /// Bytes of the file to memory-map (`PRAGMA mmap_size`); off if `None`.
+    pub mmap_size: Option<u64>,
+    /// How long a query waits for a free pooled connection before failing
+    /// with `CoreError::Timeout`; sqlx's default (30s) if `None`.
+    pub acquire_timeout: Option<Duration>,
+}
+
+/// SQLite-backed repository. Cloning is cheap: clones share the same
+/// connection pool, so the API server and background tasks can each hold one.
+#[derive(Clone)]
 pub struct SqliteRepo {
     pool: SqlitePool,
+    unique_deck_names: bool,
 }
 
 impl SqliteRepo {
     pub async fn open_file(path: impl AsRef<Path>) -> Result<Self, CoreError> {
+        Self::open_file_with(path, &SqliteOpts::default()).await
+    }
+
+    /// Like [`SqliteRepo::open_file`], applying `opts` to every pooled connection.
+    pub async fn open_file_with(
+        path: impl AsRef<Path>,
+        opts: &SqliteOpts,
+    ) -> Result<Self, CoreError> {
         let url = format!("sqlite://{}", path.as_ref().to_string_lossy());
-        let pool = SqlitePoolOptions::new()
-            .max_connections(5)
-            .connect(&url)
+        let mut conn = SqliteConnectOptions::from_str(&url)
+            .map_err(|_| CoreError::Invalid("sqlite path"))?
+            .synchronous(opts.synchronous.to_sqlx());
+        if let Some(kb) = opts.cache_size_kb {
+            // Negative values are KiB rather than pages.
+            conn = conn.pragma("cache_size", format!("-{kb}"));
+        }
+        if let Some(bytes) = opts.mmap_size {
+            conn = conn.pragma("mmap_size", bytes.to_string());
+        }
+        let mut pool = SqlitePoolOptions::new().max_connections(5);
+        if let Some(t) = opts.acquire_timeout {
+            pool = pool.acquire_timeout(t);
+        }
+        let pool = pool
+            .connect_with(conn)
             .await
-            .map_err(|_| CoreError::Storage("sqlite connect"))?;
-        let repo = Self { pool };
+            .map_err(write_err("sqlite connect"))?;
+        let repo = Self {
+            pool,
+            unique_deck_names: true,
+        };
         repo.ensure_schema().await?;
         Ok(repo)
     }
 
+    /// Opens an existing database with `mode=ro` for inspection only. Schema
+    /// creation is skipped; any write returns `Storage(READ_ONLY_MSG)`.
+    pub async fn open_file_readonly(path: impl AsRef<Path>) -> Result<Self, CoreError> {
+        let opts = SqliteConnectOptions::new()
+            .filename(path.as_ref())
+            .read_only(true);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(5)
+            .connect_with(opts)
+            .await
+            .map_err(read_err("sqlite connect"))?;
+        Ok(Self {
+            pool,
+            unique_deck_names: true,
+        })
+    }
+
     pub async fn open_memory() -> Result<Self, CoreError> {
         let pool = SqlitePoolOptions::new()
             .max_connections(5)
             .connect("sqlite::memory:")
             .await
-            .map_err(|_| CoreError::Storage("sqlite connect"))?;
-        let repo = Self { pool };
+            .map_err(write_err("sqlite connect"))?;
+        let repo = Self {
+            pool,
+            unique_deck_names: true,
+        };
         repo.ensure_schema().await?;
         Ok(repo)
     }
 
+    /// Whether `create_deck` rejects a name already used by another deck,
+    /// ignoring case (the default). A unique index on `lower(name)` backs
+    /// the check; it is created or dropped here, not on each insert.
+    pub async fn with_unique_deck_names(mut self, unique: bool) -> Result<Self, CoreError> {
+        if unique {
+            self.ensure_deck_name_index().await?;
+        } else {
+            sqlx::query(&format!("DROP INDEX IF EXISTS {DECK_NAME_INDEX}"))
+                .execute(&self.pool)
+                .await
+                .map_err(write_err("sqlite schema"))?;
+        }
+        self.unique_deck_names = unique;
+        Ok(self)
+    }
+
+    /// Underlying connection pool (shared by all clones of this repo).
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    /// Writes a consistent copy of the live database to `dest` (which must not
+    /// exist yet) with `VACUUM INTO`; other connections keep working meanwhile.
+    pub async fn backup_to(&self, dest: impl AsRef<Path>) -> Result<(), CoreError> {
+        let dest = dest.as_ref();
+        if dest.exists() {
+            return Err(CoreError::Conflict("backup file already exists"));
+        }
+        sqlx::query("VACUUM INTO ?")
+            .bind(dest.to_string_lossy().into_owned())
+            .execute(&self.pool)
+            .await
+            .map_err(write_err("sqlite backup"))?;
+        Ok(())
+    }
+
     async fn ensure_schema(&self) -> Result<(), CoreError> {
         // Create tables/indexes if they do not exist (mirrors migrations).
         const STMT: &str = r#"
@@ -38,8 +182,11 @@ impl SqliteRepo {
 
         CREATE TABLE IF NOT EXISTS decks (
           id          TEXT PRIMARY KEY,
-          name        TEXT NOT NULL UNIQUE,
-          created_at  TEXT NOT NULL
+          name        TEXT NOT NULL,
+          created_at  TEXT NOT NULL,
+          position    INTEGER NOT NULL DEFAULT 0,
+          prompt_with TEXT NOT NULL DEFAULT 'front',
+          parent_id   TEXT REFERENCES decks(id) ON DELETE CASCADE
         );
 
         CREATE TABLE IF NOT EXISTS cards (
@@ -57,6 +204,12 @@ impl SqliteRepo {
           last_reviewed_at  TEXT,
           suspended         INTEGER NOT NULL DEFAULT 0,
           created_at        TEXT NOT NULL,
+          alt_backs         TEXT NOT NULL DEFAULT '[]',
+          source            TEXT,
+          stability         REAL NOT NULL DEFAULT 0,
+          difficulty        REAL NOT NULL DEFAULT 0,
+          lapses            INTEGER NOT NULL DEFAULT 0,
+          relearning_step   INTEGER,
           FOREIGN KEY(deck_id) REFERENCES decks(id) ON DELETE CASCADE
         );
 
@@ -67,11 +220,14 @@ impl SqliteRepo {
           reviewed_at      TEXT NOT NULL,
           interval_applied INTEGER NOT NULL,
           ef_after         REAL NOT NULL,
+          scheduler        TEXT NOT NULL DEFAULT 'sm2',
+          duration_ms      INTEGER,
           FOREIGN KEY(card_id) REFERENCES cards(id) ON DELETE CASCADE
         );
 
         CREATE INDEX IF NOT EXISTS idx_cards_deck_due ON cards (deck_id, due_at);
         CREATE INDEX IF NOT EXISTS idx_reviews_card_time ON reviews (card_id, reviewed_at);
+        CREATE INDEX IF NOT EXISTS idx_reviews_time ON reviews (reviewed_at);
         "#;
 
         // Execute statements one by one for compatibility.
@@ -83,102 +239,479 @@ impl SqliteRepo {
             sqlx::query(sql)
                 .execute(&self.pool)
                 .await
-                .map_err(|_| CoreError::Storage("sqlite schema"))?;
+                .map_err(write_err("sqlite schema"))?;
+        }
+
+        // Columns added after the initial schema; SQLite has no ADD COLUMN IF NOT EXISTS.
+        self.add_column_if_missing("cards", "alt_backs", "TEXT NOT NULL DEFAULT '[]'")
+            .await?;
+        self.add_column_if_missing("cards", "source", "TEXT")
+            .await?;
+        self.add_column_if_missing("cards", "stability", "REAL NOT NULL DEFAULT 0")
+            .await?;
+        self.add_column_if_missing("cards", "difficulty", "REAL NOT NULL DEFAULT 0")
+            .await?;
+        self.add_column_if_missing("cards", "lapses", "INTEGER NOT NULL DEFAULT 0")
+            .await?;
+        self.add_column_if_missing("cards", "relearning_step", "INTEGER")
+            .await?;
+        self.add_column_if_missing("decks", "position", "INTEGER NOT NULL DEFAULT 0")
+            .await?;
+        self.add_column_if_missing("decks", "prompt_with", "TEXT NOT NULL DEFAULT 'front'")
+            .await?;
+        self.add_column_if_missing(
+            "decks",
+            "parent_id",
+            "TEXT REFERENCES decks(id) ON DELETE CASCADE",
+        )
+        .await?;
+        self.add_column_if_missing("reviews", "scheduler", "TEXT NOT NULL DEFAULT 'sm2'")
+            .await?;
+        self.add_column_if_missing("reviews", "duration_ms", "INTEGER")
+            .await?;
+        self.drop_deck_name_unique().await?;
+        self.ensure_deck_name_index().await?;
+        Ok(())
+    }
+
+    /// Keeps deck names unique in the schema too, unless the database
+    /// already holds duplicates from a run that allowed them.
+    async fn ensure_deck_name_index(&self) -> Result<(), CoreError> {
+        let dupes =
+            sqlx::query("SELECT 1 FROM decks GROUP BY lower(name) HAVING COUNT(*) > 1 LIMIT 1")
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(write_err("sqlite schema"))?
+                .is_some();
+        if !dupes {
+            sqlx::query(&format!(
+                "CREATE UNIQUE INDEX IF NOT EXISTS {DECK_NAME_INDEX} ON decks (lower(name))"
+            ))
+            .execute(&self.pool)
+            .await
+            .map_err(write_err("sqlite schema"))?;
         }
         Ok(())
     }
-}
 
-#[async_trait::async_trait]
-impl Repository for SqliteRepo {
-    // ===== Decks =====
-    async fn create_deck(&self, name: &str) -> Result<Deck, CoreError> {
-        // Pre-check for unique name
-        let exists: Option<i64> =
-            sqlx::query("SELECT 1 FROM decks WHERE lower(name)=lower(?) LIMIT 1")
-                .bind(name)
+    /// Databases created before deck-name uniqueness became optional have
+    /// `name TEXT NOT NULL UNIQUE`; SQLite can only drop that by rebuilding
+    /// the table.
+    async fn drop_deck_name_unique(&self) -> Result<(), CoreError> {
+        let sql: Option<String> =
+            sqlx::query_scalar("SELECT sql FROM sqlite_master WHERE type='table' AND name='decks'")
                 .fetch_optional(&self.pool)
                 .await
-                .map_err(|_| CoreError::Storage("read deck"))?
-                .map(|_| 1);
-        if exists.is_some() {
-            return Err(CoreError::Conflict("deck name already exists"));
+                .map_err(write_err("sqlite schema"))?;
+        if !sql.is_some_and(|s| s.contains("UNIQUE")) {
+            return Ok(());
         }
+        let mut conn = self
+            .pool
+            .acquire()
+            .await
+            .map_err(write_err("sqlite schema"))?;
+        // Off so dropping the old table doesn't cascade into cards; the
+        // pragma is a no-op inside a transaction, hence before BEGIN.
+        sqlx::query("PRAGMA foreign_keys = OFF")
+            .execute(&mut *conn)
+            .await
+            .map_err(write_err("sqlite schema"))?;
+        let rebuilt = Self::rebuild_decks(&mut conn).await;
+        // Back on however the rebuild went: the connection returns to the pool.
+        let restored = sqlx::query("PRAGMA foreign_keys = ON")
+            .execute(&mut *conn)
+            .await;
+        rebuilt
+            .and(restored.map(|_| ()))
+            .map_err(write_err("sqlite schema"))
+    }
 
-        let deck = Deck::new(name);
-        sqlx::query("INSERT INTO decks (id,name,created_at) VALUES (?,?,?)")
+    async fn rebuild_decks(conn: &mut SqliteConnection) -> Result<(), sqlx::Error> {
+        let mut tx = conn.begin().await?;
+        for sql in [
+            "CREATE TABLE decks_new (
+              id          TEXT PRIMARY KEY,
+              name        TEXT NOT NULL,
+              created_at  TEXT NOT NULL,
+              position    INTEGER NOT NULL DEFAULT 0,
+              prompt_with TEXT NOT NULL DEFAULT 'front',
+              parent_id   TEXT REFERENCES decks(id) ON DELETE CASCADE
+            )",
+            "INSERT INTO decks_new (id,name,created_at,position,prompt_with,parent_id)
+             SELECT id,name,created_at,position,prompt_with,parent_id FROM decks",
+            "DROP TABLE decks",
+            "ALTER TABLE decks_new RENAME TO decks",
+        ] {
+            sqlx::query(sql).execute(&mut *tx).await?;
+        }
+        tx.commit().await
+    }
+
+    async fn add_column_if_missing(
+        &self,
+        table: &str,
+        column: &str,
+        decl: &str,
+    ) -> Result<(), CoreError> {
+        let exists = sqlx::query("SELECT 1 FROM pragma_table_info(?) WHERE name=?")
+            .bind(table)
+            .bind(column)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(write_err("sqlite schema"))?
+            .is_some();
+        if !exists {
+            sqlx::query(&format!("ALTER TABLE {table} ADD COLUMN {column} {decl}"))
+                .execute(&self.pool)
+                .await
+                .map_err(write_err("sqlite schema"))?;
+        }
+        Ok(())
+    }
+
+    async fn insert_deck(&self, mut deck: Deck) -> Result<Deck, CoreError> {
+        // Pre-check for unique name
+        if self.unique_deck_names {
+            let exists: Option<i64> =
+                sqlx::query("SELECT 1 FROM decks WHERE lower(name)=lower(?) LIMIT 1")
+                    .bind(&deck.name)
+                    .fetch_optional(&self.pool)
+                    .await
+                    .map_err(read_err("read deck"))?
+                    .map(|_| 1);
+            if exists.is_some() {
+                return Err(CoreError::Conflict("deck name already exists"));
+            }
+        }
+
+        if let Some(parent) = deck.parent_id {
+            if !self.deck_exists(parent).await? {
+                return Err(CoreError::NotFound("deck"));
+            }
+        }
+        // New decks go after any reordered ones.
+        sqlx::query(
+            "INSERT INTO decks (id,name,created_at,position,prompt_with,parent_id)
+             SELECT ?,?,?,COALESCE(MAX(position), -1) + 1,?,? FROM decks",
+        )
+        .bind(deck.id.to_string())
+        .bind(&deck.name)
+        .bind(dt_to_str(deck.created_at))
+        .bind(deck.prompt_with.as_str())
+        .bind(deck.parent_id.map(|p| p.to_string()))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| match &e {
+            // Lost a race with another insert of the same name.
+            sqlx::Error::Database(d) if d.message().contains(DECK_NAME_INDEX) => {
+                CoreError::Conflict("deck name already exists")
+            }
+            _ => write_err("insert deck")(e),
+        })?;
+        let position: i64 = sqlx::query_scalar("SELECT position FROM decks WHERE id=?")
             .bind(deck.id.to_string())
-            .bind(&deck.name)
-            .bind(dt_to_str(deck.created_at))
-            .execute(&self.pool)
+            .fetch_one(&self.pool)
             .await
-            .map_err(|_| CoreError::Storage("insert deck"))?;
+            .map_err(read_err("read deck"))?;
+        deck.position = position as i32;
         Ok(deck)
     }
+}
+
+#[async_trait::async_trait]
+impl Repository for SqliteRepo {
+    async fn ping(&self) -> Result<(), CoreError> {
+        sqlx::query("SELECT 1")
+            .execute(&self.pool)
+            .await
+            .map_err(read_err("ping"))?;
+        Ok(())
+    }
+
+    async fn close(&self) -> Result<(), CoreError> {
+        self.pool.close().await;
+        Ok(())
+    }
+
+    // ===== Decks =====
+    async fn create_deck(&self, name: &str) -> Result<Deck, CoreError> {
+        self.insert_deck(Deck::new(name)).await
+    }
+
+    async fn create_subdeck(&self, name: &str, parent: DeckId) -> Result<Deck, CoreError> {
+        let mut deck = Deck::new(name);
+        deck.parent_id = Some(parent);
+        self.insert_deck(deck).await
+    }
 
     async fn get_deck(&self, id: DeckId) -> Result<Deck, CoreError> {
-        let row = sqlx::query("SELECT id,name,created_at FROM decks WHERE id=?")
+        let row = sqlx::query(
+            "SELECT id,name,created_at,position,prompt_with,parent_id FROM decks WHERE id=?",
+        )
+        .bind(id.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(read_err("read deck"))?;
+        let row = row.ok_or(CoreError::NotFound("deck"))?;
+        row_into_deck(&row)
+    }
+
+    async fn deck_exists(&self, id: DeckId) -> Result<bool, CoreError> {
+        let row = sqlx::query("SELECT 1 FROM decks WHERE id=? LIMIT 1")
             .bind(id.to_string())
             .fetch_optional(&self.pool)
             .await
-            .map_err(|_| CoreError::Storage("read deck"))?;
-        let row = row.ok_or(CoreError::NotFound("deck"))?;
-        Ok(Deck {
-            id: uuid_from_str(row.get::<String, _>("id"))?,
-            name: row.get::<String, _>("name"),
-            created_at: dt_from_str(row.get::<String, _>("created_at"))?,
-        })
+            .map_err(read_err("read deck"))?;
+        Ok(row.is_some())
     }
 
     async fn list_decks(&self) -> Result<Vec<Deck>, CoreError> {
-        let rows = sqlx::query("SELECT id,name,created_at FROM decks ORDER BY created_at ASC")
-            .fetch_all(&self.pool)
+        let rows = sqlx::query(
+            "SELECT id,name,created_at,position,prompt_with,parent_id FROM decks ORDER BY position ASC, created_at ASC",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(read_err("list decks"))?;
+        rows.iter().map(row_into_deck).collect()
+    }
+
+    async fn count_decks(&self) -> Result<u64, CoreError> {
+        let n: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM decks")
+            .fetch_one(&self.pool)
             .await
-            .map_err(|_| CoreError::Storage("list decks"))?;
-        let mut v = Vec::with_capacity(rows.len());
-        for row in rows {
-            v.push(Deck {
-                id: uuid_from_str(row.get::<String, _>("id"))?,
-                name: row.get::<String, _>("name"),
-                created_at: dt_from_str(row.get::<String, _>("created_at"))?,
-            });
+            .map_err(read_err("count decks"))?;
+        Ok(n as u64)
+    }
+
+    async fn list_subdecks(&self, parent: DeckId) -> Result<Vec<Deck>, CoreError> {
+        let rows = sqlx::query(
+            "SELECT id,name,created_at,position,prompt_with,parent_id FROM decks
+             WHERE parent_id=? ORDER BY position ASC, created_at ASC",
+        )
+        .bind(parent.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(read_err("list decks"))?;
+        rows.iter().map(row_into_deck).collect()
+    }
+
+    async fn deck_counts(&self, id: DeckId, now: DateTime<Utc>) -> Result<DeckCounts, CoreError> {
+        let row = sqlx::query(
+            "SELECT COUNT(*) AS total,
+                COALESCE(SUM(suspended != 0), 0) AS suspended,
+                COALESCE(SUM(suspended = 0 AND reps = 0), 0) AS new,
+                COALESCE(SUM(suspended = 0 AND reps > 0 AND julianday(due_at) <= julianday(?)), 0) AS due,
+                MAX(last_reviewed_at) AS last_reviewed_at
+             FROM cards WHERE deck_id=?",
+        )
+        .bind(dt_to_str(now))
+        .bind(id.to_string())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(read_err("count cards"))?;
+        row_into_counts(&row)
+    }
+
+    async fn list_decks_with_counts(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<(Deck, DeckCounts)>, CoreError> {
+        // due_at is stored as RFC 3339 text; julianday() compares instants
+        // rather than strings. MAX can compare the text: every stamp is UTC
+        // with a fixed-width date and time.
+        let rows = sqlx::query(
+            "SELECT d.id, d.name, d.created_at, d.position, d.prompt_with, d.parent_id,
+                COUNT(c.id) AS total,
+                COALESCE(SUM(c.suspended != 0), 0) AS suspended,
+                COALESCE(SUM(c.suspended = 0 AND c.reps = 0), 0) AS new,
+                COALESCE(SUM(c.suspended = 0 AND c.reps > 0 AND julianday(c.due_at) <= julianday(?)), 0) AS due,
+                MAX(c.last_reviewed_at) AS last_reviewed_at
+             FROM decks d LEFT JOIN cards c ON c.deck_id = d.id
+             GROUP BY d.id
+             ORDER BY d.position ASC, d.created_at ASC",
+        )
+        .bind(dt_to_str(now))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(read_err("list decks"))?;
+        rows.iter()
+            .map(|row| Ok((row_into_deck(row)?, row_into_counts(row)?)))
+            .collect()
+    }
+
+    async fn set_deck_position(&self, id: DeckId, position: i32) -> Result<(), CoreError> {
+        let res = sqlx::query("UPDATE decks SET position=? WHERE id=?")
+            .bind(position as i64)
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(write_err("update deck"))?;
+        if res.rows_affected() == 0 {
+            return Err(CoreError::NotFound("deck"));
         }
-        Ok(v)
+        Ok(())
     }
 
-    async fn delete_deck(&self, id: DeckId) -> Result<(), CoreError> {
-        let mut tx = self
-            .pool
-            .begin()
+    async fn set_deck_prompt(&self, id: DeckId, prompt: PromptWith) -> Result<(), CoreError> {
+        let res = sqlx::query("UPDATE decks SET prompt_with=? WHERE id=?")
+            .bind(prompt.as_str())
+            .bind(id.to_string())
+            .execute(&self.pool)
             .await
-            .map_err(|_| CoreError::Storage("tx"))?;
+            .map_err(write_err("update deck"))?;
+        if res.rows_affected() == 0 {
+            return Err(CoreError::NotFound("deck"));
+        }
+        Ok(())
+    }
+
+    async fn reorder_decks(&self, ids_in_order: &[DeckId]) -> Result<(), CoreError> {
+        let mut tx = self.pool.begin().await.map_err(read_err("tx"))?;
+        for (i, id) in ids_in_order.iter().enumerate() {
+            let res = sqlx::query("UPDATE decks SET position=? WHERE id=?")
+                .bind(i as i64)
+                .bind(id.to_string())
+                .execute(&mut *tx)
+                .await
+                .map_err(write_err("update deck"))?;
+            if res.rows_affected() == 0 {
+                tx.rollback().await.ok();
+                return Err(CoreError::NotFound("deck"));
+            }
+        }
+        tx.commit().await.map_err(write_err("tx commit"))
+    }
+
+    async fn delete_deck(&self, id: DeckId) -> Result<(), CoreError> {
+        let mut tx = self.pool.begin().await.map_err(read_err("tx"))?;
 
         // Manual cascade (robust even if PRAGMA foreign_keys is off)
-        sqlx::query("DELETE FROM reviews WHERE card_id IN (SELECT id FROM cards WHERE deck_id=?)")
-            .bind(id.to_string())
-            .execute(&mut *tx)
+        sqlx::query(&format!(
+            "{DECK_SUBTREE} DELETE FROM reviews WHERE card_id IN
+               (SELECT id FROM cards WHERE deck_id IN (SELECT id FROM subtree))"
+        ))
+        .bind(id.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(write_err("del reviews"))?;
+
+        sqlx::query(&format!(
+            "{DECK_SUBTREE} DELETE FROM cards WHERE deck_id IN (SELECT id FROM subtree)"
+        ))
+        .bind(id.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(write_err("del cards"))?;
+
+        let res = sqlx::query(&format!(
+            "{DECK_SUBTREE} DELETE FROM decks WHERE id IN (SELECT id FROM subtree)"
+        ))
+        .bind(id.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(write_err("del deck"))?;
+        if res.rows_affected() == 0 {
+            tx.rollback().await.ok();
+            return Err(CoreError::NotFound("deck"));
+        }
+
+        tx.commit().await.map_err(write_err("tx commit"))
+    }
+
+    async fn merge_decks(
+        &self,
+        from: DeckId,
+        into: DeckId,
+        drop: &[CardId],
+    ) -> Result<(), CoreError> {
+        let mut tx = self.pool.begin().await.map_err(read_err("tx"))?;
+
+        let found: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM decks WHERE id IN (?,?)")
+            .bind(from.to_string())
+            .bind(into.to_string())
+            .fetch_one(&mut *tx)
             .await
-            .map_err(|_| CoreError::Storage("del reviews"))?;
+            .map_err(read_err("read deck"))?;
+        if found < 2 {
+            tx.rollback().await.ok();
+            return Err(CoreError::NotFound("deck"));
+        }
 
-        sqlx::query("DELETE FROM cards WHERE deck_id=?")
-            .bind(id.to_string())
+        for cid in drop {
+            sqlx::query("DELETE FROM reviews WHERE card_id=?")
+                .bind(cid.to_string())
+                .execute(&mut *tx)
+                .await
+                .map_err(write_err("del reviews"))?;
+            sqlx::query("DELETE FROM cards WHERE id=?")
+                .bind(cid.to_string())
+                .execute(&mut *tx)
+                .await
+                .map_err(write_err("del card"))?;
+        }
+
+        sqlx::query("UPDATE cards SET deck_id=? WHERE deck_id=?")
+            .bind(into.to_string())
+            .bind(from.to_string())
             .execute(&mut *tx)
             .await
-            .map_err(|_| CoreError::Storage("del cards"))?;
+            .map_err(write_err("move cards"))?;
 
-        let res = sqlx::query("DELETE FROM decks WHERE id=?")
-            .bind(id.to_string())
+        sqlx::query(
+            "UPDATE decks SET parent_id=(SELECT parent_id FROM decks WHERE id=?) WHERE parent_id=?",
+        )
+        .bind(from.to_string())
+        .bind(from.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(write_err("update deck"))?;
+
+        sqlx::query("DELETE FROM decks WHERE id=?")
+            .bind(from.to_string())
             .execute(&mut *tx)
             .await
-            .map_err(|_| CoreError::Storage("del deck"))?;
-        if res.rows_affected() == 0 {
+            .map_err(write_err("del deck"))?;
+
+        tx.commit().await.map_err(write_err("tx commit"))
+    }
+
+    async fn reset_deck(&self, deck_id: DeckId, keep_history: bool) -> Result<u64, CoreError> {
+        let mut tx = self.pool.begin().await.map_err(read_err("tx"))?;
+        let found = sqlx::query("SELECT 1 FROM decks WHERE id=? LIMIT 1")
+            .bind(deck_id.to_string())
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(read_err("read deck"))?;
+        if found.is_none() {
             tx.rollback().await.ok();
             return Err(CoreError::NotFound("deck"));
         }
 
-        tx.commit()
+        if !keep_history {
+            sqlx::query(
+                "DELETE FROM reviews WHERE card_id IN (SELECT id FROM cards WHERE deck_id=?)",
+            )
+            .bind(deck_id.to_string())
+            .execute(&mut *tx)
             .await
-            .map_err(|_| CoreError::Storage("tx commit"))
+            .map_err(write_err("del reviews"))?;
+        }
+        let res = sqlx::query(
+            r#"UPDATE cards SET reps=0, interval_days=0, ef=?, due_at=?,
+                      last_grade=NULL, last_reviewed_at=NULL, stability=0, difficulty=0,
+                      lapses=0, relearning_step=NULL
+               WHERE deck_id=?"#,
+        )
+        .bind(EF_DEFAULT as f64)
+        .bind(dt_to_str(Utc::now()))
+        .bind(deck_id.to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(write_err("reset cards"))?;
+
+        tx.commit().await.map_err(write_err("tx commit"))?;
+        Ok(res.rows_affected())
     }
 
     // ===== Cards =====
@@ -190,28 +723,28 @@ impl Repository for SqliteRepo {
         hint: Option<&str>,
         tags: &[String],
     ) -> Result<Card, CoreError> {
-        // Ensure deck exists
-        let exists = sqlx::query("SELECT 1 FROM decks WHERE id=? LIMIT 1")
-            .bind(deck_id.to_string())
-            .fetch_optional(&self.pool)
-            .await
-            .map_err(|_| CoreError::Storage("read deck"))?
-            .is_some();
-        if !exists {
-            return Err(CoreError::NotFound("deck"));
-        }
-
         let mut card = Card::new(deck_id, front, back);
         card.hint = hint.map(|s| s.to_string());
         card.tags = tags.to_vec();
+        self.insert_card(card).await
+    }
+
+    async fn insert_card(&self, card: Card) -> Result<Card, CoreError> {
+        if !self.deck_exists(card.deck_id).await? {
+            return Err(CoreError::NotFound("deck"));
+        }
+        if self.card_exists(card.id).await? {
+            return Err(CoreError::Conflict("card already exists"));
+        }
 
         sqlx::query(
             r#"
             INSERT INTO cards (
               id, deck_id, front, back, hint, tags, reps, interval_days, ef, due_at,
-              last_grade, last_reviewed_at, suspended, created_at
+              last_grade, last_reviewed_at, suspended, created_at, alt_backs, source,
+              stability, difficulty, lapses, relearning_step
             )
-            VALUES (?,?,?,?,?,?,?,?,?,?,?,?,?,?)
+            VALUES (?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?)
             "#,
         )
         .bind(card.id.to_string())
@@ -228,9 +761,15 @@ impl Repository for SqliteRepo {
         .bind(card.last_reviewed_at.map(dt_to_str))
         .bind(bool_to_i(card.suspended))
         .bind(dt_to_str(card.created_at))
+        .bind(serde_json::to_string(&card.alt_backs).unwrap())
+        .bind(card.source.clone())
+        .bind(card.stability as f64)
+        .bind(card.difficulty as f64)
+        .bind(card.lapses as i64)
+        .bind(card.relearning_step.map(i64::from))
         .execute(&self.pool)
         .await
-        .map_err(|_| CoreError::Storage("insert card"))?;
+        .map_err(write_err("insert card"))?;
 
         Ok(card)
     }
@@ -238,37 +777,49 @@ impl Repository for SqliteRepo {
     async fn get_card(&self, id: CardId) -> Result<Card, CoreError> {
         let row = sqlx::query(
             r#"SELECT id,deck_id,front,back,hint,tags,reps,interval_days,ef,due_at,
-                       last_grade,last_reviewed_at,suspended,created_at
+                       last_grade,last_reviewed_at,suspended,created_at,alt_backs,source,
+                          stability,difficulty,lapses,relearning_step
                FROM cards WHERE id=?"#,
         )
         .bind(id.to_string())
         .fetch_optional(&self.pool)
         .await
-        .map_err(|_| CoreError::Storage("read card"))?;
+        .map_err(read_err("read card"))?;
         let row = row.ok_or(CoreError::NotFound("card"))?;
         Ok(row_into_card(row)?)
     }
 
+    async fn card_exists(&self, id: CardId) -> Result<bool, CoreError> {
+        let row = sqlx::query("SELECT 1 FROM cards WHERE id=? LIMIT 1")
+            .bind(id.to_string())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(read_err("read card"))?;
+        Ok(row.is_some())
+    }
+
     async fn list_cards(&self, deck_id: Option<DeckId>) -> Result<Vec<Card>, CoreError> {
         let rows = if let Some(did) = deck_id {
             sqlx::query(
                 r#"SELECT id,deck_id,front,back,hint,tags,reps,interval_days,ef,due_at,
-                          last_grade,last_reviewed_at,suspended,created_at
-                   FROM cards WHERE deck_id=? ORDER BY created_at ASC"#,
+                          last_grade,last_reviewed_at,suspended,created_at,alt_backs,source,
+                          stability,difficulty,lapses,relearning_step
+                   FROM cards WHERE deck_id=? ORDER BY created_at ASC, id ASC"#,
             )
             .bind(did.to_string())
             .fetch_all(&self.pool)
             .await
-            .map_err(|_| CoreError::Storage("list cards"))?
+            .map_err(read_err("list cards"))?
         } else {
             sqlx::query(
                 r#"SELECT id,deck_id,front,back,hint,tags,reps,interval_days,ef,due_at,
-                          last_grade,last_reviewed_at,suspended,created_at
-                   FROM cards ORDER BY created_at ASC"#,
+                          last_grade,last_reviewed_at,suspended,created_at,alt_backs,source,
+                          stability,difficulty,lapses,relearning_step
+                   FROM cards ORDER BY created_at ASC, id ASC"#,
             )
             .fetch_all(&self.pool)
             .await
-            .map_err(|_| CoreError::Storage("list cards"))?
+            .map_err(read_err("list cards"))?
         };
         let mut v = Vec::with_capacity(rows.len());
         for row in rows {
@@ -277,12 +828,45 @@ impl Repository for SqliteRepo {
         Ok(v)
     }
 
+    async fn count_cards(&self, deck_id: Option<DeckId>) -> Result<u64, CoreError> {
+        let n: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM cards WHERE (?1 IS NULL OR deck_id=?1)")
+                .bind(deck_id.map(|d| d.to_string()))
+                .fetch_one(&self.pool)
+                .await
+                .map_err(read_err("count cards"))?;
+        Ok(n as u64)
+    }
+
+    async fn list_cards_paged(
+        &self,
+        deck_id: Option<DeckId>,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<Card>, CoreError> {
+        let rows = sqlx::query(
+            r#"SELECT id,deck_id,front,back,hint,tags,reps,interval_days,ef,due_at,
+                      last_grade,last_reviewed_at,suspended,created_at,alt_backs,source,
+                          stability,difficulty,lapses,relearning_step
+               FROM cards WHERE (?1 IS NULL OR deck_id=?1)
+               ORDER BY created_at ASC, id ASC LIMIT ?2 OFFSET ?3"#,
+        )
+        .bind(deck_id.map(|d| d.to_string()))
+        .bind(i64::try_from(limit).unwrap_or(i64::MAX))
+        .bind(i64::try_from(offset).unwrap_or(i64::MAX))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(read_err("list cards"))?;
+        rows.into_iter().map(row_into_card).collect()
+    }
+
     async fn update_card(&self, card: &Card) -> Result<Card, CoreError> {
         let res = sqlx::query(
             r#"
             UPDATE cards SET
               deck_id=?, front=?, back=?, hint=?, tags=?, reps=?, interval_days=?,
-              ef=?, due_at=?, last_grade=?, last_reviewed_at=?, suspended=?
+              ef=?, due_at=?, last_grade=?, last_reviewed_at=?, suspended=?, alt_backs=?,
+              source=?, stability=?, difficulty=?, lapses=?, relearning_step=?
             WHERE id=?
             "#,
         )
@@ -298,39 +882,92 @@ impl Repository for SqliteRepo {
         .bind(card.last_grade.as_ref().map(grade_to_i))
         .bind(card.last_reviewed_at.map(dt_to_str))
         .bind(bool_to_i(card.suspended))
+        .bind(serde_json::to_string(&card.alt_backs).unwrap())
+        .bind(card.source.clone())
+        .bind(card.stability as f64)
+        .bind(card.difficulty as f64)
+        .bind(card.lapses as i64)
+        .bind(card.relearning_step.map(i64::from))
         .bind(card.id.to_string())
         .execute(&self.pool)
         .await
-        .map_err(|_| CoreError::Storage("update card"))?;
+        .map_err(write_err("update card"))?;
         if res.rows_affected() == 0 {
             return Err(CoreError::NotFound("card"));
         }
         Ok(card.clone())
     }
 
-    async fn delete_card(&self, id: CardId) -> Result<(), CoreError> {
-        let mut tx = self
-            .pool
-            .begin()
+    async fn patch_card(&self, id: CardId, patch: CardPatch) -> Result<Card, CoreError> {
+        if patch.is_empty() {
+            return self.get_card(id).await;
+        }
+        let row = patch_query(id, patch)
+            .build()
+            .fetch_optional(&self.pool)
             .await
-            .map_err(|_| CoreError::Storage("tx"))?;
+            .map_err(write_err("patch card"))?;
+        row_into_card(row.ok_or(CoreError::NotFound("card"))?)
+    }
+
+    async fn patch_cards(&self, patches: &[(CardId, CardPatch)]) -> Result<(), CoreError> {
+        let mut tx = self.pool.begin().await.map_err(read_err("tx"))?;
+        for (id, patch) in patches.iter().filter(|(_, p)| !p.is_empty()) {
+            patch_query(*id, patch.clone())
+                .build()
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(write_err("patch card"))?
+                .ok_or(CoreError::NotFound("card"))?;
+        }
+        tx.commit().await.map_err(write_err("patch card"))?;
+        Ok(())
+    }
+
+    async fn delete_card(&self, id: CardId) -> Result<(), CoreError> {
+        let mut tx = self.pool.begin().await.map_err(read_err("tx"))?;
         sqlx::query("DELETE FROM reviews WHERE card_id=?")
             .bind(id.to_string())
             .execute(&mut *tx)
             .await
-            .map_err(|_| CoreError::Storage("del reviews"))?;
+            .map_err(write_err("del reviews"))?;
         let res = sqlx::query("DELETE FROM cards WHERE id=?")
             .bind(id.to_string())
             .execute(&mut *tx)
             .await
-            .map_err(|_| CoreError::Storage("del card"))?;
+            .map_err(write_err("del card"))?;
         if res.rows_affected() == 0 {
             tx.rollback().await.ok();
             return Err(CoreError::NotFound("card"));
         }
-        tx.commit()
+        tx.commit().await.map_err(write_err("tx commit"))
+    }
+
+    async fn merge_cards(&self, keeper: CardId, dupes: &[CardId]) -> Result<(), CoreError> {
+        let mut tx = self.pool.begin().await.map_err(read_err("tx"))?;
+        let found: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM cards WHERE id=?")
+            .bind(keeper.to_string())
+            .fetch_one(&mut *tx)
             .await
-            .map_err(|_| CoreError::Storage("tx commit"))
+            .map_err(read_err("read card"))?;
+        if found == 0 {
+            tx.rollback().await.ok();
+            return Err(CoreError::NotFound("card"));
+        }
+        for cid in dupes.iter().filter(|c| **c != keeper) {
+            sqlx::query("UPDATE reviews SET card_id=? WHERE card_id=?")
+                .bind(keeper.to_string())
+                .bind(cid.to_string())
+                .execute(&mut *tx)
+                .await
+                .map_err(write_err("move reviews"))?;
+            sqlx::query("DELETE FROM cards WHERE id=?")
+                .bind(cid.to_string())
+                .execute(&mut *tx)
+                .await
+                .map_err(write_err("del card"))?;
+        }
+        tx.commit().await.map_err(write_err("tx commit"))
     }
 
     async fn set_suspended(&self, id: CardId, suspended: bool) -> Result<(), CoreError> {
@@ -339,7 +976,23 @@ impl Repository for SqliteRepo {
             .bind(id.to_string())
             .execute(&self.pool)
             .await
-            .map_err(|_| CoreError::Storage("suspend"))?;
+            .map_err(write_err("suspend"))?;
+        if res.rows_affected() == 0 {
+            return Err(CoreError::NotFound("card"));
+        }
+        Ok(())
+    }
+
+    async fn move_card(&self, card_id: CardId, new_deck: DeckId) -> Result<(), CoreError> {
+        if !self.deck_exists(new_deck).await? {
+            return Err(CoreError::NotFound("deck"));
+        }
+        let res = sqlx::query("UPDATE cards SET deck_id=? WHERE id=?")
+            .bind(new_deck.to_string())
+            .bind(card_id.to_string())
+            .execute(&self.pool)
+            .await
+            .map_err(write_err("move card"))?;
         if res.rows_affected() == 0 {
             return Err(CoreError::NotFound("card"));
         }
@@ -349,8 +1002,8 @@ impl Repository for SqliteRepo {
     // ===== Reviews =====
     async fn insert_review(&self, review: &Review) -> Result<(), CoreError> {
         sqlx::query(
-            r#"INSERT INTO reviews (id,card_id,grade,reviewed_at,interval_applied,ef_after)
-               VALUES (?,?,?,?,?,?)"#,
+            r#"INSERT INTO reviews (id,card_id,grade,reviewed_at,interval_applied,ef_after,scheduler,duration_ms)
+               VALUES (?,?,?,?,?,?,?,?)"#,
         )
         .bind(review.id.to_string())
         .bind(review.card_id.to_string())
@@ -358,38 +1011,114 @@ impl Repository for SqliteRepo {
         .bind(dt_to_str(review.reviewed_at))
         .bind(review.interval_applied as i64)
         .bind(review.ef_after as f64)
+        .bind(&review.scheduler)
+        .bind(review.duration_ms.map(i64::from))
         .execute(&self.pool)
         .await
-        .map_err(|_| CoreError::Storage("insert review"))?;
+        .map_err(write_err("insert review"))?;
         Ok(())
     }
 
     async fn list_reviews_for_card(&self, card_id: CardId) -> Result<Vec<Review>, CoreError> {
         let rows = sqlx::query(
-            r#"SELECT id,card_id,grade,reviewed_at,interval_applied,ef_after
+            r#"SELECT id,card_id,grade,reviewed_at,interval_applied,ef_after,scheduler,duration_ms
                FROM reviews WHERE card_id=? ORDER BY reviewed_at ASC"#,
         )
         .bind(card_id.to_string())
         .fetch_all(&self.pool)
         .await
-        .map_err(|_| CoreError::Storage("list reviews"))?;
-        let mut v = Vec::with_capacity(rows.len());
-        for row in rows {
-            v.push(Review {
-                id: uuid_from_str(row.get::<String, _>("id"))?,
-                card_id: uuid_from_str(row.get::<String, _>("card_id"))?,
-                grade: grade_from_i(row.get::<i64, _>("grade"))
-                    .ok_or(CoreError::Invalid("grade"))?,
-                reviewed_at: dt_from_str(row.get::<String, _>("reviewed_at"))?,
-                interval_applied: row.get::<i64, _>("interval_applied") as i32,
-                ef_after: row.get::<f64, _>("ef_after") as f32,
-            });
-        }
-        Ok(v)
+        .map_err(read_err("list reviews"))?;
+        rows.into_iter().map(row_into_review).collect()
+    }
+
+    async fn list_reviews_for_card_paged(
+        &self,
+        card_id: CardId,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<Review>, CoreError> {
+        // Walks idx_reviews_card_time backwards, so only the page is read.
+        let rows = sqlx::query(
+            r#"SELECT id,card_id,grade,reviewed_at,interval_applied,ef_after,scheduler,duration_ms
+               FROM reviews WHERE card_id=? ORDER BY reviewed_at DESC LIMIT ? OFFSET ?"#,
+        )
+        .bind(card_id.to_string())
+        .bind(i64::try_from(limit).unwrap_or(i64::MAX))
+        .bind(i64::try_from(offset).unwrap_or(i64::MAX))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(read_err("list reviews"))?;
+        rows.into_iter().map(row_into_review).collect()
+    }
+
+    async fn list_reviews(&self) -> Result<Vec<Review>, CoreError> {
+        let rows = sqlx::query(
+            r#"SELECT id,card_id,grade,reviewed_at,interval_applied,ef_after,scheduler,duration_ms
+               FROM reviews ORDER BY reviewed_at ASC"#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(read_err("list reviews"))?;
+        rows.into_iter().map(row_into_review).collect()
+    }
+
+    async fn list_reviews_in_range(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Review>, CoreError> {
+        // reviewed_at is UTC RFC 3339 text, which sorts chronologically, so
+        // a plain text range can use idx_reviews_time.
+        let rows = sqlx::query(
+            r#"SELECT id,card_id,grade,reviewed_at,interval_applied,ef_after,scheduler,duration_ms
+               FROM reviews
+               WHERE reviewed_at >= ?1 AND reviewed_at < ?2
+               ORDER BY reviewed_at ASC"#,
+        )
+        .bind(bound_to_str(from))
+        .bind(bound_to_str(to))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(read_err("list reviews in range"))?;
+        rows.into_iter().map(row_into_review).collect()
     }
 }
 
 // ===== Helpers =====
+/// Maps a read failure to `Storage(ctx)`, or to `Timeout(POOL_TIMEOUT_MSG)`
+/// when no connection could be acquired in time.
+fn read_err(ctx: &'static str) -> impl Fn(sqlx::Error) -> CoreError {
+    move |e| match e {
+        sqlx::Error::PoolTimedOut => CoreError::Timeout(POOL_TIMEOUT_MSG),
+        _ => CoreError::Storage(ctx),
+    }
+}
+
+/// Like [`read_err`], but maps to `Storage(READ_ONLY_MSG)` when SQLite
+/// reports a read-only database or a permission problem.
+fn write_err(ctx: &'static str) -> impl Fn(sqlx::Error) -> CoreError {
+    move |e| {
+        if is_read_only(&e) {
+            CoreError::Storage(READ_ONLY_MSG)
+        } else {
+            read_err(ctx)(e)
+        }
+    }
+}
+
+fn is_read_only(e: &sqlx::Error) -> bool {
+    match e {
+        // Primary result codes: SQLITE_PERM = 3, SQLITE_READONLY = 8.
+        sqlx::Error::Database(db) => db
+            .code()
+            .and_then(|c| c.parse::<i32>().ok())
+            .map(|c| matches!(c & 0xff, 3 | 8))
+            .unwrap_or(false),
+        sqlx::Error::Io(io) => io.kind() == std::io::ErrorKind::PermissionDenied,
+        _ => false,
+    }
+}
+
 fn uuid_from_str(s: String) -> Result<uuid::Uuid, CoreError> {
     uuid::Uuid::parse_str(&s).map_err(|_| CoreError::Invalid("uuid"))
 }
@@ -398,6 +1127,14 @@ fn dt_to_str(dt: DateTime<Utc>) -> String {
     dt.to_rfc3339()
 }
 
+/// `dt_to_str` for a range bound. Years outside 0000-9999 gain a sign and
+/// would no longer compare as text, so the bound is clamped first.
+fn bound_to_str(dt: DateTime<Utc>) -> String {
+    let lo = Utc.with_ymd_and_hms(0, 1, 1, 0, 0, 0).unwrap();
+    let hi = Utc.with_ymd_and_hms(9999, 12, 31, 23, 59, 59).unwrap();
+    dt_to_str(dt.clamp(lo, hi))
+}
+
 fn dt_from_str(s: String) -> Result<DateTime<Utc>, CoreError> {
     chrono::DateTime::parse_from_rfc3339(&s)
         .map_err(|_| CoreError::Invalid("datetime"))
@@ -406,19 +1143,50 @@ fn dt_from_str(s: String) -> Result<DateTime<Utc>, CoreError> {
 
 fn grade_to_i(g: &Grade) -> i64 {
     match g {
+        Grade::Again => 0,
         Grade::Hard => 1,
         Grade::Medium => 2,
         Grade::Easy => 3,
     }
 }
 
-fn grade_from_i(i: i64) -> Option<Grade> {
-    match i {
-        1 => Some(Grade::Hard),
-        2 => Some(Grade::Medium),
-        3 => Some(Grade::Easy),
-        _ => None,
+/// `UPDATE ... RETURNING` for the fields set in `patch` (which must not be empty).
+fn patch_query(id: CardId, patch: CardPatch) -> QueryBuilder<'static, Sqlite> {
+    let mut qb = QueryBuilder::<Sqlite>::new("UPDATE cards SET ");
+    let mut set = qb.separated(", ");
+    if let Some(f) = patch.front {
+        set.push("front=").push_bind_unseparated(f);
     }
+    if let Some(b) = patch.back {
+        set.push("back=").push_bind_unseparated(b);
+    }
+    if let Some(h) = patch.hint {
+        set.push("hint=").push_bind_unseparated(h);
+    }
+    if let Some(s) = patch.source {
+        set.push("source=").push_bind_unseparated(s);
+    }
+    if let Some(t) = patch.tags {
+        set.push("tags=")
+            .push_bind_unseparated(serde_json::to_string(&t).unwrap());
+    }
+    if let Some(s) = patch.suspended {
+        set.push("suspended=").push_bind_unseparated(bool_to_i(s));
+    }
+    qb.push(" WHERE id=").push_bind(id.to_string()).push(
+        " RETURNING id,deck_id,front,back,hint,tags,reps,interval_days,ef,due_at,\
+           last_grade,last_reviewed_at,suspended,created_at,alt_backs,source,\
+           stability,difficulty,lapses,relearning_step",
+    );
+    qb
+}
+
+fn prompt_from_str(s: &str) -> Result<PromptWith, CoreError> {
+    PromptWith::from_name(s).ok_or(CoreError::Invalid("prompt_with"))
+}
+
+fn grade_from_i(i: i64) -> Option<Grade> {
+    i32::try_from(i).ok().and_then(Grade::from_score)
 }
 
 fn bool_to_i(b: bool) -> i64 {
@@ -429,16 +1197,70 @@ fn bool_to_i(b: bool) -> i64 {
     }
 }
 
+/// Tags are stored as a JSON array. If that fails to parse (hand-edited or
+/// legacy rows), fall back to a comma/semicolon-separated list rather than
+/// silently dropping them, and log a warning (never to stderr, which the TUI
+/// draws over).
+fn parse_tags(raw: &str) -> Vec<String> {
+    if let Ok(tags) = serde_json::from_str::<Vec<String>>(raw) {
+        return tags;
+    }
+    let tags: Vec<String> = raw
+        .trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .split([',', ';'])
+        .map(|t| t.trim().trim_matches('"').trim())
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+        .collect();
+    if !raw.trim().is_empty() {
+        tracing::warn!(raw, ?tags, "card tags are not valid JSON; recovered them");
+    }
+    tags
+}
+
+fn row_into_deck(row: &sqlx::sqlite::SqliteRow) -> Result<Deck, CoreError> {
+    Ok(Deck {
+        id: uuid_from_str(row.get::<String, _>("id"))?,
+        name: row.get::<String, _>("name"),
+        created_at: dt_from_str(row.get::<String, _>("created_at"))?,
+        position: row.get::<i64, _>("position") as i32,
+        prompt_with: prompt_from_str(&row.get::<String, _>("prompt_with"))?,
+        parent_id: row
+            .get::<Option<String>, _>("parent_id")
+            .map(uuid_from_str)
+            .transpose()?,
+    })
+}
+
+fn row_into_counts(row: &sqlx::sqlite::SqliteRow) -> Result<DeckCounts, CoreError> {
+    Ok(DeckCounts {
+        total: row.get::<i64, _>("total") as u32,
+        new: row.get::<i64, _>("new") as u32,
+        due: row.get::<i64, _>("due") as u32,
+        suspended: row.get::<i64, _>("suspended") as u32,
+        last_reviewed_at: row
+            .get::<Option<String>, _>("last_reviewed_at")
+            .map(dt_from_str)
+            .transpose()?,
+    })
+}
+
 fn row_into_card(row: sqlx::sqlite::SqliteRow) -> Result<Card, CoreError> {
     let tags_json: String = row.get("tags");
-    let tags: Vec<String> = serde_json::from_str(&tags_json).unwrap_or_default();
+    let tags = parse_tags(&tags_json);
+    let alt_backs: Vec<String> =
+        serde_json::from_str(&row.get::<String, _>("alt_backs")).unwrap_or_default();
 
     Ok(Card {
         id: uuid_from_str(row.get::<String, _>("id"))?,
         deck_id: uuid_from_str(row.get::<String, _>("deck_id"))?,
         front: row.get::<String, _>("front"),
         back: row.get::<String, _>("back"),
+        alt_backs,
         hint: row.get::<Option<String>, _>("hint"),
+        source: row.get::<Option<String>, _>("source"),
         tags,
         reps: row.get::<i64, _>("reps") as u32,
         interval_days: row.get::<i64, _>("interval_days") as u32,
@@ -453,5 +1275,27 @@ fn row_into_card(row: sqlx::sqlite::SqliteRow) -> Result<Card, CoreError> {
             .transpose()?,
         suspended: row.get::<i64, _>("suspended") != 0,
         created_at: dt_from_str(row.get::<String, _>("created_at"))?,
+        stability: row.get::<f64, _>("stability") as f32,
+        difficulty: row.get::<f64, _>("difficulty") as f32,
+        lapses: u32::try_from(row.get::<i64, _>("lapses"))
+            .map_err(|_| CoreError::Invalid("lapses"))?,
+        relearning_step: row
+            .get::<Option<i64>, _>("relearning_step")
+            .map(u8::try_from)
+            .transpose()
+            .map_err(|_| CoreError::Invalid("relearning step"))?,
+    })
+}
+
+fn row_into_review(row: sqlx::sqlite::SqliteRow) -> Result<Review, CoreError> {
+    Ok(Review {
+        id: uuid_from_str(row.get::<String, _>("id"))?,
+        card_id: uuid_from_str(row.get::<String, _>("card_id"))?,
+        grade: grade_from_i(row.get::<i64, _>("grade")).ok_or(CoreError::Invalid("grade"))?,
+        reviewed_at: dt_from_str(row.get::<String, _>("reviewed_at"))?,
+        interval_applied: row.get::<i64, _>("interval_applied") as i32,
+        ef_after: row.get::<f64, _>("ef_after") as f32,
+        scheduler: row.get::<String, _>("scheduler"),
+        duration_ms: row.get::<Option<i64>, _>("duration_ms").map(|ms| ms as u32),
     })
 }
@@ -0,0 +1,31 @@
+use flashmaster_core::{apply_grade, Grade, Repository, Review};
+use flashmaster_sqlite::SqliteRepo;
+
+#[tokio::test]
+async fn again_grades_round_trip_next_to_old_ones() {
+    let repo = SqliteRepo::open_memory().await.unwrap();
+    let deck = repo.create_deck("Spanish").await.unwrap();
+    let card = repo
+        .add_card(deck.id, "hola", "hello", None, &[])
+        .await
+        .unwrap();
+
+    repo.insert_review(&Review::builder(card.id, Grade::Hard).build())
+        .await
+        .unwrap();
+    let out = apply_grade(card, Grade::Again);
+    repo.update_card(&out.updated_card).await.unwrap();
+    repo.insert_review(&out.review).await.unwrap();
+
+    let loaded = repo.get_card(out.updated_card.id).await.unwrap();
+    assert_eq!(loaded.last_grade, Some(Grade::Again));
+    let mut grades: Vec<Grade> = repo
+        .list_reviews_for_card(loaded.id)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|r| r.grade)
+        .collect();
+    grades.sort_by_key(Grade::as_score);
+    assert_eq!(grades, [Grade::Again, Grade::Hard]);
+}
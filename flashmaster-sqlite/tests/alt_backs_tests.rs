@@ -0,0 +1,19 @@
+use flashmaster_core::Repository;
+use flashmaster_sqlite::SqliteRepo;
+
+#[tokio::test]
+async fn alt_backs_round_trip() {
+    let repo = SqliteRepo::open_memory().await.unwrap();
+    let deck = repo.create_deck("German").await.unwrap();
+    let mut card = repo
+        .add_card(deck.id, "Wagen", "car", None, &[])
+        .await
+        .unwrap();
+    assert!(card.alt_backs.is_empty());
+
+    card.alt_backs = vec!["auto".into(), "automobile".into()];
+    repo.update_card(&card).await.unwrap();
+
+    let loaded = repo.get_card(card.id).await.unwrap();
+    assert_eq!(loaded.alt_backs, ["auto", "automobile"]);
+}
@@ -0,0 +1,25 @@
+use flashmaster_core::{CoreError, Repository};
+use flashmaster_sqlite::SqliteRepo;
+
+#[tokio::test]
+async fn backup_is_a_readable_copy() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("fm.sqlite3");
+    std::fs::File::create(&path).unwrap();
+    let repo = SqliteRepo::open_file(&path).await.unwrap();
+    let deck = repo.create_deck("Spanish").await.unwrap();
+    repo.add_card(deck.id, "hola", "hello", None, &[])
+        .await
+        .unwrap();
+
+    let dest = dir.path().join("copy.sqlite3");
+    repo.backup_to(&dest).await.unwrap();
+    let copy = SqliteRepo::open_file_readonly(&dest).await.unwrap();
+    assert_eq!(copy.list_decks().await.unwrap()[0].name, "Spanish");
+    assert_eq!(copy.list_cards(None).await.unwrap().len(), 1);
+
+    assert!(matches!(
+        repo.backup_to(&dest).await,
+        Err(CoreError::Conflict(_))
+    ));
+}
@@ -0,0 +1,10 @@
+use flashmaster_core::Repository;
+use flashmaster_sqlite::SqliteRepo;
+
+#[tokio::test]
+async fn close_shuts_the_pool() {
+    let repo = SqliteRepo::open_memory().await.unwrap();
+    repo.create_deck("Spanish").await.unwrap();
+    repo.close().await.unwrap();
+    assert!(repo.ping().await.is_err());
+}
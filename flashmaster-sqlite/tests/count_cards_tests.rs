@@ -0,0 +1,34 @@
+use flashmaster_core::Repository;
+use flashmaster_sqlite::SqliteRepo;
+
+#[tokio::test]
+async fn counts_match_the_lists() {
+    let repo = SqliteRepo::open_memory().await.unwrap();
+    assert_eq!(repo.count_decks().await.unwrap(), 0);
+    assert_eq!(repo.count_cards(None).await.unwrap(), 0);
+
+    let spanish = repo.create_deck("Spanish").await.unwrap();
+    let french = repo.create_deck("French").await.unwrap();
+    let empty = repo.create_deck("Empty").await.unwrap();
+    for i in 0..3 {
+        repo.add_card(spanish.id, &format!("q{i}"), "a", None, &[])
+            .await
+            .unwrap();
+    }
+    repo.add_card(french.id, "bonjour", "hello", None, &[])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        repo.count_decks().await.unwrap(),
+        repo.list_decks().await.unwrap().len() as u64
+    );
+    for deck in [None, Some(spanish.id), Some(french.id), Some(empty.id)] {
+        assert_eq!(
+            repo.count_cards(deck).await.unwrap(),
+            repo.list_cards(deck).await.unwrap().len() as u64,
+            "{deck:?}"
+        );
+    }
+    assert_eq!(repo.count_cards(None).await.unwrap(), 4);
+}
@@ -0,0 +1,84 @@
+use chrono::{Duration, Utc};
+use flashmaster_core::{DeckCounts, Repository};
+use flashmaster_sqlite::SqliteRepo;
+
+#[tokio::test]
+async fn grouped_counts_match_per_deck_counts() {
+    let repo = SqliteRepo::open_memory().await.unwrap();
+    let now = Utc::now();
+    let lang = repo.create_deck("Lang").await.unwrap();
+    let empty = repo.create_deck("Empty").await.unwrap();
+
+    repo.add_card(lang.id, "new", "a", None, &[]).await.unwrap();
+    for (front, offset) in [("due", -Duration::minutes(5)), ("later", Duration::days(2))] {
+        let mut c = repo.add_card(lang.id, front, "a", None, &[]).await.unwrap();
+        c.reps = 1;
+        c.due_at = now + offset;
+        repo.update_card(&c).await.unwrap();
+    }
+    let held = repo
+        .add_card(lang.id, "held", "a", None, &[])
+        .await
+        .unwrap();
+    repo.set_suspended(held.id, true).await.unwrap();
+
+    let listed = repo.list_decks_with_counts(now).await.unwrap();
+    let names: Vec<_> = listed.iter().map(|(d, _)| d.name.as_str()).collect();
+    assert_eq!(names, ["Lang", "Empty"]);
+    for (deck, counts) in &listed {
+        assert_eq!(*counts, repo.deck_counts(deck.id, now).await.unwrap());
+        let cards = repo.list_cards(Some(deck.id)).await.unwrap();
+        assert_eq!(*counts, DeckCounts::of(&cards, now));
+    }
+    assert_eq!(
+        listed[0].1,
+        DeckCounts {
+            total: 4,
+            new: 1,
+            due: 1,
+            suspended: 1,
+            last_reviewed_at: None,
+        }
+    );
+    assert_eq!(listed[1].1, DeckCounts::default());
+    assert_eq!(listed[1].0.id, empty.id);
+}
+
+#[tokio::test]
+async fn last_reviewed_is_the_latest_card_review() {
+    let repo = SqliteRepo::open_memory().await.unwrap();
+    let now = Utc::now();
+    let studied = repo.create_deck("Studied").await.unwrap();
+    let fresh = repo.create_deck("Fresh").await.unwrap();
+    repo.add_card(fresh.id, "q", "a", None, &[]).await.unwrap();
+    for (front, ago) in [
+        ("old", Duration::days(9)),
+        ("recent", Duration::minutes(30)),
+    ] {
+        let mut c = repo
+            .add_card(studied.id, front, "a", None, &[])
+            .await
+            .unwrap();
+        c.last_reviewed_at = Some(now - ago);
+        repo.update_card(&c).await.unwrap();
+    }
+
+    let listed = repo.list_decks_with_counts(now).await.unwrap();
+    let last = |id| {
+        listed
+            .iter()
+            .find(|(d, _)| d.id == id)
+            .unwrap()
+            .1
+            .last_reviewed_at
+    };
+    assert_eq!(last(studied.id), Some(now - Duration::minutes(30)));
+    assert_eq!(last(fresh.id), None);
+    assert_eq!(
+        repo.deck_counts(studied.id, now)
+            .await
+            .unwrap()
+            .last_reviewed_at,
+        Some(now - Duration::minutes(30))
+    );
+}
@@ -0,0 +1,19 @@
+use chrono::Utc;
+use flashmaster_core::{Grade, Repository, Review};
+use flashmaster_sqlite::SqliteRepo;
+
+#[tokio::test]
+async fn review_duration_round_trips() {
+    let repo = SqliteRepo::open_memory().await.unwrap();
+    let deck = repo.create_deck("Lang").await.unwrap();
+    let card = repo.add_card(deck.id, "q", "a", None, &[]).await.unwrap();
+    let timed = Review::new_timed(card.id, Grade::Easy, Utc::now(), 1, 2.6, Some(4200));
+    let untimed = Review::new(card.id, Grade::Hard, Utc::now(), 1, 2.4);
+    repo.insert_review(&timed).await.unwrap();
+    repo.insert_review(&untimed).await.unwrap();
+
+    let stored = repo.list_reviews_for_card(card.id).await.unwrap();
+    let by_id = |id| stored.iter().find(|r| r.id == id).unwrap().duration_ms;
+    assert_eq!(by_id(timed.id), Some(4200));
+    assert_eq!(by_id(untimed.id), None);
+}
@@ -0,0 +1,18 @@
+use flashmaster_core::Repository;
+use flashmaster_sqlite::SqliteRepo;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn exists_checks_match_stored_ids() {
+    let repo = SqliteRepo::open_memory().await.unwrap();
+    let deck = repo.create_deck("Lang").await.unwrap();
+    let card = repo.add_card(deck.id, "q", "a", None, &[]).await.unwrap();
+
+    assert!(repo.deck_exists(deck.id).await.unwrap());
+    assert!(repo.card_exists(card.id).await.unwrap());
+    assert!(!repo.deck_exists(Uuid::new_v4()).await.unwrap());
+    assert!(!repo.card_exists(Uuid::new_v4()).await.unwrap());
+
+    repo.delete_card(card.id).await.unwrap();
+    assert!(!repo.card_exists(card.id).await.unwrap());
+}
@@ -0,0 +1,27 @@
+use flashmaster_core::{apply_grade_fsrs, FsrsParams, Grade, Repository};
+use flashmaster_sqlite::SqliteRepo;
+
+#[tokio::test]
+async fn fsrs_state_round_trips() {
+    let repo = SqliteRepo::open_memory().await.unwrap();
+    let deck = repo.create_deck("Spanish").await.unwrap();
+    let card = repo
+        .add_card(deck.id, "hola", "hello", None, &[])
+        .await
+        .unwrap();
+    assert_eq!((card.stability, card.difficulty), (0.0, 0.0));
+
+    let graded = apply_grade_fsrs(card, Grade::Easy, &FsrsParams::default()).updated_card;
+    repo.update_card(&graded).await.unwrap();
+    let loaded = repo.get_card(graded.id).await.unwrap();
+    assert_eq!(
+        (loaded.stability, loaded.difficulty),
+        (graded.stability, graded.difficulty)
+    );
+    let listed = repo.list_cards(Some(deck.id)).await.unwrap();
+    assert_eq!(listed[0].stability, graded.stability);
+
+    repo.reset_deck(deck.id, true).await.unwrap();
+    let reset = repo.get_card(graded.id).await.unwrap();
+    assert_eq!((reset.stability, reset.difficulty), (0.0, 0.0));
+}
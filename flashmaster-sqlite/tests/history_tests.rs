@@ -0,0 +1,39 @@
+use chrono::{Duration, Utc};
+use flashmaster_core::{Grade, Repository, Review};
+use flashmaster_sqlite::SqliteRepo;
+
+#[tokio::test]
+async fn most_recent_reviews_come_newest_first() {
+    let repo = SqliteRepo::open_memory().await.unwrap();
+    let deck = repo.create_deck("Lang").await.unwrap();
+    let card = repo.add_card(deck.id, "q", "a", None, &[]).await.unwrap();
+    let start = Utc::now() - Duration::days(30);
+    // Inserted out of order so the result can't just mirror insertion order.
+    for i in (0..20).rev() {
+        let r = Review::new(
+            card.id,
+            Grade::Medium,
+            start + Duration::days(i),
+            i as i32,
+            2.5,
+        );
+        repo.insert_review(&r).await.unwrap();
+    }
+
+    let last5 = repo.recent_reviews_for_card(card.id, 5).await.unwrap();
+    let days: Vec<i32> = last5.iter().map(|r| r.interval_applied).collect();
+    assert_eq!(days, vec![19, 18, 17, 16, 15]);
+
+    let page2 = repo
+        .list_reviews_for_card_paged(card.id, 5, 5)
+        .await
+        .unwrap();
+    let days: Vec<i32> = page2.iter().map(|r| r.interval_applied).collect();
+    assert_eq!(days, vec![14, 13, 12, 11, 10]);
+
+    assert!(repo
+        .list_reviews_for_card_paged(card.id, 20, 5)
+        .await
+        .unwrap()
+        .is_empty());
+}
@@ -0,0 +1,27 @@
+use chrono::{Duration, Utc};
+use flashmaster_core::{Card, CoreError, Repository};
+use flashmaster_sqlite::SqliteRepo;
+
+#[tokio::test]
+async fn insert_card_keeps_id_and_schedule() {
+    let repo = SqliteRepo::open_memory().await.unwrap();
+    let deck = repo.create_deck("Spanish").await.unwrap();
+    let mut card = Card::new(deck.id, "hola", "hello");
+    (card.reps, card.interval_days) = (3, 12);
+    card.due_at = Utc::now() + Duration::days(12);
+
+    repo.insert_card(card.clone()).await.unwrap();
+    let got = repo.get_card(card.id).await.unwrap();
+    assert_eq!((got.reps, got.interval_days), (3, 12));
+    assert_eq!(got.due_at.timestamp(), card.due_at.timestamp());
+
+    assert!(matches!(
+        repo.insert_card(card.clone()).await,
+        Err(CoreError::Conflict(_))
+    ));
+    let orphan = Card::new(uuid::Uuid::new_v4(), "x", "y");
+    assert!(matches!(
+        repo.insert_card(orphan).await,
+        Err(CoreError::NotFound(_))
+    ));
+}
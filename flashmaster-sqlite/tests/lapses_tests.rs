@@ -0,0 +1,84 @@
+use flashmaster_core::{apply_grade, Grade, Repository};
+use flashmaster_sqlite::SqliteRepo;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn lapses_round_trip_and_reset() {
+    let repo = SqliteRepo::open_memory().await.unwrap();
+    let deck = repo.create_deck("Spanish").await.unwrap();
+    let card = repo
+        .add_card(deck.id, "hola", "hello", None, &[])
+        .await
+        .unwrap();
+
+    let lapsed = apply_grade(card, Grade::Again).updated_card;
+    let lapsed = apply_grade(lapsed, Grade::Hard).updated_card;
+    repo.update_card(&lapsed).await.unwrap();
+    let loaded = repo.get_card(lapsed.id).await.unwrap();
+    assert_eq!(loaded.lapses, 2);
+    assert!(loaded.is_leech(2));
+
+    repo.reset_deck(deck.id, true).await.unwrap();
+    assert_eq!(repo.get_card(lapsed.id).await.unwrap().lapses, 0);
+}
+
+#[tokio::test]
+async fn cards_from_older_databases_have_no_lapses() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("old.sqlite3");
+    let deck_id = Uuid::new_v4();
+    {
+        let url = format!("sqlite://{}?mode=rwc", path.display());
+        let pool = sqlx::SqlitePool::connect(&url).await.unwrap();
+        for sql in [
+            "CREATE TABLE decks (id TEXT PRIMARY KEY, name TEXT NOT NULL, created_at TEXT NOT NULL)",
+            "CREATE TABLE cards (id TEXT PRIMARY KEY, deck_id TEXT NOT NULL, front TEXT NOT NULL,
+               back TEXT NOT NULL, hint TEXT, tags TEXT NOT NULL, reps INTEGER NOT NULL DEFAULT 0,
+               interval_days INTEGER NOT NULL DEFAULT 0, ef REAL NOT NULL DEFAULT 2.5,
+               due_at TEXT NOT NULL, last_grade INTEGER, last_reviewed_at TEXT,
+               suspended INTEGER NOT NULL DEFAULT 0, created_at TEXT NOT NULL)",
+        ] {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+        sqlx::query("INSERT INTO decks VALUES (?, 'Legacy', '2024-01-01T00:00:00Z')")
+            .bind(deck_id.to_string())
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO cards (id, deck_id, front, back, tags, reps, due_at, last_grade, created_at)
+             VALUES (?, ?, 'hola', 'hello', '[]', 3, '2024-01-05T00:00:00Z', 1, '2024-01-01T00:00:00Z')",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(deck_id.to_string())
+        .execute(&pool)
+        .await
+        .unwrap();
+        pool.close().await;
+    }
+
+    let repo = SqliteRepo::open_file(&path).await.unwrap();
+    let cards = repo.list_cards(None).await.unwrap();
+    assert_eq!((cards[0].reps, cards[0].lapses), (3, 0));
+}
+
+#[tokio::test]
+async fn relearning_step_round_trips_and_resets() {
+    let repo = SqliteRepo::open_memory().await.unwrap();
+    let deck = repo.create_deck("Spanish").await.unwrap();
+    let mut card = repo
+        .add_card(deck.id, "hola", "hello", None, &[])
+        .await
+        .unwrap();
+    assert_eq!(card.relearning_step, None);
+
+    card.relearning_step = Some(1);
+    repo.update_card(&card).await.unwrap();
+    assert_eq!(
+        repo.get_card(card.id).await.unwrap().relearning_step,
+        Some(1)
+    );
+
+    repo.reset_deck(deck.id, true).await.unwrap();
+    assert_eq!(repo.get_card(card.id).await.unwrap().relearning_step, None);
+}
@@ -0,0 +1,53 @@
+use flashmaster_core::{apply_grade, Grade, Repository};
+use flashmaster_sqlite::SqliteRepo;
+
+#[tokio::test]
+async fn merge_moves_cards_and_reviews() {
+    let repo = SqliteRepo::open_memory().await.unwrap();
+    let from = repo.create_deck("From").await.unwrap();
+    let into = repo.create_deck("Into").await.unwrap();
+    let kept = repo.add_card(from.id, "a", "b", None, &[]).await.unwrap();
+    let dropped = repo.add_card(from.id, "c", "d", None, &[]).await.unwrap();
+    repo.add_card(into.id, "e", "f", None, &[]).await.unwrap();
+
+    let out = apply_grade(kept.clone(), Grade::Easy);
+    repo.update_card(&out.updated_card).await.unwrap();
+    repo.insert_review(&out.review).await.unwrap();
+
+    repo.merge_decks(from.id, into.id, &[dropped.id])
+        .await
+        .unwrap();
+
+    assert!(repo.get_deck(from.id).await.is_err());
+    assert!(repo.get_card(dropped.id).await.is_err());
+    assert_eq!(repo.list_cards(Some(into.id)).await.unwrap().len(), 2);
+    assert_eq!(repo.get_card(kept.id).await.unwrap().deck_id, into.id);
+    assert_eq!(repo.list_reviews_for_card(kept.id).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn merge_cards_moves_reviews_onto_the_keeper() {
+    let repo = SqliteRepo::open_memory().await.unwrap();
+    let deck = repo.create_deck("Spanish").await.unwrap();
+    let keep = repo
+        .add_card(deck.id, "hola", "hello", None, &[])
+        .await
+        .unwrap();
+    let dup = repo
+        .add_card(deck.id, "Hola", "hello", None, &[])
+        .await
+        .unwrap();
+    let out = apply_grade(dup.clone(), Grade::Easy);
+    repo.insert_review(&out.review).await.unwrap();
+
+    repo.merge_cards(keep.id, &[dup.id]).await.unwrap();
+
+    assert!(repo.get_card(dup.id).await.is_err());
+    let moved = repo.list_reviews_for_card(keep.id).await.unwrap();
+    assert_eq!(moved.len(), 1);
+    assert_eq!(moved[0].id, out.review.id);
+    assert!(
+        repo.merge_cards(dup.id, &[keep.id]).await.is_err(),
+        "keeper is gone"
+    );
+}
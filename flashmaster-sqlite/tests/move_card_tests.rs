@@ -0,0 +1,33 @@
+use flashmaster_core::{apply_grade, CoreError, Grade, Repository};
+use flashmaster_sqlite::SqliteRepo;
+
+#[tokio::test]
+async fn move_card_keeps_schedule_and_history() {
+    let repo = SqliteRepo::open_memory().await.unwrap();
+    let spanish = repo.create_deck("Spanish").await.unwrap();
+    let verbs = repo.create_deck("Verbs").await.unwrap();
+    let card = repo
+        .add_card(spanish.id, "ser", "to be", None, &[])
+        .await
+        .unwrap();
+    let out = apply_grade(card, Grade::Easy);
+    repo.update_card(&out.updated_card).await.unwrap();
+    repo.insert_review(&out.review).await.unwrap();
+
+    repo.move_card(out.updated_card.id, verbs.id).await.unwrap();
+    let moved = repo.get_card(out.updated_card.id).await.unwrap();
+    assert_eq!(moved.deck_id, verbs.id);
+    assert_eq!(moved.reps, 1);
+    assert_eq!(moved.due_at, out.updated_card.due_at);
+    assert_eq!(repo.list_reviews_for_card(moved.id).await.unwrap().len(), 1);
+    assert!(repo.list_cards(Some(spanish.id)).await.unwrap().is_empty());
+
+    assert!(matches!(
+        repo.move_card(moved.id, uuid::Uuid::new_v4()).await,
+        Err(CoreError::NotFound("deck"))
+    ));
+    assert!(matches!(
+        repo.move_card(uuid::Uuid::new_v4(), spanish.id).await,
+        Err(CoreError::NotFound("card"))
+    ));
+}
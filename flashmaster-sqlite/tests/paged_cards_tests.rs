@@ -0,0 +1,32 @@
+use flashmaster_core::Repository;
+use flashmaster_sqlite::SqliteRepo;
+
+#[tokio::test]
+async fn second_page_is_the_next_slice() {
+    let repo = SqliteRepo::open_memory().await.unwrap();
+    let deck = repo.create_deck("Spanish").await.unwrap();
+    let other = repo.create_deck("French").await.unwrap();
+    for i in 0..50 {
+        repo.add_card(deck.id, &format!("q{i}"), "a", None, &[])
+            .await
+            .unwrap();
+    }
+    repo.add_card(other.id, "bonjour", "hello", None, &[])
+        .await
+        .unwrap();
+
+    let all = repo.list_cards(Some(deck.id)).await.unwrap();
+    let page = repo.list_cards_paged(Some(deck.id), 20, 20).await.unwrap();
+    let ids: Vec<_> = page.iter().map(|c| c.id).collect();
+    let want: Vec<_> = all[20..40].iter().map(|c| c.id).collect();
+    assert_eq!(ids, want);
+
+    let tail = repo.list_cards_paged(Some(deck.id), 40, 20).await.unwrap();
+    assert_eq!(tail.len(), 10);
+    assert_eq!(repo.list_cards_paged(None, 0, 100).await.unwrap().len(), 51);
+    assert!(repo
+        .list_cards_paged(None, 60, 20)
+        .await
+        .unwrap()
+        .is_empty());
+}
@@ -0,0 +1,83 @@
+use flashmaster_core::{record_grade, CardPatch, Grade, Repository, ReviewMode};
+use flashmaster_sqlite::SqliteRepo;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn patch_updates_only_the_given_columns() {
+    let repo = SqliteRepo::open_memory().await.unwrap();
+    let deck = repo.create_deck("Lang").await.unwrap();
+    let card = repo
+        .add_card(deck.id, "hola", "hello", Some("greeting"), &["es".into()])
+        .await
+        .unwrap();
+    let reviewed = record_grade(&repo, card.clone(), Grade::Easy, ReviewMode::Scheduled)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let patch = CardPatch {
+        front: Some("¡hola!".into()),
+        ..Default::default()
+    };
+    let out = repo.patch_card(card.id, patch).await.unwrap();
+    assert_eq!(out.front, "¡hola!");
+    assert_eq!(out.hint.as_deref(), Some("greeting"));
+    assert_eq!(out.tags, vec!["es".to_string()]);
+    assert_eq!(out.reps, reviewed.reps);
+    assert_eq!(out.interval_days, reviewed.interval_days);
+
+    let patch = CardPatch {
+        hint: Some(None),
+        tags: Some(vec!["spanish".into()]),
+        suspended: Some(true),
+        ..Default::default()
+    };
+    let out = repo.patch_card(card.id, patch).await.unwrap();
+    let stored = repo.get_card(card.id).await.unwrap();
+    for c in [&out, &stored] {
+        assert!(c.hint.is_none() && c.suspended);
+        assert_eq!(c.tags, vec!["spanish".to_string()]);
+        assert_eq!(c.front, "¡hola!");
+    }
+
+    assert!(repo
+        .patch_card(
+            Uuid::new_v4(),
+            CardPatch {
+                front: Some("x".into()),
+                ..Default::default()
+            }
+        )
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn batch_patch_is_all_or_nothing() {
+    let repo = SqliteRepo::open_memory().await.unwrap();
+    let deck = repo.create_deck("Lang").await.unwrap();
+    let a = repo
+        .add_card(deck.id, "uno", "one", None, &[])
+        .await
+        .unwrap();
+    let b = repo
+        .add_card(deck.id, "dos", "two", None, &[])
+        .await
+        .unwrap();
+    let tag = |t: &str| CardPatch {
+        tags: Some(vec![t.to_string()]),
+        ..Default::default()
+    };
+
+    let res = repo
+        .patch_cards(&[(a.id, tag("x")), (Uuid::new_v4(), tag("x"))])
+        .await;
+    assert!(res.is_err());
+    assert!(repo.get_card(a.id).await.unwrap().tags.is_empty());
+
+    repo.patch_cards(&[(a.id, tag("x")), (b.id, tag("y"))])
+        .await
+        .unwrap();
+    assert_eq!(repo.get_card(a.id).await.unwrap().tags, ["x"]);
+    assert_eq!(repo.get_card(b.id).await.unwrap().tags, ["y"]);
+}
@@ -0,0 +1,11 @@
+use flashmaster_core::{CoreError, Repository};
+use flashmaster_sqlite::SqliteRepo;
+
+#[tokio::test]
+async fn ping_succeeds_until_the_pool_closes() {
+    let repo = SqliteRepo::open_memory().await.unwrap();
+    repo.ping().await.unwrap();
+
+    repo.pool().close().await;
+    assert!(matches!(repo.ping().await, Err(CoreError::Storage(_))));
+}
@@ -0,0 +1,63 @@
+use flashmaster_core::Repository;
+use flashmaster_sqlite::SqliteRepo;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn reorder_persists_and_orders_listing() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("fm.sqlite3");
+    std::fs::File::create(&path).unwrap();
+    let (a, b, c) = {
+        let repo = SqliteRepo::open_file(&path).await.unwrap();
+        let a = repo.create_deck("A").await.unwrap();
+        let b = repo.create_deck("B").await.unwrap();
+        let c = repo.create_deck("C").await.unwrap();
+        repo.reorder_decks(&[c.id, a.id, b.id]).await.unwrap();
+        (a, b, c)
+    };
+
+    let repo = SqliteRepo::open_file(&path).await.unwrap();
+    let ids: Vec<Uuid> = repo
+        .list_decks()
+        .await
+        .unwrap()
+        .iter()
+        .map(|d| d.id)
+        .collect();
+    assert_eq!(ids, vec![c.id, a.id, b.id]);
+    assert_eq!(repo.get_deck(b.id).await.unwrap().position, 2);
+
+    // A deck created after a reorder lists last.
+    let d = repo.create_deck("D").await.unwrap();
+    assert_eq!(d.position, 3);
+    assert_eq!(repo.list_decks().await.unwrap().last().unwrap().id, d.id);
+
+    // A missing id rolls the whole reorder back.
+    assert!(repo.reorder_decks(&[b.id, Uuid::new_v4()]).await.is_err());
+    assert_eq!(repo.get_deck(b.id).await.unwrap().position, 2);
+}
+
+#[tokio::test]
+async fn position_column_is_added_to_older_databases() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("old.sqlite3");
+    {
+        let url = format!("sqlite://{}?mode=rwc", path.display());
+        let pool = sqlx::SqlitePool::connect(&url).await.unwrap();
+        sqlx::query("CREATE TABLE decks (id TEXT PRIMARY KEY, name TEXT NOT NULL UNIQUE, created_at TEXT NOT NULL)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO decks VALUES (?, 'Legacy', '2024-01-01T00:00:00Z')")
+            .bind(Uuid::new_v4().to_string())
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool.close().await;
+    }
+
+    let repo = SqliteRepo::open_file(&path).await.unwrap();
+    let decks = repo.list_decks().await.unwrap();
+    assert_eq!(decks.len(), 1);
+    assert_eq!((decks[0].name.as_str(), decks[0].position), ("Legacy", 0));
+}
@@ -0,0 +1,40 @@
+use flashmaster_core::Repository;
+use flashmaster_sqlite::{SqliteOpts, SqliteRepo, Synchronous};
+use sqlx::Row;
+
+#[tokio::test]
+async fn synchronous_off_still_reads_and_writes() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("fm.sqlite3");
+    std::fs::File::create(&path).unwrap();
+    let opts = SqliteOpts {
+        synchronous: Synchronous::Off,
+        cache_size_kb: Some(4096),
+        mmap_size: Some(1 << 20),
+        ..Default::default()
+    };
+
+    let repo = SqliteRepo::open_file_with(&path, &opts).await.unwrap();
+    let sync: i64 = sqlx::query("PRAGMA synchronous")
+        .fetch_one(repo.pool())
+        .await
+        .unwrap()
+        .get(0);
+    assert_eq!(sync, 0);
+    let cache: i64 = sqlx::query("PRAGMA cache_size")
+        .fetch_one(repo.pool())
+        .await
+        .unwrap()
+        .get(0);
+    assert_eq!(cache, -4096);
+
+    let deck = repo.create_deck("Spanish").await.unwrap();
+    let card = repo
+        .add_card(deck.id, "hola", "hello", None, &[])
+        .await
+        .unwrap();
+    repo.pool().close().await;
+
+    let reopened = SqliteRepo::open_file(&path).await.unwrap();
+    assert_eq!(reopened.get_card(card.id).await.unwrap().front, "hola");
+}
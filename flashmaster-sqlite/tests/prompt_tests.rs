@@ -0,0 +1,33 @@
+use flashmaster_core::{CoreError, PromptWith, Repository};
+use flashmaster_sqlite::SqliteRepo;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn prompt_side_persists_across_reopen() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("fm.sqlite3");
+    std::fs::File::create(&path).unwrap();
+    let deck = {
+        let repo = SqliteRepo::open_file(&path).await.unwrap();
+        let deck = repo.create_deck("Spanish").await.unwrap();
+        assert_eq!(deck.prompt_with, PromptWith::Front);
+        repo.set_deck_prompt(deck.id, PromptWith::Back)
+            .await
+            .unwrap();
+        deck
+    };
+
+    let repo = SqliteRepo::open_file(&path).await.unwrap();
+    assert_eq!(
+        repo.get_deck(deck.id).await.unwrap().prompt_with,
+        PromptWith::Back
+    );
+    assert_eq!(
+        repo.list_decks().await.unwrap()[0].prompt_with,
+        PromptWith::Back
+    );
+    assert!(matches!(
+        repo.set_deck_prompt(Uuid::new_v4(), PromptWith::Back).await,
+        Err(CoreError::NotFound(_))
+    ));
+}
@@ -0,0 +1,25 @@
+use flashmaster_core::{CoreError, Repository};
+use flashmaster_sqlite::{SqliteRepo, READ_ONLY_MSG};
+
+#[tokio::test]
+async fn readonly_open_can_read_but_not_write() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("fm.sqlite3");
+    std::fs::File::create(&path).unwrap();
+
+    {
+        let rw = SqliteRepo::open_file(&path).await.unwrap();
+        rw.create_deck("Spanish").await.unwrap();
+        rw.pool().close().await;
+    }
+
+    let ro = SqliteRepo::open_file_readonly(&path).await.unwrap();
+    let decks = ro.list_decks().await.unwrap();
+    assert_eq!(decks.len(), 1);
+    assert_eq!(decks[0].name, "Spanish");
+
+    match ro.create_deck("French").await {
+        Err(CoreError::Storage(msg)) => assert_eq!(msg, READ_ONLY_MSG),
+        other => panic!("expected read-only storage error, got {other:?}"),
+    }
+}
@@ -0,0 +1,22 @@
+use flashmaster_core::{CoreError, Repository};
+use flashmaster_sqlite::SqliteRepo;
+
+#[tokio::test]
+async fn out_of_range_relearning_step_is_an_error() {
+    let repo = SqliteRepo::open_memory().await.unwrap();
+    let deck = repo.create_deck("Spanish").await.unwrap();
+    let card = repo
+        .add_card(deck.id, "hola", "hello", None, &[])
+        .await
+        .unwrap();
+    sqlx::query("UPDATE cards SET relearning_step=300 WHERE id=?")
+        .bind(card.id.to_string())
+        .execute(repo.pool())
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        repo.get_card(card.id).await,
+        Err(CoreError::Invalid(_))
+    ));
+}
@@ -0,0 +1,27 @@
+use flashmaster_core::{record_grade, Grade, Repository, ReviewMode};
+use flashmaster_sqlite::SqliteRepo;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn reset_deck_relearns_cards_and_drops_reviews() {
+    let repo = SqliteRepo::open_memory().await.unwrap();
+    let deck = repo.create_deck("Lang").await.unwrap();
+    for i in 0..2 {
+        let card = repo
+            .add_card(deck.id, &format!("q{i}"), "a", None, &[])
+            .await
+            .unwrap();
+        record_grade(&repo, card, Grade::Medium, ReviewMode::Scheduled)
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(repo.reset_deck(deck.id, false).await.unwrap(), 2);
+    for c in repo.list_cards(Some(deck.id)).await.unwrap() {
+        assert_eq!((c.reps, c.interval_days), (0, 0));
+        assert!(c.last_grade.is_none());
+    }
+    assert!(repo.list_reviews().await.unwrap().is_empty());
+
+    assert!(repo.reset_deck(Uuid::new_v4(), true).await.is_err());
+}
@@ -0,0 +1,28 @@
+use flashmaster_core::{Grade, Repository, Review, SCHEDULER_NAME};
+use flashmaster_sqlite::SqliteRepo;
+
+#[tokio::test]
+async fn review_scheduler_label_round_trips() {
+    let repo = SqliteRepo::open_memory().await.unwrap();
+    let deck = repo.create_deck("Lang").await.unwrap();
+    let card = repo.add_card(deck.id, "q", "a", None, &[]).await.unwrap();
+
+    let sm2 = Review::builder(card.id, Grade::Easy).build();
+    assert_eq!(sm2.scheduler, "sm2");
+    assert_eq!(SCHEDULER_NAME, "sm2");
+    let other = Review::builder(card.id, Grade::Hard)
+        .scheduler("fsrs")
+        .build();
+    repo.insert_review(&sm2).await.unwrap();
+    repo.insert_review(&other).await.unwrap();
+
+    let mut labels: Vec<String> = repo
+        .list_reviews_for_card(card.id)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|r| r.scheduler)
+        .collect();
+    labels.sort();
+    assert_eq!(labels, ["fsrs", "sm2"]);
+}
@@ -0,0 +1,22 @@
+use flashmaster_core::Repository;
+use flashmaster_sqlite::SqliteRepo;
+
+#[tokio::test]
+async fn clones_share_the_same_pool() {
+    let a = SqliteRepo::open_memory().await.unwrap();
+    let b = a.clone();
+
+    let deck = a.create_deck("Shared").await.unwrap();
+    let card = b
+        .add_card(deck.id, "hola", "hello", None, &[])
+        .await
+        .unwrap();
+
+    let decks = b.list_decks().await.unwrap();
+    assert_eq!(decks.len(), 1);
+    assert_eq!(decks[0].id, deck.id);
+
+    let cards = a.list_cards(Some(deck.id)).await.unwrap();
+    assert_eq!(cards.len(), 1);
+    assert_eq!(cards[0].id, card.id);
+}
@@ -0,0 +1,37 @@
+use flashmaster_core::{CardPatch, Repository};
+use flashmaster_sqlite::SqliteRepo;
+
+#[tokio::test]
+async fn source_round_trips_and_is_searchable() {
+    let repo = SqliteRepo::open_memory().await.unwrap();
+    let deck = repo.create_deck("Biology").await.unwrap();
+    let mut card = repo
+        .add_card(deck.id, "mitosis", "cell division", None, &[])
+        .await
+        .unwrap();
+    assert!(card.source.is_none());
+    repo.add_card(deck.id, "chapter", "ch3 of a book", None, &[])
+        .await
+        .unwrap();
+
+    card.source = Some("Ch3, p.42".into());
+    repo.update_card(&card).await.unwrap();
+    let loaded = repo.get_card(card.id).await.unwrap();
+    assert_eq!(loaded.source.as_deref(), Some("Ch3, p.42"));
+
+    let hits = repo.search_cards("source:ch3", None).await.unwrap();
+    assert_eq!(hits.iter().map(|c| c.id).collect::<Vec<_>>(), [card.id]);
+    assert_eq!(repo.search_cards("ch3", None).await.unwrap().len(), 2);
+
+    let patch = CardPatch {
+        source: Some(None),
+        ..Default::default()
+    };
+    assert!(repo
+        .patch_card(card.id, patch)
+        .await
+        .unwrap()
+        .source
+        .is_none());
+    assert!(repo.search_cards("source:", None).await.unwrap().is_empty());
+}
@@ -0,0 +1,62 @@
+use flashmaster_core::{apply_grade, CoreError, Grade, Repository};
+use flashmaster_sqlite::SqliteRepo;
+
+#[tokio::test]
+async fn subdecks_persist_and_delete_with_their_parent() {
+    let repo = SqliteRepo::open_memory().await.unwrap();
+    let spanish = repo.create_deck("Spanish").await.unwrap();
+    let verbs = repo.create_subdeck("Verbs", spanish.id).await.unwrap();
+    let irregular = repo.create_subdeck("Irregular", verbs.id).await.unwrap();
+    let french = repo.create_deck("French").await.unwrap();
+
+    assert_eq!(
+        repo.get_deck(verbs.id).await.unwrap().parent_id,
+        Some(spanish.id)
+    );
+    assert_eq!(repo.get_deck(spanish.id).await.unwrap().parent_id, None);
+    let under: Vec<_> = repo.list_subdecks(verbs.id).await.unwrap();
+    assert_eq!(under.len(), 1);
+    assert_eq!(under[0].id, irregular.id);
+    assert!(matches!(
+        repo.create_subdeck("x", uuid::Uuid::new_v4()).await,
+        Err(CoreError::NotFound("deck"))
+    ));
+
+    let card = repo
+        .add_card(irregular.id, "ser", "to be", None, &[])
+        .await
+        .unwrap();
+    repo.insert_review(&apply_grade(card.clone(), Grade::Easy).review)
+        .await
+        .unwrap();
+
+    repo.delete_deck(spanish.id).await.unwrap();
+    let left = repo.list_decks().await.unwrap();
+    assert_eq!(left.len(), 1);
+    assert_eq!(left[0].id, french.id);
+    assert!(!repo.card_exists(card.id).await.unwrap());
+    assert!(repo.list_reviews().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn merging_a_parent_lifts_its_subdecks() {
+    let repo = SqliteRepo::open_memory().await.unwrap();
+    let lang = repo.create_deck("Languages").await.unwrap();
+    let spanish = repo.create_subdeck("Spanish", lang.id).await.unwrap();
+    let verbs = repo.create_subdeck("Verbs", spanish.id).await.unwrap();
+    let other = repo.create_deck("Other").await.unwrap();
+
+    repo.merge_decks(spanish.id, other.id, &[]).await.unwrap();
+    assert_eq!(
+        repo.get_deck(verbs.id).await.unwrap().parent_id,
+        Some(lang.id)
+    );
+    let (_, counts) = repo
+        .list_decks_with_counts(chrono::Utc::now())
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|(d, _)| d.id == verbs.id)
+        .unwrap();
+    assert_eq!(counts.total, 0);
+}
@@ -0,0 +1,22 @@
+use flashmaster_core::Repository;
+use flashmaster_sqlite::SqliteRepo;
+
+#[tokio::test]
+async fn corrupt_tags_fall_back_to_separated_list() {
+    let repo = SqliteRepo::open_memory().await.unwrap();
+    let deck = repo.create_deck("Lang").await.unwrap();
+    let card = repo
+        .add_card(deck.id, "hola", "hello", None, &["x".to_string()])
+        .await
+        .unwrap();
+
+    sqlx::query("UPDATE cards SET tags=? WHERE id=?")
+        .bind("greeting; spanish,verbs")
+        .bind(card.id.to_string())
+        .execute(repo.pool())
+        .await
+        .unwrap();
+
+    let loaded = repo.get_card(card.id).await.unwrap();
+    assert_eq!(loaded.tags, ["greeting", "spanish", "verbs"]);
+}
@@ -0,0 +1,28 @@
+use flashmaster_core::{CoreError, Repository};
+use flashmaster_sqlite::{SqliteOpts, SqliteRepo, POOL_TIMEOUT_MSG};
+use std::time::Duration;
+
+#[tokio::test]
+async fn exhausted_pool_times_out() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("fm.sqlite3");
+    std::fs::File::create(&path).unwrap();
+    let opts = SqliteOpts {
+        acquire_timeout: Some(Duration::from_millis(50)),
+        ..Default::default()
+    };
+    let repo = SqliteRepo::open_file_with(&path, &opts).await.unwrap();
+    repo.create_deck("Spanish").await.unwrap();
+
+    let mut held = Vec::new();
+    for _ in 0..5 {
+        held.push(repo.pool().acquire().await.unwrap());
+    }
+    match repo.list_decks().await {
+        Err(CoreError::Timeout(msg)) => assert_eq!(msg, POOL_TIMEOUT_MSG),
+        other => panic!("expected pool timeout, got {other:?}"),
+    }
+
+    drop(held);
+    assert_eq!(repo.list_decks().await.unwrap().len(), 1);
+}
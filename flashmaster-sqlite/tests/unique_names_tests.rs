@@ -0,0 +1,71 @@
+use flashmaster_core::{CoreError, Repository};
+use flashmaster_sqlite::SqliteRepo;
+
+#[tokio::test]
+async fn duplicate_deck_names_follow_the_setting() {
+    let strict = SqliteRepo::open_memory().await.unwrap();
+    strict.create_deck("Verbs").await.unwrap();
+    assert!(matches!(
+        strict.create_deck("verbs").await,
+        Err(CoreError::Conflict(_))
+    ));
+
+    // The schema backs the check, so a write that skips it still fails.
+    let raw = sqlx::query(
+        "INSERT INTO decks (id,name,created_at) VALUES ('x','VERBS','2025-01-01T00:00:00+00:00')",
+    )
+    .execute(strict.pool())
+    .await;
+    assert!(raw.is_err());
+
+    let loose = SqliteRepo::open_memory()
+        .await
+        .unwrap()
+        .with_unique_deck_names(false)
+        .await
+        .unwrap();
+    let a = loose.create_deck("Verbs").await.unwrap();
+    let b = loose.create_deck("Verbs").await.unwrap();
+    assert_ne!(a.id, b.id);
+    assert_eq!(loose.list_decks().await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn legacy_unique_column_is_dropped_keeping_cards() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("fm.sqlite3");
+    {
+        // A database as the first release created it.
+        let url = format!("sqlite://{}?mode=rwc", path.to_string_lossy());
+        let pool = sqlx::SqlitePool::connect(&url).await.unwrap();
+        let init = include_str!("../migrations/2025xxxxxx_init/up.sql");
+        for sql in init.split(';').map(str::trim).filter(|s| !s.is_empty()) {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+        sqlx::query("INSERT INTO decks (id,name,created_at) VALUES ('00000000-0000-0000-0000-00000000000d','Verbs','2025-01-01T00:00:00+00:00')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO cards (id,deck_id,front,back,tags,due_at,created_at)
+             VALUES ('00000000-0000-0000-0000-000000000001','00000000-0000-0000-0000-00000000000d','ir','to go','[]',
+                     '2025-01-01T00:00:00+00:00','2025-01-01T00:00:00+00:00')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        pool.close().await;
+    }
+
+    let repo = SqliteRepo::open_file(&path)
+        .await
+        .unwrap()
+        .with_unique_deck_names(false)
+        .await
+        .unwrap();
+    repo.create_deck("Verbs").await.unwrap();
+    assert_eq!(repo.list_decks().await.unwrap().len(), 2);
+    let cards = repo.list_cards(None).await.unwrap();
+    assert_eq!(cards.len(), 1);
+    assert_eq!(cards[0].front, "ir");
+}